@@ -0,0 +1,174 @@
+// Rasterizes the glyphs of every font of `static/` into a glyph atlas and
+// writes it, along with the metrics FontAtlas reads, to `static/fonts/`.
+//
+//   cargo run --example bake_fonts
+//
+// Run it again whenever a font in `static/` changes.
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+// Glyphs are drawn scaled from this size, large enough for titles.
+const BAKE_SIZE: f32 = 64.0;
+const ATLAS_WIDTH: usize = 1024;
+// Empty pixels around each glyph, so bilinear filtering never samples a
+// neighbouring glyph.
+const PADDING: usize = 2;
+
+fn get_characters() -> Vec<char> {
+  let printable_ascii = (0x20u8..0x7f).map(|byte| byte as char);
+  let latin_1 = (0xa1u32..0x100).filter_map(std::char::from_u32);
+  return printable_ascii.chain(latin_1).collect();
+}
+
+#[derive(Serialize)]
+struct GlyphEntry {
+  // Cell of the glyph in the atlas, in pixels.
+  x: usize,
+  y: usize,
+  width: usize,
+  height: usize,
+  // From the pen position on the baseline to the bottom left of the glyph, y
+  // going up, in pixels of the baked size.
+  xmin: f32,
+  ymin: f32,
+  advance: f32,
+}
+
+#[derive(Serialize)]
+struct FontManifest {
+  src: String,
+  size: f32,
+  ascent: f32,
+  descent: f32,
+  width: usize,
+  height: usize,
+  glyphs: BTreeMap<String, GlyphEntry>,
+}
+
+fn save_png(path: &PathBuf, width: usize, height: usize, pixels: &[u8]) {
+  let file = std::fs::File::create(path).expect("File::create failed");
+  let mut encoder = png::Encoder::new(std::io::BufWriter::new(file), width as u32, height as u32);
+  encoder.set_color(png::ColorType::Rgba);
+  encoder.set_depth(png::BitDepth::Eight);
+  encoder.set_compression(png::Compression::Best);
+  let mut writer = encoder.write_header().expect("write_header failed");
+  writer
+    .write_image_data(pixels)
+    .expect("write_image_data failed");
+}
+
+fn bake_font(path: &PathBuf, output_dir: &PathBuf) {
+  let name = path.file_stem().unwrap().to_str().unwrap();
+  let font = fontdue::Font::from_bytes(
+    std::fs::read(path).expect("read failed"),
+    fontdue::FontSettings::default(),
+  )
+  .expect("invalid font");
+  let line_metrics = font
+    .horizontal_line_metrics(BAKE_SIZE)
+    .expect("horizontal_line_metrics failed");
+
+  let mut glyphs: Vec<(char, fontdue::Metrics, Vec<u8>)> = get_characters()
+    .into_iter()
+    .filter(|character| font.lookup_glyph_index(*character) != 0 || *character == ' ')
+    .map(|character| {
+      let (metrics, coverage) = font.rasterize(character, BAKE_SIZE);
+      (character, metrics, coverage)
+    })
+    .collect();
+  glyphs.sort_by(|a, b| b.1.height.cmp(&a.1.height).then(a.0.cmp(&b.0)));
+
+  // Shelves, tallest glyphs first, like the texture atlases.
+  let mut cells = Vec::new();
+  let (mut shelf_x, mut shelf_y, mut shelf_height) = (0, 0, 0);
+  for (_, metrics, _) in glyphs.iter() {
+    let (cell_width, cell_height) = (metrics.width + 2 * PADDING, metrics.height + 2 * PADDING);
+    if shelf_x + cell_width > ATLAS_WIDTH {
+      shelf_x = 0;
+      shelf_y += shelf_height;
+      shelf_height = 0;
+    }
+    cells.push((shelf_x + PADDING, shelf_y + PADDING));
+    shelf_x += cell_width;
+    shelf_height = shelf_height.max(cell_height);
+  }
+  let height = (shelf_y + shelf_height).next_power_of_two();
+
+  // White glyphs, so draws can tint them with any color.
+  let mut pixels = vec![0; ATLAS_WIDTH * height * 4];
+  let mut manifest = FontManifest {
+    src: format!("/static/fonts/{}.png", name),
+    size: BAKE_SIZE,
+    ascent: line_metrics.ascent,
+    descent: -line_metrics.descent,
+    width: ATLAS_WIDTH,
+    height,
+    glyphs: BTreeMap::new(),
+  };
+  for ((character, metrics, coverage), (x, y)) in glyphs.iter().zip(cells.iter()) {
+    for glyph_y in 0..metrics.height {
+      for glyph_x in 0..metrics.width {
+        let index = ((y + glyph_y) * ATLAS_WIDTH + x + glyph_x) * 4;
+        pixels[index..index + 4].copy_from_slice(&[
+          255,
+          255,
+          255,
+          coverage[glyph_y * metrics.width + glyph_x],
+        ]);
+      }
+    }
+    manifest.glyphs.insert(
+      character.to_string(),
+      GlyphEntry {
+        x: *x,
+        y: *y,
+        width: metrics.width,
+        height: metrics.height,
+        xmin: metrics.xmin as f32,
+        ymin: metrics.ymin as f32,
+        advance: metrics.advance_width,
+      },
+    );
+  }
+
+  save_png(
+    &output_dir.join(format!("{}.png", name)),
+    ATLAS_WIDTH,
+    height,
+    &pixels,
+  );
+  std::fs::write(
+    output_dir.join(format!("{}.json", name)),
+    serde_json::to_string_pretty(&manifest).expect("to_string_pretty failed"),
+  )
+  .expect("write failed");
+  println!(
+    "Baked {} glyphs of {} into a {}x{} atlas",
+    glyphs.len(),
+    name,
+    ATLAS_WIDTH,
+    height
+  );
+}
+
+fn main() {
+  let root_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+  let static_dir = root_dir.join("static");
+  let output_dir = static_dir.join("fonts");
+  std::fs::create_dir_all(&output_dir).expect("create_dir_all failed");
+
+  let mut fonts: Vec<PathBuf> = std::fs::read_dir(&static_dir)
+    .expect("read_dir failed")
+    .map(|entry| entry.expect("read_dir entry failed").path())
+    .filter(|path| {
+      path
+        .extension()
+        .map_or(false, |extension| extension == "ttf" || extension == "otf")
+    })
+    .collect();
+  fonts.sort();
+  for path in fonts.iter() {
+    bake_font(path, &output_dir);
+  }
+}
@@ -0,0 +1,135 @@
+// Writes the @2x and @3x variants of the textures of `static/` which have a
+// larger master image, by downscaling the master.
+//
+//   cargo run --example make_scale_variants
+//
+// Run it again whenever a master or its @1x image changes, and list the
+// variants in `TextureParamsOptional::scales`.
+use std::path::{Path, PathBuf};
+
+// @1x image, its master and the scales written from the master.
+const VARIANTS: [(&str, &str, &[u32]); 2] = [
+  ("flare_small.png", "flare.png", &[2, 3]),
+  ("miniStar.png", "especial.png", &[2]),
+];
+
+struct Image {
+  width: usize,
+  height: usize,
+  // Straight alpha RGBA.
+  pixels: Vec<u8>,
+}
+
+impl Image {
+  fn load(path: &Path) -> Image {
+    let mut decoder = png::Decoder::new(std::fs::File::open(path).expect("File::open failed"));
+    decoder.set_transformations(png::Transformations::normalize_to_color8());
+    let mut reader = decoder.read_info().expect("read_info failed");
+    let mut buffer = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut buffer).expect("next_frame failed");
+    let channels = info.color_type.samples();
+
+    let mut pixels = Vec::with_capacity(info.width as usize * info.height as usize * 4);
+    for bytes in buffer[..info.buffer_size()].chunks(channels) {
+      let rgba = match channels {
+        1 => [bytes[0], bytes[0], bytes[0], 255],
+        2 => [bytes[0], bytes[0], bytes[0], bytes[1]],
+        3 => [bytes[0], bytes[1], bytes[2], 255],
+        _ => [bytes[0], bytes[1], bytes[2], bytes[3]],
+      };
+      pixels.extend_from_slice(&rgba);
+    }
+    return Image {
+      width: info.width as usize,
+      height: info.height as usize,
+      pixels,
+    };
+  }
+
+  fn save(&self, path: &Path) {
+    let file = std::fs::File::create(path).expect("File::create failed");
+    let mut encoder = png::Encoder::new(
+      std::io::BufWriter::new(file),
+      self.width as u32,
+      self.height as u32,
+    );
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder.set_compression(png::Compression::Best);
+    let mut writer = encoder.write_header().expect("write_header failed");
+    writer
+      .write_image_data(&self.pixels)
+      .expect("write_image_data failed");
+  }
+
+  // Averages the pixels whose center falls in each pixel of the result, with
+  // premultiplied alpha so transparent pixels don't darken the edges.
+  fn downscale(&self, width: usize, height: usize) -> Image {
+    let mut pixels = Vec::with_capacity(width * height * 4);
+    for y in 0..height {
+      let (start_y, end_y) = get_source_range(y, height, self.height);
+      for x in 0..width {
+        let (start_x, end_x) = get_source_range(x, width, self.width);
+        let mut sum = [0.0; 4];
+        for source_y in start_y..end_y {
+          for source_x in start_x..end_x {
+            let index = (source_y * self.width + source_x) * 4;
+            let alpha = self.pixels[index + 3] as f64;
+            for channel in 0..3 {
+              sum[channel] += self.pixels[index + channel] as f64 * alpha;
+            }
+            sum[3] += alpha;
+          }
+        }
+        let count = ((end_x - start_x) * (end_y - start_y)) as f64;
+        if sum[3] == 0.0 {
+          pixels.extend_from_slice(&[0, 0, 0, 0]);
+          continue;
+        }
+        for channel in 0..3 {
+          pixels.push((sum[channel] / sum[3]).round() as u8);
+        }
+        pixels.push((sum[3] / count).round() as u8);
+      }
+    }
+    return Image {
+      width,
+      height,
+      pixels,
+    };
+  }
+}
+
+// Source pixels whose center is inside pixel `i` of a `size` long result,
+// at least one.
+fn get_source_range(i: usize, size: usize, source_size: usize) -> (usize, usize) {
+  let ratio = source_size as f64 / size as f64;
+  let start = ((i as f64 * ratio - 0.5).ceil().max(0.0) as usize).min(source_size - 1);
+  let end = (((i + 1) as f64 * ratio - 0.5).ceil() as usize).clamp(start + 1, source_size);
+  return (start, end);
+}
+
+fn main() {
+  let root_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+  let static_dir = root_dir.join("static");
+
+  for (src, master_src, scales) in VARIANTS.iter() {
+    let image = Image::load(&static_dir.join(src));
+    let master = Image::load(&static_dir.join(master_src));
+    for scale in scales.iter() {
+      let (width, height) = (
+        image.width * *scale as usize,
+        image.height * *scale as usize,
+      );
+      if width > master.width || height > master.height {
+        panic!("{} is smaller than {} at @{}x", master_src, src, scale);
+      }
+      let (stem, extension) = src.rsplit_once('.').expect("src has no extension");
+      let variant_src = format!("{}@{}x.{}", stem, scale, extension);
+      master
+        .downscale(width, height)
+        .save(&static_dir.join(&variant_src));
+      println!("Wrote {} from {}", variant_src, master_src);
+    }
+  }
+}
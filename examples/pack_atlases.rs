@@ -0,0 +1,226 @@
+// Packs the small images of `static/` into a few atlas pages and writes them,
+// along with the manifest TextureLoader reads, to `static/atlases/`.
+//
+//   cargo run --example pack_atlases
+//
+// Run it again whenever an image in `static/` changes.
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+const ATLAS_SIZE: usize = 1024;
+// Larger images are kept as separate files.
+const MAX_SPRITE_SIZE: usize = 256;
+// Each sprite's border pixels are repeated this many times around it, so
+// bilinear filtering never samples a neighbouring sprite.
+const EXTRUDE: usize = 1;
+const PADDING: usize = 1;
+
+struct Image {
+  width: usize,
+  height: usize,
+  // Straight alpha RGBA.
+  pixels: Vec<u8>,
+}
+
+impl Image {
+  fn new(width: usize, height: usize) -> Image {
+    return Image {
+      width,
+      height,
+      pixels: vec![0; width * height * 4],
+    };
+  }
+
+  fn load(path: &Path) -> Image {
+    let mut decoder = png::Decoder::new(std::fs::File::open(path).expect("File::open failed"));
+    decoder.set_transformations(png::Transformations::normalize_to_color8());
+    let mut reader = decoder.read_info().expect("read_info failed");
+    let mut buffer = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut buffer).expect("next_frame failed");
+    let channels = info.color_type.samples();
+
+    let mut image = Image::new(info.width as usize, info.height as usize);
+    for (pixel, bytes) in image
+      .pixels
+      .chunks_mut(4)
+      .zip(buffer[..info.buffer_size()].chunks(channels))
+    {
+      let rgba = match channels {
+        1 => [bytes[0], bytes[0], bytes[0], 255],
+        2 => [bytes[0], bytes[0], bytes[0], bytes[1]],
+        3 => [bytes[0], bytes[1], bytes[2], 255],
+        _ => [bytes[0], bytes[1], bytes[2], bytes[3]],
+      };
+      pixel.copy_from_slice(&rgba);
+    }
+    return image;
+  }
+
+  fn save(&self, path: &Path) {
+    let file = std::fs::File::create(path).expect("File::create failed");
+    let mut encoder = png::Encoder::new(
+      std::io::BufWriter::new(file),
+      self.width as u32,
+      self.height as u32,
+    );
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder.set_compression(png::Compression::Best);
+    let mut writer = encoder.write_header().expect("write_header failed");
+    writer
+      .write_image_data(&self.pixels)
+      .expect("write_image_data failed");
+  }
+
+  // Copies `source` with its top left corner at (x, y), extruding its borders.
+  fn blit_extruded(&mut self, source: &Image, x: usize, y: usize) {
+    let extrude = EXTRUDE as i64;
+    for dest_y in -extrude..(source.height as i64 + extrude) {
+      for dest_x in -extrude..(source.width as i64 + extrude) {
+        let source_x = dest_x.max(0).min(source.width as i64 - 1) as usize;
+        let source_y = dest_y.max(0).min(source.height as i64 - 1) as usize;
+        let source_index = (source_y * source.width + source_x) * 4;
+        let dest_index =
+          (((y as i64 + dest_y) as usize) * self.width + (x as i64 + dest_x) as usize) * 4;
+        self.pixels[dest_index..dest_index + 4]
+          .copy_from_slice(&source.pixels[source_index..source_index + 4]);
+      }
+    }
+  }
+}
+
+#[derive(Serialize)]
+struct SpriteEntry {
+  x: usize,
+  y: usize,
+  width: usize,
+  height: usize,
+}
+
+#[derive(Serialize)]
+struct AtlasEntry {
+  src: String,
+  width: usize,
+  height: usize,
+  sprites: BTreeMap<String, SpriteEntry>,
+}
+
+#[derive(Serialize)]
+struct Manifest {
+  atlases: Vec<AtlasEntry>,
+}
+
+// Fills pages row by row ("shelves"), tallest sprites first.
+struct ShelfPacker {
+  page_count: usize,
+  shelf_x: usize,
+  shelf_y: usize,
+  shelf_height: usize,
+}
+
+impl ShelfPacker {
+  fn new() -> ShelfPacker {
+    return ShelfPacker {
+      page_count: 0,
+      shelf_x: ATLAS_SIZE,
+      shelf_y: 0,
+      shelf_height: 0,
+    };
+  }
+
+  // Returns the page and the top left corner of the padded cell.
+  fn place(&mut self, cell_width: usize, cell_height: usize) -> (usize, usize, usize) {
+    if self.shelf_x + cell_width > ATLAS_SIZE {
+      self.shelf_x = 0;
+      self.shelf_y += self.shelf_height;
+      self.shelf_height = cell_height;
+    }
+    if self.page_count == 0 || self.shelf_y + cell_height > ATLAS_SIZE {
+      self.page_count += 1;
+      self.shelf_x = 0;
+      self.shelf_y = 0;
+      self.shelf_height = cell_height;
+    }
+    let position = (self.shelf_x, self.shelf_y);
+    self.shelf_x += cell_width;
+    return (self.page_count - 1, position.0, position.1);
+  }
+}
+
+fn main() {
+  let root_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+  let static_dir = root_dir.join("static");
+  let output_dir = static_dir.join("atlases");
+
+  let mut sprites: Vec<(String, Image)> = std::fs::read_dir(&static_dir)
+    .expect("read_dir failed")
+    .map(|entry| entry.expect("read_dir entry failed").path())
+    .filter(|path| {
+      path
+        .extension()
+        .map_or(false, |extension| extension == "png")
+    })
+    // High density variants like "star@2x.png" are loaded on their own.
+    .filter(|path| !path.file_name().unwrap().to_str().unwrap().contains('@'))
+    .map(|path| {
+      let src = format!("/static/{}", path.file_name().unwrap().to_str().unwrap());
+      (src, Image::load(&path))
+    })
+    .filter(|(_, image)| image.width <= MAX_SPRITE_SIZE && image.height <= MAX_SPRITE_SIZE)
+    .collect();
+  sprites.sort_by(|(src_a, a), (src_b, b)| b.height.cmp(&a.height).then(src_a.cmp(src_b)));
+
+  let border = EXTRUDE + PADDING;
+  let mut packer = ShelfPacker::new();
+  let mut placements = vec![];
+  for (_, image) in sprites.iter() {
+    placements.push(packer.place(image.width + 2 * border, image.height + 2 * border));
+  }
+
+  let mut pages: Vec<Image> = (0..packer.page_count)
+    .map(|_| Image::new(ATLAS_SIZE, ATLAS_SIZE))
+    .collect();
+  let mut manifest = Manifest {
+    atlases: (0..pages.len())
+      .map(|page| AtlasEntry {
+        src: format!("/static/atlases/atlas{}.png", page),
+        width: ATLAS_SIZE,
+        height: ATLAS_SIZE,
+        sprites: BTreeMap::new(),
+      })
+      .collect(),
+  };
+  for ((src, image), (page, cell_x, cell_y)) in sprites.iter().zip(placements.iter()) {
+    let (x, y) = (cell_x + border, cell_y + border);
+    pages[*page].blit_extruded(image, x, y);
+    manifest.atlases[*page].sprites.insert(
+      src.clone(),
+      SpriteEntry {
+        x,
+        y,
+        width: image.width,
+        height: image.height,
+      },
+    );
+  }
+
+  if output_dir.exists() {
+    std::fs::remove_dir_all(&output_dir).expect("remove_dir_all failed");
+  }
+  std::fs::create_dir_all(&output_dir).expect("create_dir_all failed");
+  for (page, image) in pages.iter().enumerate() {
+    image.save(&output_dir.join(format!("atlas{}.png", page)));
+  }
+  std::fs::write(
+    output_dir.join("manifest.json"),
+    serde_json::to_string_pretty(&manifest).expect("to_string_pretty failed"),
+  )
+  .expect("write failed");
+  println!(
+    "Packed {} images into {} atlases in {}",
+    sprites.len(),
+    pages.len(),
+    output_dir.display()
+  );
+}
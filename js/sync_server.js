@@ -0,0 +1,151 @@
+// In-memory stand-in for the progress sync and leaderboard servers described
+// in the README. Data is lost when the process exits.
+const http = require("http");
+
+const port = process.env.PORT || 8090;
+const progressStore = new Map();
+// "{book}/{stage}" -> Map of player name -> best entry.
+const leaderboardStore = new Map();
+
+function send(response, status, body) {
+  response.writeHead(status, {
+    "Access-Control-Allow-Origin": "*",
+    "Access-Control-Allow-Methods": "GET, PUT, POST, OPTIONS",
+    "Access-Control-Allow-Headers": "Content-Type",
+    "Content-Type": "application/json",
+  });
+  response.end(body === undefined ? "" : JSON.stringify(body));
+}
+
+function readJson(request, response, onJson) {
+  let body = "";
+  request.on("data", (chunk) => (body += chunk));
+  request.on("end", () => {
+    let json;
+    try {
+      json = JSON.parse(body);
+    } catch (error) {
+      send(response, 400, { error: "invalid json" });
+      return;
+    }
+    onJson(json);
+  });
+}
+
+function handleProgress(request, response, storeKey) {
+  const stored = progressStore.get(storeKey);
+
+  if (request.method === "GET") {
+    if (stored) {
+      send(response, 200, stored);
+    } else {
+      send(response, 404, { error: "no progress stored" });
+    }
+    return;
+  }
+
+  if (request.method === "PUT") {
+    readJson(request, response, (update) => {
+      // A missing copy (e.g. after a restart) accepts any base revision.
+      const currentRevision = stored ? stored.revision : null;
+      if (stored && update.base_revision !== currentRevision) {
+        send(response, 409, stored);
+        return;
+      }
+      const revision = (currentRevision || 0) + 1;
+      progressStore.set(storeKey, { revision: revision, data: update.data });
+      console.log(`progress ${storeKey} -> revision ${revision}`);
+      send(response, 200, { revision: revision });
+    });
+    return;
+  }
+
+  send(response, 405, { error: "method not allowed" });
+}
+
+// The stand-in can't run the game simulation, so it only checks that the
+// replay belongs to the stage.
+function checkReplay(submission, book, stage) {
+  const replay = submission.replay && submission.replay.data;
+  if (!replay || submission.replay.version !== 3 || !Array.isArray(replay.steps)) {
+    return "missing replay";
+  }
+  if (replay.book_number !== book || replay.stage_number !== stage) {
+    return "replay is for another stage";
+  }
+  return null;
+}
+
+function handleLeaderboard(request, response, book, stage, url) {
+  const storeKey = book + "/" + stage;
+  if (!leaderboardStore.has(storeKey)) {
+    leaderboardStore.set(storeKey, new Map());
+  }
+  const entries = leaderboardStore.get(storeKey);
+
+  if (request.method === "GET") {
+    const limit = parseInt(url.searchParams.get("limit") || "10", 10);
+    const sorted = Array.from(entries.values())
+      .sort((a, b) => b.score - a.score)
+      .slice(0, limit);
+    send(response, 200, { entries: sorted });
+    return;
+  }
+
+  if (request.method === "POST") {
+    readJson(request, response, (submission) => {
+      const error = checkReplay(submission, book, stage);
+      if (error) {
+        send(response, 422, { error: error });
+        return;
+      }
+      const previous = entries.get(submission.player_name);
+      if (!previous || submission.score > previous.score) {
+        entries.set(submission.player_name, {
+          player_name: submission.player_name,
+          score: submission.score,
+          stars: submission.stars,
+        });
+      }
+      const rank =
+        Array.from(entries.values())
+          .sort((a, b) => b.score - a.score)
+          .findIndex((entry) => entry.player_name === submission.player_name) + 1;
+      console.log(`leaderboard ${storeKey}: ${submission.player_name} ${submission.score}`);
+      send(response, 200, { rank: rank });
+    });
+    return;
+  }
+
+  send(response, 405, { error: "method not allowed" });
+}
+
+http
+  .createServer((request, response) => {
+    if (request.method === "OPTIONS") {
+      send(response, 204);
+      return;
+    }
+    const url = new URL(request.url, "http://localhost");
+
+    const progressMatch = /^\/progress\/([^/]+)\/([^/]+)$/.exec(url.pathname);
+    if (progressMatch) {
+      handleProgress(request, response, progressMatch[1] + "/" + progressMatch[2]);
+      return;
+    }
+
+    const leaderboardMatch = /^\/leaderboards\/(\d+)\/(\d+)$/.exec(url.pathname);
+    if (leaderboardMatch) {
+      handleLeaderboard(
+        request,
+        response,
+        parseInt(leaderboardMatch[1], 10),
+        parseInt(leaderboardMatch[2], 10),
+        url
+      );
+      return;
+    }
+
+    send(response, 404, { error: "not found" });
+  })
+  .listen(port, () => console.log(`Stand-in server listening on port ${port}`));
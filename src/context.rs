@@ -1,8 +1,14 @@
 use crate::audio_manager::*;
-use crate::draw_depths::*;
+use crate::draw_layers::*;
 use crate::engine::*;
+use crate::game::achievement_tracker::*;
 use crate::game::achievments_manager::*;
 use crate::game::game_mode::*;
+use crate::game::gameplay_event::*;
+use crate::game::leaderboard::*;
+use crate::game::point_grid::PaintRenderer;
+use crate::game::profile_manager::*;
+use crate::game::progress_storage::*;
 use crate::game::stages_data::AllStagesData;
 use crate::game_ui::Book;
 use crate::texture_manager::*;
@@ -10,8 +16,20 @@ use crate::*;
 
 use wasm_bindgen::JsCast;
 
-static PANDA_DOODLE_AUDIO_SETTINGS: &str = "PandaDoodleAudioSettings";
-static PANDA_DOODLE_VIBRATION_SETTINGS: &str = "PandaDoodleVibrationSettings";
+pub static PANDA_DOODLE_ACHIEVMENTS: &str = "PandaDoodleAchievments";
+pub static PANDA_DOODLE_AUDIO_SETTINGS: &str = "PandaDoodleAudioSettings";
+pub static PANDA_DOODLE_VIBRATION_SETTINGS: &str = "PandaDoodleVibrationSettings";
+pub static PANDA_DOODLE_SHOW_FPS: &str = "show_fps_key";
+
+// Height of the game viewport frame relative to its width. Stages are this size
+// unless they set their own.
+pub const GAME_VIEWPORT_YX_RATIO: F1 = 540.0 / 480.0;
+// Screens wider than this use the landscape layout.
+const LANDSCAPE_MAX_YX_RATIO: F1 = 0.8;
+// In ui viewport widths.
+pub const LANDSCAPE_HUD_WIDTH: F1 = 180.0 / 480.0;
+// Part of the screen height taken by the game frame in the landscape layout.
+const LANDSCAPE_GAME_HEIGHT_RATIO: F1 = 0.92;
 
 #[wasm_bindgen]
 extern "C" {
@@ -25,6 +43,19 @@ pub enum UiEvent {
   LoadMainMenu,
   LoadMenuChooseStage,
   LoadGame(LoadGameParams),
+  LoadStageStats(Book),
+  LoadAchievements,
+  LoadLeaderboard(Book, usize),
+}
+
+// Where the ingame HUD (buttons and star bar) goes.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum UiLayout {
+  // Above the game board, on narrow screens.
+  Portrait,
+  // In a column beside the game board, on wide screens. `hud_position` is the
+  // top left corner of the column, in ui viewport coordinates.
+  Landscape { hud_position: F2 },
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -34,32 +65,40 @@ pub struct LoadGameParams {
 }
 
 pub struct Context {
-  pub window: Rc<web_sys::Window>,
+  // The browser parts are None in native tests, see Context::new_headless.
+  pub window: Option<Rc<web_sys::Window>>,
 
   pub platform_manager: PlatformManager,
   pub running_as_pwa: bool,
 
-  pub canvas: web_sys::HtmlCanvasElement,
+  pub canvas: Option<web_sys::HtmlCanvasElement>,
   pub screen_size: F2,
   pub device_pixel_ratio: F1,
+  pub safe_area_insets: SafeAreaInsets,
   pub screen_viewport: Rc<Viewport>,
   pub ui_viewport: Rc<Viewport>,
   pub game_viewport: Rc<Viewport>,
+  pub ui_layout: UiLayout,
 
   pub texture_manager: TextureManager,
   pub draw_manager: DrawManager,
-  pub draw_depths: DrawDepths,
+  pub draw_layers: DrawLayers,
+  pub paint_renderer: PaintRenderer,
 
   pub audio_manager: AudioManager,
   pub audio_player: AudioPlayer,
 
   pub vibration_manager: VibrationManager,
 
-  pub local_storage: Rc<web_sys::Storage>,
+  pub local_storage: Rc<dyn KeyValueStorage>,
+  pub profile_manager: ProfileManager,
+  pub sync_config: Option<SyncConfig>,
   pub achievments_manager: AchievmentsManager,
+  pub achievement_tracker: AchievementTracker,
+  pub leaderboard_provider: Box<dyn LeaderboardProvider>,
   pub stages_data: AllStagesData,
 
-  pub history: Rc<web_sys::History>,
+  pub history: Option<Rc<web_sys::History>>,
 
   pub dt_ms: F1,
   pub latest_timestamp_ms: F1,
@@ -69,6 +108,7 @@ pub struct Context {
   pub artificial_input_events: Rc<EventManager<InputEvent>>,
   pub ui_events: Rc<EventManager<UiEvent>>,
   pub menu_choose_stages_events: Rc<EventManager<MenuChooseStageEvent>>,
+  pub gameplay_events: Rc<EventManager<GameplayEvent>>,
   pub stage_opacity: Shared<F1>,
   pub game_mode: RefCell<Option<Rc<GameMode>>>,
 
@@ -83,78 +123,162 @@ impl Context {
 
     let document = Rc::new(window.document().unwrap());
 
-    let canvas = document
-      .create_element("canvas")
-      .expect("document.create_element failed")
-      .dyn_into::<web_sys::HtmlCanvasElement>()
-      .expect("dyn_into::<web_sys::HtmlCanvasElement> failed");
-    document
-      .body()
-      .unwrap()
-      .append_child(&canvas)
-      .expect("document.body.append_child failed");
-
     let screen_size = get_screen_size(&window);
     let device_pixel_ratio = get_device_pixel_ratio(&window);
-    set_canvas_size(&canvas, &screen_size, &device_pixel_ratio);
-    let canvas_context = Rc::new(
-      canvas
-        .get_context("2d")
-        .expect("canvas.get_context 2d failed")
-        .unwrap()
-        .dyn_into::<web_sys::CanvasRenderingContext2d>()
-        .expect("dyn_into::<web_sys::CanvasRenderingContext2d> failed"),
-    );
-    let (screen_viewport, ui_viewport, game_viewport) =
+    let (canvas, screen_draw_backend) =
+      create_screen_draw_backend(&window, &document, &screen_size, &device_pixel_ratio);
+    let (screen_viewport, ui_viewport, game_viewport, ui_layout) =
       generate_viewports(&screen_size, &device_pixel_ratio);
+    let mut draw_manager = DrawManager::new(screen_draw_backend, &screen_size, &device_pixel_ratio);
+    // `debug_dirty_regions=1` tints the regions redrawn in each frame.
+    draw_manager.set_show_dirty_regions(
+      HttpUtil::get_query_param(&window, "debug_dirty_regions").as_deref() == Some("1"),
+    );
 
-    let local_storage = Rc::new(
+    let local_storage: Rc<dyn KeyValueStorage> = Rc::new(
       window
         .local_storage()
         .expect("window.local_storage failed")
         .unwrap(),
     );
 
+    let profile_manager = ProfileManager::new(local_storage.clone());
+    let sync_config = SyncConfig::load(&window, local_storage.as_ref());
+
     let vibration_manager = VibrationManager::new(
-      &window,
+      Some(&window),
       platform_manager.ios(),
       local_storage.clone(),
-      PANDA_DOODLE_VIBRATION_SETTINGS.to_string(),
+      profile_manager.get_storage_key(PANDA_DOODLE_VIBRATION_SETTINGS),
     );
 
     return Context {
-      window: window.clone(),
+      window: Some(window.clone()),
       platform_manager,
       running_as_pwa,
 
-      canvas,
+      canvas: Some(canvas),
       screen_size,
       device_pixel_ratio,
+      safe_area_insets: SafeAreaInsets::from_window(&window),
       screen_viewport: screen_viewport,
       ui_viewport: ui_viewport,
       game_viewport: game_viewport,
+      ui_layout,
 
-      texture_manager: TextureManager::new(document),
-      draw_manager: DrawManager::new(
-        Canvas2dDrawBackend::new(canvas_context),
-        &screen_size,
-        &device_pixel_ratio,
-      ),
-      draw_depths: DrawDepths::new(),
+      texture_manager: TextureManager::new(document, device_pixel_ratio),
+      draw_manager: draw_manager,
+      draw_layers: DrawLayers::new(),
+      paint_renderer: PaintRenderer::from_query_param(&window),
 
       audio_manager: AudioManager::new(),
       audio_player: AudioPlayer::new(
         local_storage.clone(),
-        PANDA_DOODLE_AUDIO_SETTINGS.to_string(),
+        profile_manager.get_storage_key(PANDA_DOODLE_AUDIO_SETTINGS),
       ),
 
       vibration_manager: vibration_manager,
 
       local_storage: local_storage.clone(),
-      achievments_manager: AchievmentsManager::new(local_storage.clone()),
+      achievments_manager: AchievmentsManager::new(
+        create_progress_storage(
+          Some(&window),
+          &local_storage,
+          profile_manager.get_storage_key(PANDA_DOODLE_ACHIEVMENTS),
+          &sync_config,
+        ),
+        load_device_id(local_storage.as_ref()),
+      ),
+      sync_config,
+      achievement_tracker: AchievementTracker::new(),
+      leaderboard_provider: create_leaderboard_provider(&window, &local_storage),
+      stages_data: AllStagesData::new(),
+
+      history: Some(Rc::new(window.history().expect("window.history failed"))),
+
+      dt_ms: 0.0,
+      latest_timestamp_ms: 0.0,
+      draw_cycle: 0,
+
+      ui_manager_events: EventManager::new(),
+      artificial_input_events: EventManager::new(),
+      ui_events: EventManager::new(),
+      menu_choose_stages_events: EventManager::new(),
+      gameplay_events: EventManager::new(),
+      stage_opacity: Shared::new(1.0),
+      game_mode: RefCell::new(None),
+
+      show_fps: LocalStorageUtil::read(
+        local_storage.as_ref(),
+        &profile_manager.get_storage_key(PANDA_DOODLE_SHOW_FPS),
+      )
+      .unwrap_or(false),
+      profile_manager: profile_manager,
+    };
+  }
+
+  // Context of native tests: no browser, drawing with the software backend into
+  // the returned image, textures read from `static/` and storage kept in
+  // memory.
+  #[cfg(all(test, not(target_arch = "wasm32")))]
+  pub fn new_headless(screen_size: &F2) -> (Context, Rc<RefCell<SoftwareImage>>) {
+    let device_pixel_ratio = 1.0;
+    let draw_backend = SoftwareDrawBackend::new(
+      screen_size,
+      std::path::Path::new(env!("CARGO_MANIFEST_DIR")),
+    );
+    let image = draw_backend.get_image();
+    let (screen_viewport, ui_viewport, game_viewport, ui_layout) =
+      generate_viewports(screen_size, &device_pixel_ratio);
+    let local_storage: Rc<dyn KeyValueStorage> = Rc::new(MemoryStorage::new());
+    let profile_manager = ProfileManager::new(local_storage.clone());
+
+    let context = Context {
+      window: None,
+      platform_manager: PlatformManager::new_headless(),
+      running_as_pwa: false,
+
+      canvas: None,
+      screen_size: *screen_size,
+      device_pixel_ratio,
+      safe_area_insets: SafeAreaInsets::default(),
+      screen_viewport,
+      ui_viewport,
+      game_viewport,
+      ui_layout,
+
+      texture_manager: TextureManager::new_headless(),
+      draw_manager: DrawManager::new(Box::new(draw_backend), screen_size, &device_pixel_ratio),
+      draw_layers: DrawLayers::new(),
+      paint_renderer: PaintRenderer::Stamps,
+
+      audio_manager: AudioManager::new(),
+      audio_player: AudioPlayer::new(
+        local_storage.clone(),
+        profile_manager.get_storage_key(PANDA_DOODLE_AUDIO_SETTINGS),
+      ),
+
+      vibration_manager: VibrationManager::new(
+        None,
+        false,
+        local_storage.clone(),
+        profile_manager.get_storage_key(PANDA_DOODLE_VIBRATION_SETTINGS),
+      ),
+
+      local_storage: local_storage.clone(),
+      achievments_manager: AchievmentsManager::new(
+        Box::new(LocalProgressStorage::new(
+          local_storage.clone(),
+          profile_manager.get_storage_key(PANDA_DOODLE_ACHIEVMENTS),
+        )),
+        String::from("headless"),
+      ),
+      sync_config: None,
+      achievement_tracker: AchievementTracker::new(),
+      leaderboard_provider: Box::new(LocalLeaderboardProvider::new(local_storage)),
       stages_data: AllStagesData::new(),
 
-      history: Rc::new(window.history().expect("window.history failed")),
+      history: None,
 
       dt_ms: 0.0,
       latest_timestamp_ms: 0.0,
@@ -164,11 +288,53 @@ impl Context {
       artificial_input_events: EventManager::new(),
       ui_events: EventManager::new(),
       menu_choose_stages_events: EventManager::new(),
+      gameplay_events: EventManager::new(),
       stage_opacity: Shared::new(1.0),
       game_mode: RefCell::new(None),
 
-      show_fps: LocalStorageUtil::read(local_storage.as_ref(), "show_fps_key").unwrap_or(false),
+      show_fps: false,
+      profile_manager,
     };
+    return (context, image);
+  }
+
+  // Starts a frame `dt_ms` after the previous one, like the game loop does.
+  #[cfg(all(test, not(target_arch = "wasm32")))]
+  pub fn next_frame(&mut self, dt_ms: F1) {
+    self.dt_ms = dt_ms;
+    self.latest_timestamp_ms += dt_ms;
+    self.draw_cycle += 1;
+  }
+
+  // Reloads every per-profile manager from the storage keys of `profile_id`.
+  pub fn switch_profile(&mut self, profile_id: u32) {
+    self.profile_manager.set_active_profile(profile_id);
+    self.achievments_manager = AchievmentsManager::new(
+      create_progress_storage(
+        self.window.as_ref(),
+        &self.local_storage,
+        self
+          .profile_manager
+          .get_storage_key(PANDA_DOODLE_ACHIEVMENTS),
+        &self.sync_config,
+      ),
+      load_device_id(self.local_storage.as_ref()),
+    );
+    self.audio_player.set_local_storage_key(
+      self
+        .profile_manager
+        .get_storage_key(PANDA_DOODLE_AUDIO_SETTINGS),
+    );
+    self.vibration_manager.set_local_storage_key(
+      self
+        .profile_manager
+        .get_storage_key(PANDA_DOODLE_VIBRATION_SETTINGS),
+    );
+    self.show_fps = LocalStorageUtil::read(
+      self.local_storage.as_ref(),
+      &self.profile_manager.get_storage_key(PANDA_DOODLE_SHOW_FPS),
+    )
+    .unwrap_or(false);
   }
 
   pub fn update_timestamp(&mut self, timestamp_ms: F1) {
@@ -183,18 +349,51 @@ impl Context {
     return self.screen_size * self.device_pixel_ratio;
   }
 
+  // Top left corner of the ingame HUD in ui viewport coordinates, moved out of
+  // the notch and rounded corners.
+  pub fn get_hud_origin(&self) -> F2 {
+    let safe_top_left = self.get_ui_viewport_safe_top_left();
+    return match self.ui_layout {
+      UiLayout::Portrait => F2 {
+        x: 0.0,
+        y: safe_top_left.y.max(0.0),
+      },
+      UiLayout::Landscape { hud_position } => F2 {
+        x: hud_position
+          .x
+          .min(self.get_ui_viewport_safe_bottom_right().x - LANDSCAPE_HUD_WIDTH),
+        y: hud_position.y.max(safe_top_left.y),
+      },
+    };
+  }
+
+  pub fn update_game_camera<F: FnOnce(&mut Camera)>(&mut self, update: F) {
+    let mut camera = self.game_viewport.get_camera();
+    update(&mut camera);
+    if camera != self.game_viewport.get_camera() {
+      self.game_viewport = Rc::new(self.game_viewport.with_camera(&camera));
+    }
+  }
+
   pub fn check_screen_updated(&mut self) {
-    let screen_size = get_screen_size(&self.window);
-    let device_pixel_ratio = get_device_pixel_ratio(&self.window);
+    let (window, canvas) = match (&self.window, &self.canvas) {
+      (Some(window), Some(canvas)) => (window, canvas),
+      _ => return,
+    };
+    let screen_size = get_screen_size(window);
+    let device_pixel_ratio = get_device_pixel_ratio(window);
     if screen_size != self.screen_size || device_pixel_ratio != self.device_pixel_ratio {
       self.screen_size = screen_size;
       self.device_pixel_ratio = device_pixel_ratio;
-      set_canvas_size(&self.canvas, &screen_size, &device_pixel_ratio);
-      let (screen_viewport, ui_viewport, game_viewport) =
+      // Insets change with the orientation of the device.
+      self.safe_area_insets = SafeAreaInsets::from_window(window);
+      set_canvas_size(canvas, &screen_size, &device_pixel_ratio);
+      let (screen_viewport, ui_viewport, game_viewport, ui_layout) =
         generate_viewports(&screen_size, &device_pixel_ratio);
       self.screen_viewport = screen_viewport;
       self.ui_viewport = ui_viewport;
-      self.game_viewport = game_viewport;
+      self.ui_layout = ui_layout;
+      self.game_viewport = Rc::new(game_viewport.with_camera(&self.game_viewport.get_camera()));
       self
         .draw_manager
         .update_screen_size(&screen_size, &device_pixel_ratio);
@@ -215,6 +414,57 @@ fn get_device_pixel_ratio(window: &web_sys::Window) -> F1 {
   return window.device_pixel_ratio() as F1;
 }
 
+fn create_canvas(
+  document: &web_sys::Document,
+  screen_size: &F2,
+  device_pixel_ratio: &F1,
+) -> web_sys::HtmlCanvasElement {
+  let canvas = document
+    .create_element("canvas")
+    .expect("document.create_element failed")
+    .dyn_into::<web_sys::HtmlCanvasElement>()
+    .expect("dyn_into::<web_sys::HtmlCanvasElement> failed");
+  document
+    .body()
+    .unwrap()
+    .append_child(&canvas)
+    .expect("document.body.append_child failed");
+  set_canvas_size(&canvas, screen_size, device_pixel_ratio);
+  return canvas;
+}
+
+// Prefers WebGL unless `renderer=canvas2d` is passed as a query parameter. A
+// canvas keeps the first context type requested from it, so falling back to
+// Canvas2D needs a new canvas.
+fn create_screen_draw_backend(
+  window: &web_sys::Window,
+  document: &web_sys::Document,
+  screen_size: &F2,
+  device_pixel_ratio: &F1,
+) -> (web_sys::HtmlCanvasElement, Box<dyn DrawBackend>) {
+  let canvas = create_canvas(document, screen_size, device_pixel_ratio);
+  if HttpUtil::get_query_param(window, "renderer").as_deref() != Some("canvas2d") {
+    if let Some(webgl_draw_backend) = WebGlDrawBackend::new(&canvas) {
+      console_log_with_div!("renderer: webgl");
+      return (canvas, Box::new(webgl_draw_backend));
+    }
+    console_log_with_div!("WebGL unavailable, falling back to canvas2d");
+    canvas.remove();
+  }
+
+  let canvas = create_canvas(document, screen_size, device_pixel_ratio);
+  let canvas_context = Rc::new(
+    canvas
+      .get_context("2d")
+      .expect("canvas.get_context 2d failed")
+      .unwrap()
+      .dyn_into::<web_sys::CanvasRenderingContext2d>()
+      .expect("dyn_into::<web_sys::CanvasRenderingContext2d> failed"),
+  );
+  console_log_with_div!("renderer: canvas2d");
+  return (canvas, Box::new(Canvas2dDrawBackend::new(canvas_context)));
+}
+
 fn set_canvas_size(canvas: &web_sys::HtmlCanvasElement, size: &F2, device_pixel_ratio: &F1) {
   canvas
     .style()
@@ -234,7 +484,7 @@ fn set_canvas_size(canvas: &web_sys::HtmlCanvasElement, size: &F2, device_pixel_
 fn generate_viewports(
   screen_size: &F2,
   device_pixel_ratio: &F1,
-) -> (Rc<Viewport>, Rc<Viewport>, Rc<Viewport>) {
+) -> (Rc<Viewport>, Rc<Viewport>, Rc<Viewport>, UiLayout) {
   let screen_viewport = Rc::new(Viewport::new(
     &F2 { x: 0.0, y: 0.0 },
     screen_size,
@@ -268,10 +518,38 @@ fn generate_viewports(
     &device_pixel_ratio,
   ));
 
-  let game_y_x_ratio = 540.0 / 480.0;
+  if screen_y_x_ratio < LANDSCAPE_MAX_YX_RATIO {
+    // The board takes the height of the screen, with the HUD column on its right
+    // and the same space on its left to keep it centered.
+    let hud_width = LANDSCAPE_HUD_WIDTH * ui_frame_size.x;
+    let game_frame_width = (screen_size.y * LANDSCAPE_GAME_HEIGHT_RATIO / GAME_VIEWPORT_YX_RATIO)
+      .min(screen_size.x - 2.0 * hud_width);
+    let game_frame_size = F2 {
+      x: game_frame_width,
+      y: game_frame_width * GAME_VIEWPORT_YX_RATIO,
+    };
+    let game_frame_position = (screen_size - game_frame_size) * 0.5;
+    let game_viewport = Rc::new(Viewport::new(
+      &game_frame_position,
+      &game_frame_size,
+      &screen_size,
+      &device_pixel_ratio,
+    ));
+    let hud_position = ui_viewport.screen_to_viewport(&F2 {
+      x: game_frame_position.x + game_frame_size.x,
+      y: game_frame_position.y,
+    });
+    return (
+      screen_viewport,
+      ui_viewport,
+      game_viewport,
+      UiLayout::Landscape { hud_position },
+    );
+  }
+
   let game_frame_size = F2 {
     x: ui_frame_size.x,
-    y: ui_frame_size.x * game_y_x_ratio,
+    y: ui_frame_size.x * GAME_VIEWPORT_YX_RATIO,
   };
   let game_frame_position = F2 {
     x: screen_size.x / 2.0 - game_frame_size.x / 2.0,
@@ -283,7 +561,48 @@ fn generate_viewports(
     &screen_size,
     &device_pixel_ratio,
   ));
-  return (screen_viewport, ui_viewport, game_viewport);
+  return (
+    screen_viewport,
+    ui_viewport,
+    game_viewport,
+    UiLayout::Portrait,
+  );
+}
+
+// Progress stays in local storage unless a sync server is configured.
+fn create_progress_storage(
+  window: Option<&Rc<web_sys::Window>>,
+  local_storage: &Rc<dyn KeyValueStorage>,
+  local_storage_key: String,
+  sync_config: &Option<SyncConfig>,
+) -> Box<dyn ProgressStorage> {
+  return match sync_config {
+    Some(sync_config) => Box::new(RemoteProgressStorage::new(
+      window.expect("sync_config without window").clone(),
+      local_storage.clone(),
+      local_storage_key,
+      sync_config,
+    )),
+    None => Box::new(LocalProgressStorage::new(
+      local_storage.clone(),
+      local_storage_key,
+    )),
+  };
+}
+
+// Leaderboards rank the local profiles unless a leaderboard server is
+// configured.
+fn create_leaderboard_provider(
+  window: &Rc<web_sys::Window>,
+  local_storage: &Rc<dyn KeyValueStorage>,
+) -> Box<dyn LeaderboardProvider> {
+  return match LeaderboardConfig::load(window, local_storage.as_ref()) {
+    Some(leaderboard_config) => Box::new(HttpLeaderboardProvider::new(
+      window.clone(),
+      &leaderboard_config,
+    )),
+    None => Box::new(LocalLeaderboardProvider::new(local_storage.clone())),
+  };
 }
 
 fn is_running_as_pwa(ios: bool) -> bool {
@@ -332,6 +651,10 @@ impl ContextTrait for Context {
     return &self.screen_size;
   }
 
+  fn get_safe_area_insets(&self) -> &SafeAreaInsets {
+    return &self.safe_area_insets;
+  }
+
   fn draw_ui_viewport(&mut self, args: DrawImageArgs) {
     self.draw_manager.draw_viewport(&self.ui_viewport, args);
   }
@@ -346,8 +669,12 @@ impl ContextTrait for Context {
     return self.texture_manager.pixel.clone();
   }
 
-  fn get_front_board_depth(&self) -> F1 {
-    return self.draw_depths.front_board;
+  fn get_ui_layer(&self) -> RenderLayer {
+    return self.draw_layers.ui;
+  }
+
+  fn get_front_board_layer(&self) -> RenderLayer {
+    return self.draw_layers.front_board;
   }
 
   fn get_ui_manager_events(&self) -> &Rc<EventManager<UiManagerEventGeneric<Self>>> {
@@ -362,11 +689,11 @@ impl ContextTrait for Context {
     return &self.platform_manager;
   }
 
-  fn window(&self) -> &web_sys::Window {
-    return &self.window;
+  fn window(&self) -> Option<&web_sys::Window> {
+    return self.window.as_deref();
   }
 
-  fn local_storage(&self) -> &web_sys::Storage {
+  fn local_storage(&self) -> &dyn KeyValueStorage {
     return self.local_storage.as_ref();
   }
 
@@ -376,6 +703,10 @@ impl ContextTrait for Context {
 
   fn toggle_show_fps(&mut self) {
     self.show_fps = !self.show_fps;
-    LocalStorageUtil::write(self.local_storage(), "show_fps_key", &self.show_fps);
+    LocalStorageUtil::write(
+      self.local_storage(),
+      &self.profile_manager.get_storage_key(PANDA_DOODLE_SHOW_FPS),
+      &self.show_fps,
+    );
   }
 }
@@ -0,0 +1,64 @@
+use crate::engine::*;
+
+// From the back to the front.
+pub struct DrawLayers {
+  pub board: RenderLayer,
+  pub background: RenderLayer,
+  pub background_decoration: RenderLayer,
+  // Paint points are drawn in the order they were painted.
+  pub path: RenderLayer,
+  pub time_lapse: RenderLayer,
+  pub mirror: RenderLayer,
+  pub portal_glow: RenderLayer,
+  pub barrier: RenderLayer,
+  pub goal: RenderLayer,
+  pub goal_collect: RenderLayer,
+  pub goal_wrong_reach: RenderLayer,
+  pub source_back: RenderLayer,
+  pub source: RenderLayer,
+  pub smoke: RenderLayer,
+  pub source_label: RenderLayer,
+  pub sparkle: RenderLayer,
+  pub hud: RenderLayer,
+  // Screen fade and overlay background, below the UI elements.
+  pub front_board: RenderLayer,
+  pub ui: RenderLayer,
+  pub glow_bar: RenderLayer,
+  pub black_border: RenderLayer,
+  pub toast: RenderLayer,
+}
+
+impl DrawLayers {
+  pub fn new() -> DrawLayers {
+    let mut index = 0;
+    let mut get_new_layer = || -> RenderLayer {
+      index += 1;
+      return RenderLayer(index);
+    };
+
+    return DrawLayers {
+      board: get_new_layer(),
+      background: get_new_layer(),
+      background_decoration: get_new_layer(),
+      path: get_new_layer(),
+      time_lapse: get_new_layer(),
+      mirror: get_new_layer(),
+      portal_glow: get_new_layer(),
+      barrier: get_new_layer(),
+      goal: get_new_layer(),
+      goal_collect: get_new_layer(),
+      goal_wrong_reach: get_new_layer(),
+      source_back: get_new_layer(),
+      source: get_new_layer(),
+      smoke: get_new_layer(),
+      source_label: get_new_layer(),
+      sparkle: get_new_layer(),
+      hud: get_new_layer(),
+      front_board: get_new_layer(),
+      ui: get_new_layer(),
+      glow_bar: get_new_layer(),
+      black_border: get_new_layer(),
+      toast: get_new_layer(),
+    };
+  }
+}
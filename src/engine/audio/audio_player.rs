@@ -1,7 +1,7 @@
 use crate::engine::*;
 
 pub struct AudioPlayer {
-  local_storage: Rc<web_sys::Storage>,
+  local_storage: Rc<dyn KeyValueStorage>,
   local_storage_key: String,
   song_playing: Option<Rc<Audio>>,
   pub settings: AudioPlayerSettings,
@@ -15,8 +15,12 @@ pub struct AudioPlayerSettings {
   pub sound_muted: bool,
 }
 
+impl VersionedData for AudioPlayerSettings {
+  const VERSION: u32 = 1;
+}
+
 fn load_audio_settings(
-  local_storage: &web_sys::Storage,
+  local_storage: &dyn KeyValueStorage,
   local_storage_key: &str,
 ) -> AudioPlayerSettings {
   return LocalStorageUtil::read(local_storage, local_storage_key).unwrap_or_else(
@@ -32,7 +36,7 @@ fn load_audio_settings(
 }
 
 fn store_audio_settings(
-  local_storage: &web_sys::Storage,
+  local_storage: &dyn KeyValueStorage,
   settings: &AudioPlayerSettings,
   local_storage_key: &str,
 ) {
@@ -40,15 +44,27 @@ fn store_audio_settings(
 }
 
 impl AudioPlayer {
-  pub fn new(local_storage: Rc<web_sys::Storage>, local_storage_key: String) -> AudioPlayer {
+  pub fn new(local_storage: Rc<dyn KeyValueStorage>, local_storage_key: String) -> AudioPlayer {
     return AudioPlayer {
       local_storage: local_storage.clone(),
       local_storage_key: local_storage_key.clone(),
       song_playing: None,
-      settings: load_audio_settings(&local_storage, &local_storage_key),
+      settings: load_audio_settings(local_storage.as_ref(), &local_storage_key),
     };
   }
 
+  // Switches to the settings stored under `local_storage_key`, e.g. when
+  // another player profile becomes active.
+  pub fn set_local_storage_key(&mut self, local_storage_key: String) {
+    self.settings = load_audio_settings(self.local_storage.as_ref(), &local_storage_key);
+    self.local_storage_key = local_storage_key;
+    if self.settings.song_muted {
+      self.stop_song();
+    } else {
+      self.resume_song();
+    }
+  }
+
   pub fn play_song(&mut self, song: &Rc<Audio>) {
     if let Some(song_playing) = self.song_playing.as_ref() {
       if song_playing.is_playing() && RcUtil::eq_ptr(song_playing, song) {
@@ -88,7 +104,11 @@ impl AudioPlayer {
 
   pub fn set_song_volume(&mut self, song_volume: F1) {
     self.settings.song_volume = song_volume;
-    store_audio_settings(&self.local_storage, &self.settings, &self.local_storage_key);
+    store_audio_settings(
+      self.local_storage.as_ref(),
+      &self.settings,
+      &self.local_storage_key,
+    );
     if let Some(song_playing) = self.song_playing.as_ref() {
       song_playing.set_volume(song_volume);
     }
@@ -100,7 +120,11 @@ impl AudioPlayer {
 
   pub fn toggle_mute_song(&mut self) {
     self.settings.song_muted = !self.settings.song_muted;
-    store_audio_settings(&self.local_storage, &self.settings, &self.local_storage_key);
+    store_audio_settings(
+      self.local_storage.as_ref(),
+      &self.settings,
+      &self.local_storage_key,
+    );
     if self.settings.song_muted {
       self.stop_song();
     } else {
@@ -110,7 +134,11 @@ impl AudioPlayer {
 
   pub fn set_sound_volume(&mut self, sound_volume: F1) {
     self.settings.sound_volume = sound_volume;
-    store_audio_settings(&self.local_storage, &self.settings, &self.local_storage_key);
+    store_audio_settings(
+      self.local_storage.as_ref(),
+      &self.settings,
+      &self.local_storage_key,
+    );
   }
 
   pub fn get_sound_volume(&self) -> F1 {
@@ -119,6 +147,10 @@ impl AudioPlayer {
 
   pub fn toggle_mute_sound(&mut self) {
     self.settings.sound_muted = !self.settings.sound_muted;
-    store_audio_settings(&self.local_storage, &self.settings, &self.local_storage_key);
+    store_audio_settings(
+      self.local_storage.as_ref(),
+      &self.settings,
+      &self.local_storage_key,
+    );
   }
 }
@@ -24,6 +24,25 @@ where
 
   fn get_screen_size(&self) -> &F2;
 
+  fn get_safe_area_insets(&self) -> &SafeAreaInsets;
+
+  // Corners of the part of the screen free of notches and home indicators.
+  fn get_ui_viewport_safe_top_left(&self) -> F2 {
+    let insets = self.get_safe_area_insets();
+    return self.get_ui_viewport().screen_to_viewport(&F2 {
+      x: insets.left,
+      y: insets.top,
+    });
+  }
+
+  fn get_ui_viewport_safe_bottom_right(&self) -> F2 {
+    let insets = self.get_safe_area_insets();
+    return self.get_ui_viewport().screen_to_viewport(&F2 {
+      x: self.get_screen_size().x - insets.right,
+      y: self.get_screen_size().y - insets.bottom,
+    });
+  }
+
   fn get_ui_viewport_screen_center(&self) -> F2 {
     return self
       .get_ui_viewport()
@@ -38,19 +57,25 @@ where
 
   fn get_pixel_texture(&self) -> Rc<Texture>;
 
-  fn get_front_board_depth(&self) -> F1;
+  // Layer of the UI elements that don't set their own.
+  fn get_ui_layer(&self) -> RenderLayer;
+
+  // Layer of the fade and overlay sprites.
+  fn get_front_board_layer(&self) -> RenderLayer;
 
   fn get_ui_manager_events(&self) -> &Rc<EventManager<UiManagerEventGeneric<Self>>>;
 
   fn play_sound(&mut self, sound: &Rc<Audio>);
 
-  fn window(&self) -> &web_sys::Window;
+  // None where there is no browser, like in tests.
+  fn window(&self) -> Option<&web_sys::Window>;
 
   fn get_platform_manager(&self) -> &PlatformManager;
 
   fn alert(&self, message: &str) {
     self
       .window()
+      .expect("alert without window")
       .alert_with_message(message)
       .expect("window.alert_with_message failed");
   }
@@ -67,6 +92,7 @@ where
     // console_log_with_div!("going fullscreen");
     self
       .window()
+      .expect("fullscreen without window")
       .document()
       .unwrap()
       .document_element()
@@ -75,7 +101,7 @@ where
       .expect("request_fullscreen failed");
   }
 
-  fn local_storage(&self) -> &web_sys::Storage;
+  fn local_storage(&self) -> &dyn KeyValueStorage;
 
   fn show_fps(&self) -> bool;
   fn toggle_show_fps(&mut self);
@@ -0,0 +1,113 @@
+use crate::engine::*;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+
+const OBJECT_URL_REVOKE_DELAY_MS: i32 = 1000;
+
+pub struct FileTransferUtil {}
+
+impl FileTransferUtil {
+  pub fn download_text(window: &web_sys::Window, file_name: &str, mime_type: &str, text: &str) {
+    let blob_parts = js_sys::Array::of1(&JsValue::from_str(text));
+    let mut blob_options = web_sys::BlobPropertyBag::new();
+    blob_options.type_(mime_type);
+    let blob = web_sys::Blob::new_with_str_sequence_and_options(&blob_parts, &blob_options)
+      .expect("Blob::new_with_str_sequence_and_options failed");
+    let url =
+      web_sys::Url::create_object_url_with_blob(&blob).expect("Url::create_object_url failed");
+    FileTransferUtil::download_object_url(window, file_name, url);
+  }
+
+  pub fn download_bytes(window: &web_sys::Window, file_name: &str, mime_type: &str, bytes: &[u8]) {
+    let blob_parts = js_sys::Array::of1(&js_sys::Uint8Array::from(bytes));
+    let mut blob_options = web_sys::BlobPropertyBag::new();
+    blob_options.type_(mime_type);
+    let blob = web_sys::Blob::new_with_u8_array_sequence_and_options(&blob_parts, &blob_options)
+      .expect("Blob::new_with_u8_array_sequence_and_options failed");
+    let url =
+      web_sys::Url::create_object_url_with_blob(&blob).expect("Url::create_object_url failed");
+    FileTransferUtil::download_object_url(window, file_name, url);
+  }
+
+  // Saves the canvas contents as a PNG file.
+  pub fn download_canvas_png(
+    window: &web_sys::Window,
+    file_name: &str,
+    canvas: &web_sys::HtmlCanvasElement,
+  ) {
+    let url = canvas
+      .to_data_url_with_type("image/png")
+      .expect("canvas.to_data_url_with_type failed");
+    FileTransferUtil::download_url(window, file_name, &url);
+  }
+
+  // Firefox and Safari cancel downloads whose object URL is revoked while the
+  // click is handled, so the URL is only revoked once it has been picked up.
+  fn download_object_url(window: &web_sys::Window, file_name: &str, url: String) {
+    FileTransferUtil::download_url(window, file_name, &url);
+    let closure = Closure::once(Box::new(move || {
+      web_sys::Url::revoke_object_url(&url).expect("Url::revoke_object_url failed");
+    }) as Box<dyn FnOnce()>);
+    window
+      .set_timeout_with_callback_and_timeout_and_arguments_0(
+        closure.as_ref().unchecked_ref(),
+        OBJECT_URL_REVOKE_DELAY_MS,
+      )
+      .expect("window.set_timeout failed");
+    closure.forget();
+  }
+
+  // Clicks a temporary link, attached to the page since Firefox ignores clicks
+  // on detached ones.
+  fn download_url(window: &web_sys::Window, file_name: &str, url: &str) {
+    let document = window.document().expect("window.document failed");
+    let anchor = document
+      .create_element("a")
+      .expect("document.create_element(a) failed")
+      .dyn_into::<web_sys::HtmlAnchorElement>()
+      .expect("dyn_into::<HtmlAnchorElement> failed");
+    anchor.set_href(url);
+    anchor.set_download(file_name);
+    anchor
+      .style()
+      .set_property("display", "none")
+      .expect("anchor.style().set_property failed");
+    let body = document.body().expect("document.body failed");
+    body
+      .append_child(&anchor)
+      .expect("body.append_child failed");
+    anchor.click();
+    anchor.remove();
+  }
+
+  // Opens the browser file picker. The contents of the chosen file are added to
+  // `events` asynchronously, so they should be consumed from an update loop.
+  pub fn pick_text_file(window: &web_sys::Window, accept: &str, events: Rc<EventManager<String>>) {
+    let input = window
+      .document()
+      .expect("window.document failed")
+      .create_element("input")
+      .expect("document.create_element(input) failed")
+      .dyn_into::<web_sys::HtmlInputElement>()
+      .expect("dyn_into::<HtmlInputElement> failed");
+    input.set_type("file");
+    input.set_accept(accept);
+
+    let input_clone = input.clone();
+    let closure = Closure::wrap(Box::new(move || {
+      if let Some(file) = input_clone.files().and_then(|files| files.get(0)) {
+        let events = events.clone();
+        wasm_bindgen_futures::spawn_local(async move {
+          if let Ok(text) = wasm_bindgen_futures::JsFuture::from(file.text()).await {
+            if let Some(text) = text.as_string() {
+              events.add_event(text);
+            }
+          }
+        });
+      }
+    }) as Box<dyn FnMut()>);
+    input.set_onchange(Some(closure.as_ref().unchecked_ref()));
+    closure.forget();
+    input.click();
+  }
+}
@@ -33,7 +33,8 @@ impl FpsTracker {
           text: String::from(format!("fps {:3.1}", fps)),
           position: F2 { x: 0.98, y: 0.045 },
           font_size: 0.05,
-          depth: -150.0,
+          layer: RenderLayer::OVERLAY,
+          depth: 0.0,
           optional: DrawStringOptionalArgs {
             border: true,
             color: DrawColor {
@@ -0,0 +1,149 @@
+use crate::engine::render::software_backend::*;
+use crate::engine::*;
+use std::path::Path;
+
+// Context drawing with the software backend, for golden-image tests of UI
+// elements without a browser. It has no window, and its local storage is kept
+// in memory.
+pub struct HeadlessContext {
+  dt_ms: F1,
+  latest_timestamp_ms: F1,
+  draw_cycle: u32,
+  draw_manager: DrawManager,
+  image: Rc<RefCell<SoftwareImage>>,
+  screen_size: F2,
+  screen_viewport: Rc<Viewport>,
+  ui_viewport: Rc<Viewport>,
+  safe_area_insets: SafeAreaInsets,
+  pixel_texture: Rc<Texture>,
+  ui_manager_events: Rc<EventManager<UiManagerEventGeneric<HeadlessContext>>>,
+  platform_manager: PlatformManager,
+  local_storage: MemoryStorage,
+}
+
+impl HeadlessContext {
+  // The ui viewport, 1 unit wide, fills the whole screen.
+  pub fn new(screen_size: &F2) -> HeadlessContext {
+    let draw_backend = SoftwareDrawBackend::new(screen_size, Path::new(env!("CARGO_MANIFEST_DIR")));
+    let image = draw_backend.get_image();
+    let viewport = Rc::new(Viewport::new(
+      &F2 { x: 0.0, y: 0.0 },
+      screen_size,
+      screen_size,
+      &1.0,
+    ));
+    return HeadlessContext {
+      dt_ms: 0.0,
+      latest_timestamp_ms: 0.0,
+      draw_cycle: 0,
+      draw_manager: DrawManager::new(Box::new(draw_backend), screen_size, &1.0),
+      image,
+      screen_size: *screen_size,
+      screen_viewport: viewport.clone(),
+      ui_viewport: viewport,
+      safe_area_insets: SafeAreaInsets::default(),
+      pixel_texture: Texture::new_headless("/static/pixel.png"),
+      ui_manager_events: EventManager::new(),
+      platform_manager: PlatformManager::new_headless(),
+      local_storage: MemoryStorage::new(),
+    };
+  }
+
+  // Starts a frame `dt_ms` after the previous one, like the game loop does.
+  pub fn next_frame(&mut self, dt_ms: F1) {
+    self.dt_ms = dt_ms;
+    self.latest_timestamp_ms += dt_ms;
+    self.draw_cycle += 1;
+  }
+
+  // Executes the queued draws and compares the screen with
+  // `tests/golden/{name}.png`.
+  pub fn check_golden(&mut self, name: &str) {
+    self.draw_manager.execute_draws();
+    check_golden(name, &self.image.borrow());
+  }
+}
+
+impl ContextTrait for HeadlessContext {
+  fn get_dt(&self) -> &F1 {
+    return &self.dt_ms;
+  }
+
+  fn get_latest_timestamp(&self) -> &F1 {
+    return &self.latest_timestamp_ms;
+  }
+
+  fn get_draw_cycle(&self) -> &u32 {
+    return &self.draw_cycle;
+  }
+
+  fn draw_ui_viewport(&mut self, args: DrawImageArgs) {
+    self.draw_manager.draw_viewport(&self.ui_viewport, args);
+  }
+
+  fn draw_string_ui_viewport(&mut self, args: DrawStringArgs) {
+    self
+      .draw_manager
+      .draw_string_viewport(&self.ui_viewport, args);
+  }
+
+  fn get_draw_manager(&mut self) -> &mut DrawManager {
+    return &mut self.draw_manager;
+  }
+
+  fn get_screen_viewport(&self) -> &Rc<Viewport> {
+    return &self.screen_viewport;
+  }
+
+  fn get_ui_viewport(&self) -> &Rc<Viewport> {
+    return &self.ui_viewport;
+  }
+
+  fn get_game_viewport(&self) -> &Rc<Viewport> {
+    return &self.ui_viewport;
+  }
+
+  fn get_screen_size(&self) -> &F2 {
+    return &self.screen_size;
+  }
+
+  fn get_safe_area_insets(&self) -> &SafeAreaInsets {
+    return &self.safe_area_insets;
+  }
+
+  fn get_pixel_texture(&self) -> Rc<Texture> {
+    return self.pixel_texture.clone();
+  }
+
+  fn get_ui_layer(&self) -> RenderLayer {
+    return RenderLayer::default();
+  }
+
+  fn get_front_board_layer(&self) -> RenderLayer {
+    return RenderLayer::default();
+  }
+
+  fn get_ui_manager_events(&self) -> &Rc<EventManager<UiManagerEventGeneric<Self>>> {
+    return &self.ui_manager_events;
+  }
+
+  fn play_sound(&mut self, _sound: &Rc<Audio>) {}
+
+  fn window(&self) -> Option<&web_sys::Window> {
+    return None;
+  }
+
+  fn get_platform_manager(&self) -> &PlatformManager {
+    return &self.platform_manager;
+  }
+
+  fn local_storage(&self) -> &dyn KeyValueStorage {
+    return &self.local_storage;
+  }
+
+  fn show_fps(&self) -> bool {
+    return false;
+  }
+
+  fn toggle_show_fps(&mut self) {}
+}
@@ -0,0 +1,76 @@
+use crate::engine::*;
+use wasm_bindgen::JsCast;
+
+pub struct HttpResponse {
+  pub status: u16,
+  pub body: String,
+}
+
+pub struct HttpUtil {}
+
+impl HttpUtil {
+  pub fn get_query_param(window: &web_sys::Window, name: &str) -> Option<String> {
+    let href = window
+      .location()
+      .href()
+      .expect("window.location().href() failed");
+    return web_sys::Url::new(&href)
+      .expect("web_sys::Url::new failed")
+      .search_params()
+      .get(name);
+  }
+
+  // Sends a JSON request with fetch. The outcome is added to `events` once the
+  // response body has been read; network failures are reported as Err.
+  pub fn send_json<E: 'static>(
+    window: &web_sys::Window,
+    method: &str,
+    url: &str,
+    body: Option<String>,
+    events: Rc<EventManager<E>>,
+    to_event: Box<dyn FnOnce(Result<HttpResponse, String>) -> E>,
+  ) {
+    let mut request_init = web_sys::RequestInit::new();
+    request_init.method(method);
+    if let Some(body) = body.as_ref() {
+      request_init.body(Some(&wasm_bindgen::JsValue::from_str(body)));
+    }
+    let request = match web_sys::Request::new_with_str_and_init(url, &request_init) {
+      Ok(request) => request,
+      Err(error) => {
+        events.add_event(to_event(Err(format!("invalid request: {:?}", error))));
+        return;
+      }
+    };
+    request
+      .headers()
+      .set("Content-Type", "application/json")
+      .expect("request.headers().set failed");
+
+    let fetch_promise = window.fetch_with_request(&request);
+    wasm_bindgen_futures::spawn_local(async move {
+      let result = HttpUtil::read_response(fetch_promise).await;
+      events.add_event(to_event(result));
+    });
+  }
+
+  async fn read_response(fetch_promise: js_sys::Promise) -> Result<HttpResponse, String> {
+    let response = wasm_bindgen_futures::JsFuture::from(fetch_promise)
+      .await
+      .map_err(|error| format!("fetch failed: {:?}", error))?
+      .dyn_into::<web_sys::Response>()
+      .map_err(|_| String::from("fetch did not return a Response"))?;
+    let text_promise = response
+      .text()
+      .map_err(|error| format!("response.text failed: {:?}", error))?;
+    let body = wasm_bindgen_futures::JsFuture::from(text_promise)
+      .await
+      .map_err(|error| format!("reading response failed: {:?}", error))?
+      .as_string()
+      .unwrap_or_default();
+    return Ok(HttpResponse {
+      status: response.status(),
+      body: body,
+    });
+  }
+}
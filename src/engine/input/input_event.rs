@@ -28,10 +28,19 @@ pub struct MouseEvent {
   pub event_type: MouseEventType,
 }
 
+// Scrolling of the mouse wheel, `delta_y` in pixels and positive downwards.
+#[derive(Copy, Clone)]
+pub struct WheelEvent {
+  pub x: i32,
+  pub y: i32,
+  pub delta_y: f64,
+}
+
 #[derive(Copy, Clone)]
 pub enum InputEvent {
   Touch(TouchEvent),
   Mouse(MouseEvent),
+  Wheel(WheelEvent),
   KeyDown(u32),
   KeyUp(u32),
   BackButton,
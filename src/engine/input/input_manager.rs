@@ -37,6 +37,7 @@ impl InputManager {
     register_mouse_down(canvas, app_shared.clone());
     register_mouse_move(canvas, app_shared.clone());
     register_mouse_up(canvas, app_shared.clone());
+    register_wheel(canvas, app_shared.clone());
     register_context_menu(canvas);
   }
 
@@ -309,6 +310,36 @@ fn register_mouse_up(canvas: &web_sys::HtmlCanvasElement, app_shared: Shared<dyn
   closure.forget();
 }
 
+fn register_wheel(canvas: &web_sys::HtmlCanvasElement, app_shared: Shared<dyn AppTrait>) {
+  let closure = Closure::wrap(Box::new(move |event: web_sys::WheelEvent| {
+    event.prevent_default();
+    // Lines and pages are rare, and only the direction matters to most uses.
+    let delta_y = match event.delta_mode() {
+      web_sys::WheelEvent::DOM_DELTA_LINE => event.delta_y() * 16.0,
+      web_sys::WheelEvent::DOM_DELTA_PAGE => event.delta_y() * 400.0,
+      _ => event.delta_y(),
+    };
+    let input_event = InputEvent::Wheel(WheelEvent {
+      x: event.client_x(),
+      y: event.client_y(),
+      delta_y,
+    });
+    app_shared.borrow_mut().process_input_event(input_event);
+    if LOG_INPUT_EVENTS {
+      console_log_with_div!(
+        "wheel: {}, {}, {}",
+        event.client_x(),
+        event.client_y(),
+        delta_y,
+      );
+    }
+  }) as Box<dyn FnMut(_)>);
+  canvas
+    .add_event_listener_with_callback("wheel", closure.as_ref().unchecked_ref())
+    .expect("canvas.add_event_listener_with_callback wheel failed");
+  closure.forget();
+}
+
 fn register_context_menu(canvas: &web_sys::HtmlCanvasElement) {
   let closure = Closure::wrap(Box::new(move |event: web_sys::MouseEvent| {
     event.prevent_default();
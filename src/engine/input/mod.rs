@@ -1,3 +1,4 @@
 pub mod input_event;
 pub mod input_manager;
 pub mod input_types;
+pub mod pinch_gesture;
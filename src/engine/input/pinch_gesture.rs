@@ -0,0 +1,122 @@
+use crate::engine::*;
+
+// Pinches closer than this, in screen pixels, are ignored to avoid huge zoom
+// factors.
+const MIN_PINCH_DISTANCE: F1 = 10.0;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum PinchEvent {
+  Started,
+  // In screen coordinates. Zooms by `zoom_factor` around `anchor`, the current
+  // center of the two fingers, after panning by `pan`.
+  Moved {
+    anchor: F2,
+    zoom_factor: F1,
+    pan: F2,
+  },
+  Ended,
+}
+
+// Recognizes two finger pinches from the touches with ids 0 and 1. A pinch lasts
+// until both fingers are released, so that lifting one of them doesn't start
+// painting.
+#[derive(Default)]
+pub struct PinchGesture {
+  positions: [Option<F2>; 2],
+  pinching: bool,
+}
+
+impl PinchGesture {
+  pub fn is_pinching(&self) -> bool {
+    return self.pinching;
+  }
+
+  pub fn process_touch(&mut self, touch: &ScreenTouch) -> Option<PinchEvent> {
+    if touch.id < 0 || touch.id > 1 {
+      return None;
+    }
+    let index = touch.id as usize;
+    let previous_positions = self.positions;
+    self.positions[index] = match touch.touch_type {
+      TouchType::Released => None,
+      _ => Some(touch.position),
+    };
+
+    if !self.pinching {
+      if self.positions[0].is_some() && self.positions[1].is_some() {
+        self.pinching = true;
+        return Some(PinchEvent::Started);
+      }
+      return None;
+    }
+
+    if self.positions[0].is_none() && self.positions[1].is_none() {
+      self.pinching = false;
+      return Some(PinchEvent::Ended);
+    }
+    return match (previous_positions, self.positions) {
+      ([Some(previous_0), Some(previous_1)], [Some(position_0), Some(position_1)]) => {
+        let previous_center = (previous_0 + previous_1) * 0.5;
+        let center = (position_0 + position_1) * 0.5;
+        let previous_distance = F2::distance(&previous_0, &previous_1).max(MIN_PINCH_DISTANCE);
+        let distance = F2::distance(&position_0, &position_1).max(MIN_PINCH_DISTANCE);
+        Some(PinchEvent::Moved {
+          anchor: center,
+          zoom_factor: distance / previous_distance,
+          pan: center - previous_center,
+        })
+      }
+      _ => None,
+    };
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn touch(id: i32, x: F1, y: F1, touch_type: TouchType) -> ScreenTouch {
+    return ScreenTouch {
+      id,
+      position: F2 { x, y },
+      touch_type,
+    };
+  }
+
+  #[test]
+  fn test_pinch() {
+    let mut pinch = PinchGesture::default();
+    assert_eq!(
+      pinch.process_touch(&touch(0, 100.0, 100.0, TouchType::Pressed)),
+      None
+    );
+    assert_eq!(
+      pinch.process_touch(&touch(1, 200.0, 100.0, TouchType::Pressed)),
+      Some(PinchEvent::Started)
+    );
+    assert_eq!(
+      pinch.process_touch(&touch(1, 300.0, 100.0, TouchType::Moved)),
+      Some(PinchEvent::Moved {
+        anchor: F2 { x: 200.0, y: 100.0 },
+        zoom_factor: 2.0,
+        pan: F2 { x: 50.0, y: 0.0 },
+      })
+    );
+
+    // Still pinching with a single finger down.
+    assert_eq!(
+      pinch.process_touch(&touch(1, 300.0, 100.0, TouchType::Released)),
+      None
+    );
+    assert!(pinch.is_pinching());
+    assert_eq!(
+      pinch.process_touch(&touch(0, 120.0, 100.0, TouchType::Moved)),
+      None
+    );
+    assert_eq!(
+      pinch.process_touch(&touch(0, 120.0, 100.0, TouchType::Released)),
+      Some(PinchEvent::Ended)
+    );
+    assert!(!pinch.is_pinching());
+  }
+}
@@ -0,0 +1,57 @@
+// The string store behind LocalStorageUtil: the browser local storage, or an
+// in-memory map where there is no browser.
+pub trait KeyValueStorage {
+  fn get_item(&self, key: &str) -> Option<String>;
+
+  fn set_item(&self, key: &str, value: &str);
+
+  fn remove_item(&self, key: &str);
+}
+
+impl KeyValueStorage for web_sys::Storage {
+  fn get_item(&self, key: &str) -> Option<String> {
+    return web_sys::Storage::get_item(self, key)
+      .expect(&format!("local_storage.get_item({}) failed", key));
+  }
+
+  fn set_item(&self, key: &str, value: &str) {
+    web_sys::Storage::set_item(self, key, value).expect("local_storage.set_item failed.");
+  }
+
+  fn remove_item(&self, key: &str) {
+    web_sys::Storage::remove_item(self, key)
+      .expect(&format!("local_storage.remove_item({}) failed", key));
+  }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+pub struct MemoryStorage {
+  items: std::cell::RefCell<std::collections::HashMap<String, String>>,
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+impl MemoryStorage {
+  pub fn new() -> MemoryStorage {
+    return MemoryStorage {
+      items: std::cell::RefCell::new(std::collections::HashMap::new()),
+    };
+  }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+impl KeyValueStorage for MemoryStorage {
+  fn get_item(&self, key: &str) -> Option<String> {
+    return self.items.borrow().get(key).cloned();
+  }
+
+  fn set_item(&self, key: &str, value: &str) {
+    self
+      .items
+      .borrow_mut()
+      .insert(String::from(key), String::from(value));
+  }
+
+  fn remove_item(&self, key: &str) {
+    self.items.borrow_mut().remove(key);
+  }
+}
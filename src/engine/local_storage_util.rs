@@ -1,29 +1,183 @@
+use crate::engine::KeyValueStorage;
+use serde::{Deserialize, Serialize};
+
+// Implemented by every struct persisted through LocalStorageUtil. Values are
+// stored wrapped in an envelope holding VERSION, and older envelopes are brought
+// up to date one `migrate` step at a time when read. Data written before
+// envelopes existed is treated as version 0.
+pub trait VersionedData: Serialize + serde::de::DeserializeOwned {
+  const VERSION: u32;
+
+  // Converts `data` stored with `from_version` into the layout of
+  // `from_version + 1`.
+  fn migrate(from_version: u32, data: serde_json::Value) -> Result<serde_json::Value, String> {
+    return match from_version {
+      0 => Ok(data),
+      _ => Err(format!("no migration from version {}", from_version)),
+    };
+  }
+}
+
+impl VersionedData for bool {
+  const VERSION: u32 = 1;
+}
+
+impl VersionedData for String {
+  const VERSION: u32 = 1;
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct VersionedEnvelope {
+  version: u32,
+  data: serde_json::Value,
+}
+
+fn backup_key(key: &str) -> String {
+  return format!("{}.backup", key);
+}
+
+fn quarantine_key(key: &str) -> String {
+  return format!("{}.quarantine", key);
+}
+
 pub struct LocalStorageUtil {}
 
 impl LocalStorageUtil {
-  pub fn read<T: serde::de::DeserializeOwned>(
-    local_storage: &web_sys::Storage,
-    key: &str,
-  ) -> Option<T> {
-    return local_storage
-      .get_item(key)
-      .expect(&format!("local_storage.get_item({}) failed", key))
-      .map(|value_txt| {
-        serde_json::from_str(value_txt.as_str()).expect(&format!(
-          "failed to parse local_storage.get_item({}) as json",
-          key
-        ))
-      });
-  }
-
-  pub fn write<T: serde::Serialize>(local_storage: &web_sys::Storage, key: &str, value: &T) {
-    local_storage
-      .set_item(
-        key,
-        serde_json::to_string(value)
-          .expect(&format!("serde_json::to_string({}) failed.", key))
-          .as_str(),
-      )
-      .expect("local_storage.set_item failed.");
+  // Returns None when nothing usable is stored. Unreadable data is moved to a
+  // quarantine key, and the copy saved by the previous write is used instead.
+  pub fn read<T: VersionedData>(local_storage: &dyn KeyValueStorage, key: &str) -> Option<T> {
+    let value_txt = LocalStorageUtil::read_raw(local_storage, key)?;
+    let error = match LocalStorageUtil::decode_str::<T>(&value_txt) {
+      Ok(value) => return Some(value),
+      Err(error) => error,
+    };
+    console_log!("unreadable local_storage {}: {}", key, error);
+    LocalStorageUtil::write_raw(local_storage, &quarantine_key(key), &value_txt);
+    LocalStorageUtil::remove(local_storage, key);
+
+    if let Some(backup_txt) = LocalStorageUtil::read_raw(local_storage, &backup_key(key)) {
+      if let Ok(value) = LocalStorageUtil::decode_str::<T>(&backup_txt) {
+        LocalStorageUtil::write_raw(local_storage, key, &backup_txt);
+        return Some(value);
+      }
+    }
+    return None;
+  }
+
+  // The previous value is kept under a backup key as long as it is still
+  // readable, so a bad write never destroys the last good copy.
+  pub fn write<T: VersionedData>(local_storage: &dyn KeyValueStorage, key: &str, value: &T) {
+    if let Some(previous_txt) = LocalStorageUtil::read_raw(local_storage, key) {
+      if LocalStorageUtil::decode_str::<T>(&previous_txt).is_ok() {
+        LocalStorageUtil::write_raw(local_storage, &backup_key(key), &previous_txt);
+      }
+    }
+    let envelope = LocalStorageUtil::encode(value);
+    LocalStorageUtil::write_raw(
+      local_storage,
+      key,
+      serde_json::to_string(&envelope)
+        .expect(&format!("serde_json::to_string({}) failed.", key))
+        .as_str(),
+    );
+  }
+
+  pub fn encode<T: VersionedData>(value: &T) -> serde_json::Value {
+    let envelope = VersionedEnvelope {
+      version: T::VERSION,
+      data: serde_json::to_value(value).expect("serde_json::to_value failed."),
+    };
+    return serde_json::to_value(envelope).expect("serde_json::to_value(envelope) failed.");
+  }
+
+  pub fn decode<T: VersionedData>(value: serde_json::Value) -> Result<T, String> {
+    let (mut version, mut data) = match serde_json::from_value::<VersionedEnvelope>(value.clone()) {
+      Ok(envelope) => (envelope.version, envelope.data),
+      Err(_) => (0, value),
+    };
+    if version > T::VERSION {
+      return Err(format!(
+        "version {} is newer than the supported {}",
+        version,
+        T::VERSION
+      ));
+    }
+    while version < T::VERSION {
+      data = T::migrate(version, data)?;
+      version += 1;
+    }
+    return serde_json::from_value(data).map_err(|error| error.to_string());
+  }
+
+  fn decode_str<T: VersionedData>(value_txt: &str) -> Result<T, String> {
+    let value = serde_json::from_str(value_txt).map_err(|error| error.to_string())?;
+    return LocalStorageUtil::decode(value);
+  }
+
+  pub fn read_raw(local_storage: &dyn KeyValueStorage, key: &str) -> Option<String> {
+    return local_storage.get_item(key);
+  }
+
+  pub fn write_raw(local_storage: &dyn KeyValueStorage, key: &str, value_txt: &str) {
+    local_storage.set_item(key, value_txt);
+  }
+
+  pub fn remove(local_storage: &dyn KeyValueStorage, key: &str) {
+    local_storage.remove_item(key);
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[derive(Serialize, Deserialize, PartialEq, Debug)]
+  struct Settings {
+    volume: f32,
+  }
+
+  // Version 1 stored the volume as a percentage under "volume_percent".
+  impl VersionedData for Settings {
+    const VERSION: u32 = 2;
+
+    fn migrate(from_version: u32, data: serde_json::Value) -> Result<serde_json::Value, String> {
+      return match from_version {
+        0 => Ok(data),
+        1 => {
+          let volume_percent = data["volume_percent"]
+            .as_f64()
+            .ok_or("missing volume_percent")?;
+          Ok(serde_json::json!({ "volume": volume_percent / 100.0 }))
+        }
+        _ => Err(format!("no migration from version {}", from_version)),
+      };
+    }
+  }
+
+  #[test]
+  fn test_encode_decode() {
+    let settings = Settings { volume: 0.5 };
+    let decoded: Settings = LocalStorageUtil::decode(LocalStorageUtil::encode(&settings)).unwrap();
+    assert_eq!(decoded, settings);
+  }
+
+  #[test]
+  fn test_decode_migrates_old_versions() {
+    let stored = serde_json::json!({ "version": 1, "data": { "volume_percent": 50.0 } });
+    let decoded: Settings = LocalStorageUtil::decode(stored).unwrap();
+    assert_eq!(decoded, Settings { volume: 0.5 });
+  }
+
+  #[test]
+  fn test_decode_rejects_newer_versions() {
+    let stored = serde_json::json!({ "version": 3, "data": { "volume": 0.5 } });
+    assert!(LocalStorageUtil::decode::<Settings>(stored).is_err());
+  }
+
+  #[test]
+  fn test_decode_legacy_data_without_envelope() {
+    let decoded: bool = LocalStorageUtil::decode(serde_json::json!(true)).unwrap();
+    assert_eq!(decoded, true);
   }
 }
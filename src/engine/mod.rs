@@ -5,9 +5,14 @@ mod effect;
 mod entity;
 mod event;
 mod events;
+mod file_transfer_util;
 mod fps_tracker;
 mod geometry_utils;
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod headless_context;
+mod http_util;
 mod input;
+mod key_value_storage;
 mod local_storage_util;
 mod particle;
 mod platform;
@@ -22,6 +27,7 @@ pub use app_trait::AppTrait;
 pub use audio::audio::Audio;
 pub use audio::audio_loader::AudioLoader;
 pub use audio::audio_player::AudioPlayer;
+pub use audio::audio_player::AudioPlayerSettings;
 pub use context_trait::ContextTrait;
 // pub use context_trait::ContextTraitDef;
 pub use effect::chained_effect::ChainedEffect as ChainedEffectGeneric;
@@ -50,42 +56,91 @@ pub use event::Event1ArgMut;
 pub use event::Event2ArgMutRef;
 pub use event::Event3ArgMutRefRef;
 pub use events::event_manager::EventManager;
+pub use file_transfer_util::FileTransferUtil;
 pub use fps_tracker::FpsTracker;
 pub use geometry_utils::GeometryUtils;
+#[cfg(all(test, not(target_arch = "wasm32")))]
+pub use headless_context::HeadlessContext;
+pub use http_util::HttpResponse;
+pub use http_util::HttpUtil;
 pub use input::input_event::InputEvent;
 pub use input::input_event::MouseEvent;
 pub use input::input_event::MouseEventType;
 pub use input::input_event::TouchEvent;
 pub use input::input_event::TouchEventType;
+pub use input::input_event::WheelEvent;
 pub use input::input_manager::InputManager;
 pub use input::input_types::GameTouch;
 pub use input::input_types::InputState;
 pub use input::input_types::ScreenTouch;
 pub use input::input_types::TouchType;
 pub use input::input_types::UiTouch;
+pub use input::pinch_gesture::PinchEvent;
+pub use input::pinch_gesture::PinchGesture;
+pub use key_value_storage::KeyValueStorage;
+#[cfg(all(test, not(target_arch = "wasm32")))]
+pub use key_value_storage::MemoryStorage;
 pub use local_storage_util::LocalStorageUtil;
+pub use local_storage_util::VersionedData;
 pub use particle::emitter::Emitter;
 pub use particle::particle::Particle;
 pub use platform::PlatformManager;
+pub use platform::SafeAreaInsets;
+pub use random::get_random_seed;
+pub use random::seed_random;
+pub use render::apng_encoder::ApngEncoder;
+pub use render::apng_encoder::ApngFrame;
 pub use render::cached_canvas_backend::CachedCanvasBackend;
+pub use render::camera::Camera;
 pub use render::canvas_backend::Canvas2dDrawBackend;
+pub use render::dirty_region_tracker::DirtyRect;
+pub use render::dirty_region_tracker::DirtyRedraw;
+pub use render::dirty_region_tracker::DirtyRegionTracker;
 pub use render::draw_args::DrawArgs;
 pub use render::draw_args::DrawColor;
 pub use render::draw_args::DrawGradientBoxArgs;
 pub use render::draw_args::DrawImageArgs;
 pub use render::draw_args::DrawImageOptionalArgs;
+pub use render::draw_args::DrawShape;
+pub use render::draw_args::DrawShapeArgs;
+pub use render::draw_args::DrawShapeOptionalArgs;
 pub use render::draw_args::DrawSource;
 pub use render::draw_args::DrawStringArgs;
 pub use render::draw_args::DrawStringOptionalArgs;
+pub use render::draw_args::DrawStrokeArgs;
+pub use render::draw_args::DrawStrokeOptionalArgs;
+pub use render::draw_args::LineCap;
 pub use render::draw_args::TextAlignment;
+pub use render::draw_backend::DrawBackend;
 pub use render::draw_manager::DrawManager;
+pub use render::font_atlas::FontAtlas;
+pub use render::font_atlas::FontAtlasManifest;
+pub use render::render_queue::RenderLayer;
+pub use render::render_queue::RenderQueue;
+pub use render::render_target::CanvasRenderTarget;
+pub use render::render_target::RenderTarget;
+pub use render::shape_geometry::ShapeGeometry;
+#[cfg(all(test, not(target_arch = "wasm32")))]
+pub use render::software_backend::check_golden;
+#[cfg(all(test, not(target_arch = "wasm32")))]
+pub use render::software_backend::SoftwareDrawBackend;
+#[cfg(all(test, not(target_arch = "wasm32")))]
+pub use render::software_backend::SoftwareImage;
+pub use render::stroke_geometry::StrokeGeometry;
+pub use render::stroke_geometry::StrokePoint;
 pub use render::text_cache::TextCache;
+pub use render::text_layout::TextLayout;
+pub use render::text_layout::TextSpan;
+pub use render::text_layout::TextStyle;
 pub use render::texture::Texture;
+pub use render::texture_atlas::TextureAtlasManifest;
+pub use render::texture_atlas::TextureAtlasRegion;
 pub use render::texture_loader::ColorAlphaCacheParams;
 pub use render::texture_loader::TextureLoader;
 pub use render::texture_loader::TextureParams;
 pub use render::texture_loader::TextureParamsOptional;
 pub use render::viewport::Viewport;
+pub use render::webgl_backend::WebGlDrawBackend;
 pub use shape::CircleShape;
 pub use shape::SegmentShape;
 pub use std::cell::Cell;
@@ -115,6 +170,8 @@ pub use ui::ui_element::UiElementParams;
 pub use ui::ui_element::UiElementTrait;
 pub use ui::ui_manager::UiManager as UiManagerGeneric;
 pub use ui::ui_manager::UiManagerEvent as UiManagerEventGeneric;
+pub use ui::ui_nine_slice::NineSliceMargins;
+pub use ui::ui_nine_slice::UiNineSlice as UiNineSliceGeneric;
 pub use ui::ui_overlay::UiOverlayTrait;
 pub use ui::ui_pivot::UiPivot as UiPivotGeneric;
 pub use ui::ui_root::UiRootTrait;
@@ -123,3 +180,4 @@ pub use ui::ui_sprite::UiSprite as UiSpriteGeneric;
 pub use ui::ui_text::UiText as UiTextGeneric;
 pub use ui::ui_touchable::UiTouchable as UiTouchableGeneric;
 pub use vibration::vibration_manager::VibrationManager;
+pub use vibration::vibration_manager::VibrationManagerSettings;
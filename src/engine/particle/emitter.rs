@@ -33,7 +33,7 @@ pub struct Emitter {
 
   pub color: Cell<DrawColor>,
   pub texture: Rc<Texture>,
-  pub depth: F1,
+  pub layer: RenderLayer,
 
   pub start_position_delta: F2,
   pub rand_region_size: F2,
@@ -96,7 +96,7 @@ impl Emitter {
 
         color: self.color.get(),
         texture: self.texture.clone(),
-        depth: self.depth,
+        layer: self.layer,
         additive_blending: self.additive_blending,
       });
     }
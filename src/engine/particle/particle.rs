@@ -22,7 +22,7 @@ pub struct Particle {
 
   pub color: DrawColor,
   pub texture: Rc<Texture>,
-  pub depth: F1,
+  pub layer: RenderLayer,
 
   pub additive_blending: bool,
 }
@@ -61,7 +61,8 @@ impl Particle {
         source: DrawSource::Texture(self.texture.clone()),
         position: self.position,
         size: self.size,
-        depth: self.depth, // context.draw_depths.source + 0.1
+        layer: self.layer,
+        depth: 0.0,
         optional: DrawImageOptionalArgs {
           opacity: self.opacity,
           color: self.color,
@@ -1,3 +1,5 @@
 mod platform_manager;
+mod safe_area_insets;
 
 pub use platform_manager::PlatformManager;
+pub use safe_area_insets::SafeAreaInsets;
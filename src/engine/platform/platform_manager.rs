@@ -14,6 +14,11 @@ impl PlatformManager {
     return PlatformManager { ios: ios };
   }
 
+  #[cfg(all(test, not(target_arch = "wasm32")))]
+  pub fn new_headless() -> PlatformManager {
+    return PlatformManager { ios: false };
+  }
+
   pub fn ios(&self) -> bool {
     return self.ios;
   }
@@ -0,0 +1,70 @@
+use crate::engine::*;
+
+// Space taken by notches, rounded corners and the home indicator on each side
+// of the screen, in screen pixels. Read from the `--safe-area-inset-*` CSS
+// variables of the document element, which the page sets to the
+// `env(safe-area-inset-*)` values.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct SafeAreaInsets {
+  pub top: F1,
+  pub right: F1,
+  pub bottom: F1,
+  pub left: F1,
+}
+
+impl SafeAreaInsets {
+  pub fn from_window(window: &web_sys::Window) -> SafeAreaInsets {
+    let document_element = window
+      .document()
+      .expect("window.document failed")
+      .document_element()
+      .expect("document.document_element failed");
+    let style = match window
+      .get_computed_style(&document_element)
+      .expect("window.get_computed_style failed")
+    {
+      Some(style) => style,
+      None => return SafeAreaInsets::default(),
+    };
+    let get_inset = |side: &str| {
+      SafeAreaInsets::parse_css_pixels(
+        &style
+          .get_property_value(&format!("--safe-area-inset-{}", side))
+          .unwrap_or_default(),
+      )
+    };
+    return SafeAreaInsets {
+      top: get_inset("top"),
+      right: get_inset("right"),
+      bottom: get_inset("bottom"),
+      left: get_inset("left"),
+    };
+  }
+
+  // "44px" is 44. Anything unparsable, like a browser without `env()`, is 0.
+  pub fn parse_css_pixels(value: &str) -> F1 {
+    return value
+      .trim()
+      .trim_end_matches("px")
+      .parse::<F1>()
+      .unwrap_or(0.0)
+      .max(0.0);
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_parse_css_pixels() {
+    assert_eq!(SafeAreaInsets::parse_css_pixels(" 44px"), 44.0);
+    assert_eq!(SafeAreaInsets::parse_css_pixels("20.5px"), 20.5);
+    assert_eq!(SafeAreaInsets::parse_css_pixels("0"), 0.0);
+    assert_eq!(SafeAreaInsets::parse_css_pixels(""), 0.0);
+    assert_eq!(
+      SafeAreaInsets::parse_css_pixels("env(safe-area-inset-top)"),
+      0.0
+    );
+  }
+}
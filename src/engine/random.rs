@@ -1,9 +1,24 @@
 use crate::engine::*;
 use rand::prelude::*;
+use rand::rngs::StdRng;
+
+thread_local! {
+  static RNG: RefCell<StdRng> = RefCell::new(StdRng::from_entropy());
+}
+
+// Restarts the random sequence, so the values after the same seed repeat. A
+// replayed stage seeds it like the recorded attempt did.
+pub fn seed_random(seed: u64) {
+  RNG.with(|rng| rng.replace(StdRng::seed_from_u64(seed)));
+}
+
+pub fn get_random_seed() -> u64 {
+  return RNG.with(|rng| rng.borrow_mut().next_u64());
+}
 
 pub fn get_random_from_0_to_1() -> F1 {
   let max_u32 = std::u32::MAX as f64;
-  let rand_value = rand::thread_rng().next_u32() as f64;
+  let rand_value = RNG.with(|rng| rng.borrow_mut().next_u32()) as f64;
   return (rand_value / max_u32) as F1;
 }
 
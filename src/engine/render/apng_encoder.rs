@@ -0,0 +1,71 @@
+pub struct ApngFrame {
+  // Straight alpha RGBA pixels, row by row.
+  pub rgba: Vec<u8>,
+  pub delay_ms: u16,
+}
+
+// Encodes animated PNGs, which browsers play like GIFs but without the 256
+// color limit.
+pub struct ApngEncoder {}
+
+impl ApngEncoder {
+  pub fn encode(width: u32, height: u32, frames: &[ApngFrame]) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    {
+      let mut encoder = png::Encoder::new(&mut bytes, width, height);
+      encoder.set_color(png::ColorType::Rgba);
+      encoder.set_depth(png::BitDepth::Eight);
+      // Loops forever.
+      encoder
+        .set_animated(frames.len() as u32, 0)
+        .expect("set_animated failed");
+      let mut writer = encoder.write_header().expect("write_header failed");
+      for frame in frames.iter() {
+        writer
+          .set_frame_delay(frame.delay_ms, 1000)
+          .expect("set_frame_delay failed");
+        writer
+          .write_image_data(&frame.rgba)
+          .expect("write_image_data failed");
+      }
+      writer.finish().expect("finish failed");
+    }
+    return bytes;
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_encode_frames() {
+    let frames = vec![
+      ApngFrame {
+        rgba: vec![255, 0, 0, 255, 0, 0, 0, 0],
+        delay_ms: 100,
+      },
+      ApngFrame {
+        rgba: vec![0, 255, 0, 255, 0, 0, 255, 128],
+        delay_ms: 500,
+      },
+    ];
+    let bytes = ApngEncoder::encode(2, 1, &frames);
+
+    let mut reader = png::Decoder::new(bytes.as_slice())
+      .read_info()
+      .expect("read_info failed");
+    let animation_control = reader.info().animation_control.expect("not animated");
+    assert_eq!(animation_control.num_frames, 2);
+    assert_eq!(animation_control.num_plays, 0);
+    let mut buffer = vec![0; reader.output_buffer_size()];
+    for frame in frames.iter() {
+      reader.next_frame(&mut buffer).expect("next_frame failed");
+      assert_eq!(buffer, frame.rgba);
+      assert_eq!(
+        reader.info().frame_control.unwrap().delay_num,
+        frame.delay_ms
+      );
+    }
+  }
+}
@@ -1,57 +1,92 @@
 use crate::engine::*;
-use wasm_bindgen::JsCast;
+
+// Largest side of a world cache in pixels, zoomed in views of big worlds
+// scale up a smaller cache.
+const MAX_WORLD_CACHE_SIZE: F1 = 4096.0;
 
 pub struct CachedCanvasBackend {
-  pub canvas: Rc<web_sys::HtmlCanvasElement>,
-  pub canvas_context: Rc<web_sys::CanvasRenderingContext2d>,
+  pub render_target: Rc<dyn RenderTarget>,
   pub canvas_size: Cell<F2>,
-  pub draw_backend: Canvas2dDrawBackend,
   pub clear_cache_required: Cell<bool>,
+  // Viewport drawing into the cache, when it caches the world of a viewport.
+  world_viewport: RefCell<Rc<Viewport>>,
+  // Zoom of the camera of that viewport on the previous frame.
+  latest_zoom: Cell<F1>,
 }
 
 impl CachedCanvasBackend {
-  pub fn new(canvas_size: &F2) -> CachedCanvasBackend {
-    let document = web_sys::window().unwrap().document().unwrap();
-    let cache_canvas = document
-      .create_element("canvas")
-      .expect("failed to create canvas")
-      .dyn_into::<web_sys::HtmlCanvasElement>()
-      .expect("failed to dyn_into");
-    cache_canvas.set_width(canvas_size.x as u32);
-    cache_canvas.set_height(canvas_size.y as u32);
-
-    let canvas_context = Rc::new(
-      cache_canvas
-        .get_context("2d")
-        .expect("failed to get_context(\"2d\")")
-        .unwrap()
-        .dyn_into::<web_sys::CanvasRenderingContext2d>()
-        .expect("failed to dyn_into::<web_sys::CanvasRenderingContext2d>()"),
-    );
-
+  pub fn new(render_target: Rc<dyn RenderTarget>) -> CachedCanvasBackend {
+    let canvas_size = render_target.get_size();
     return CachedCanvasBackend {
-      canvas: Rc::new(cache_canvas),
-      canvas_context: canvas_context.clone(),
-      canvas_size: Cell::new(*canvas_size),
-      draw_backend: Canvas2dDrawBackend::new(canvas_context),
+      render_target,
+      canvas_size: Cell::new(canvas_size),
       clear_cache_required: Cell::new(true),
+      world_viewport: RefCell::new(Rc::new(Viewport::default())),
+      latest_zoom: Cell::new(0.0),
     };
   }
 
+  pub fn get_draw_backend(&self) -> &dyn DrawBackend {
+    return self.render_target.get_draw_backend();
+  }
+
   pub fn check_canvas_size_changed(&self, expected_canvas_size: &F2) {
     if self.canvas_size.get() != *expected_canvas_size {
       self.canvas_size.set(*expected_canvas_size);
       self.clear_cache_required.set(true);
-      self.canvas.set_width(expected_canvas_size.x as u32);
-      self.canvas.set_height(expected_canvas_size.y as u32);
+      self.render_target.set_size(expected_canvas_size);
+    }
+  }
+
+  // Caches the whole world of `viewport` at the zoom of its camera, so panning
+  // keeps the cache. While the camera zooms the cache is scaled, it's redrawn
+  // at the new zoom once the zoom stops changing.
+  pub fn check_world_viewport_changed(&self, viewport: &Viewport) {
+    let zoom = viewport.get_camera().zoom;
+    let zoom_settled = zoom == self.latest_zoom.get();
+    self.latest_zoom.set(zoom);
+
+    let world_size = viewport.world_size;
+    let max_zoom =
+      MAX_WORLD_CACHE_SIZE / (viewport.viewport_size_on_canvas.x * world_size.x.max(world_size.y));
+    let world_viewport = viewport.with_world_at_origin(zoom.min(max_zoom));
+    let cached_viewport = self.world_viewport.borrow().clone();
+    let world_changed = world_viewport.world_size != cached_viewport.world_size;
+    let scale_changed =
+      world_viewport.viewport_to_canvas_ratio != cached_viewport.viewport_to_canvas_ratio;
+    if world_changed || (scale_changed && zoom_settled) {
+      let canvas_size = world_viewport.viewport_to_canvas_ratio(&world_size);
+      self.check_canvas_size_changed(&F2 {
+        x: canvas_size.x.ceil(),
+        y: canvas_size.y.ceil(),
+      });
+      self.clear_cache_required.set(true);
+      self.world_viewport.replace(Rc::new(world_viewport));
     }
   }
 
+  pub fn get_world_viewport(&self) -> Rc<Viewport> {
+    return self.world_viewport.borrow().clone();
+  }
+
+  // Area of the world covered by the cache, to draw it from (0, 0) with the
+  // viewport it caches.
+  pub fn get_world_cache_size(&self) -> F2 {
+    let canvas_size = self.canvas_size.get();
+    let viewport_to_canvas_ratio = self.world_viewport.borrow().viewport_to_canvas_ratio;
+    return F2 {
+      x: canvas_size.x / viewport_to_canvas_ratio.x,
+      y: canvas_size.y / viewport_to_canvas_ratio.y,
+    };
+  }
+
   pub fn check_clear_cache(&self) -> bool {
     let cache_cleared = self.clear_cache_required.get();
     if cache_cleared {
       self.clear_cache_required.set(false);
-      self.draw_backend.clear_screen(&self.canvas_size.get());
+      self
+        .get_draw_backend()
+        .clear_screen(&self.canvas_size.get());
     }
     return cache_cleared;
   }
@@ -0,0 +1,179 @@
+use crate::engine::*;
+
+const MAX_ZOOM: F1 = 3.0;
+// Time for the camera to cover most of the distance to its follow target.
+const FOLLOW_TIME_MS: F1 = 250.0;
+// The follow target is kept within this ratio of the visible area, around its
+// center.
+const FOLLOW_AREA_RATIO: F1 = 0.5;
+
+// Zoom and pan of a Viewport over its world, the area from (0, 0) to
+// `bounds_size`. At zoom 1 the frame of the viewport shows `frame_size` of the
+// world.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Camera {
+  pub center: F2,
+  pub zoom: F1,
+  frame_size: F2,
+  bounds_size: F2,
+  follow_target: Option<F2>,
+}
+
+impl Camera {
+  pub fn new(frame_size: &F2) -> Camera {
+    return Camera {
+      center: frame_size * 0.5,
+      zoom: 1.0,
+      frame_size: *frame_size,
+      bounds_size: *frame_size,
+      follow_target: None,
+    };
+  }
+
+  pub fn get_bounds_size(&self) -> &F2 {
+    return &self.bounds_size;
+  }
+
+  pub fn set_bounds_size(&mut self, bounds_size: &F2) {
+    self.bounds_size = *bounds_size;
+    self.clamp();
+  }
+
+  // Shows the whole world.
+  pub fn reset(&mut self) {
+    self.zoom = self.get_min_zoom();
+    self.center = self.bounds_size * 0.5;
+    self.follow_target = None;
+  }
+
+  // Zoom showing the whole world, at most 1.
+  pub fn get_min_zoom(&self) -> F1 {
+    return (self.frame_size.x / self.bounds_size.x)
+      .min(self.frame_size.y / self.bounds_size.y)
+      .min(1.0);
+  }
+
+  pub fn get_visible_size(&self) -> F2 {
+    return self.frame_size * (1.0 / self.zoom);
+  }
+
+  pub fn get_top_left(&self) -> F2 {
+    return self.center - self.get_visible_size() * 0.5;
+  }
+
+  // Multiplies the zoom by `factor`, keeping `anchor` at the same place in the
+  // frame.
+  pub fn zoom_at(&mut self, anchor: &F2, factor: F1) {
+    let zoom = F1Util::move_within_range(&(self.zoom * factor), &self.get_min_zoom(), &MAX_ZOOM);
+    self.center = anchor + (self.center - anchor) * (self.zoom / zoom);
+    self.zoom = zoom;
+    self.clamp();
+  }
+
+  pub fn pan(&mut self, offset: &F2) {
+    self.center += offset;
+    self.clamp();
+  }
+
+  // Keeps `target` in view from now on, or stops following with None.
+  pub fn follow(&mut self, target: Option<F2>) {
+    self.follow_target = target;
+  }
+
+  pub fn update(&mut self, dt_ms: F1) {
+    let target = match self.follow_target {
+      Some(target) => target,
+      None => return,
+    };
+    let follow_area = self.get_visible_size() * (FOLLOW_AREA_RATIO * 0.5);
+    let desired_center = F2 {
+      x: F1Util::move_within_range(
+        &self.center.x,
+        &(target.x - follow_area.x),
+        &(target.x + follow_area.x),
+      ),
+      y: F1Util::move_within_range(
+        &self.center.y,
+        &(target.y - follow_area.y),
+        &(target.y + follow_area.y),
+      ),
+    };
+    let ratio = 1.0 - (-dt_ms / FOLLOW_TIME_MS).exp();
+    self.center += &((desired_center - self.center) * ratio);
+    self.clamp();
+  }
+
+  // Keeps the visible area inside the world, or centered on the axes where
+  // the world is smaller than it.
+  fn clamp(&mut self) {
+    self.zoom = F1Util::move_within_range(&self.zoom, &self.get_min_zoom(), &MAX_ZOOM);
+    let half_visible_size = self.get_visible_size() * 0.5;
+    let clamp_axis = |center: F1, half_visible: F1, bounds: F1| {
+      if half_visible * 2.0 >= bounds {
+        return bounds * 0.5;
+      }
+      return F1Util::move_within_range(&center, &half_visible, &(bounds - half_visible));
+    };
+    self.center = F2 {
+      x: clamp_axis(self.center.x, half_visible_size.x, self.bounds_size.x),
+      y: clamp_axis(self.center.y, half_visible_size.y, self.bounds_size.y),
+    };
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const FRAME_SIZE: F2 = F2 { x: 1.0, y: 1.5 };
+
+  #[test]
+  fn test_zoom_at_keeps_anchor_in_place() {
+    let mut camera = Camera::new(&FRAME_SIZE);
+    camera.set_bounds_size(&F2 { x: 2.0, y: 3.0 });
+    let anchor = F2 { x: 0.6, y: 0.5 };
+    let anchor_in_frame = (anchor - camera.get_top_left()) * camera.zoom;
+    camera.zoom_at(&anchor, 2.0);
+    assert_eq!(camera.zoom, 2.0);
+    assert!(((anchor - camera.get_top_left()) * camera.zoom).eq_near(&anchor_in_frame));
+
+    camera.zoom_at(&anchor, 10.0);
+    assert_eq!(camera.zoom, MAX_ZOOM);
+  }
+
+  #[test]
+  fn test_clamps_to_bounds() {
+    let mut camera = Camera::new(&FRAME_SIZE);
+    camera.zoom_at(&F2 { x: 0.5, y: 0.75 }, 2.0);
+    camera.pan(&F2 { x: -5.0, y: 5.0 });
+    assert!(camera.get_top_left().eq_near(&F2 { x: 0.0, y: 0.75 }));
+
+    // Zooming out of a world the size of the frame recenters it.
+    camera.zoom_at(&F2 { x: 0.0, y: 0.0 }, 0.1);
+    assert_eq!(camera.zoom, 1.0);
+    assert_eq!(camera.center, FRAME_SIZE * 0.5);
+  }
+
+  #[test]
+  fn test_reset_shows_whole_world() {
+    let mut camera = Camera::new(&FRAME_SIZE);
+    camera.set_bounds_size(&F2 { x: 2.0, y: 1.5 });
+    camera.reset();
+    assert_eq!(camera.zoom, 0.5);
+    assert_eq!(camera.center, F2 { x: 1.0, y: 0.75 });
+  }
+
+  #[test]
+  fn test_follow_moves_towards_target() {
+    let mut camera = Camera::new(&FRAME_SIZE);
+    camera.set_bounds_size(&F2 { x: 4.0, y: 6.0 });
+    camera.follow(Some(F2 { x: 3.0, y: 0.75 }));
+    for _ in 0..200 {
+      camera.update(16.0);
+    }
+    // The target ends up at the border of the follow area.
+    let follow_area_x = camera.get_visible_size().x * FOLLOW_AREA_RATIO * 0.5;
+    assert!((camera.center.x - (3.0 - follow_area_x)).abs() < 0.001);
+    assert_eq!(camera.center.y, 0.75);
+  }
+}
@@ -1,6 +1,6 @@
 use crate::engine::render::draw_args::*;
+use crate::engine::render::text_cache::*;
 use crate::engine::*;
-use wasm_bindgen::prelude::*;
 
 pub const TEXT_FONT_SIZE_ADJUSTMENT_RATIO: F1 = 0.7;
 
@@ -49,10 +49,112 @@ fn get_source_offset(
   return None;
 }
 
+pub fn get_font(bold: bool) -> &'static str {
+  return if bold {
+    "bold 200px Oregano-Regular"
+  } else {
+    "200px Oregano-Regular"
+  };
+}
+
+// Lays out the text of `args` measured with a 200px font, `scale` being the
+// ratio between the drawn font size and 200px.
+pub fn get_text_layout(
+  canvas_context: &web_sys::CanvasRenderingContext2d,
+  args: &DrawStringArgs,
+  scale: f64,
+) -> TextLayout {
+  let max_width = args
+    .optional
+    .max_width
+    .map(|max_width| max_width / scale as F1);
+  let layout = TextLayout::new(
+    &args.text,
+    &args.optional.spans,
+    max_width,
+    &mut |text, bold| {
+      canvas_context.set_font(get_font(bold));
+      return canvas_context
+        .measure_text(text)
+        .expect("measure_text failed")
+        .width() as F1;
+    },
+  );
+  canvas_context.set_font(get_font(false));
+  return layout;
+}
+
+// Fills `layout` with a 200px font, the first baseline at y 0 and the lines
+// aligned on x 0. Borders are filled first so they never cover the text of a
+// neighbouring run.
+pub fn fill_text_layout(
+  canvas_context: &web_sys::CanvasRenderingContext2d,
+  layout: &TextLayout,
+  line_height: f64,
+  alignment: TextAlignment,
+  args: &DrawStringArgs,
+) {
+  let border_offsets = TextLayout::get_border_offsets(args.optional.border_scale);
+  let plain_offset = [F2 { x: 0.0, y: 0.0 }];
+
+  let passes: &[bool] = if args.optional.border {
+    &[true, false]
+  } else {
+    &[false]
+  };
+  for border in passes.iter() {
+    let offsets: &[F2] = if *border {
+      &border_offsets
+    } else {
+      &plain_offset
+    };
+    for (index, line) in layout.lines.iter().enumerate() {
+      let y = index as f64 * line_height;
+      // A single run keeps the alignment of the canvas, as plain strings do.
+      let start_x = if line.runs.len() == 1 {
+        canvas_context.set_text_align(match alignment {
+          TextAlignment::Left => "left",
+          TextAlignment::Center => "center",
+          TextAlignment::Right => "right",
+        });
+        0.0
+      } else {
+        canvas_context.set_text_align("left");
+        match alignment {
+          TextAlignment::Left => 0.0,
+          TextAlignment::Center => -line.width * 0.5,
+          TextAlignment::Right => -line.width,
+        }
+      };
+      for run in line.runs.iter() {
+        canvas_context.set_font(get_font(run.style.bold));
+        let color = if *border {
+          args.optional.border_color
+        } else {
+          run.style.color.unwrap_or(args.optional.color)
+        };
+        canvas_context.set_fill_style(&color.as_rgb_js_value());
+        for offset in offsets.iter() {
+          canvas_context
+            .fill_text(
+              run.text.as_str(),
+              (start_x + run.x + offset.x) as f64,
+              y + offset.y as f64,
+            )
+            .expect("fill_text failed");
+        }
+      }
+    }
+  }
+  canvas_context.set_font(get_font(false));
+}
+
 pub struct Canvas2dDrawBackend {
   canvas_context: Rc<web_sys::CanvasRenderingContext2d>,
   last_opacity: Cell<F1>,
   last_composite_operation: RefCell<String>,
+  // Whether clear_regions saved the context state to clip the frame.
+  clipped: Cell<bool>,
 }
 
 impl Canvas2dDrawBackend {
@@ -62,10 +164,26 @@ impl Canvas2dDrawBackend {
       canvas_context: canvas_context,
       last_opacity: Cell::new(1.0),
       last_composite_operation: RefCell::new(String::from("source-over")),
+      clipped: Cell::new(false),
     };
   }
 
-  pub fn clear_screen(&self, canvas_size: &F2) {
+  // Restores the opacity and composite operation restore() goes back to.
+  fn reset_blending(&self) {
+    self.last_opacity.set(1.0);
+    self.canvas_context.set_global_alpha(1.0);
+    self
+      .last_composite_operation
+      .replace(String::from("source-over"));
+    self
+      .canvas_context
+      .set_global_composite_operation("source-over")
+      .expect("set_global_composite_operation failed");
+  }
+}
+
+impl DrawBackend for Canvas2dDrawBackend {
+  fn clear_screen(&self, canvas_size: &F2) {
     self
       .canvas_context
       .reset_transform()
@@ -75,7 +193,46 @@ impl Canvas2dDrawBackend {
       .clear_rect(0.0, 0.0, canvas_size.x.into(), canvas_size.y.into());
   }
 
-  pub fn execute_image_draw(&self, args: &DrawImageArgs, canvas_size: &F2) {
+  fn supports_dirty_regions(&self) -> bool {
+    return true;
+  }
+
+  fn clear_regions(&self, regions: &[DirtyRect]) {
+    self
+      .canvas_context
+      .reset_transform()
+      .expect("reset_transform failed");
+    self.reset_blending();
+    self.canvas_context.save();
+    self.canvas_context.begin_path();
+    for region in regions.iter() {
+      let size = region.get_size();
+      self.canvas_context.clear_rect(
+        region.min.x.into(),
+        region.min.y.into(),
+        size.x.into(),
+        size.y.into(),
+      );
+      self.canvas_context.rect(
+        region.min.x.into(),
+        region.min.y.into(),
+        size.x.into(),
+        size.y.into(),
+      );
+    }
+    self.canvas_context.clip();
+    self.clipped.set(true);
+  }
+
+  fn finish_frame(&self) {
+    if self.clipped.get() {
+      self.canvas_context.restore();
+      self.reset_blending();
+      self.clipped.set(false);
+    }
+  }
+
+  fn execute_image_draw(&self, args: &DrawImageArgs, canvas_size: &F2) {
     let mut center_position = args.position;
     let mut top_left_rel_position = F2 {
       x: -args.size.x * args.optional.anchor_point.x,
@@ -96,17 +253,23 @@ impl Canvas2dDrawBackend {
     }
 
     let (mut draw_from, mut source_offset) = match &args.source {
-      DrawSource::Canvas(canvas) => (
-        DrawFrom::Canvas(canvas.clone()),
-        get_source_offset(
-          args,
-          None,
-          F2 {
-            x: canvas.width() as F1,
-            y: canvas.height() as F1,
-          },
-        ),
-      ),
+      DrawSource::RenderTarget(render_target) => {
+        let canvas = match CanvasRenderTarget::from_render_target(render_target.as_ref()) {
+          Some(canvas_render_target) => &canvas_render_target.canvas,
+          None => return,
+        };
+        (
+          DrawFrom::Canvas(canvas.clone()),
+          get_source_offset(
+            args,
+            None,
+            F2 {
+              x: canvas.width() as F1,
+              y: canvas.height() as F1,
+            },
+          ),
+        )
+      }
       DrawSource::Texture(texture) => {
         let texture_size = F2 {
           x: texture.width.get() as F1,
@@ -116,7 +279,7 @@ impl Canvas2dDrawBackend {
         if args.optional.color == DrawColor::default() {
           (
             DrawFrom::Image(texture.clone()),
-            get_source_offset(args, None, texture_size),
+            get_source_offset(args, texture.get_atlas_offset(), texture_size),
           )
         } else {
           if texture.color_alpha_cache.is_some() {
@@ -158,7 +321,9 @@ impl Canvas2dDrawBackend {
     // Optimization for better performance in Firefox: cache scaled images.
     if !args.optional.subpixel_precision {
       let mut draw_from_replacement = None;
-      if source_offset.is_none() {
+      let has_partial_region = args.optional.partial_region_offset != PARTIAL_REGION_OFFSET_DEFAULT
+        || args.optional.partial_region_size != PARTIAL_REGION_SIZE_DEFAULT;
+      if !has_partial_region {
         if let DrawFrom::Image(texture) = &draw_from {
           draw_from_replacement = texture.get_size_cache(size);
         }
@@ -272,12 +437,7 @@ impl Canvas2dDrawBackend {
     }
   }
 
-  pub fn execute_string_draw(
-    &self,
-    args: &DrawStringArgs,
-    device_pixel_ratio: &F1,
-    canvas_size: &F2,
-  ) {
+  fn execute_string_draw(&self, args: &DrawStringArgs, device_pixel_ratio: &F1, canvas_size: &F2) {
     let mut center_position = args.position;
     if let None = args.optional.text_cache.as_ref() {
       center_position.y += args.font_size * 0.25 * TEXT_FONT_SIZE_ADJUSTMENT_RATIO;
@@ -324,7 +484,7 @@ impl Canvas2dDrawBackend {
       self
         .canvas_context
         .draw_image_with_html_canvas_element_and_dw_and_dh(
-          text_cache.cached_canvas.canvas.as_ref(),
+          text_cache.canvas_render_target.canvas.as_ref(),
           top_left_rel_position_x.into(),
           top_left_rel_position_y.into(),
           size.x.into(),
@@ -335,7 +495,7 @@ impl Canvas2dDrawBackend {
       return;
     }
 
-    self.canvas_context.set_font("200px Oregano-Regular");
+    self.canvas_context.set_font(get_font(false));
 
     let scale = (args.font_size / 200.0 * TEXT_FONT_SIZE_ADJUSTMENT_RATIO) as f64;
     self
@@ -343,13 +503,10 @@ impl Canvas2dDrawBackend {
       .scale(scale, scale)
       .expect("scale failed");
 
+    let layout = get_text_layout(&self.canvas_context, args, scale);
     {
       // Do not draw things that are completely outside the screen.
-      let text_metrics = self
-        .canvas_context
-        .measure_text(args.text.as_str())
-        .expect("measure_text failed");
-      let width = text_metrics.width() as F1;
+      let width = layout.get_width();
 
       let top_left_rel_position_x = match args.optional.alignment {
         TextAlignment::Left => 0.0,
@@ -363,88 +520,31 @@ impl Canvas2dDrawBackend {
       }
     }
 
-    self
-      .canvas_context
-      .set_text_align(match args.optional.alignment {
-        TextAlignment::Left => "left",
-        TextAlignment::Center => "center",
-        TextAlignment::Right => "right",
-      });
-
-    if args.optional.border {
-      self
-        .canvas_context
-        .set_fill_style(&JsValue::from_str(&format!(
-          "rgb({}, {}, {}",
-          args.optional.border_color.r, args.optional.border_color.g, args.optional.border_color.b
-        )));
-
-      let border_width = 12.0 * args.optional.border_scale as f64 / 2.75;
-      let border_width_diagonal = 0.70 * border_width;
-      let extra_bottom = 4.0;
-      self
-        .canvas_context
-        .fill_text(args.text.as_str(), border_width, 0.0)
-        .expect("fill_text failed");
-      self
-        .canvas_context
-        .fill_text(args.text.as_str(), -border_width, 0.0)
-        .expect("fill_text failed");
-      self
-        .canvas_context
-        .fill_text(args.text.as_str(), 0.0, extra_bottom + border_width)
-        .expect("fill_text failed");
-      self
-        .canvas_context
-        .fill_text(args.text.as_str(), 0.0, -border_width)
-        .expect("fill_text failed");
-      self
-        .canvas_context
-        .fill_text(
-          args.text.as_str(),
-          border_width_diagonal,
-          extra_bottom + border_width_diagonal,
-        )
-        .expect("fill_text failed");
-      self
-        .canvas_context
-        .fill_text(
-          args.text.as_str(),
-          -border_width_diagonal,
-          extra_bottom + border_width_diagonal,
-        )
-        .expect("fill_text failed");
-      self
+    // Lines are centered around the position, like a single line is.
+    let mut line_height = 0.0;
+    if layout.lines.len() > 1 {
+      let text_metrics = self
         .canvas_context
-        .fill_text(
-          args.text.as_str(),
-          border_width_diagonal,
-          -border_width_diagonal,
-        )
-        .expect("fill_text failed");
+        .measure_text(args.text.as_str())
+        .expect("measure_text failed");
+      let (ascent, descent) = get_font_bounding_box(text_metrics);
+      line_height = (ascent + descent) * args.optional.line_spacing as f64;
       self
         .canvas_context
-        .fill_text(
-          args.text.as_str(),
-          -border_width_diagonal,
-          -border_width_diagonal,
-        )
-        .expect("fill_text failed");
+        .translate(0.0, -0.5 * (layout.lines.len() - 1) as f64 * line_height)
+        .expect("translate failed");
     }
 
-    self
-      .canvas_context
-      .set_fill_style(&JsValue::from_str(&format!(
-        "rgb({}, {}, {}",
-        args.optional.color.r, args.optional.color.g, args.optional.color.b
-      )));
-    self
-      .canvas_context
-      .fill_text(args.text.as_str(), 0.0, 0.0)
-      .expect("fill_text failed");
+    fill_text_layout(
+      &self.canvas_context,
+      &layout,
+      line_height,
+      args.optional.alignment,
+      args,
+    );
   }
 
-  pub fn execute_gradient_box_draw(&mut self, args: &DrawGradientBoxArgs, canvas_size: &F2) {
+  fn execute_gradient_box_draw(&self, args: &DrawGradientBoxArgs, canvas_size: &F2) {
     let center_position = args.position;
     let top_left_rel_position = F2 {
       x: -args.size.x * args.anchor_point.x,
@@ -517,4 +617,119 @@ impl Canvas2dDrawBackend {
       args.size.y.into(),
     );
   }
+
+  // Fills the outline of the stroke in a single path, the brush is ignored.
+  fn execute_stroke_draw(&self, args: &DrawStrokeArgs, canvas_size: &F2) {
+    if args.points.is_empty() || args.optional.opacity == 0.0 {
+      return;
+    }
+    let (min, max) = StrokeGeometry::get_bounds(&args.points);
+    if min.x > canvas_size.x || min.y > canvas_size.y || max.x < 0.0 || max.y < 0.0 {
+      return;
+    }
+    self
+      .canvas_context
+      .reset_transform()
+      .expect("reset_transform failed");
+
+    if args.optional.opacity != self.last_opacity.get() {
+      self.last_opacity.set(args.optional.opacity);
+      self
+        .canvas_context
+        .set_global_alpha(self.last_opacity.get() as f64);
+    }
+
+    let composite_operation = String::from("source-over");
+    if composite_operation != *self.last_composite_operation.borrow() {
+      self.last_composite_operation.replace(composite_operation);
+      self
+        .canvas_context
+        .set_global_composite_operation(&self.last_composite_operation.borrow())
+        .expect("set_global_composite_operation failed");
+    }
+
+    self.canvas_context.begin_path();
+    for position in StrokeGeometry::get_outline(&args.points).iter() {
+      self
+        .canvas_context
+        .line_to(position.x.into(), position.y.into());
+    }
+    self.canvas_context.close_path();
+    self
+      .canvas_context
+      .set_fill_style(&args.optional.color.as_rgb_js_value());
+    self.canvas_context.fill();
+  }
+
+  fn execute_shape_draw(&self, args: &DrawShapeArgs, canvas_size: &F2) {
+    if args.optional.opacity == 0.0 {
+      return;
+    }
+    let (min, max) = ShapeGeometry::get_bounds(args);
+    if min.x > canvas_size.x || min.y > canvas_size.y || max.x < 0.0 || max.y < 0.0 {
+      return;
+    }
+    self
+      .canvas_context
+      .reset_transform()
+      .expect("reset_transform failed");
+
+    if args.optional.opacity != self.last_opacity.get() {
+      self.last_opacity.set(args.optional.opacity);
+      self
+        .canvas_context
+        .set_global_alpha(self.last_opacity.get() as f64);
+    }
+
+    let composite_operation = String::from("source-over");
+    if composite_operation != *self.last_composite_operation.borrow() {
+      self.last_composite_operation.replace(composite_operation);
+      self
+        .canvas_context
+        .set_global_composite_operation(&self.last_composite_operation.borrow())
+        .expect("set_global_composite_operation failed");
+    }
+
+    if let (Some(fill_color), true) = (args.optional.fill_color, args.shape.is_closed()) {
+      self.canvas_context.begin_path();
+      for position in ShapeGeometry::get_outline(&args.shape).iter() {
+        self
+          .canvas_context
+          .line_to(position.x.into(), position.y.into());
+      }
+      self.canvas_context.close_path();
+      self
+        .canvas_context
+        .set_fill_style(&fill_color.as_rgb_js_value());
+      self.canvas_context.fill();
+    }
+    // Filled as the union of the stroke triangles, which share their winding.
+    if let Some(stroke_color) = args.optional.stroke_color {
+      self.canvas_context.begin_path();
+      for triangle in ShapeGeometry::get_stroke_triangles(&args.shape, &args.optional).iter() {
+        self
+          .canvas_context
+          .move_to(triangle[0].x.into(), triangle[0].y.into());
+        self
+          .canvas_context
+          .line_to(triangle[1].x.into(), triangle[1].y.into());
+        self
+          .canvas_context
+          .line_to(triangle[2].x.into(), triangle[2].y.into());
+        self.canvas_context.close_path();
+      }
+      self
+        .canvas_context
+        .set_fill_style(&stroke_color.as_rgb_js_value());
+      self.canvas_context.fill();
+    }
+  }
+
+  fn create_render_target(&self, size: &F2) -> Rc<dyn RenderTarget> {
+    return CanvasRenderTarget::new(size);
+  }
+
+  fn create_text_cache(&self) -> Option<Rc<TextCache>> {
+    return Some(TextCache::new());
+  }
 }
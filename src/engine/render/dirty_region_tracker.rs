@@ -0,0 +1,511 @@
+use crate::engine::*;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+// Extra pixels around every draw, covering rounding and antialiasing.
+const DIRTY_RECT_MARGIN: F1 = 2.0;
+// Above these the whole screen is redrawn instead.
+const MAX_DIRTY_RECTS: usize = 8;
+const MAX_DIRTY_AREA_RATIO: F1 = 0.5;
+
+// Axis aligned rectangle in canvas pixels.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct DirtyRect {
+  pub min: F2,
+  pub max: F2,
+}
+
+impl DirtyRect {
+  pub fn get_size(&self) -> F2 {
+    return self.max - self.min;
+  }
+
+  pub fn get_area(&self) -> F1 {
+    let size = self.get_size();
+    return size.x * size.y;
+  }
+
+  pub fn is_empty(&self) -> bool {
+    return self.max.x <= self.min.x || self.max.y <= self.min.y;
+  }
+
+  pub fn intersects(&self, other: &DirtyRect) -> bool {
+    return self.min.x < other.max.x
+      && other.min.x < self.max.x
+      && self.min.y < other.max.y
+      && other.min.y < self.max.y;
+  }
+
+  pub fn union(&self, other: &DirtyRect) -> DirtyRect {
+    return DirtyRect {
+      min: F2 {
+        x: self.min.x.min(other.min.x),
+        y: self.min.y.min(other.min.y),
+      },
+      max: F2 {
+        x: self.max.x.max(other.max.x),
+        y: self.max.y.max(other.max.y),
+      },
+    };
+  }
+
+  // Grows the rect by the margin, rounds it out to whole pixels and clips it to
+  // the canvas.
+  fn expand_and_clip(&self, canvas_size: &F2) -> DirtyRect {
+    return DirtyRect {
+      min: F2 {
+        x: (self.min.x - DIRTY_RECT_MARGIN).floor().max(0.0),
+        y: (self.min.y - DIRTY_RECT_MARGIN).floor().max(0.0),
+      },
+      max: F2 {
+        x: (self.max.x + DIRTY_RECT_MARGIN).ceil().min(canvas_size.x),
+        y: (self.max.y + DIRTY_RECT_MARGIN).ceil().min(canvas_size.y),
+      },
+    };
+  }
+}
+
+pub enum DirtyRedraw {
+  // Nothing changed since the last frame.
+  Skip,
+  Full,
+  Regions(Vec<DirtyRect>),
+}
+
+struct DrawFingerprint {
+  // None for draws whose pixels can change without their args changing.
+  hash: Option<u64>,
+  rect: DirtyRect,
+}
+
+// Compares the draws of consecutive frames to find the parts of the screen that
+// need to be redrawn.
+pub struct DirtyRegionTracker {
+  previous_draws: Vec<DrawFingerprint>,
+  previous_canvas_size: Option<F2>,
+  // Rects highlighted by the debug overlay in the last frame.
+  overlay_rects: Vec<DirtyRect>,
+  pub show_overlay: bool,
+}
+
+impl DirtyRegionTracker {
+  pub fn new() -> DirtyRegionTracker {
+    return DirtyRegionTracker {
+      previous_draws: Vec::new(),
+      previous_canvas_size: None,
+      overlay_rects: Vec::new(),
+      show_overlay: false,
+    };
+  }
+
+  // Bounds of the draws passed to the last update, in the same order.
+  pub fn get_draw_rects(&self) -> impl Iterator<Item = &DirtyRect> {
+    return self.previous_draws.iter().map(|draw| &draw.rect);
+  }
+
+  pub fn get_overlay_rects(&self) -> &Vec<DirtyRect> {
+    return &self.overlay_rects;
+  }
+
+  pub fn update(
+    &mut self,
+    draws: &[DrawArgs],
+    canvas_size: &F2,
+    device_pixel_ratio: &F1,
+    supports_regions: bool,
+  ) -> DirtyRedraw {
+    let draws: Vec<DrawFingerprint> = draws
+      .iter()
+      .map(|args| get_draw_fingerprint(args, canvas_size, device_pixel_ratio))
+      .collect();
+
+    let canvas_rect = DirtyRect {
+      min: F2 { x: 0.0, y: 0.0 },
+      max: *canvas_size,
+    };
+    let changed_rects = if self.previous_canvas_size != Some(*canvas_size) {
+      vec![canvas_rect]
+    } else {
+      get_changed_rects(&self.previous_draws, &draws)
+    };
+    let changed_rects: Vec<DirtyRect> = changed_rects
+      .iter()
+      .map(|rect| rect.expand_and_clip(canvas_size))
+      .filter(|rect| !rect.is_empty())
+      .collect();
+
+    // The overlay of the last frame has to be erased as well.
+    let mut dirty_rects = changed_rects.clone();
+    dirty_rects.extend(self.overlay_rects.iter().cloned());
+
+    self.previous_draws = draws;
+    self.previous_canvas_size = Some(*canvas_size);
+    self.overlay_rects = if self.show_overlay {
+      changed_rects
+    } else {
+      Vec::new()
+    };
+
+    if dirty_rects.is_empty() {
+      return DirtyRedraw::Skip;
+    }
+    if !supports_regions {
+      return DirtyRedraw::Full;
+    }
+    let dirty_rects = merge_rects(dirty_rects);
+    let dirty_area: F1 = dirty_rects.iter().map(|rect| rect.get_area()).sum();
+    if dirty_rects.len() > MAX_DIRTY_RECTS
+      || dirty_area > canvas_rect.get_area() * MAX_DIRTY_AREA_RATIO
+    {
+      return DirtyRedraw::Full;
+    }
+    return DirtyRedraw::Regions(dirty_rects);
+  }
+}
+
+// Rects of the draws that appeared or disappeared since the last frame. Draws
+// are matched by content, so one that moved dirties both where it was and
+// where it is. Reordering draws of the same layer and depth isn't detected.
+fn get_changed_rects(previous: &[DrawFingerprint], current: &[DrawFingerprint]) -> Vec<DirtyRect> {
+  let count_hashes = |draws: &[DrawFingerprint]| {
+    let mut counts: HashMap<u64, usize> = HashMap::new();
+    for draw in draws.iter() {
+      if let Some(hash) = draw.hash {
+        *counts.entry(hash).or_insert(0) += 1;
+      }
+    }
+    return counts;
+  };
+  let mut previous_counts = count_hashes(previous);
+  let mut current_counts = count_hashes(current);
+
+  let mut changed_rects = Vec::new();
+  let mut collect_unmatched =
+    |draws: &[DrawFingerprint], other_counts: &mut HashMap<u64, usize>| {
+      for draw in draws.iter() {
+        let matched = match draw.hash {
+          Some(hash) => match other_counts.get_mut(&hash) {
+            Some(count) if *count > 0 => {
+              *count -= 1;
+              true
+            }
+            _ => false,
+          },
+          None => false,
+        };
+        if !matched {
+          changed_rects.push(draw.rect);
+        }
+      }
+    };
+  collect_unmatched(current, &mut previous_counts);
+  collect_unmatched(previous, &mut current_counts);
+  return changed_rects;
+}
+
+// Merges overlapping rects until none overlap.
+fn merge_rects(mut rects: Vec<DirtyRect>) -> Vec<DirtyRect> {
+  let mut merged: Vec<DirtyRect> = Vec::new();
+  while let Some(mut rect) = rects.pop() {
+    let mut i = 0;
+    while i < merged.len() {
+      if merged[i].intersects(&rect) {
+        rect = rect.union(&merged.swap_remove(i));
+        i = 0;
+      } else {
+        i += 1;
+      }
+    }
+    merged.push(rect);
+  }
+  return merged;
+}
+
+fn hash_f1<H: Hasher>(value: F1, state: &mut H) {
+  value.to_bits().hash(state);
+}
+
+fn hash_f2<H: Hasher>(value: &F2, state: &mut H) {
+  hash_f1(value.x, state);
+  hash_f1(value.y, state);
+}
+
+fn get_draw_fingerprint(
+  args: &DrawArgs,
+  canvas_size: &F2,
+  device_pixel_ratio: &F1,
+) -> DrawFingerprint {
+  let mut hasher = DefaultHasher::new();
+  args.get_layer().hash(&mut hasher);
+  hash_f1(*args.get_depth(), &mut hasher);
+  return match args {
+    DrawArgs::Image(image_args) => {
+      0u8.hash(&mut hasher);
+      let volatile = match &image_args.source {
+        // Render targets are redrawn by their owners without notice.
+        DrawSource::RenderTarget(_) => true,
+        DrawSource::Texture(texture) => {
+          (Rc::as_ptr(texture) as usize).hash(&mut hasher);
+          // A reloaded or placeholder image draws differently at the same place.
+          texture.get_load_generation().hash(&mut hasher);
+          false
+        }
+      };
+      hash_f2(&image_args.position, &mut hasher);
+      hash_f2(&image_args.size, &mut hasher);
+      let optional = &image_args.optional;
+      optional.color.hash(&mut hasher);
+      hash_f1(optional.rotation, &mut hasher);
+      hash_f2(&optional.anchor_point, &mut hasher);
+      hash_f1(optional.opacity, &mut hasher);
+      hash_f2(&optional.partial_region_offset, &mut hasher);
+      hash_f2(&optional.partial_region_size, &mut hasher);
+      optional.composite_operation.hash(&mut hasher);
+      optional.subpixel_precision.hash(&mut hasher);
+      DrawFingerprint {
+        hash: if volatile {
+          None
+        } else {
+          Some(hasher.finish())
+        },
+        rect: get_image_rect(image_args),
+      }
+    }
+    DrawArgs::String(string_args) => {
+      1u8.hash(&mut hasher);
+      string_args.text.hash(&mut hasher);
+      hash_f2(&string_args.position, &mut hasher);
+      hash_f1(string_args.font_size, &mut hasher);
+      let optional = &string_args.optional;
+      (optional.alignment as u8).hash(&mut hasher);
+      optional.color.hash(&mut hasher);
+      optional.border.hash(&mut hasher);
+      optional.border_color.hash(&mut hasher);
+      hash_f1(optional.border_scale, &mut hasher);
+      hash_f1(optional.opacity, &mut hasher);
+      optional.text_cache.is_some().hash(&mut hasher);
+      optional.max_width.is_some().hash(&mut hasher);
+      hash_f1(optional.max_width.unwrap_or(0.0), &mut hasher);
+      hash_f1(optional.line_spacing, &mut hasher);
+      optional.spans.hash(&mut hasher);
+      DrawFingerprint {
+        hash: Some(hasher.finish()),
+        rect: get_string_rect(string_args, canvas_size, device_pixel_ratio),
+      }
+    }
+    DrawArgs::GradientBox(gradient_box_args) => {
+      2u8.hash(&mut hasher);
+      hash_f2(&gradient_box_args.position, &mut hasher);
+      hash_f2(&gradient_box_args.size, &mut hasher);
+      gradient_box_args.draw_color_start.hash(&mut hasher);
+      hash_f1(gradient_box_args.alpha_start, &mut hasher);
+      gradient_box_args.draw_color_end.hash(&mut hasher);
+      hash_f1(gradient_box_args.alpha_end, &mut hasher);
+      hash_f2(&gradient_box_args.anchor_point, &mut hasher);
+      let top_left = gradient_box_args.position
+        - F2 {
+          x: gradient_box_args.size.x * gradient_box_args.anchor_point.x,
+          y: gradient_box_args.size.y * gradient_box_args.anchor_point.y,
+        };
+      DrawFingerprint {
+        hash: Some(hasher.finish()),
+        rect: DirtyRect {
+          min: top_left,
+          max: top_left + gradient_box_args.size,
+        },
+      }
+    }
+    DrawArgs::Stroke(stroke_args) => {
+      3u8.hash(&mut hasher);
+      for point in stroke_args.points.iter() {
+        hash_f2(&point.position, &mut hasher);
+        hash_f1(point.width, &mut hasher);
+      }
+      let optional = &stroke_args.optional;
+      optional.color.hash(&mut hasher);
+      hash_f1(optional.opacity, &mut hasher);
+      optional
+        .brush
+        .as_ref()
+        .map(|brush| Rc::as_ptr(brush) as usize)
+        .hash(&mut hasher);
+      let (min, max) = StrokeGeometry::get_bounds(&stroke_args.points);
+      DrawFingerprint {
+        hash: Some(hasher.finish()),
+        rect: DirtyRect { min, max },
+      }
+    }
+    DrawArgs::Shape(shape_args) => {
+      4u8.hash(&mut hasher);
+      for point in ShapeGeometry::get_outline(&shape_args.shape).iter() {
+        hash_f2(point, &mut hasher);
+      }
+      shape_args.shape.is_closed().hash(&mut hasher);
+      let optional = &shape_args.optional;
+      optional.fill_color.hash(&mut hasher);
+      optional.stroke_color.hash(&mut hasher);
+      hash_f1(optional.stroke_width, &mut hasher);
+      optional.line_cap.hash(&mut hasher);
+      for length in optional.dash.iter() {
+        hash_f1(*length, &mut hasher);
+      }
+      hash_f1(optional.opacity, &mut hasher);
+      let (min, max) = ShapeGeometry::get_bounds(shape_args);
+      DrawFingerprint {
+        hash: Some(hasher.finish()),
+        rect: DirtyRect { min, max },
+      }
+    }
+  };
+}
+
+fn get_image_rect(args: &DrawImageArgs) -> DirtyRect {
+  let top_left = F2 {
+    x: -args.size.x * args.optional.anchor_point.x,
+    y: -args.size.y * args.optional.anchor_point.y,
+  };
+  let corners = [
+    top_left,
+    top_left
+      + F2 {
+        x: args.size.x,
+        y: 0.0,
+      },
+    top_left + args.size,
+    top_left
+      + F2 {
+        x: 0.0,
+        y: args.size.y,
+      },
+  ];
+  let mut rect: Option<DirtyRect> = None;
+  for corner in corners.iter() {
+    let corner = args.position + F2::rotate_new(corner, &args.optional.rotation);
+    let corner_rect = DirtyRect {
+      min: corner,
+      max: corner,
+    };
+    rect = Some(match rect {
+      Some(rect) => rect.union(&corner_rect),
+      None => corner_rect,
+    });
+  }
+  return rect.unwrap();
+}
+
+// The width of a text is only known once the backend rasterizes it, so strings
+// dirty the whole width of the canvas.
+fn get_string_rect(args: &DrawStringArgs, canvas_size: &F2, device_pixel_ratio: &F1) -> DirtyRect {
+  let border = if args.optional.border {
+    args.font_size * 0.1 * args.optional.border_scale
+  } else {
+    0.0
+  };
+  // Wrapped lines are only known once measured, so they may take the whole
+  // canvas.
+  if args.optional.max_width.is_some() {
+    return DirtyRect {
+      min: F2 { x: 0.0, y: 0.0 },
+      max: *canvas_size,
+    };
+  }
+  let line_count = args.text.split('\n').count() as F1;
+  let half_height = args.font_size * (1.0 + (line_count - 1.0) * args.optional.line_spacing)
+    + border
+    + 4.0 * device_pixel_ratio;
+  return DirtyRect {
+    min: F2 {
+      x: 0.0,
+      y: args.position.y - half_height,
+    },
+    max: F2 {
+      x: canvas_size.x,
+      y: args.position.y + half_height,
+    },
+  };
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const CANVAS_SIZE: F2 = F2 { x: 400.0, y: 800.0 };
+
+  fn fingerprint(hash: u64, x: F1, y: F1) -> DrawFingerprint {
+    return DrawFingerprint {
+      hash: Some(hash),
+      rect: DirtyRect {
+        min: F2 { x: x, y: y },
+        max: F2 {
+          x: x + 10.0,
+          y: y + 10.0,
+        },
+      },
+    };
+  }
+
+  #[test]
+  fn test_unchanged_draws_have_no_changed_rects() {
+    let previous = vec![fingerprint(1, 0.0, 0.0), fingerprint(2, 50.0, 50.0)];
+    let current = vec![fingerprint(2, 50.0, 50.0), fingerprint(1, 0.0, 0.0)];
+    assert_eq!(get_changed_rects(&previous, &current).len(), 0);
+  }
+
+  #[test]
+  fn test_moved_draw_dirties_old_and_new_rects() {
+    let previous = vec![fingerprint(1, 0.0, 0.0), fingerprint(2, 50.0, 50.0)];
+    let current = vec![fingerprint(1, 0.0, 0.0), fingerprint(3, 60.0, 50.0)];
+    let changed_rects = get_changed_rects(&previous, &current);
+    assert_eq!(changed_rects.len(), 2);
+    assert_eq!(changed_rects[0], current[1].rect);
+    assert_eq!(changed_rects[1], previous[1].rect);
+  }
+
+  #[test]
+  fn test_merge_rects() {
+    let merged = merge_rects(vec![
+      fingerprint(0, 0.0, 0.0).rect,
+      fingerprint(0, 5.0, 5.0).rect,
+      fingerprint(0, 100.0, 100.0).rect,
+    ]);
+    assert_eq!(merged.len(), 2);
+    assert!(merged.contains(&DirtyRect {
+      min: F2 { x: 0.0, y: 0.0 },
+      max: F2 { x: 15.0, y: 15.0 },
+    }));
+  }
+
+  #[test]
+  fn test_update_skips_static_frames() {
+    let mut tracker = DirtyRegionTracker::new();
+    let draws = vec![DrawArgs::GradientBox(DrawGradientBoxArgs {
+      position: F2 { x: 100.0, y: 100.0 },
+      size: F2 { x: 20.0, y: 20.0 },
+      draw_color_start: DrawColor::default(),
+      alpha_start: 1.0,
+      draw_color_end: DrawColor::default(),
+      alpha_end: 1.0,
+      layer: RenderLayer::default(),
+      depth: 0.0,
+      anchor_point: F2 { x: 0.5, y: 0.5 },
+    })];
+    assert!(matches!(
+      tracker.update(&draws, &CANVAS_SIZE, &1.0, true),
+      DirtyRedraw::Full
+    ));
+    assert!(matches!(
+      tracker.update(&draws, &CANVAS_SIZE, &1.0, true),
+      DirtyRedraw::Skip
+    ));
+    match tracker.update(&[], &CANVAS_SIZE, &1.0, true) {
+      DirtyRedraw::Regions(regions) => assert_eq!(
+        regions,
+        vec![DirtyRect {
+          min: F2 { x: 88.0, y: 88.0 },
+          max: F2 { x: 112.0, y: 112.0 },
+        }]
+      ),
+      _ => panic!("Expected a partial redraw"),
+    }
+  }
+}
@@ -5,20 +5,35 @@ pub enum DrawArgs {
   Image(DrawImageArgs),
   String(DrawStringArgs),
   GradientBox(DrawGradientBoxArgs),
+  Stroke(DrawStrokeArgs),
+  Shape(DrawShapeArgs),
 }
 
 impl DrawArgs {
+  pub fn get_layer(&self) -> RenderLayer {
+    return match self {
+      DrawArgs::Image(image_args) => image_args.layer,
+      DrawArgs::String(string_args) => string_args.layer,
+      DrawArgs::GradientBox(gradient_box_args) => gradient_box_args.layer,
+      DrawArgs::Stroke(stroke_args) => stroke_args.layer,
+      DrawArgs::Shape(shape_args) => shape_args.layer,
+    };
+  }
+
+  // Only orders the draws of a layer, see RenderQueue.
   pub fn get_depth(&self) -> &F1 {
     return match self {
       DrawArgs::Image(image_args) => &image_args.depth,
       DrawArgs::String(string_args) => &string_args.depth,
       DrawArgs::GradientBox(gradient_box_args) => &gradient_box_args.depth,
+      DrawArgs::Stroke(stroke_args) => &stroke_args.depth,
+      DrawArgs::Shape(shape_args) => &shape_args.depth,
     };
   }
 }
 
 pub enum DrawSource {
-  Canvas(Rc<web_sys::HtmlCanvasElement>),
+  RenderTarget(Rc<dyn RenderTarget>),
   Texture(Rc<Texture>),
 }
 
@@ -26,6 +41,7 @@ pub struct DrawImageArgs {
   pub source: DrawSource,
   pub position: F2,
   pub size: F2,
+  pub layer: RenderLayer,
   pub depth: F1,
   pub optional: DrawImageOptionalArgs,
 }
@@ -94,6 +110,7 @@ pub struct DrawStringArgs {
   pub text: String,
   pub position: F2,
   pub font_size: F1,
+  pub layer: RenderLayer,
   pub depth: F1,
   pub optional: DrawStringOptionalArgs,
 }
@@ -113,6 +130,14 @@ pub struct DrawStringOptionalArgs {
   pub border_scale: F1,
   pub opacity: F1,
   pub text_cache: Option<Rc<TextCache>>,
+  // Lines are wrapped between words to fit it.
+  pub max_width: Option<F1>,
+  // Times the height of a line between two baselines.
+  pub line_spacing: F1,
+  pub spans: Vec<TextSpan>,
+  // Draws the glyphs of the atlas instead of rasterizing the text, ignoring
+  // the text cache.
+  pub font_atlas: Option<Rc<FontAtlas>>,
 }
 
 impl Default for DrawStringOptionalArgs {
@@ -125,6 +150,10 @@ impl Default for DrawStringOptionalArgs {
       border_scale: 1.0,
       opacity: 1.0,
       text_cache: None,
+      max_width: None,
+      line_spacing: 1.0,
+      spans: Vec::new(),
+      font_atlas: None,
     };
   }
 }
@@ -136,6 +165,115 @@ pub struct DrawGradientBoxArgs {
   pub alpha_start: F1,
   pub draw_color_end: DrawColor,
   pub alpha_end: F1,
+  pub layer: RenderLayer,
   pub depth: F1,
   pub anchor_point: F2,
 }
+
+// Line through `points`, with round caps. Backends draw the points as given,
+// see StrokeGeometry::smooth to draw a curve through them.
+pub struct DrawStrokeArgs {
+  pub points: Vec<StrokePoint>,
+  pub layer: RenderLayer,
+  pub depth: F1,
+  pub optional: DrawStrokeOptionalArgs,
+}
+
+pub struct DrawStrokeOptionalArgs {
+  pub color: DrawColor,
+  pub opacity: F1,
+  // Its alpha modulates the stroke, see StrokeVertex. Canvas2dDrawBackend
+  // ignores it and fills the stroke with a solid color.
+  pub brush: Option<Rc<Texture>>,
+}
+
+impl Default for DrawStrokeOptionalArgs {
+  fn default() -> DrawStrokeOptionalArgs {
+    return DrawStrokeOptionalArgs {
+      color: DrawColor::default(),
+      opacity: 1.0,
+      brush: None,
+    };
+  }
+}
+
+pub enum DrawShape {
+  Line {
+    start: F2,
+    end: F2,
+  },
+  Polyline {
+    points: Vec<F2>,
+  },
+  // Angles in radians, from the x axis towards the y axis.
+  Arc {
+    center: F2,
+    radius: F1,
+    start_angle: F1,
+    end_angle: F1,
+  },
+  Circle {
+    center: F2,
+    radius: F1,
+  },
+  Polygon {
+    points: Vec<F2>,
+  },
+  // Centered on `position`.
+  RoundedRect {
+    position: F2,
+    size: F2,
+    corner_radius: F1,
+  },
+}
+
+impl DrawShape {
+  // Lines, polylines and arcs are only stroked.
+  pub fn is_closed(&self) -> bool {
+    return match self {
+      DrawShape::Line { .. } | DrawShape::Polyline { .. } | DrawShape::Arc { .. } => false,
+      DrawShape::Circle { .. } | DrawShape::Polygon { .. } | DrawShape::RoundedRect { .. } => true,
+    };
+  }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum LineCap {
+  Butt,
+  Round,
+  Square,
+}
+
+// Lines, circles and polygons of a solid color. ShapeGeometry turns them into
+// triangles for the backends.
+pub struct DrawShapeArgs {
+  pub shape: DrawShape,
+  pub layer: RenderLayer,
+  pub depth: F1,
+  pub optional: DrawShapeOptionalArgs,
+}
+
+pub struct DrawShapeOptionalArgs {
+  pub fill_color: Option<DrawColor>,
+  pub stroke_color: Option<DrawColor>,
+  pub stroke_width: F1,
+  // Ends of open shapes and of dashes. Corners are always round.
+  pub line_cap: LineCap,
+  // Lengths of the dashes and the gaps between them, repeated along the
+  // outline. Solid when empty.
+  pub dash: Vec<F1>,
+  pub opacity: F1,
+}
+
+impl Default for DrawShapeOptionalArgs {
+  fn default() -> DrawShapeOptionalArgs {
+    return DrawShapeOptionalArgs {
+      fill_color: None,
+      stroke_color: Some(DrawColor::default()),
+      stroke_width: 1.0,
+      line_cap: LineCap::Butt,
+      dash: Vec::new(),
+      opacity: 1.0,
+    };
+  }
+}
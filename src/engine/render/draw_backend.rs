@@ -0,0 +1,27 @@
+use crate::engine::*;
+
+// Executes queued draws on a surface. DrawManager owns the one drawing the
+// screen, and every RenderTarget owns one drawing into it.
+pub trait DrawBackend {
+  fn clear_screen(&self, canvas_size: &F2);
+  fn execute_image_draw(&self, args: &DrawImageArgs, canvas_size: &F2);
+  fn execute_string_draw(&self, args: &DrawStringArgs, device_pixel_ratio: &F1, canvas_size: &F2);
+  fn execute_gradient_box_draw(&self, args: &DrawGradientBoxArgs, canvas_size: &F2);
+  fn execute_stroke_draw(&self, args: &DrawStrokeArgs, canvas_size: &F2);
+  fn execute_shape_draw(&self, args: &DrawShapeArgs, canvas_size: &F2);
+  // Whether clear_regions is implemented. Otherwise any change redraws the
+  // whole screen.
+  fn supports_dirty_regions(&self) -> bool {
+    return false;
+  }
+  // Clears the regions and restricts the draws of the rest of the frame to them.
+  fn clear_regions(&self, _regions: &[DirtyRect]) {}
+  // Called after the last draw of a frame.
+  fn finish_frame(&self) {}
+  // Creates an offscreen surface this backend can draw with DrawSource::RenderTarget.
+  fn create_render_target(&self, size: &F2) -> Rc<dyn RenderTarget>;
+  // Backends without one draw DrawStringArgs::text_cache strings directly.
+  fn create_text_cache(&self) -> Option<Rc<TextCache>> {
+    return None;
+  }
+}
@@ -1,21 +1,26 @@
 use crate::engine::*;
 
 pub struct DrawManager {
-  draw_backend: Canvas2dDrawBackend,
+  draw_backend: Box<dyn DrawBackend>,
+  render_queue: RenderQueue,
+  // Draws of the frame being executed, in execution order.
   queued: Vec<DrawArgs>,
+  dirty_region_tracker: DirtyRegionTracker,
   device_pixel_ratio: F1,
   canvas_size: F2,
 }
 
 impl DrawManager {
   pub fn new(
-    draw_backend: Canvas2dDrawBackend,
+    draw_backend: Box<dyn DrawBackend>,
     screen_size: &F2,
     device_pixel_ratio: &F1,
   ) -> DrawManager {
     return DrawManager {
       draw_backend: draw_backend,
+      render_queue: RenderQueue::new(),
       queued: Vec::new(),
+      dirty_region_tracker: DirtyRegionTracker::new(),
       device_pixel_ratio: *device_pixel_ratio,
       canvas_size: screen_size * device_pixel_ratio,
     };
@@ -26,39 +31,101 @@ impl DrawManager {
     self.canvas_size = screen_size * device_pixel_ratio;
   }
 
+  // Highlights the regions redrawn in each frame.
+  pub fn set_show_dirty_regions(&mut self, show_dirty_regions: bool) {
+    self.dirty_region_tracker.show_overlay = show_dirty_regions;
+  }
+
   pub fn execute_draws(&mut self) {
-    self.draw_backend.clear_screen(&self.canvas_size);
+    self.render_queue.drain_into(&mut self.queued);
+
+    let redraw = self.dirty_region_tracker.update(
+      &self.queued,
+      &self.canvas_size,
+      &self.device_pixel_ratio,
+      self.draw_backend.supports_dirty_regions(),
+    );
+    let regions = match redraw {
+      DirtyRedraw::Skip => {
+        self.queued.clear();
+        return;
+      }
+      DirtyRedraw::Full => {
+        self.draw_backend.clear_screen(&self.canvas_size);
+        None
+      }
+      DirtyRedraw::Regions(regions) => {
+        self.draw_backend.clear_regions(&regions);
+        Some(regions)
+      }
+    };
 
-    self
+    for (args, rect) in self
       .queued
-      .sort_by(|a, b| b.get_depth().partial_cmp(a.get_depth()).unwrap());
+      .iter()
+      .zip(self.dirty_region_tracker.get_draw_rects())
+    {
+      if let Some(regions) = regions.as_ref() {
+        if !regions.iter().any(|region| region.intersects(rect)) {
+          continue;
+        }
+      }
+      execute_draw(
+        self.draw_backend.as_ref(),
+        args,
+        &self.device_pixel_ratio,
+        &self.canvas_size,
+      );
+    }
+    for rect in self.dirty_region_tracker.get_overlay_rects().iter() {
+      self.draw_backend.execute_gradient_box_draw(
+        &DrawGradientBoxArgs {
+          position: rect.min,
+          size: rect.get_size(),
+          draw_color_start: DrawColor { r: 255, g: 0, b: 0 },
+          alpha_start: 0.25,
+          draw_color_end: DrawColor { r: 255, g: 0, b: 0 },
+          alpha_end: 0.25,
+          layer: RenderLayer::OVERLAY,
+          depth: 0.0,
+          anchor_point: F2 { x: 0.0, y: 0.0 },
+        },
+        &self.canvas_size,
+      );
+    }
+    self.draw_backend.finish_frame();
+    self.queued.clear();
+  }
 
+  // Executes the queued draws on `render_target` instead of the screen. Meant
+  // for offscreen renders outside of the draw phase, when no screen draws are
+  // queued.
+  pub fn execute_draws_on_render_target(
+    &mut self,
+    render_target: &Rc<dyn RenderTarget>,
+    device_pixel_ratio: &F1,
+  ) {
+    self.render_queue.drain_into(&mut self.queued);
+    let draw_backend = render_target.get_draw_backend();
+    let size = render_target.get_size();
+    draw_backend.clear_screen(&size);
     for args in self.queued.iter() {
-      match args {
-        DrawArgs::Image(image_args) => {
-          self
-            .draw_backend
-            .execute_image_draw(image_args, &self.canvas_size);
-        }
-        DrawArgs::String(string_args) => {
-          self.draw_backend.execute_string_draw(
-            string_args,
-            &self.device_pixel_ratio,
-            &self.canvas_size,
-          );
-        }
-        DrawArgs::GradientBox(gradient_box_args) => {
-          self
-            .draw_backend
-            .execute_gradient_box_draw(gradient_box_args, &self.canvas_size);
-        }
-      };
+      execute_draw(draw_backend, args, device_pixel_ratio, &size);
     }
+    draw_backend.finish_frame();
     self.queued.clear();
   }
 
+  pub fn create_render_target(&self, size: &F2) -> Rc<dyn RenderTarget> {
+    return self.draw_backend.create_render_target(size);
+  }
+
+  pub fn create_text_cache(&self) -> Option<Rc<TextCache>> {
+    return self.draw_backend.create_text_cache();
+  }
+
   pub fn draw_canvas(&mut self, args: DrawImageArgs) {
-    self.queued.push(DrawArgs::Image(args));
+    self.render_queue.push(DrawArgs::Image(args));
   }
 
   pub fn draw_screen(&mut self, mut args: DrawImageArgs) {
@@ -68,7 +135,7 @@ impl DrawManager {
   }
 
   pub fn draw_gradient_box_canvas(&mut self, args: DrawGradientBoxArgs) {
-    self.queued.push(DrawArgs::GradientBox(args));
+    self.render_queue.push(DrawArgs::GradientBox(args));
   }
 
   pub fn draw_gradient_box_screen(&mut self, mut args: DrawGradientBoxArgs) {
@@ -92,12 +159,109 @@ impl DrawManager {
   }
 
   fn draw_string_canvas(&mut self, args: DrawStringArgs) {
-    self.queued.push(DrawArgs::String(args));
+    if let Some(font_atlas) = args.optional.font_atlas.clone() {
+      for image_args in font_atlas.get_image_draws(&args) {
+        self.draw_canvas(image_args);
+      }
+      return;
+    }
+    self.render_queue.push(DrawArgs::String(args));
   }
 
   pub fn draw_string_viewport(&mut self, viewport: &Viewport, mut args: DrawStringArgs) {
     args.position = viewport.viewport_to_canvas(&args.position);
     args.font_size = viewport.viewport_to_canvas_ratio_y(&args.font_size);
+    args.optional.max_width = args
+      .optional
+      .max_width
+      .map(|max_width| viewport.viewport_to_canvas_ratio_x(&max_width));
     self.draw_string_canvas(args);
   }
+
+  fn draw_stroke_canvas(&mut self, args: DrawStrokeArgs) {
+    self.render_queue.push(DrawArgs::Stroke(args));
+  }
+
+  pub fn convert_viewport_into_canvas_stroke_args(
+    &self,
+    viewport: &Viewport,
+    mut args: DrawStrokeArgs,
+  ) -> DrawStrokeArgs {
+    for point in args.points.iter_mut() {
+      point.position = viewport.viewport_to_canvas(&point.position);
+      point.width = viewport.viewport_to_canvas_ratio_x(&point.width);
+    }
+    return args;
+  }
+
+  pub fn draw_stroke_viewport(&mut self, viewport: &Viewport, args: DrawStrokeArgs) {
+    self.draw_stroke_canvas(self.convert_viewport_into_canvas_stroke_args(viewport, args));
+  }
+
+  fn draw_shape_canvas(&mut self, args: DrawShapeArgs) {
+    self.render_queue.push(DrawArgs::Shape(args));
+  }
+
+  pub fn convert_viewport_into_canvas_shape_args(
+    &self,
+    viewport: &Viewport,
+    mut args: DrawShapeArgs,
+  ) -> DrawShapeArgs {
+    let to_canvas = |position: &mut F2| *position = viewport.viewport_to_canvas(position);
+    let to_canvas_length = |length: &mut F1| *length = viewport.viewport_to_canvas_ratio_x(length);
+    match &mut args.shape {
+      DrawShape::Line { start, end } => {
+        to_canvas(start);
+        to_canvas(end);
+      }
+      DrawShape::Polyline { points } | DrawShape::Polygon { points } => {
+        points.iter_mut().for_each(to_canvas);
+      }
+      DrawShape::Arc { center, radius, .. } | DrawShape::Circle { center, radius } => {
+        to_canvas(center);
+        to_canvas_length(radius);
+      }
+      DrawShape::RoundedRect {
+        position,
+        size,
+        corner_radius,
+      } => {
+        to_canvas(position);
+        *size = viewport.viewport_to_canvas_ratio(size);
+        to_canvas_length(corner_radius);
+      }
+    }
+    to_canvas_length(&mut args.optional.stroke_width);
+    args.optional.dash.iter_mut().for_each(to_canvas_length);
+    return args;
+  }
+
+  pub fn draw_shape_viewport(&mut self, viewport: &Viewport, args: DrawShapeArgs) {
+    self.draw_shape_canvas(self.convert_viewport_into_canvas_shape_args(viewport, args));
+  }
+}
+
+fn execute_draw(
+  draw_backend: &dyn DrawBackend,
+  args: &DrawArgs,
+  device_pixel_ratio: &F1,
+  canvas_size: &F2,
+) {
+  match args {
+    DrawArgs::Image(image_args) => {
+      draw_backend.execute_image_draw(image_args, canvas_size);
+    }
+    DrawArgs::String(string_args) => {
+      draw_backend.execute_string_draw(string_args, device_pixel_ratio, canvas_size);
+    }
+    DrawArgs::GradientBox(gradient_box_args) => {
+      draw_backend.execute_gradient_box_draw(gradient_box_args, canvas_size);
+    }
+    DrawArgs::Stroke(stroke_args) => {
+      draw_backend.execute_stroke_draw(stroke_args, canvas_size);
+    }
+    DrawArgs::Shape(shape_args) => {
+      draw_backend.execute_shape_draw(shape_args, canvas_size);
+    }
+  };
 }
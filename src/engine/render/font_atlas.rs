@@ -0,0 +1,232 @@
+use crate::engine::*;
+use serde::Deserialize;
+
+// A baked glyph. Positions are in pixels of the atlas, metrics in pixels of
+// the baked font size, y going up from the baseline.
+#[derive(Deserialize)]
+pub struct FontAtlasGlyph {
+  pub x: F1,
+  pub y: F1,
+  pub width: F1,
+  pub height: F1,
+  pub xmin: F1,
+  pub ymin: F1,
+  pub advance: F1,
+}
+
+// Written by `cargo run --example bake_fonts`.
+#[derive(Deserialize)]
+pub struct FontAtlasManifest {
+  pub src: String,
+  pub size: F1,
+  pub ascent: F1,
+  pub descent: F1,
+  pub width: F1,
+  pub height: F1,
+  // Keyed by a single character.
+  glyphs: HashMap<String, FontAtlasGlyph>,
+}
+
+// Part of the atlas drawn for a glyph.
+#[derive(Debug, PartialEq)]
+pub struct FontAtlasQuad {
+  pub top_left: F2,
+  pub size: F2,
+  pub partial_region_offset: F2,
+  pub partial_region_size: F2,
+}
+
+impl FontAtlasManifest {
+  pub fn from_json(json: &str) -> FontAtlasManifest {
+    return serde_json::from_str(json).expect("invalid font atlas manifest");
+  }
+
+  // Characters that were not baked are drawn as a question mark.
+  fn get_glyph(&self, character: char) -> Option<&FontAtlasGlyph> {
+    let mut buffer = [0; 4];
+    return self
+      .glyphs
+      .get(character.encode_utf8(&mut buffer) as &str)
+      .or_else(|| self.glyphs.get("?"));
+  }
+
+  pub fn get_advance(&self, text: &str, pixel_size: F1) -> F1 {
+    let scale = pixel_size / self.size;
+    return text
+      .chars()
+      .filter_map(|character| self.get_glyph(character))
+      .map(|glyph| glyph.advance * scale)
+      .sum();
+  }
+
+  // Between two baselines, before line spacing.
+  pub fn get_line_height(&self, pixel_size: F1) -> F1 {
+    return (self.ascent + self.descent) * pixel_size / self.size;
+  }
+
+  // Quads of the glyphs of `text`, starting from the pen position `start` on
+  // the baseline.
+  pub fn get_glyph_quads(&self, text: &str, pixel_size: F1, start: &F2) -> Vec<FontAtlasQuad> {
+    let scale = pixel_size / self.size;
+    let mut quads = Vec::new();
+    let mut pen_x = start.x;
+    for glyph in text
+      .chars()
+      .filter_map(|character| self.get_glyph(character))
+    {
+      if glyph.width > 0.0 && glyph.height > 0.0 {
+        quads.push(FontAtlasQuad {
+          top_left: F2 {
+            x: pen_x + glyph.xmin * scale,
+            y: start.y - (glyph.ymin + glyph.height) * scale,
+          },
+          size: F2 {
+            x: glyph.width * scale,
+            y: glyph.height * scale,
+          },
+          partial_region_offset: F2 {
+            x: glyph.x / self.width,
+            y: glyph.y / self.height,
+          },
+          partial_region_size: F2 {
+            x: glyph.width / self.width,
+            y: glyph.height / self.height,
+          },
+        });
+      }
+      pen_x += glyph.advance * scale;
+    }
+    return quads;
+  }
+}
+
+// Draws strings as textured quads of a baked glyph atlas instead of
+// rasterizing them, see DrawStringOptionalArgs::font_atlas. Every backend can
+// draw them and changing the text costs nothing, but the glyphs blur when
+// drawn much larger than the baked size.
+pub struct FontAtlas {
+  pub texture: Rc<Texture>,
+  pub manifest: FontAtlasManifest,
+}
+
+impl FontAtlas {
+  pub fn new(texture: Rc<Texture>, manifest: FontAtlasManifest) -> Rc<FontAtlas> {
+    return Rc::new(FontAtlas { texture, manifest });
+  }
+
+  // Image draws of the glyphs of `args`, positioned like Canvas2dDrawBackend
+  // positions text: lines centered around the position, the alphabetic
+  // baseline a quarter of the size below it.
+  pub fn get_image_draws(&self, args: &DrawStringArgs) -> Vec<DrawImageArgs> {
+    let pixel_size = args.font_size * super::canvas_backend::TEXT_FONT_SIZE_ADJUSTMENT_RATIO;
+    let layout = TextLayout::new(
+      &args.text,
+      &args.optional.spans,
+      args.optional.max_width,
+      &mut |text, _bold| self.manifest.get_advance(text, pixel_size),
+    );
+    let line_height = self.manifest.get_line_height(pixel_size) * args.optional.line_spacing;
+    let first_baseline =
+      args.position.y + pixel_size * 0.25 - 0.5 * (layout.lines.len() - 1) as F1 * line_height;
+
+    let mut passes = Vec::new();
+    if args.optional.border {
+      for offset in TextLayout::get_border_offsets(args.optional.border_scale).iter() {
+        passes.push((true, pixel_size / 200.0 * *offset));
+      }
+    }
+    passes.push((false, F2 { x: 0.0, y: 0.0 }));
+
+    let mut draws = Vec::new();
+    for (border, offset) in passes.iter() {
+      for (index, line) in layout.lines.iter().enumerate() {
+        let start_x = args.position.x
+          + offset.x
+          + match args.optional.alignment {
+            TextAlignment::Left => 0.0,
+            TextAlignment::Center => -line.width * 0.5,
+            TextAlignment::Right => -line.width,
+          };
+        let baseline = first_baseline + offset.y + index as F1 * line_height;
+        for run in line.runs.iter() {
+          let color = if *border {
+            args.optional.border_color
+          } else {
+            run.style.color.unwrap_or(args.optional.color)
+          };
+          // Bold runs are drawn twice, slightly shifted.
+          let shifts: &[F1] = if run.style.bold {
+            &[0.0, pixel_size * 0.04]
+          } else {
+            &[0.0]
+          };
+          for shift in shifts.iter() {
+            let start = F2 {
+              x: start_x + run.x + shift,
+              y: baseline,
+            };
+            for quad in self.manifest.get_glyph_quads(&run.text, pixel_size, &start) {
+              draws.push(DrawImageArgs {
+                source: DrawSource::Texture(self.texture.clone()),
+                position: quad.top_left,
+                size: quad.size,
+                layer: args.layer,
+                depth: args.depth,
+                optional: DrawImageOptionalArgs {
+                  color,
+                  anchor_point: F2 { x: 0.0, y: 0.0 },
+                  opacity: args.optional.opacity,
+                  partial_region_offset: quad.partial_region_offset,
+                  partial_region_size: quad.partial_region_size,
+                  subpixel_precision: true,
+                  ..Default::default()
+                },
+              });
+            }
+          }
+        }
+      }
+    }
+    return draws;
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_font_atlas_glyph_quads() {
+    let manifest = FontAtlasManifest::from_json(
+      r#"{
+        "src": "/static/fonts/Test.png",
+        "size": 10.0,
+        "ascent": 8.0,
+        "descent": 2.0,
+        "width": 100.0,
+        "height": 50.0,
+        "glyphs": {
+          " ": {"x": 0, "y": 0, "width": 0, "height": 0, "xmin": 0, "ymin": 0, "advance": 3},
+          "?": {"x": 10, "y": 0, "width": 5, "height": 8, "xmin": 1, "ymin": 0, "advance": 6},
+          "g": {"x": 20, "y": 10, "width": 6, "height": 9, "xmin": 0, "ymin": -3, "advance": 7}
+        }
+      }"#,
+    );
+    assert_eq!(manifest.get_line_height(20.0), 20.0);
+    // The unknown "x" is measured and drawn as "?".
+    assert_eq!(manifest.get_advance("g x", 20.0), 32.0);
+
+    let quads = manifest.get_glyph_quads("g x", 20.0, &F2 { x: 100.0, y: 50.0 });
+    assert_eq!(quads.len(), 2);
+    assert_eq!(
+      quads[0],
+      FontAtlasQuad {
+        top_left: F2 { x: 100.0, y: 38.0 },
+        size: F2 { x: 12.0, y: 18.0 },
+        partial_region_offset: F2 { x: 0.2, y: 0.2 },
+        partial_region_size: F2 { x: 0.06, y: 0.18 },
+      }
+    );
+    assert_eq!(quads[1].top_left, F2 { x: 122.0, y: 34.0 });
+  }
+}
@@ -1,8 +1,22 @@
+pub mod apng_encoder;
 pub mod cached_canvas_backend;
+pub mod camera;
 pub mod canvas_backend;
+pub mod dirty_region_tracker;
 pub mod draw_args;
+pub mod draw_backend;
 pub mod draw_manager;
+pub mod font_atlas;
+pub mod render_queue;
+pub mod render_target;
+pub mod shape_geometry;
+#[cfg(all(test, not(target_arch = "wasm32")))]
+pub mod software_backend;
+pub mod stroke_geometry;
 pub mod text_cache;
+pub mod text_layout;
 pub mod texture;
+pub mod texture_atlas;
 pub mod texture_loader;
 pub mod viewport;
+pub mod webgl_backend;
@@ -0,0 +1,101 @@
+use crate::engine::*;
+
+// Draws of a lower layer are executed first, so they end up below the draws of
+// higher layers. Games name their layers, see ContextTrait::get_ui_layer.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct RenderLayer(pub u8);
+
+impl RenderLayer {
+  // Above any layer of the game, for debug overlays.
+  pub const OVERLAY: RenderLayer = RenderLayer(u8::MAX);
+}
+
+// Draws queued during a frame, grouped by layer. Within a layer draws are
+// executed in the order they were queued. Depth is optional: a layer whose
+// draws set different depths is sorted stably, a higher depth first, so draws
+// of equal depth keep their queue order. Layers leaving depth at 0 are never
+// sorted.
+pub struct RenderQueue {
+  layers: Vec<Vec<DrawArgs>>,
+}
+
+impl RenderQueue {
+  pub fn new() -> RenderQueue {
+    return RenderQueue { layers: Vec::new() };
+  }
+
+  pub fn push(&mut self, args: DrawArgs) {
+    let index = args.get_layer().0 as usize;
+    if index >= self.layers.len() {
+      self.layers.resize_with(index + 1, Vec::new);
+    }
+    self.layers[index].push(args);
+  }
+
+  // Moves all the queued draws to the end of `ordered`, in execution order.
+  pub fn drain_into(&mut self, ordered: &mut Vec<DrawArgs>) {
+    for layer in self.layers.iter_mut() {
+      let in_depth_order = layer
+        .windows(2)
+        .all(|pair| pair[0].get_depth() >= pair[1].get_depth());
+      if !in_depth_order {
+        layer.sort_by(|a, b| b.get_depth().total_cmp(a.get_depth()));
+      }
+      ordered.append(layer);
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn gradient_box(layer: u8, depth: F1, x: F1) -> DrawArgs {
+    return DrawArgs::GradientBox(DrawGradientBoxArgs {
+      position: F2 { x, y: 0.0 },
+      size: F2 { x: 1.0, y: 1.0 },
+      draw_color_start: DrawColor::default(),
+      alpha_start: 1.0,
+      draw_color_end: DrawColor::default(),
+      alpha_end: 1.0,
+      layer: RenderLayer(layer),
+      depth,
+      anchor_point: F2 { x: 0.0, y: 0.0 },
+    });
+  }
+
+  fn get_order(ordered: &[DrawArgs]) -> Vec<F1> {
+    return ordered
+      .iter()
+      .map(|args| match args {
+        DrawArgs::GradientBox(args) => args.position.x,
+        _ => panic!("unexpected draw"),
+      })
+      .collect();
+  }
+
+  #[test]
+  fn test_render_queue_order() {
+    let mut queue = RenderQueue::new();
+    queue.push(gradient_box(2, 0.0, 0.0));
+    queue.push(gradient_box(0, 0.0, 1.0));
+    queue.push(gradient_box(2, 0.0, 2.0));
+    queue.push(gradient_box(0, 0.0, 3.0));
+    // Inside a layer a higher depth goes below, NaN doesn't panic.
+    queue.push(gradient_box(1, 0.0, 4.0));
+    queue.push(gradient_box(1, 0.5, 5.0));
+    queue.push(gradient_box(1, 0.0, 6.0));
+    queue.push(gradient_box(1, F1::NAN, 7.0));
+
+    let mut ordered = Vec::new();
+    queue.drain_into(&mut ordered);
+    assert_eq!(
+      get_order(&ordered),
+      vec![1.0, 3.0, 7.0, 5.0, 4.0, 6.0, 0.0, 2.0]
+    );
+
+    ordered.clear();
+    queue.drain_into(&mut ordered);
+    assert!(ordered.is_empty());
+  }
+}
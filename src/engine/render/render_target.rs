@@ -0,0 +1,97 @@
+use crate::engine::*;
+use std::any::Any;
+use wasm_bindgen::JsCast;
+
+// Offscreen surface that can be drawn into and then drawn like a texture.
+pub trait RenderTarget {
+  fn get_size(&self) -> F2;
+  // Resizing discards the current contents.
+  fn set_size(&self, size: &F2);
+  fn get_draw_backend(&self) -> &dyn DrawBackend;
+  // Lets backends reach the surface type they know how to sample from.
+  fn as_any(&self) -> &dyn Any;
+}
+
+// Render target backed by an offscreen HtmlCanvasElement, used by the browser
+// backends.
+pub struct CanvasRenderTarget {
+  pub canvas: Rc<web_sys::HtmlCanvasElement>,
+  pub canvas_context: Rc<web_sys::CanvasRenderingContext2d>,
+  size: Cell<F2>,
+  draw_backend: Canvas2dDrawBackend,
+  // Bumped every time the canvas may have been redrawn or resized.
+  generation: Cell<u32>,
+}
+
+impl CanvasRenderTarget {
+  pub fn new(size: &F2) -> Rc<CanvasRenderTarget> {
+    let document = web_sys::window().unwrap().document().unwrap();
+    let canvas = document
+      .create_element("canvas")
+      .expect("failed to create canvas")
+      .dyn_into::<web_sys::HtmlCanvasElement>()
+      .expect("failed to dyn_into");
+    canvas.set_width(size.x as u32);
+    canvas.set_height(size.y as u32);
+
+    let canvas_context = Rc::new(
+      canvas
+        .get_context("2d")
+        .expect("failed to get_context(\"2d\")")
+        .unwrap()
+        .dyn_into::<web_sys::CanvasRenderingContext2d>()
+        .expect("failed to dyn_into::<web_sys::CanvasRenderingContext2d>()"),
+    );
+
+    return Rc::new(CanvasRenderTarget {
+      canvas: Rc::new(canvas),
+      canvas_context: canvas_context.clone(),
+      size: Cell::new(*size),
+      draw_backend: Canvas2dDrawBackend::new(canvas_context),
+      generation: Cell::new(0),
+    });
+  }
+
+  // None for render targets created by other backends.
+  pub fn from_render_target(render_target: &dyn RenderTarget) -> Option<&CanvasRenderTarget> {
+    return render_target.as_any().downcast_ref::<CanvasRenderTarget>();
+  }
+
+  pub fn get_generation(&self) -> u32 {
+    return self.generation.get();
+  }
+
+  // Straight alpha RGBA pixels, row by row.
+  pub fn get_rgba_pixels(&self) -> Vec<u8> {
+    let size = self.size.get();
+    return self
+      .canvas_context
+      .get_image_data(0.0, 0.0, size.x.into(), size.y.into())
+      .expect("canvas_context.get_image_data failed")
+      .data()
+      .0;
+  }
+}
+
+impl RenderTarget for CanvasRenderTarget {
+  fn get_size(&self) -> F2 {
+    return self.size.get();
+  }
+
+  fn set_size(&self, size: &F2) {
+    self.generation.set(self.generation.get().wrapping_add(1));
+    self.size.set(*size);
+    self.canvas.set_width(size.x as u32);
+    self.canvas.set_height(size.y as u32);
+  }
+
+  // Whoever asks for the backend draws with it.
+  fn get_draw_backend(&self) -> &dyn DrawBackend {
+    self.generation.set(self.generation.get().wrapping_add(1));
+    return &self.draw_backend;
+  }
+
+  fn as_any(&self) -> &dyn Any {
+    return self;
+  }
+}
@@ -0,0 +1,514 @@
+use crate::engine::*;
+use std::f64::consts::TAU;
+
+// Longest side of the segments approximating arcs, in pixels.
+const ARC_SEGMENT_LENGTH: F1 = 4.0;
+const MIN_CIRCLE_SEGMENTS: usize = 8;
+const MAX_ARC_SEGMENTS: usize = 128;
+// Points closer than this are merged, since they have no direction.
+const MIN_POINT_DISTANCE: F1 = 0.001;
+// Corners turning more than this, in radians, are rounded with a full circle.
+// Gentler ones are bevelled, which looks the same.
+const MAX_BEVEL_TURN: F1 = 0.5;
+
+// Turns shapes into triangles a backend can fill. All the triangles are wound
+// the same way, so a path made of them fills their union.
+pub struct ShapeGeometry {
+  _not_instatiable: (),
+}
+
+impl ShapeGeometry {
+  // Points along the shape, arcs included. Closed shapes don't repeat their
+  // first point.
+  pub fn get_outline(shape: &DrawShape) -> Vec<F2> {
+    return match shape {
+      DrawShape::Line { start, end } => vec![*start, *end],
+      DrawShape::Polyline { points } | DrawShape::Polygon { points } => points.clone(),
+      DrawShape::Arc {
+        center,
+        radius,
+        start_angle,
+        end_angle,
+      } => get_arc(center, *radius, *start_angle, *end_angle, true),
+      DrawShape::Circle { center, radius } => get_arc(center, *radius, 0.0, TAU as F1, false),
+      DrawShape::RoundedRect {
+        position,
+        size,
+        corner_radius,
+      } => {
+        let half_size = F2 {
+          x: size.x.abs() * 0.5,
+          y: size.y.abs() * 0.5,
+        };
+        let radius = corner_radius.max(0.0).min(half_size.x).min(half_size.y);
+        let min = *position - half_size
+          + F2 {
+            x: radius,
+            y: radius,
+          };
+        let max = *position + half_size
+          - F2 {
+            x: radius,
+            y: radius,
+          };
+        let quarter_turn = TAU as F1 * 0.25;
+        let corners = [
+          (F2 { x: max.x, y: min.y }, -quarter_turn),
+          (max, 0.0),
+          (F2 { x: min.x, y: max.y }, quarter_turn),
+          (min, 2.0 * quarter_turn),
+        ];
+        let mut outline = Vec::new();
+        for (center, start_angle) in corners.iter() {
+          if radius == 0.0 {
+            outline.push(*center);
+            continue;
+          }
+          outline.extend(get_arc(
+            center,
+            radius,
+            *start_angle,
+            start_angle + quarter_turn,
+            true,
+          ));
+        }
+        outline
+      }
+    };
+  }
+
+  // Triangles covering the inside of closed shapes. Open shapes have none.
+  pub fn get_fill_triangles(shape: &DrawShape) -> Vec<[F2; 3]> {
+    if !shape.is_closed() {
+      return Vec::new();
+    }
+    let mut outline = dedup_points(&ShapeGeometry::get_outline(shape));
+    if outline.len() > 1
+      && F2::distance(&outline[0], &outline[outline.len() - 1]) < MIN_POINT_DISTANCE
+    {
+      outline.pop();
+    }
+    return triangulate(&outline);
+  }
+
+  // Triangles covering the outline of the shape, `optional.stroke_width` wide.
+  pub fn get_stroke_triangles(shape: &DrawShape, optional: &DrawShapeOptionalArgs) -> Vec<[F2; 3]> {
+    let half_width = optional.stroke_width * 0.5;
+    let mut outline = ShapeGeometry::get_outline(shape);
+    if half_width <= 0.0 || outline.is_empty() {
+      return Vec::new();
+    }
+    let closed = shape.is_closed();
+    if closed {
+      outline.push(outline[0]);
+    }
+    let dashes = get_dashes(&outline, &optional.dash);
+    // A solid closed outline has no ends to cap.
+    let line_cap = if closed && dashes.len() == 1 && optional.dash.iter().sum::<F1>() <= 0.0 {
+      None
+    } else {
+      Some(optional.line_cap)
+    };
+    let mut triangles = Vec::new();
+    for dash in dashes.iter() {
+      push_polyline_triangles(&mut triangles, dash, half_width, line_cap);
+    }
+    return triangles;
+  }
+
+  // Bounding box of the shape and its stroke, as (min, max).
+  pub fn get_bounds(args: &DrawShapeArgs) -> (F2, F2) {
+    let mut min = F2 {
+      x: F1::MAX,
+      y: F1::MAX,
+    };
+    let mut max = F2 {
+      x: F1::MIN,
+      y: F1::MIN,
+    };
+    for point in ShapeGeometry::get_outline(&args.shape).iter() {
+      min.x = min.x.min(point.x);
+      min.y = min.y.min(point.y);
+      max.x = max.x.max(point.x);
+      max.y = max.y.max(point.y);
+    }
+    if args.optional.stroke_color.is_some() {
+      // Enough for the corners of square caps.
+      let margin = args.optional.stroke_width * 0.5 * (2.0 as F1).sqrt();
+      min -= &F2 {
+        x: margin,
+        y: margin,
+      };
+      max += &F2 {
+        x: margin,
+        y: margin,
+      };
+    }
+    return (min, max);
+  }
+}
+
+// Points from `start_angle` to `end_angle`, the last one only if `include_end`.
+fn get_arc(center: &F2, radius: F1, start_angle: F1, end_angle: F1, include_end: bool) -> Vec<F2> {
+  let sweep = end_angle - start_angle;
+  let segments = ((sweep.abs() * radius.abs() / ARC_SEGMENT_LENGTH).ceil() as usize)
+    .max(if include_end { 1 } else { MIN_CIRCLE_SEGMENTS })
+    .min(MAX_ARC_SEGMENTS);
+  let count = if include_end { segments + 1 } else { segments };
+  return (0..count)
+    .map(|i| {
+      let angle = start_angle + sweep * (i as F1) / (segments as F1);
+      *center
+        + F2 {
+          x: angle.cos() * radius,
+          y: angle.sin() * radius,
+        }
+    })
+    .collect();
+}
+
+fn get_direction(start: &F2, end: &F2) -> F2 {
+  let mut direction = *end - *start;
+  direction.normalize();
+  return direction;
+}
+
+fn dedup_points(points: &[F2]) -> Vec<F2> {
+  let mut deduped: Vec<F2> = Vec::with_capacity(points.len());
+  for point in points.iter() {
+    match deduped.last() {
+      Some(last) if F2::distance(last, point) < MIN_POINT_DISTANCE => {}
+      _ => deduped.push(*point),
+    }
+  }
+  return deduped;
+}
+
+fn get_area(triangle: &[F2; 3]) -> F1 {
+  return F2::crossp(&(triangle[1] - triangle[0]), &(triangle[2] - triangle[0])) * 0.5;
+}
+
+fn push_triangle(triangles: &mut Vec<[F2; 3]>, triangle: [F2; 3]) {
+  let area = get_area(&triangle);
+  if area > 0.0 {
+    triangles.push(triangle);
+  } else if area < 0.0 {
+    triangles.push([triangle[0], triangle[2], triangle[1]]);
+  }
+}
+
+// Ear clipping, for simple polygons. What's left of self-intersecting ones is
+// filled as a fan.
+fn triangulate(polygon: &[F2]) -> Vec<[F2; 3]> {
+  let mut triangles = Vec::new();
+  if polygon.len() < 3 {
+    return triangles;
+  }
+  let mut signed_area = 0.0;
+  for i in 0..polygon.len() {
+    signed_area += F2::crossp(&polygon[i], &polygon[(i + 1) % polygon.len()]);
+  }
+  let mut indices: Vec<usize> = (0..polygon.len()).collect();
+  if signed_area < 0.0 {
+    indices.reverse();
+  }
+
+  while indices.len() > 3 {
+    let count = indices.len();
+    let mut clipped = false;
+    for i in 0..count {
+      let (previous, current, next) = (
+        polygon[indices[(i + count - 1) % count]],
+        polygon[indices[i]],
+        polygon[indices[(i + 1) % count]],
+      );
+      let turn = F2::crossp(&(current - previous), &(next - current));
+      if turn == 0.0 {
+        // Straight through, nothing to fill.
+        indices.remove(i);
+        clipped = true;
+        break;
+      }
+      if turn < 0.0 {
+        continue;
+      }
+      let ear = [previous, current, next];
+      let contains_other_point = indices.iter().any(|index| {
+        let point = polygon[*index];
+        !ear.contains(&point) && is_inside_triangle(&point, &ear)
+      });
+      if !contains_other_point {
+        push_triangle(&mut triangles, ear);
+        indices.remove(i);
+        clipped = true;
+        break;
+      }
+    }
+    if !clipped {
+      for i in 1..(indices.len() - 1) {
+        push_triangle(
+          &mut triangles,
+          [
+            polygon[indices[0]],
+            polygon[indices[i]],
+            polygon[indices[i + 1]],
+          ],
+        );
+      }
+      return triangles;
+    }
+  }
+  push_triangle(
+    &mut triangles,
+    [
+      polygon[indices[0]],
+      polygon[indices[1]],
+      polygon[indices[2]],
+    ],
+  );
+  return triangles;
+}
+
+// For triangles wound with a positive area, edges included.
+fn is_inside_triangle(point: &F2, triangle: &[F2; 3]) -> bool {
+  return (0..3).all(|i| {
+    F2::crossp(
+      &(triangle[(i + 1) % 3] - triangle[i]),
+      &(*point - triangle[i]),
+    ) >= 0.0
+  });
+}
+
+// Splits `outline` into the polylines of the dashes. Like canvas line dashes,
+// a pattern with an odd number of lengths is repeated twice.
+fn get_dashes(outline: &[F2], dash: &[F1]) -> Vec<Vec<F2>> {
+  if dash.iter().sum::<F1>() <= 0.0 || dash.iter().any(|length| *length < 0.0) {
+    return vec![outline.to_vec()];
+  }
+  let pattern: Vec<F1> = if dash.len() % 2 == 1 {
+    dash.iter().chain(dash.iter()).copied().collect()
+  } else {
+    dash.to_vec()
+  };
+
+  let mut dashes = Vec::new();
+  let mut current = vec![outline[0]];
+  let mut index = 0;
+  let mut remaining = pattern[0];
+  let mut on = true;
+  for segment in outline.windows(2) {
+    let length = F2::distance(&segment[0], &segment[1]);
+    let mut position = 0.0;
+    while length - position >= remaining {
+      position += remaining;
+      let point = segment[0] + (segment[1] - segment[0]) * (position / length);
+      if on {
+        current.push(point);
+        dashes.push(std::mem::take(&mut current));
+      } else {
+        current = vec![point];
+      }
+      index = (index + 1) % pattern.len();
+      remaining = pattern[index];
+      on = !on;
+    }
+    remaining -= length - position;
+    if on {
+      current.push(segment[1]);
+    }
+  }
+  if on && current.len() > 1 {
+    dashes.push(current);
+  }
+  return dashes;
+}
+
+fn push_disc_triangles(triangles: &mut Vec<[F2; 3]>, center: &F2, radius: F1) {
+  let rim = get_arc(center, radius, 0.0, TAU as F1, false);
+  for i in 0..rim.len() {
+    push_triangle(triangles, [*center, rim[i], rim[(i + 1) % rim.len()]]);
+  }
+}
+
+// Quads along each segment, joined by bevels or discs. `line_cap` is None for
+// closed outlines, whose ends meet.
+fn push_polyline_triangles(
+  triangles: &mut Vec<[F2; 3]>,
+  points: &[F2],
+  half_width: F1,
+  line_cap: Option<LineCap>,
+) {
+  let mut points = dedup_points(points);
+  if points.len() == 1 {
+    match line_cap {
+      Some(LineCap::Round) => push_disc_triangles(triangles, &points[0], half_width),
+      Some(LineCap::Square) => {
+        let corner = F2 {
+          x: half_width,
+          y: half_width,
+        };
+        let (min, max) = (points[0] - corner, points[0] + corner);
+        push_triangle(triangles, [min, F2 { x: max.x, y: min.y }, max]);
+        push_triangle(triangles, [min, max, F2 { x: min.x, y: max.y }]);
+      }
+      _ => {}
+    }
+    return;
+  }
+  if points.len() < 2 {
+    return;
+  }
+
+  let last = points.len() - 1;
+  if line_cap == Some(LineCap::Square) {
+    let start_direction = get_direction(&points[1], &points[0]);
+    let end_direction = get_direction(&points[last - 1], &points[last]);
+    points[0] += &(start_direction * half_width);
+    points[last] += &(end_direction * half_width);
+  }
+
+  let get_normal = |start: &F2, end: &F2| {
+    let direction = get_direction(start, end);
+    return F2 {
+      x: -direction.y * half_width,
+      y: direction.x * half_width,
+    };
+  };
+  for segment in points.windows(2) {
+    let normal = get_normal(&segment[0], &segment[1]);
+    let corners = [
+      segment[0] + normal,
+      segment[1] + normal,
+      segment[1] - normal,
+      segment[0] - normal,
+    ];
+    push_triangle(triangles, [corners[0], corners[1], corners[2]]);
+    push_triangle(triangles, [corners[0], corners[2], corners[3]]);
+  }
+
+  let mut joins: Vec<usize> = (1..last).collect();
+  if line_cap.is_none() && last > 1 {
+    // The first point is also the last one.
+    joins.push(0);
+  }
+  for i in joins.into_iter() {
+    let previous = if i == 0 {
+      points[last - 1]
+    } else {
+      points[i - 1]
+    };
+    let next = points[i + 1];
+    let (normal_in, normal_out) = (
+      get_normal(&previous, &points[i]),
+      get_normal(&points[i], &next),
+    );
+    let turn = F2::crossp(&normal_in, &normal_out)
+      .atan2(F2::dotp(&normal_in, &normal_out))
+      .abs();
+    if turn > MAX_BEVEL_TURN {
+      push_disc_triangles(triangles, &points[i], half_width);
+    } else {
+      push_triangle(
+        triangles,
+        [points[i], points[i] + normal_in, points[i] + normal_out],
+      );
+      push_triangle(
+        triangles,
+        [points[i], points[i] - normal_in, points[i] - normal_out],
+      );
+    }
+  }
+
+  if line_cap == Some(LineCap::Round) {
+    push_disc_triangles(triangles, &points[0], half_width);
+    push_disc_triangles(triangles, &points[last], half_width);
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn get_total_area(triangles: &[[F2; 3]]) -> F1 {
+    return triangles.iter().map(get_area).sum();
+  }
+
+  #[test]
+  fn test_fill_concave_polygon() {
+    // L shape, 3 squares of 10x10.
+    let shape = DrawShape::Polygon {
+      points: vec![
+        F2 { x: 0.0, y: 0.0 },
+        F2 { x: 10.0, y: 0.0 },
+        F2 { x: 10.0, y: 10.0 },
+        F2 { x: 20.0, y: 10.0 },
+        F2 { x: 20.0, y: 20.0 },
+        F2 { x: 0.0, y: 20.0 },
+      ],
+    };
+    let triangles = ShapeGeometry::get_fill_triangles(&shape);
+    assert_eq!(triangles.len(), 4);
+    assert!((get_total_area(&triangles) - 300.0).abs() < 1e-3);
+  }
+
+  #[test]
+  fn test_fill_rounded_rect() {
+    let shape = DrawShape::RoundedRect {
+      position: F2 { x: 50.0, y: 50.0 },
+      size: F2 { x: 40.0, y: 20.0 },
+      corner_radius: 0.0,
+    };
+    assert_eq!(ShapeGeometry::get_outline(&shape).len(), 4);
+    let triangles = ShapeGeometry::get_fill_triangles(&shape);
+    assert!((get_total_area(&triangles) - 800.0).abs() < 1e-3);
+
+    let rounded = DrawShape::RoundedRect {
+      position: F2 { x: 50.0, y: 50.0 },
+      size: F2 { x: 40.0, y: 20.0 },
+      corner_radius: 100.0,
+    };
+    // A stadium: the radius is clamped to half the height.
+    let area = get_total_area(&ShapeGeometry::get_fill_triangles(&rounded));
+    let expected_area = 20.0 * 20.0 + std::f64::consts::PI as F1 * 10.0 * 10.0;
+    assert!(area < expected_area && area > expected_area * 0.98);
+  }
+
+  #[test]
+  fn test_stroke_line() {
+    let line = DrawShape::Line {
+      start: F2 { x: 0.0, y: 0.0 },
+      end: F2 { x: 100.0, y: 0.0 },
+    };
+    let optional = DrawShapeOptionalArgs {
+      stroke_width: 4.0,
+      ..Default::default()
+    };
+    let butt = ShapeGeometry::get_stroke_triangles(&line, &optional);
+    assert!((get_total_area(&butt) - 400.0).abs() < 1e-3);
+
+    let square = ShapeGeometry::get_stroke_triangles(
+      &line,
+      &DrawShapeOptionalArgs {
+        line_cap: LineCap::Square,
+        ..optional
+      },
+    );
+    assert!((get_total_area(&square) - 416.0).abs() < 1e-3);
+  }
+
+  #[test]
+  fn test_dashes() {
+    let outline = [F2 { x: 0.0, y: 0.0 }, F2 { x: 25.0, y: 0.0 }];
+    let dashes = get_dashes(&outline, &[10.0, 5.0]);
+    let expected_dashes = [
+      [F2 { x: 0.0, y: 0.0 }, F2 { x: 10.0, y: 0.0 }],
+      [F2 { x: 15.0, y: 0.0 }, F2 { x: 25.0, y: 0.0 }],
+    ];
+    assert_eq!(dashes.len(), expected_dashes.len());
+    for (dash, expected_dash) in dashes.iter().zip(expected_dashes.iter()) {
+      assert_eq!(dash.len(), 2);
+      assert!(dash[0].eq_near(&expected_dash[0]) && dash[1].eq_near(&expected_dash[1]));
+    }
+    assert_eq!(get_dashes(&outline, &[]), vec![outline.to_vec()]);
+  }
+}
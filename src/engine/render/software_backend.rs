@@ -0,0 +1,1145 @@
+use crate::engine::*;
+use std::any::Any;
+use std::path::{Path, PathBuf};
+
+static FONT_PATH: &str = "static/Oregano-Regular.ttf";
+
+// RGBA image with premultiplied alpha, each channel in [0, 1].
+#[derive(Clone)]
+pub struct SoftwareImage {
+  pub width: usize,
+  pub height: usize,
+  pixels: Vec<[f32; 4]>,
+}
+
+impl SoftwareImage {
+  pub fn new(width: usize, height: usize) -> SoftwareImage {
+    return SoftwareImage {
+      width,
+      height,
+      pixels: vec![[0.0; 4]; width * height],
+    };
+  }
+
+  pub fn load_png(path: &Path) -> Result<SoftwareImage, String> {
+    let file =
+      std::fs::File::open(path).map_err(|error| format!("{}: {}", path.display(), error))?;
+    let mut decoder = png::Decoder::new(file);
+    // Expands palettes and low bit depths to 8 bit channels.
+    decoder.set_transformations(png::Transformations::normalize_to_color8());
+    let mut reader = decoder
+      .read_info()
+      .map_err(|error| format!("{}: {}", path.display(), error))?;
+    let mut buffer = vec![0; reader.output_buffer_size()];
+    let info = reader
+      .next_frame(&mut buffer)
+      .map_err(|error| format!("{}: {}", path.display(), error))?;
+
+    let mut image = SoftwareImage::new(info.width as usize, info.height as usize);
+    let channels = info.color_type.samples();
+    for (pixel, bytes) in image
+      .pixels
+      .iter_mut()
+      .zip(buffer[..info.buffer_size()].chunks(channels))
+    {
+      let (rgb, alpha) = match channels {
+        1 => ([bytes[0]; 3], 255),
+        2 => ([bytes[0]; 3], bytes[1]),
+        3 => ([bytes[0], bytes[1], bytes[2]], 255),
+        _ => ([bytes[0], bytes[1], bytes[2]], bytes[3]),
+      };
+      let alpha = alpha as f32 / 255.0;
+      *pixel = [
+        rgb[0] as f32 / 255.0 * alpha,
+        rgb[1] as f32 / 255.0 * alpha,
+        rgb[2] as f32 / 255.0 * alpha,
+        alpha,
+      ];
+    }
+    return Ok(image);
+  }
+
+  pub fn save_png(&self, path: &Path) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+      std::fs::create_dir_all(parent).map_err(|error| error.to_string())?;
+    }
+    let file =
+      std::fs::File::create(path).map_err(|error| format!("{}: {}", path.display(), error))?;
+    let mut encoder = png::Encoder::new(
+      std::io::BufWriter::new(file),
+      self.width as u32,
+      self.height as u32,
+    );
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder
+      .write_header()
+      .map_err(|error| format!("{}: {}", path.display(), error))?;
+    writer
+      .write_image_data(&self.to_rgba8())
+      .map_err(|error| format!("{}: {}", path.display(), error))?;
+    return Ok(());
+  }
+
+  // Straight alpha RGBA bytes, row by row.
+  pub fn to_rgba8(&self) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(self.pixels.len() * 4);
+    for pixel in self.pixels.iter() {
+      let alpha = pixel[3].max(0.0).min(1.0);
+      for channel in 0..3 {
+        let value = if alpha > 0.0 {
+          pixel[channel] / alpha
+        } else {
+          0.0
+        };
+        bytes.push((value.max(0.0).min(1.0) * 255.0).round() as u8);
+      }
+      bytes.push((alpha * 255.0).round() as u8);
+    }
+    return bytes;
+  }
+
+  // Number of pixels with a premultiplied channel differing by more than
+  // `tolerance` (out of 255), or None if the sizes differ. Premultiplied values
+  // ignore the colour of nearly transparent pixels, which PNG can't keep.
+  pub fn count_different_pixels(&self, other: &SoftwareImage, tolerance: u8) -> Option<usize> {
+    if self.width != other.width || self.height != other.height {
+      return None;
+    }
+    let tolerance = tolerance as f32 / 255.0;
+    let count = self
+      .pixels
+      .iter()
+      .zip(other.pixels.iter())
+      .filter(|(a, b)| {
+        a.iter()
+          .zip(b.iter())
+          .any(|(a, b)| (a - b).abs() > tolerance)
+      })
+      .count();
+    return Some(count);
+  }
+
+  fn clear(&mut self) {
+    for pixel in self.pixels.iter_mut() {
+      *pixel = [0.0; 4];
+    }
+  }
+
+  fn resize(&mut self, width: usize, height: usize) {
+    *self = SoftwareImage::new(width, height);
+  }
+
+  fn get(&self, x: i64, y: i64) -> [f32; 4] {
+    let x = x.max(0).min(self.width as i64 - 1) as usize;
+    let y = y.max(0).min(self.height as i64 - 1) as usize;
+    return self.pixels[y * self.width + x];
+  }
+
+  // Bilinear sample at pixel coordinates, clamped to the `min`..`max` region.
+  fn sample(&self, position: &F2, min: &F2, max: &F2) -> [f32; 4] {
+    let x = (position.x - 0.5).max(min.x).min(max.x - 1.0);
+    let y = (position.y - 0.5).max(min.y).min(max.y - 1.0);
+    let (x0, y0) = (x.floor(), y.floor());
+    let (fx, fy) = (x - x0, y - y0);
+    let (x0, y0) = (x0 as i64, y0 as i64);
+    let x1 = (x0 + 1).min(max.x as i64 - 1);
+    let y1 = (y0 + 1).min(max.y as i64 - 1);
+    let (a, b, c, d) = (
+      self.get(x0, y0),
+      self.get(x1, y0),
+      self.get(x0, y1),
+      self.get(x1, y1),
+    );
+    let mut result = [0.0; 4];
+    for channel in 0..4 {
+      let top = a[channel] + (b[channel] - a[channel]) * fx;
+      let bottom = c[channel] + (d[channel] - c[channel]) * fx;
+      result[channel] = top + (bottom - top) * fy;
+    }
+    return result;
+  }
+
+  fn blend(&mut self, x: usize, y: usize, source: [f32; 4], additive: bool) {
+    let destination = &mut self.pixels[y * self.width + x];
+    for channel in 0..4 {
+      destination[channel] = if additive {
+        (destination[channel] + source[channel]).min(1.0)
+      } else {
+        source[channel] + destination[channel] * (1.0 - source[3])
+      };
+    }
+  }
+}
+
+// Textures and the font, shared by a backend and the render targets it creates.
+struct SoftwareResources {
+  root_dir: PathBuf,
+  textures: RefCell<HashMap<String, Rc<SoftwareImage>>>,
+  font: Option<fontdue::Font>,
+}
+
+// Rasterizes draws into a SoftwareImage without a browser. Texture sources are
+// read from the filesystem by resolving their `src` (e.g. "/static/x.png")
+// against the root directory. Strings are always rendered directly, even when
+// a TextCache is given, because TextCache needs a canvas.
+pub struct SoftwareDrawBackend {
+  image: Rc<RefCell<SoftwareImage>>,
+  resources: Rc<SoftwareResources>,
+}
+
+impl SoftwareDrawBackend {
+  // `root_dir` is the directory holding `static/`, usually the repository root.
+  pub fn new(size: &F2, root_dir: &Path) -> SoftwareDrawBackend {
+    let font = std::fs::read(root_dir.join(FONT_PATH))
+      .ok()
+      .and_then(|bytes| fontdue::Font::from_bytes(bytes, fontdue::FontSettings::default()).ok());
+    return SoftwareDrawBackend {
+      image: Rc::new(RefCell::new(SoftwareImage::new(
+        size.x as usize,
+        size.y as usize,
+      ))),
+      resources: Rc::new(SoftwareResources {
+        root_dir: root_dir.to_path_buf(),
+        textures: RefCell::new(HashMap::new()),
+        font,
+      }),
+    };
+  }
+
+  // Shares the pixels this backend draws into, e.g. to read them back after
+  // the backend was handed to a DrawManager.
+  pub fn get_image(&self) -> Rc<RefCell<SoftwareImage>> {
+    return self.image.clone();
+  }
+
+  pub fn load_texture_image(&self, src: &str) -> Rc<SoftwareImage> {
+    let mut textures = self.resources.textures.borrow_mut();
+    if let Some(image) = textures.get(src) {
+      return image.clone();
+    }
+    let path = self.resources.root_dir.join(src.trim_start_matches('/'));
+    let image = Rc::new(SoftwareImage::load_png(&path).expect("SoftwareImage::load_png failed"));
+    textures.insert(String::from(src), image.clone());
+    return image;
+  }
+
+  fn draw_image(
+    &self,
+    source: &SoftwareImage,
+    args: &DrawImageArgs,
+    tint: DrawColor,
+    canvas_size: &F2,
+  ) {
+    let mut center_position = args.position;
+    let mut top_left_rel_position = F2 {
+      x: -args.size.x * args.optional.anchor_point.x,
+      y: -args.size.y * args.optional.anchor_point.y,
+    };
+    let mut size = args.size;
+    // Same rounding as Canvas2dDrawBackend, so both backends line up.
+    if !args.optional.subpixel_precision {
+      center_position.round();
+      top_left_rel_position.round();
+      size.round();
+    }
+    if size.x == 0.0 || size.y == 0.0 || source.width == 0 || source.height == 0 {
+      return;
+    }
+
+    let source_size = F2 {
+      x: source.width as F1,
+      y: source.height as F1,
+    };
+    let region_min = F2 {
+      x: source_size.x * args.optional.partial_region_offset.x,
+      y: source_size.y * args.optional.partial_region_offset.y,
+    };
+    let region_size = F2 {
+      x: source_size.x * args.optional.partial_region_size.x,
+      y: source_size.y * args.optional.partial_region_size.y,
+    };
+    let region_max = region_min + region_size;
+
+    let rotation = args.optional.rotation;
+    let mut corners = [
+      top_left_rel_position,
+      top_left_rel_position + F2 { x: size.x, y: 0.0 },
+      top_left_rel_position + size,
+      top_left_rel_position + F2 { x: 0.0, y: size.y },
+    ];
+    for corner in corners.iter_mut() {
+      corner.rotate(&rotation);
+      *corner += &center_position;
+    }
+    let (min, max) = get_pixel_bounds(&corners, canvas_size);
+
+    let tint = [
+      tint.r as f32 / 255.0 * args.optional.opacity,
+      tint.g as f32 / 255.0 * args.optional.opacity,
+      tint.b as f32 / 255.0 * args.optional.opacity,
+      args.optional.opacity,
+    ];
+    let additive = args.optional.composite_operation.as_deref() == Some("lighter");
+    let mut image = self.image.borrow_mut();
+    for y in min.1..max.1 {
+      for x in min.0..max.0 {
+        let mut local = F2 {
+          x: x as F1 + 0.5,
+          y: y as F1 + 0.5,
+        } - &center_position;
+        local.rotate(&-rotation);
+        local -= &top_left_rel_position;
+        if local.x < 0.0 || local.y < 0.0 || local.x >= size.x || local.y >= size.y {
+          continue;
+        }
+        let source_position = F2 {
+          x: region_min.x + local.x / size.x * region_size.x,
+          y: region_min.y + local.y / size.y * region_size.y,
+        };
+        let mut color = source.sample(&source_position, &region_min, &region_max);
+        for channel in 0..4 {
+          color[channel] *= tint[channel];
+        }
+        image.blend(x, y, color, additive);
+      }
+    }
+  }
+
+  // `brush` replaces the image of args.optional.brush.
+  fn draw_stroke(&self, brush: Option<&SoftwareImage>, args: &DrawStrokeArgs, canvas_size: &F2) {
+    if args.points.is_empty() || args.optional.opacity == 0.0 {
+      return;
+    }
+    let opacity = args.optional.opacity;
+    let tint = [
+      args.optional.color.r as f32 / 255.0 * opacity,
+      args.optional.color.g as f32 / 255.0 * opacity,
+      args.optional.color.b as f32 / 255.0 * opacity,
+      opacity,
+    ];
+    let mut image = self.image.borrow_mut();
+    for triangle in StrokeGeometry::get_triangles(&args.points).iter() {
+      let corners = [
+        triangle[0].position,
+        triangle[1].position,
+        triangle[2].position,
+      ];
+      let area = F2::crossp(&(corners[1] - corners[0]), &(corners[2] - corners[0]));
+      if area == 0.0 {
+        continue;
+      }
+      let (min, max) = get_pixel_bounds(&corners, canvas_size);
+      for y in min.1..max.1 {
+        for x in min.0..max.0 {
+          let center = F2 {
+            x: x as F1 + 0.5,
+            y: y as F1 + 0.5,
+          };
+          // Barycentric weights, all positive inside the triangle.
+          let weights = [
+            F2::crossp(&(corners[2] - corners[1]), &(center - corners[1])) / area,
+            F2::crossp(&(corners[0] - corners[2]), &(center - corners[2])) / area,
+            F2::crossp(&(corners[1] - corners[0]), &(center - corners[0])) / area,
+          ];
+          if weights.iter().any(|weight| *weight < 0.0) {
+            continue;
+          }
+          let mut color = tint;
+          if let Some(brush) = brush {
+            let brush_size = F2 {
+              x: brush.width as F1,
+              y: brush.height as F1,
+            };
+            let mut tex_coord = F2::default();
+            for (vertex, weight) in triangle.iter().zip(weights.iter()) {
+              tex_coord += &(vertex.tex_coord * *weight);
+            }
+            let sample = brush.sample(
+              &F2 {
+                x: tex_coord.x * brush_size.x,
+                y: tex_coord.y * brush_size.y,
+              },
+              &F2::default(),
+              &brush_size,
+            );
+            for channel in 0..4 {
+              color[channel] *= sample[3];
+            }
+          }
+          image.blend(x, y, color, false);
+        }
+      }
+    }
+  }
+
+  // Pixels covered by several triangles are blended once.
+  fn fill_triangles(&self, triangles: &[[F2; 3]], color: DrawColor, opacity: F1, canvas_size: &F2) {
+    if triangles.is_empty() {
+      return;
+    }
+    let corners: Vec<F2> = triangles.iter().flatten().copied().collect();
+    let (min, max) = get_pixel_bounds(&corners, canvas_size);
+    let width = max.0 - min.0;
+    let mut covered = vec![false; width * (max.1 - min.1)];
+    for triangle in triangles.iter() {
+      let (triangle_min, triangle_max) = get_pixel_bounds(triangle, canvas_size);
+      for y in triangle_min.1..triangle_max.1 {
+        for x in triangle_min.0..triangle_max.0 {
+          let center = F2 {
+            x: x as F1 + 0.5,
+            y: y as F1 + 0.5,
+          };
+          // ShapeGeometry winds all the triangles with a positive area.
+          let inside = (0..3).all(|i| {
+            F2::crossp(
+              &(triangle[(i + 1) % 3] - triangle[i]),
+              &(center - triangle[i]),
+            ) >= 0.0
+          });
+          if inside {
+            covered[(y - min.1) * width + (x - min.0)] = true;
+          }
+        }
+      }
+    }
+    let source = [
+      color.r as f32 / 255.0 * opacity,
+      color.g as f32 / 255.0 * opacity,
+      color.b as f32 / 255.0 * opacity,
+      opacity,
+    ];
+    let mut image = self.image.borrow_mut();
+    for y in min.1..max.1 {
+      for x in min.0..max.0 {
+        if covered[(y - min.1) * width + (x - min.0)] {
+          image.blend(x, y, source, false);
+        }
+      }
+    }
+  }
+
+  // Draws the lines of `layout` centered around the position, like
+  // Canvas2dDrawBackend does. Bold runs are drawn twice, slightly shifted.
+  fn draw_text_layout(
+    &self,
+    font: &fontdue::Font,
+    args: &DrawStringArgs,
+    layout: &TextLayout,
+    border: bool,
+    offset: &F2,
+  ) {
+    let pixel_size = args.font_size * super::canvas_backend::TEXT_FONT_SIZE_ADJUSTMENT_RATIO;
+    let line_height = font
+      .horizontal_line_metrics(pixel_size)
+      .map_or(pixel_size, |metrics| metrics.ascent - metrics.descent)
+      * args.optional.line_spacing;
+    // Canvas2dDrawBackend puts the alphabetic baseline a quarter of the size
+    // below the position.
+    let first_baseline = args.position.y + offset.y + pixel_size * 0.25
+      - 0.5 * (layout.lines.len() - 1) as F1 * line_height;
+    for (index, line) in layout.lines.iter().enumerate() {
+      let start_x = args.position.x
+        + offset.x
+        + match args.optional.alignment {
+          TextAlignment::Left => 0.0,
+          TextAlignment::Center => -line.width * 0.5,
+          TextAlignment::Right => -line.width,
+        };
+      let baseline = first_baseline + index as F1 * line_height;
+      for run in line.runs.iter() {
+        let color = if border {
+          args.optional.border_color
+        } else {
+          run.style.color.unwrap_or(args.optional.color)
+        };
+        let shifts: &[F1] = if run.style.bold {
+          &[0.0, pixel_size * 0.04]
+        } else {
+          &[0.0]
+        };
+        for shift in shifts.iter() {
+          self.draw_text(
+            font,
+            &run.text,
+            pixel_size,
+            &F2 {
+              x: start_x + run.x + shift,
+              y: baseline,
+            },
+            color,
+            args.optional.opacity,
+          );
+        }
+      }
+    }
+  }
+
+  fn draw_text(
+    &self,
+    font: &fontdue::Font,
+    text: &str,
+    pixel_size: F1,
+    start: &F2,
+    color: DrawColor,
+    opacity: F1,
+  ) {
+    let color = [
+      color.r as f32 / 255.0,
+      color.g as f32 / 255.0,
+      color.b as f32 / 255.0,
+    ];
+    let mut image = self.image.borrow_mut();
+    let mut pen_x = start.x;
+    for character in text.chars() {
+      let (metrics, coverage) = font.rasterize(character, pixel_size);
+      let left = (pen_x + metrics.xmin as F1).round() as i64;
+      let top = (start.y - (metrics.ymin + metrics.height as i32) as F1).round() as i64;
+      for glyph_y in 0..metrics.height {
+        for glyph_x in 0..metrics.width {
+          let (x, y) = (left + glyph_x as i64, top + glyph_y as i64);
+          if x < 0 || y < 0 || x >= image.width as i64 || y >= image.height as i64 {
+            continue;
+          }
+          let alpha = coverage[glyph_y * metrics.width + glyph_x] as f32 / 255.0 * opacity;
+          if alpha > 0.0 {
+            image.blend(
+              x as usize,
+              y as usize,
+              [color[0] * alpha, color[1] * alpha, color[2] * alpha, alpha],
+              false,
+            );
+          }
+        }
+      }
+      pen_x += metrics.advance_width;
+    }
+  }
+}
+
+// Pixel rows and columns covered by the corners, clipped to the canvas.
+fn get_pixel_bounds(corners: &[F2], canvas_size: &F2) -> ((usize, usize), (usize, usize)) {
+  let mut min = F2 {
+    x: F1::MAX,
+    y: F1::MAX,
+  };
+  let mut max = F2 {
+    x: F1::MIN,
+    y: F1::MIN,
+  };
+  for corner in corners.iter() {
+    min.x = min.x.min(corner.x);
+    min.y = min.y.min(corner.y);
+    max.x = max.x.max(corner.x);
+    max.y = max.y.max(corner.y);
+  }
+  let clip = |value: F1, limit: F1| value.max(0.0).min(limit) as usize;
+  return (
+    (
+      clip(min.x.floor(), canvas_size.x),
+      clip(min.y.floor(), canvas_size.y),
+    ),
+    (
+      clip(max.x.ceil(), canvas_size.x),
+      clip(max.y.ceil(), canvas_size.y),
+    ),
+  );
+}
+
+impl DrawBackend for SoftwareDrawBackend {
+  fn clear_screen(&self, canvas_size: &F2) {
+    let mut image = self.image.borrow_mut();
+    if image.width != canvas_size.x as usize || image.height != canvas_size.y as usize {
+      image.resize(canvas_size.x as usize, canvas_size.y as usize);
+    } else {
+      image.clear();
+    }
+  }
+
+  fn execute_image_draw(&self, args: &DrawImageArgs, canvas_size: &F2) {
+    if args.optional.opacity == 0.0 {
+      return;
+    }
+    match &args.source {
+      DrawSource::Texture(texture) => {
+        let source = self.load_texture_image(&texture.src);
+        self.draw_image(&source, args, args.optional.color, canvas_size);
+      }
+      DrawSource::RenderTarget(render_target) => {
+        let source = match SoftwareRenderTarget::from_render_target(render_target.as_ref()) {
+          Some(software_render_target) => {
+            software_render_target.draw_backend.image.borrow().clone()
+          }
+          None => return,
+        };
+        self.draw_image(&source, args, DrawColor::default(), canvas_size);
+      }
+    }
+  }
+
+  fn execute_string_draw(
+    &self,
+    args: &DrawStringArgs,
+    _device_pixel_ratio: &F1,
+    _canvas_size: &F2,
+  ) {
+    let font = match self.resources.font.as_ref() {
+      Some(font) => font,
+      None => return,
+    };
+    if args.optional.opacity == 0.0 {
+      return;
+    }
+    let pixel_size = args.font_size * super::canvas_backend::TEXT_FONT_SIZE_ADJUSTMENT_RATIO;
+    let layout = TextLayout::new(
+      &args.text,
+      &args.optional.spans,
+      args.optional.max_width,
+      &mut |text, _bold| {
+        return text
+          .chars()
+          .map(|character| font.metrics(character, pixel_size).advance_width)
+          .sum();
+      },
+    );
+    if args.optional.border {
+      // Same outline as Canvas2dDrawBackend.
+      let scale = args.font_size / 200.0 * super::canvas_backend::TEXT_FONT_SIZE_ADJUSTMENT_RATIO;
+      for offset in TextLayout::get_border_offsets(args.optional.border_scale).iter() {
+        self.draw_text_layout(font, args, &layout, true, &(scale * *offset));
+      }
+    }
+    self.draw_text_layout(font, args, &layout, false, &F2 { x: 0.0, y: 0.0 });
+  }
+
+  fn execute_gradient_box_draw(&self, args: &DrawGradientBoxArgs, canvas_size: &F2) {
+    let top_left = args.position
+      + F2 {
+        x: -args.size.x * args.anchor_point.x,
+        y: -args.size.y * args.anchor_point.y,
+      };
+    let (min, max) = get_pixel_bounds(&[top_left, top_left + args.size], canvas_size);
+    let start = [
+      args.draw_color_start.r as f32 / 255.0,
+      args.draw_color_start.g as f32 / 255.0,
+      args.draw_color_start.b as f32 / 255.0,
+      args.alpha_start,
+    ];
+    let end = [
+      args.draw_color_end.r as f32 / 255.0,
+      args.draw_color_end.g as f32 / 255.0,
+      args.draw_color_end.b as f32 / 255.0,
+      args.alpha_end,
+    ];
+    let mut image = self.image.borrow_mut();
+    for x in min.0..max.0 {
+      let t = ((x as F1 + 0.5 - top_left.x) / args.size.x)
+        .max(0.0)
+        .min(1.0);
+      let mut color = [0.0; 4];
+      for channel in 0..4 {
+        color[channel] = start[channel] + (end[channel] - start[channel]) * t;
+      }
+      let color = [
+        color[0] * color[3],
+        color[1] * color[3],
+        color[2] * color[3],
+        color[3],
+      ];
+      for y in min.1..max.1 {
+        image.blend(x, y, color, false);
+      }
+    }
+  }
+
+  fn execute_stroke_draw(&self, args: &DrawStrokeArgs, canvas_size: &F2) {
+    let brush = args
+      .optional
+      .brush
+      .as_ref()
+      .map(|brush| self.load_texture_image(&brush.src));
+    self.draw_stroke(brush.as_deref(), args, canvas_size);
+  }
+
+  fn execute_shape_draw(&self, args: &DrawShapeArgs, canvas_size: &F2) {
+    if args.optional.opacity == 0.0 {
+      return;
+    }
+    if let Some(fill_color) = args.optional.fill_color {
+      self.fill_triangles(
+        &ShapeGeometry::get_fill_triangles(&args.shape),
+        fill_color,
+        args.optional.opacity,
+        canvas_size,
+      );
+    }
+    if let Some(stroke_color) = args.optional.stroke_color {
+      self.fill_triangles(
+        &ShapeGeometry::get_stroke_triangles(&args.shape, &args.optional),
+        stroke_color,
+        args.optional.opacity,
+        canvas_size,
+      );
+    }
+  }
+
+  fn create_render_target(&self, size: &F2) -> Rc<dyn RenderTarget> {
+    return Rc::new(SoftwareRenderTarget {
+      draw_backend: SoftwareDrawBackend {
+        image: Rc::new(RefCell::new(SoftwareImage::new(
+          size.x as usize,
+          size.y as usize,
+        ))),
+        resources: self.resources.clone(),
+      },
+    });
+  }
+}
+
+pub struct SoftwareRenderTarget {
+  draw_backend: SoftwareDrawBackend,
+}
+
+impl SoftwareRenderTarget {
+  // None for render targets created by other backends.
+  pub fn from_render_target(render_target: &dyn RenderTarget) -> Option<&SoftwareRenderTarget> {
+    return render_target
+      .as_any()
+      .downcast_ref::<SoftwareRenderTarget>();
+  }
+}
+
+impl RenderTarget for SoftwareRenderTarget {
+  fn get_size(&self) -> F2 {
+    let image = self.draw_backend.image.borrow();
+    return F2 {
+      x: image.width as F1,
+      y: image.height as F1,
+    };
+  }
+
+  fn set_size(&self, size: &F2) {
+    self
+      .draw_backend
+      .image
+      .borrow_mut()
+      .resize(size.x as usize, size.y as usize);
+  }
+
+  fn get_draw_backend(&self) -> &dyn DrawBackend {
+    return &self.draw_backend;
+  }
+
+  fn as_any(&self) -> &dyn Any {
+    return self;
+  }
+}
+
+// Set UPDATE_GOLDEN_IMAGES=1 to rewrite the golden images instead of
+// comparing against them.
+pub fn check_golden(name: &str, image: &SoftwareImage) {
+  let root_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+  let golden_path = root_dir.join("tests/golden").join(format!("{}.png", name));
+  if std::env::var("UPDATE_GOLDEN_IMAGES").is_ok() {
+    image.save_png(&golden_path).unwrap();
+    return;
+  }
+  let golden = SoftwareImage::load_png(&golden_path).unwrap();
+  let different_pixels = image.count_different_pixels(&golden, 2);
+  if different_pixels != Some(0) {
+    let actual_path = root_dir
+      .join("target/golden")
+      .join(format!("{}.actual.png", name));
+    image.save_png(&actual_path).unwrap();
+    panic!(
+      "{} doesn't match {} ({:?} different pixels), see {}",
+      name,
+      golden_path.display(),
+      different_pixels,
+      actual_path.display()
+    );
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn create_backend(size: &F2) -> SoftwareDrawBackend {
+    return SoftwareDrawBackend::new(size, Path::new(env!("CARGO_MANIFEST_DIR")));
+  }
+
+  fn create_image_render_target(
+    backend: &SoftwareDrawBackend,
+    image: &SoftwareImage,
+  ) -> Rc<dyn RenderTarget> {
+    return Rc::new(SoftwareRenderTarget {
+      draw_backend: SoftwareDrawBackend {
+        image: Rc::new(RefCell::new(image.clone())),
+        resources: backend.resources.clone(),
+      },
+    });
+  }
+
+  #[test]
+  fn test_png_round_trip() {
+    let backend = create_backend(&F2 { x: 1.0, y: 1.0 });
+    let image = backend.load_texture_image("/static/star_active_bright.png");
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("target/golden/round_trip.png");
+    image.save_png(&path).unwrap();
+    assert_eq!(
+      SoftwareImage::load_png(&path)
+        .unwrap()
+        .count_different_pixels(&image, 0),
+      Some(0)
+    );
+  }
+
+  #[test]
+  fn test_golden_draw_manager_scene() {
+    let size = F2 { x: 160.0, y: 120.0 };
+    let backend = create_backend(&size);
+    let image = backend.get_image();
+    let star = create_image_render_target(
+      &backend,
+      &backend.load_texture_image("/static/star_active_bright.png"),
+    );
+    let mut draw_manager = DrawManager::new(Box::new(backend), &size, &1.0);
+
+    draw_manager.draw_gradient_box_canvas(DrawGradientBoxArgs {
+      position: F2 { x: 0.0, y: 0.0 },
+      size,
+      draw_color_start: DrawColor {
+        r: 40,
+        g: 90,
+        b: 160,
+      },
+      alpha_start: 1.0,
+      draw_color_end: DrawColor {
+        r: 230,
+        g: 200,
+        b: 120,
+      },
+      alpha_end: 0.5,
+      layer: RenderLayer::default(),
+      depth: 10.0,
+      anchor_point: F2 { x: 0.0, y: 0.0 },
+    });
+    // Drawn first but on top, since lower depths are drawn last within a layer.
+    draw_manager.draw_canvas(DrawImageArgs {
+      source: DrawSource::RenderTarget(star.clone()),
+      position: F2 { x: 50.0, y: 60.0 },
+      size: F2 { x: 60.0, y: 60.0 },
+      layer: RenderLayer::default(),
+      depth: 1.0,
+      optional: DrawImageOptionalArgs {
+        rotation: 0.4,
+        ..Default::default()
+      },
+    });
+    draw_manager.draw_canvas(DrawImageArgs {
+      source: DrawSource::RenderTarget(star.clone()),
+      position: F2 { x: 115.0, y: 60.0 },
+      size: F2 { x: 60.0, y: 60.0 },
+      layer: RenderLayer::default(),
+      depth: 2.0,
+      optional: DrawImageOptionalArgs {
+        opacity: 0.6,
+        partial_region_offset: F2 { x: 0.5, y: 0.0 },
+        partial_region_size: F2 { x: 0.5, y: 1.0 },
+        composite_operation: Some(String::from("lighter")),
+        ..Default::default()
+      },
+    });
+    draw_manager.execute_draws();
+
+    check_golden("draw_manager_scene", &image.borrow());
+  }
+
+  #[test]
+  fn test_golden_tinted_texture_and_text() {
+    let size = F2 { x: 200.0, y: 80.0 };
+    let backend = create_backend(&size);
+    backend.clear_screen(&size);
+    let star = backend.load_texture_image("/static/star_active_bright.png");
+    backend.draw_image(
+      &star,
+      &DrawImageArgs {
+        source: DrawSource::RenderTarget(create_image_render_target(&backend, &star)),
+        position: F2 { x: 40.0, y: 40.0 },
+        size: F2 { x: 64.0, y: 64.0 },
+        layer: RenderLayer::default(),
+        depth: 0.0,
+        optional: DrawImageOptionalArgs::default(),
+      },
+      DrawColor {
+        r: 80,
+        g: 200,
+        b: 255,
+      },
+      &size,
+    );
+    backend.execute_string_draw(
+      &DrawStringArgs {
+        text: String::from("Panda"),
+        position: F2 { x: 135.0, y: 40.0 },
+        font_size: 40.0,
+        layer: RenderLayer::default(),
+        depth: 0.0,
+        optional: DrawStringOptionalArgs {
+          alignment: TextAlignment::Center,
+          color: DrawColor {
+            r: 255,
+            g: 240,
+            b: 200,
+          },
+          border: true,
+          border_color: DrawColor {
+            r: 60,
+            g: 30,
+            b: 10,
+          },
+          ..Default::default()
+        },
+      },
+      &1.0,
+      &size,
+    );
+
+    check_golden("tinted_texture_and_text", &backend.get_image().borrow());
+  }
+
+  #[test]
+  fn test_golden_wrapped_text() {
+    let size = F2 { x: 200.0, y: 110.0 };
+    let backend = create_backend(&size);
+    backend.clear_screen(&size);
+    let (text, spans) = TextSpan::from_parts(&[
+      ("Fill the ", TextStyle::default()),
+      (
+        "red heart",
+        TextStyle {
+          color: Some(DrawColor {
+            r: 230,
+            g: 40,
+            b: 40,
+          }),
+          bold: true,
+        },
+      ),
+      (" with red ink.\nSolved!", TextStyle::default()),
+    ]);
+    backend.execute_string_draw(
+      &DrawStringArgs {
+        text,
+        position: F2 { x: 100.0, y: 55.0 },
+        font_size: 36.0,
+        layer: RenderLayer::default(),
+        depth: 0.0,
+        optional: DrawStringOptionalArgs {
+          alignment: TextAlignment::Center,
+          border: true,
+          border_color: DrawColor {
+            r: 60,
+            g: 30,
+            b: 10,
+          },
+          max_width: Some(170.0),
+          line_spacing: 0.9,
+          spans,
+          ..Default::default()
+        },
+      },
+      &1.0,
+      &size,
+    );
+
+    check_golden("wrapped_text", &backend.get_image().borrow());
+  }
+
+  #[test]
+  fn test_golden_strokes() {
+    let size = F2 { x: 160.0, y: 80.0 };
+    let backend = create_backend(&size);
+    backend.clear_screen(&size);
+    let control_points = [
+      StrokePoint {
+        position: F2 { x: 15.0, y: 60.0 },
+        width: 6.0,
+      },
+      StrokePoint {
+        position: F2 { x: 50.0, y: 15.0 },
+        width: 16.0,
+      },
+      StrokePoint {
+        position: F2 { x: 90.0, y: 55.0 },
+        width: 10.0,
+      },
+      StrokePoint {
+        position: F2 { x: 140.0, y: 25.0 },
+        width: 20.0,
+      },
+    ];
+    backend.execute_stroke_draw(
+      &DrawStrokeArgs {
+        points: StrokeGeometry::smooth(&control_points, 8),
+        layer: RenderLayer::default(),
+        depth: 0.0,
+        optional: DrawStrokeOptionalArgs {
+          color: DrawColor {
+            r: 220,
+            g: 60,
+            b: 40,
+          },
+          ..Default::default()
+        },
+      },
+      &size,
+    );
+    let brush = backend.load_texture_image("/static/mancha.png");
+    backend.draw_stroke(
+      Some(&brush),
+      &DrawStrokeArgs {
+        points: StrokeGeometry::smooth(&control_points, 8)
+          .iter()
+          .map(|point| StrokePoint {
+            position: point.position + F2 { x: 0.0, y: 12.0 },
+            width: point.width,
+          })
+          .collect(),
+        layer: RenderLayer::default(),
+        depth: 0.0,
+        optional: DrawStrokeOptionalArgs {
+          color: DrawColor {
+            r: 40,
+            g: 90,
+            b: 200,
+          },
+          opacity: 0.8,
+          ..Default::default()
+        },
+      },
+      &size,
+    );
+
+    check_golden("strokes", &backend.get_image().borrow());
+  }
+
+  #[test]
+  fn test_golden_shapes() {
+    let size = F2 { x: 200.0, y: 100.0 };
+    let backend = create_backend(&size);
+    backend.clear_screen(&size);
+    let draw_shape = |shape: DrawShape, optional: DrawShapeOptionalArgs| {
+      backend.execute_shape_draw(
+        &DrawShapeArgs {
+          shape,
+          layer: RenderLayer::default(),
+          depth: 0.0,
+          optional,
+        },
+        &size,
+      );
+    };
+    draw_shape(
+      DrawShape::RoundedRect {
+        position: F2 { x: 45.0, y: 50.0 },
+        size: F2 { x: 70.0, y: 50.0 },
+        corner_radius: 12.0,
+      },
+      DrawShapeOptionalArgs {
+        fill_color: Some(DrawColor {
+          r: 240,
+          g: 220,
+          b: 160,
+        }),
+        stroke_color: Some(DrawColor {
+          r: 120,
+          g: 80,
+          b: 40,
+        }),
+        stroke_width: 4.0,
+        ..Default::default()
+      },
+    );
+    draw_shape(
+      DrawShape::Circle {
+        center: F2 { x: 120.0, y: 35.0 },
+        radius: 22.0,
+      },
+      DrawShapeOptionalArgs {
+        stroke_color: Some(DrawColor {
+          r: 90,
+          g: 60,
+          b: 140,
+        }),
+        stroke_width: 3.0,
+        dash: vec![8.0, 4.0],
+        line_cap: LineCap::Round,
+        opacity: 0.8,
+        ..Default::default()
+      },
+    );
+    draw_shape(
+      DrawShape::Polygon {
+        points: vec![
+          F2 { x: 150.0, y: 90.0 },
+          F2 { x: 170.0, y: 55.0 },
+          F2 { x: 175.0, y: 75.0 },
+          F2 { x: 195.0, y: 60.0 },
+          F2 { x: 190.0, y: 95.0 },
+        ],
+      },
+      DrawShapeOptionalArgs {
+        fill_color: Some(DrawColor {
+          r: 60,
+          g: 160,
+          b: 90,
+        }),
+        stroke_color: None,
+        opacity: 0.7,
+        ..Default::default()
+      },
+    );
+    draw_shape(
+      DrawShape::Polyline {
+        points: vec![
+          F2 { x: 95.0, y: 90.0 },
+          F2 { x: 110.0, y: 70.0 },
+          F2 { x: 125.0, y: 88.0 },
+          F2 { x: 140.0, y: 68.0 },
+        ],
+      },
+      DrawShapeOptionalArgs {
+        stroke_color: Some(DrawColor {
+          r: 200,
+          g: 60,
+          b: 60,
+        }),
+        stroke_width: 6.0,
+        line_cap: LineCap::Square,
+        ..Default::default()
+      },
+    );
+    draw_shape(
+      DrawShape::Arc {
+        center: F2 { x: 175.0, y: 25.0 },
+        radius: 15.0,
+        start_angle: 0.0,
+        end_angle: 4.0,
+      },
+      DrawShapeOptionalArgs {
+        stroke_color: Some(DrawColor {
+          r: 80,
+          g: 140,
+          b: 230,
+        }),
+        stroke_width: 5.0,
+        line_cap: LineCap::Round,
+        ..Default::default()
+      },
+    );
+
+    check_golden("shapes", &backend.get_image().borrow());
+  }
+}
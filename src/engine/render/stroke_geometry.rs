@@ -0,0 +1,332 @@
+use crate::engine::*;
+
+// Vertices in each round cap.
+const CAP_SEGMENTS: usize = 6;
+// Points closer than this are merged, since they have no direction.
+const MIN_POINT_DISTANCE: F1 = 0.001;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct StrokePoint {
+  pub position: F2,
+  pub width: F1,
+}
+
+// Texture coordinates map the stroke across the vertical center line of the
+// brush: y 0 on the left side of the stroke, y 1 on the right side. Round caps
+// map to the brush circle around its center.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct StrokeVertex {
+  pub position: F2,
+  pub tex_coord: F2,
+}
+
+// Turns the center line of a stroke into shapes a backend can fill.
+pub struct StrokeGeometry {
+  _not_instatiable: (),
+}
+
+impl StrokeGeometry {
+  // Catmull-Rom curve from p1 to p2, with p0 and p3 as the neighbouring control
+  // points. Pushes `subdivisions` points, p2 excluded.
+  pub fn push_catmull_rom_segment(
+    result: &mut Vec<StrokePoint>,
+    p0: &StrokePoint,
+    p1: &StrokePoint,
+    p2: &StrokePoint,
+    p3: &StrokePoint,
+    subdivisions: usize,
+  ) {
+    for i in 0..subdivisions {
+      let t = i as F1 / subdivisions as F1;
+      let t2 = t * t;
+      let t3 = t2 * t;
+      let interpolate = |v0: F1, v1: F1, v2: F1, v3: F1| {
+        0.5
+          * (2.0 * v1
+            + (v2 - v0) * t
+            + (2.0 * v0 - 5.0 * v1 + 4.0 * v2 - v3) * t2
+            + (3.0 * v1 - v0 - 3.0 * v2 + v3) * t3)
+      };
+      result.push(StrokePoint {
+        position: F2 {
+          x: interpolate(p0.position.x, p1.position.x, p2.position.x, p3.position.x),
+          y: interpolate(p0.position.y, p1.position.y, p2.position.y, p3.position.y),
+        },
+        width: p1.width + (p2.width - p1.width) * t,
+      });
+    }
+  }
+
+  // Smooth curve through all the control points.
+  pub fn smooth(control_points: &[StrokePoint], subdivisions: usize) -> Vec<StrokePoint> {
+    let count = control_points.len();
+    if count < 2 {
+      return control_points.to_vec();
+    }
+    let mut result = Vec::with_capacity((count - 1) * subdivisions + 1);
+    for i in 0..count - 1 {
+      StrokeGeometry::push_catmull_rom_segment(
+        &mut result,
+        &control_points[i.saturating_sub(1)],
+        &control_points[i],
+        &control_points[i + 1],
+        &control_points[(i + 2).min(count - 1)],
+        subdivisions,
+      );
+    }
+    result.push(control_points[count - 1]);
+    return result;
+  }
+
+  // Closed polygon around the stroke, round caps included.
+  pub fn get_outline(points: &[StrokePoint]) -> Vec<F2> {
+    let points = dedup_points(points);
+    if points.len() == 1 {
+      return get_arc(&points[0], &get_direction(&points, 0), 0.0, 2.0)
+        .iter()
+        .map(|(position, _)| *position)
+        .collect();
+    }
+    let sides: Vec<(F2, F2)> = (0..points.len())
+      .map(|i| get_sides(&points[i], &get_direction(&points, i)))
+      .collect();
+    let last = points.len() - 1;
+    let mut result: Vec<F2> = sides.iter().map(|(left, _)| *left).collect();
+    let end_cap = get_arc(&points[last], &get_direction(&points, last), 0.0, 1.0);
+    result.extend(
+      end_cap[1..CAP_SEGMENTS]
+        .iter()
+        .map(|(position, _)| *position),
+    );
+    result.extend(sides.iter().rev().map(|(_, right)| *right));
+    let start_cap = get_arc(&points[0], &get_direction(&points, 0), 1.0, 2.0);
+    result.extend(
+      start_cap[1..CAP_SEGMENTS]
+        .iter()
+        .map(|(position, _)| *position),
+    );
+    return result;
+  }
+
+  // Triangles covering the stroke, round caps included.
+  pub fn get_triangles(points: &[StrokePoint]) -> Vec<[StrokeVertex; 3]> {
+    let points = dedup_points(points);
+    let mut result = Vec::new();
+    let last = points.len() - 1;
+    if last == 0 {
+      push_cap_triangles(
+        &mut result,
+        &points[0],
+        &get_direction(&points, 0),
+        0.0,
+        2.0,
+      );
+      return result;
+    }
+    for i in 0..last {
+      let (left_start, right_start) = get_sides(&points[i], &get_direction(&points, i));
+      let (left_end, right_end) = get_sides(&points[i + 1], &get_direction(&points, i + 1));
+      let left_start = get_side_vertex(left_start, 0.0);
+      let right_start = get_side_vertex(right_start, 1.0);
+      let left_end = get_side_vertex(left_end, 0.0);
+      let right_end = get_side_vertex(right_end, 1.0);
+      result.push([left_start, left_end, right_end]);
+      result.push([left_start, right_end, right_start]);
+    }
+    push_cap_triangles(
+      &mut result,
+      &points[last],
+      &get_direction(&points, last),
+      0.0,
+      1.0,
+    );
+    push_cap_triangles(
+      &mut result,
+      &points[0],
+      &get_direction(&points, 0),
+      1.0,
+      2.0,
+    );
+    return result;
+  }
+
+  // Bounding box of the stroke, as (min, max).
+  pub fn get_bounds(points: &[StrokePoint]) -> (F2, F2) {
+    let mut min = F2 {
+      x: F1::MAX,
+      y: F1::MAX,
+    };
+    let mut max = F2 {
+      x: F1::MIN,
+      y: F1::MIN,
+    };
+    for point in points.iter() {
+      let radius = point.width * 0.5;
+      min.x = min.x.min(point.position.x - radius);
+      min.y = min.y.min(point.position.y - radius);
+      max.x = max.x.max(point.position.x + radius);
+      max.y = max.y.max(point.position.y + radius);
+    }
+    return (min, max);
+  }
+}
+
+fn dedup_points(points: &[StrokePoint]) -> Vec<StrokePoint> {
+  let mut result: Vec<StrokePoint> = Vec::with_capacity(points.len());
+  for point in points.iter() {
+    match result.last_mut() {
+      Some(last)
+        if F2::distance2(&last.position, &point.position)
+          < MIN_POINT_DISTANCE * MIN_POINT_DISTANCE =>
+      {
+        last.width = last.width.max(point.width);
+      }
+      _ => result.push(*point),
+    }
+  }
+  return result;
+}
+
+// Unit direction of the stroke at point `i`, averaged with its neighbours.
+fn get_direction(points: &[StrokePoint], i: usize) -> F2 {
+  let mut direction =
+    points[(i + 1).min(points.len() - 1)].position - &points[i.saturating_sub(1)].position;
+  if direction.length2() == 0.0 {
+    return F2 { x: 1.0, y: 0.0 };
+  }
+  direction.normalize();
+  return direction;
+}
+
+// Left of the direction is the side turned counter-clockwise on screen.
+fn get_normal(direction: &F2) -> F2 {
+  return F2 {
+    x: direction.y,
+    y: -direction.x,
+  };
+}
+
+// Left and right borders of the stroke at `point`.
+fn get_sides(point: &StrokePoint, direction: &F2) -> (F2, F2) {
+  let offset = get_normal(direction) * (point.width * 0.5);
+  return (point.position + offset, point.position - offset);
+}
+
+fn get_side_vertex(position: F2, tex_coord_y: F1) -> StrokeVertex {
+  return StrokeVertex {
+    position,
+    tex_coord: F2 {
+      x: 0.5,
+      y: tex_coord_y,
+    },
+  };
+}
+
+// Arc around `point` from `turn_start` to `turn_end` half turns, 0 on the left
+// side of the stroke, 0.5 ahead, 1 on the right side and 1.5 behind.
+fn get_arc(point: &StrokePoint, direction: &F2, turn_start: F1, turn_end: F1) -> Vec<(F2, F2)> {
+  let normal = get_normal(direction);
+  let segments = (CAP_SEGMENTS as F1 * (turn_end - turn_start)).round() as usize;
+  let radius = point.width * 0.5;
+  return (0..=segments)
+    .map(|i| {
+      let angle =
+        std::f32::consts::PI * (turn_start + (turn_end - turn_start) * i as F1 / segments as F1);
+      let (sin, cos) = angle.sin_cos();
+      let offset = normal * cos + *direction * sin;
+      (
+        point.position + offset * radius,
+        F2 {
+          x: 0.5 + 0.5 * sin,
+          y: 0.5 - 0.5 * cos,
+        },
+      )
+    })
+    .collect();
+}
+
+fn push_cap_triangles(
+  result: &mut Vec<[StrokeVertex; 3]>,
+  point: &StrokePoint,
+  direction: &F2,
+  turn_start: F1,
+  turn_end: F1,
+) {
+  let center = StrokeVertex {
+    position: point.position,
+    tex_coord: F2 { x: 0.5, y: 0.5 },
+  };
+  let arc = get_arc(point, direction, turn_start, turn_end);
+  for pair in arc.windows(2) {
+    result.push([
+      center,
+      StrokeVertex {
+        position: pair[0].0,
+        tex_coord: pair[0].1,
+      },
+      StrokeVertex {
+        position: pair[1].0,
+        tex_coord: pair[1].1,
+      },
+    ]);
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn stroke_point(x: F1, y: F1, width: F1) -> StrokePoint {
+    return StrokePoint {
+      position: F2 { x, y },
+      width,
+    };
+  }
+
+  #[test]
+  fn test_smooth_passes_through_control_points() {
+    let control_points = [
+      stroke_point(0.0, 0.0, 2.0),
+      stroke_point(10.0, 5.0, 4.0),
+      stroke_point(20.0, 0.0, 2.0),
+    ];
+    let smoothed = StrokeGeometry::smooth(&control_points, 4);
+    assert_eq!(smoothed.len(), 9);
+    assert_eq!(smoothed[0], control_points[0]);
+    assert!(smoothed[4].position.eq_near(&control_points[1].position));
+    assert_eq!(smoothed[4].width, 4.0);
+    assert_eq!(smoothed[8], control_points[2]);
+    assert_eq!(smoothed[2].width, 3.0);
+  }
+
+  #[test]
+  fn test_triangles_and_outline() {
+    let points = [stroke_point(0.0, 0.0, 4.0), stroke_point(10.0, 0.0, 4.0)];
+    // Two triangles for the body and one per cap segment.
+    assert_eq!(
+      StrokeGeometry::get_triangles(&points).len(),
+      2 + 2 * CAP_SEGMENTS
+    );
+    let outline = StrokeGeometry::get_outline(&points);
+    assert_eq!(outline.len(), 2 * (CAP_SEGMENTS + 1));
+    assert!(outline[0].eq_near(&F2 { x: 0.0, y: -2.0 }));
+    assert!(outline[1].eq_near(&F2 { x: 10.0, y: -2.0 }));
+    assert!(outline[1 + CAP_SEGMENTS / 2].eq_near(&F2 { x: 12.0, y: 0.0 }));
+
+    let (min, max) = StrokeGeometry::get_bounds(&points);
+    assert_eq!(min, F2 { x: -2.0, y: -2.0 });
+    assert_eq!(max, F2 { x: 12.0, y: 2.0 });
+  }
+
+  #[test]
+  fn test_single_point_is_a_circle() {
+    let points = [stroke_point(5.0, 5.0, 2.0), stroke_point(5.0, 5.0, 3.0)];
+    assert_eq!(
+      StrokeGeometry::get_triangles(&points).len(),
+      2 * CAP_SEGMENTS
+    );
+    for position in StrokeGeometry::get_outline(&points).iter() {
+      assert!((F2::distance(position, &F2 { x: 5.0, y: 5.0 }) - 1.5).abs() < 0.001);
+    }
+  }
+}
@@ -1,3 +1,4 @@
+use crate::engine::render::canvas_backend::*;
 use crate::engine::*;
 use wasm_bindgen::prelude::*;
 use wasm_bindgen::JsCast;
@@ -28,9 +29,30 @@ extern "C" {
   fn width(this: &ExtendedTextMetrics) -> f64;
 }
 
+// Ascent and descent of the font, whatever the text, with the values of
+// Oregano-Regular at 200px for browsers that don't report them.
+pub fn get_font_bounding_box(text_metrics: web_sys::TextMetrics) -> (f64, f64) {
+  let text_metrics: ExtendedTextMetrics = text_metrics.unchecked_into();
+  let font_bounding_box_ascent = if !text_metrics.font_bounding_box_ascent().is_nan() {
+    text_metrics.font_bounding_box_ascent()
+  } else {
+    193.0
+  };
+  let font_bounding_box_descent = if !text_metrics.font_bounding_box_descent().is_nan() {
+    text_metrics.font_bounding_box_descent()
+  } else {
+    65.0
+  };
+  return (font_bounding_box_ascent, font_bounding_box_descent);
+}
+
 pub struct TextCache {
+  // Text is rasterized by the browser, so the cache is always a canvas.
+  pub canvas_render_target: Rc<CanvasRenderTarget>,
   pub cached_canvas: CachedCanvasBackend,
   pub bounding_box_ascent: Cell<F1>,
+  // Bumped every time the cached canvas is redrawn.
+  pub generation: Cell<u32>,
 
   text: RefCell<String>,
   font_size: Cell<F1>,
@@ -39,13 +61,19 @@ pub struct TextCache {
   border: Cell<bool>,
   border_color: Cell<DrawColor>,
   border_scale: Cell<F1>,
+  max_width: Cell<Option<F1>>,
+  line_spacing: Cell<F1>,
+  spans: RefCell<Vec<TextSpan>>,
 }
 
 impl TextCache {
   pub fn new() -> Rc<TextCache> {
+    let canvas_render_target = CanvasRenderTarget::new(&F2 { x: 0.0, y: 0.0 });
     return Rc::new(TextCache {
-      cached_canvas: CachedCanvasBackend::new(&F2 { x: 0.0, y: 0.0 }),
+      cached_canvas: CachedCanvasBackend::new(canvas_render_target.clone()),
+      canvas_render_target,
       bounding_box_ascent: Cell::new(0.0),
+      generation: Cell::new(0),
 
       text: RefCell::default(),
       font_size: Cell::default(),
@@ -54,6 +82,9 @@ impl TextCache {
       border: Cell::default(),
       border_color: Cell::default(),
       border_scale: Cell::default(),
+      max_width: Cell::default(),
+      line_spacing: Cell::default(),
+      spans: RefCell::default(),
     });
   }
 
@@ -65,6 +96,9 @@ impl TextCache {
       || self.border.get() != args.optional.border
       || self.border_color.get() != args.optional.border_color
       || self.border_scale.get() != args.optional.border_scale
+      || self.max_width.get() != args.optional.max_width
+      || self.line_spacing.get() != args.optional.line_spacing
+      || *self.spans.borrow() != args.optional.spans
     {
       self.text.replace(args.text.clone());
       self.font_size.set(args.font_size);
@@ -73,13 +107,17 @@ impl TextCache {
       self.border.set(args.optional.border);
       self.border_color.set(args.optional.border_color);
       self.border_scale.set(args.optional.border_scale);
+      self.max_width.set(args.optional.max_width);
+      self.line_spacing.set(args.optional.line_spacing);
+      self.spans.replace(args.optional.spans.clone());
+      self.generation.set(self.generation.get().wrapping_add(1));
 
-      let canvas_context = self.cached_canvas.canvas_context.as_ref();
+      let canvas_context = self.canvas_render_target.canvas_context.as_ref();
       canvas_context
         .reset_transform()
         .expect("reset_transform failed");
 
-      canvas_context.set_font("200px Oregano-Regular");
+      canvas_context.set_font(get_font(false));
 
       let scale =
         (args.font_size / 200.0 * super::canvas_backend::TEXT_FONT_SIZE_ADJUSTMENT_RATIO) as f64;
@@ -88,21 +126,22 @@ impl TextCache {
 
       canvas_context.set_text_align("left");
 
+      let layout = get_text_layout(canvas_context, args, scale);
+
       let text_metrics = canvas_context
         .measure_text(args.text.as_str())
         .expect("measure_text failed");
-      let text_metrics: ExtendedTextMetrics = text_metrics.unchecked_into();
-
-      let font_bounding_box_ascent = if !text_metrics.font_bounding_box_ascent().is_nan() {
-        text_metrics.font_bounding_box_ascent()
-      } else {
-        193.0
-      };
-      let font_bounding_box_descent = if !text_metrics.font_bounding_box_descent().is_nan() {
-        text_metrics.font_bounding_box_descent()
+      let extended_text_metrics: &ExtendedTextMetrics = text_metrics.unchecked_ref();
+      let width = if layout.is_plain() {
+        extended_text_metrics.actual_bounding_box_left()
+          + extended_text_metrics.actual_bounding_box_right()
       } else {
-        65.0
+        layout.get_width() as f64
       };
+      let (font_bounding_box_ascent, font_bounding_box_descent) =
+        get_font_bounding_box(text_metrics);
+      let line_height =
+        (font_bounding_box_ascent + font_bounding_box_descent) * args.optional.line_spacing as f64;
 
       let (extra_border, border_width) = if args.optional.border {
         let border_width = 12.0 * args.optional.border_scale as F1 / 2.75;
@@ -126,9 +165,10 @@ impl TextCache {
 
       let size = scale as F1
         * F2 {
-          x: (text_metrics.actual_bounding_box_left() + text_metrics.actual_bounding_box_right())
-            as F1,
-          y: (font_bounding_box_descent + font_bounding_box_ascent) as F1,
+          x: width as F1,
+          y: (font_bounding_box_descent
+            + font_bounding_box_ascent
+            + (layout.lines.len() - 1) as f64 * line_height) as F1,
         }
         + extra_border;
       let position = F2 {
@@ -140,92 +180,45 @@ impl TextCache {
       self.cached_canvas.clear_cache_required.set(true);
       self.cached_canvas.check_clear_cache();
 
-      // canvas_context.set_fill_style(
-      //   &DrawColor {
-      //     r: 0,
-      //     g: 200,
-      //     b: 200,
-      //   }
-      //   .as_rgb_js_value(),
-      // );
-      // canvas_context.fill_rect(0.0, 0.0, size.x.into(), size.y.into());
-
       canvas_context
         .reset_transform()
         .expect("reset_transform failed");
 
-      canvas_context.set_font("200px Oregano-Regular");
-
-      let scale =
-        (args.font_size / 200.0 * super::canvas_backend::TEXT_FONT_SIZE_ADJUSTMENT_RATIO) as f64;
+      canvas_context.set_font(get_font(false));
 
       canvas_context.scale(scale, scale).expect("scale failed");
 
-      canvas_context.set_text_align("center");
-
       canvas_context
         .translate((size.x * 0.5 / scale as F1).into(), position.y.into())
         .expect("translate failed");
 
-      if args.optional.border {
-        canvas_context.set_fill_style(&JsValue::from_str(&format!(
-          "rgb({}, {}, {}",
-          args.optional.border_color.r, args.optional.border_color.g, args.optional.border_color.b
-        )));
-
-        let border_width = 12.0 * args.optional.border_scale as f64 / 2.75;
-        let border_width_diagonal = 0.70 * border_width;
-        let extra_bottom = 4.0;
-        canvas_context
-          .fill_text(args.text.as_str(), border_width, 0.0)
-          .expect("fill_text failed");
-        canvas_context
-          .fill_text(args.text.as_str(), -border_width, 0.0)
-          .expect("fill_text failed");
-        canvas_context
-          .fill_text(args.text.as_str(), 0.0, extra_bottom + border_width)
-          .expect("fill_text failed");
-        canvas_context
-          .fill_text(args.text.as_str(), 0.0, -border_width)
-          .expect("fill_text failed");
-        canvas_context
-          .fill_text(
-            args.text.as_str(),
-            border_width_diagonal,
-            extra_bottom + border_width_diagonal,
-          )
-          .expect("fill_text failed");
-        canvas_context
-          .fill_text(
-            args.text.as_str(),
-            -border_width_diagonal,
-            extra_bottom + border_width_diagonal,
-          )
-          .expect("fill_text failed");
-        canvas_context
-          .fill_text(
-            args.text.as_str(),
-            border_width_diagonal,
-            -border_width_diagonal,
-          )
-          .expect("fill_text failed");
+      // A plain string is centered in the cache, lines are aligned on the side
+      // of the alignment.
+      if layout.is_plain() {
+        fill_text_layout(
+          canvas_context,
+          &layout,
+          line_height,
+          TextAlignment::Center,
+          args,
+        );
+      } else {
+        let anchor_x = match args.optional.alignment {
+          TextAlignment::Left => -0.5 * width,
+          TextAlignment::Center => 0.0,
+          TextAlignment::Right => 0.5 * width,
+        };
         canvas_context
-          .fill_text(
-            args.text.as_str(),
-            -border_width_diagonal,
-            -border_width_diagonal,
-          )
-          .expect("fill_text failed");
+          .translate(anchor_x, 0.0)
+          .expect("translate failed");
+        fill_text_layout(
+          canvas_context,
+          &layout,
+          line_height,
+          args.optional.alignment,
+          args,
+        );
       }
-
-      canvas_context.set_fill_style(&JsValue::from_str(&format!(
-        "rgb({}, {}, {}",
-        args.optional.color.r, args.optional.color.g, args.optional.color.b
-      )));
-
-      canvas_context
-        .fill_text(args.text.as_str(), 0.0, 0.0)
-        .expect("fill_text failed");
     }
   }
 }
@@ -0,0 +1,329 @@
+use crate::engine::*;
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct TextStyle {
+  // None keeps the color of the string.
+  pub color: Option<DrawColor>,
+  pub bold: bool,
+}
+
+// Styles the bytes from `start` to `end` of a string. When spans overlap, the
+// last one sets the color.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct TextSpan {
+  pub start: usize,
+  pub end: usize,
+  pub style: TextStyle,
+}
+
+impl TextSpan {
+  // Concatenates styled parts, returning the text and its spans.
+  pub fn from_parts(parts: &[(&str, TextStyle)]) -> (String, Vec<TextSpan>) {
+    let mut text = String::new();
+    let mut spans = Vec::new();
+    for (part, style) in parts.iter() {
+      if *style != TextStyle::default() {
+        spans.push(TextSpan {
+          start: text.len(),
+          end: text.len() + part.len(),
+          style: *style,
+        });
+      }
+      text.push_str(part);
+    }
+    return (text, spans);
+  }
+}
+
+// Part of a line drawn with a single style.
+#[derive(Debug, PartialEq)]
+pub struct TextRun {
+  pub text: String,
+  pub style: TextStyle,
+  // From the start of the line.
+  pub x: F1,
+  pub width: F1,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct TextLine {
+  pub runs: Vec<TextRun>,
+  pub width: F1,
+}
+
+// Splits a string into lines at its line breaks and, given a max width, between
+// words. A word wider than the max width gets a line of its own. Widths come
+// from `measure`, which returns the advance of a text, bold or not, so backends
+// lay text out with their own font metrics.
+pub struct TextLayout {
+  pub lines: Vec<TextLine>,
+}
+
+impl TextLayout {
+  pub fn new(
+    text: &str,
+    spans: &[TextSpan],
+    max_width: Option<F1>,
+    measure: &mut dyn FnMut(&str, bool) -> F1,
+  ) -> TextLayout {
+    let mut lines = Vec::new();
+    let mut paragraph_start = 0;
+    for paragraph in text.split('\n') {
+      let paragraph_end = paragraph_start + paragraph.len();
+      if let Some(max_width) = max_width {
+        let mut line_start = paragraph_start;
+        let mut line_end = paragraph_start;
+        for (word_start, word_end) in get_words(paragraph, paragraph_start) {
+          if line_end > line_start
+            && get_runs(text, spans, line_start, word_end, measure).1 > max_width
+          {
+            lines.push(get_line(text, spans, line_start, line_end, measure));
+            line_start = word_start;
+          } else if line_end == line_start {
+            line_start = word_start;
+          }
+          line_end = word_end;
+        }
+        lines.push(get_line(text, spans, line_start, line_end, measure));
+      } else {
+        lines.push(get_line(
+          text,
+          spans,
+          paragraph_start,
+          paragraph_end,
+          measure,
+        ));
+      }
+      paragraph_start = paragraph_end + 1;
+    }
+    return TextLayout { lines };
+  }
+
+  pub fn get_width(&self) -> F1 {
+    return self
+      .lines
+      .iter()
+      .map(|line| line.width)
+      .fold(0.0, |a, b| a.max(b));
+  }
+
+  // Offsets of the eight copies of the text drawn below it as a border, for a
+  // 200px font.
+  pub fn get_border_offsets(border_scale: F1) -> [F2; 8] {
+    let border_width = 12.0 * border_scale / 2.75;
+    let diagonal = 0.70 * border_width;
+    let extra_bottom = 4.0;
+    return [
+      F2 {
+        x: border_width,
+        y: 0.0,
+      },
+      F2 {
+        x: -border_width,
+        y: 0.0,
+      },
+      F2 {
+        x: 0.0,
+        y: extra_bottom + border_width,
+      },
+      F2 {
+        x: 0.0,
+        y: -border_width,
+      },
+      F2 {
+        x: diagonal,
+        y: extra_bottom + diagonal,
+      },
+      F2 {
+        x: -diagonal,
+        y: extra_bottom + diagonal,
+      },
+      F2 {
+        x: diagonal,
+        y: -diagonal,
+      },
+      F2 {
+        x: -diagonal,
+        y: -diagonal,
+      },
+    ];
+  }
+
+  // A single unstyled line, which backends draw as they draw plain strings.
+  pub fn is_plain(&self) -> bool {
+    return self.lines.len() == 1
+      && self.lines[0].runs.len() <= 1
+      && self.lines[0]
+        .runs
+        .iter()
+        .all(|run| run.style == TextStyle::default());
+  }
+}
+
+// Byte ranges of the words of a paragraph, without the spaces around them.
+fn get_words(paragraph: &str, offset: usize) -> Vec<(usize, usize)> {
+  let mut words = Vec::new();
+  let mut word_start = None;
+  for (index, character) in paragraph.char_indices() {
+    if character.is_whitespace() {
+      if let Some(start) = word_start.take() {
+        words.push((offset + start, offset + index));
+      }
+    } else if word_start.is_none() {
+      word_start = Some(index);
+    }
+  }
+  if let Some(start) = word_start {
+    words.push((offset + start, offset + paragraph.len()));
+  }
+  return words;
+}
+
+fn get_line(
+  text: &str,
+  spans: &[TextSpan],
+  start: usize,
+  end: usize,
+  measure: &mut dyn FnMut(&str, bool) -> F1,
+) -> TextLine {
+  let (runs, width) = get_runs(text, spans, start, end, measure);
+  return TextLine { runs, width };
+}
+
+fn get_runs(
+  text: &str,
+  spans: &[TextSpan],
+  start: usize,
+  end: usize,
+  measure: &mut dyn FnMut(&str, bool) -> F1,
+) -> (Vec<TextRun>, F1) {
+  let mut boundaries = vec![start, end];
+  for span in spans.iter() {
+    for boundary in [span.start, span.end].iter() {
+      if *boundary > start && *boundary < end && text.is_char_boundary(*boundary) {
+        boundaries.push(*boundary);
+      }
+    }
+  }
+  boundaries.sort();
+  boundaries.dedup();
+
+  let mut runs: Vec<TextRun> = Vec::new();
+  let mut x = 0.0;
+  for pair in boundaries.windows(2) {
+    let style = get_style(spans, pair[0], pair[1]);
+    let run_start = match runs.last() {
+      Some(last) if last.style == style => {
+        let last = runs.pop().expect("pop failed");
+        x = last.x;
+        pair[0] - last.text.len()
+      }
+      _ => pair[0],
+    };
+    let run_text = &text[run_start..pair[1]];
+    let width = measure(run_text, style.bold);
+    runs.push(TextRun {
+      text: String::from(run_text),
+      style,
+      x,
+      width,
+    });
+    x += width;
+  }
+  return (runs, x);
+}
+
+fn get_style(spans: &[TextSpan], start: usize, end: usize) -> TextStyle {
+  let mut style = TextStyle::default();
+  for span in spans.iter() {
+    if span.start <= start && span.end >= end {
+      if span.style.color.is_some() {
+        style.color = span.style.color;
+      }
+      style.bold |= span.style.bold;
+    }
+  }
+  return style;
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  // One unit per character, two for bold ones.
+  fn measure(text: &str, bold: bool) -> F1 {
+    let width = text.chars().count() as F1;
+    return if bold { 2.0 * width } else { width };
+  }
+
+  fn get_texts(layout: &TextLayout) -> Vec<String> {
+    return layout
+      .lines
+      .iter()
+      .map(|line| {
+        line
+          .runs
+          .iter()
+          .map(|run| run.text.as_str())
+          .collect::<String>()
+      })
+      .collect();
+  }
+
+  #[test]
+  fn test_text_layout_wrap() {
+    let layout = TextLayout::new(
+      "Touch the paw and drag\nto start drawing. Extraordinarily",
+      &[],
+      Some(10.0),
+      &mut measure,
+    );
+    assert_eq!(
+      get_texts(&layout),
+      vec![
+        "Touch the",
+        "paw and",
+        "drag",
+        "to start",
+        "drawing.",
+        "Extraordinarily"
+      ]
+    );
+    assert_eq!(layout.lines[0].width, 9.0);
+    assert_eq!(layout.get_width(), 15.0);
+    assert!(!layout.is_plain());
+
+    let layout = TextLayout::new("Hi,  Panda\n\n", &[], None, &mut measure);
+    assert_eq!(get_texts(&layout), vec!["Hi,  Panda", "", ""]);
+  }
+
+  #[test]
+  fn test_text_layout_spans() {
+    let red = DrawColor { r: 255, g: 0, b: 0 };
+    let (text, spans) = TextSpan::from_parts(&[
+      ("Fill the ", TextStyle::default()),
+      (
+        "red heart",
+        TextStyle {
+          color: Some(red),
+          bold: true,
+        },
+      ),
+      (" with ink", TextStyle::default()),
+    ]);
+    assert_eq!(spans.len(), 1);
+
+    let layout = TextLayout::new(&text, &spans, Some(22.0), &mut measure);
+    assert_eq!(get_texts(&layout), vec!["Fill the red", "heart with ink"]);
+    let runs = &layout.lines[0].runs;
+    assert_eq!(runs.len(), 2);
+    assert_eq!(runs[1].text, "red");
+    assert_eq!(runs[1].x, 9.0);
+    assert_eq!(runs[1].style.color, Some(red));
+    assert_eq!(layout.lines[0].width, 15.0);
+    assert_eq!(layout.lines[1].runs[0].text, "heart");
+    assert_eq!(layout.lines[1].width, 19.0);
+
+    assert!(TextLayout::new("Panda", &[], None, &mut measure).is_plain());
+  }
+}
@@ -1,14 +1,29 @@
+use super::texture_loader::{get_texture_load_action, TextureLoadAction};
 use crate::engine::*;
 use wasm_bindgen::prelude::*;
 use wasm_bindgen::JsCast;
 
+// A transparent 1x1 PNG, drawn instead of images that failed to load.
+const PLACEHOLDER_SRC: &str = "data:image/png;base64,iVBORw0KGgoAAAANSUhEUgAAAAEAAAABCAQAAAC1HAwCAAAAC0lEQVR42mNkYAAAAAYAAjCB0C8AAAAASUVORK5CYII=";
+
 pub struct Texture {
   loaded: Cell<bool>,
   image: web_sys::HtmlImageElement,
   pub image_loaded: Shared<bool>,
-
+  // Counts the images loaded into `image`, so copies of its pixels can tell
+  // when they are stale.
+  load_generation: Shared<u32>,
+  // Set by the image's onerror, until the next attempt.
+  image_failed: Shared<bool>,
+  // The image holds PLACEHOLDER_SRC, after every attempt failed.
+  placeholder: Shared<bool>,
+  load_attempts: Cell<u32>,
+  attempt_started_ms: Cell<f64>,
+
+  // Size of the image in pixels, `scale` times its size at @1x.
   pub width: Cell<f64>,
   pub height: Cell<f64>,
+  pub scale: F1,
   color_cache: RefCell<HashMap<DrawColor, Rc<web_sys::HtmlCanvasElement>>>,
   pub src: String,
 
@@ -18,6 +33,8 @@ pub struct Texture {
   last_size: Cell<F2>,
   cache_hits: Cell<i32>,
   size_cache: RefCell<Option<Rc<web_sys::HtmlCanvasElement>>>,
+
+  atlas_region: Option<TextureAtlasRegion>,
 }
 
 impl Texture {
@@ -25,6 +42,7 @@ impl Texture {
     document: &web_sys::Document,
     color_alpha_cache: Option<ColorAlphaCacheParams>,
     src: &str,
+    scale: F1,
   ) -> Rc<Texture> {
     let image = document
       .create_element("img")
@@ -46,8 +64,14 @@ impl Texture {
       loaded: Cell::new(false),
       image: image,
       image_loaded: Shared::new(false),
+      load_generation: Shared::new(0),
+      image_failed: Shared::new(false),
+      placeholder: Shared::new(false),
+      load_attempts: Cell::new(0),
+      attempt_started_ms: Cell::new(0.0),
       width: Cell::new(0.0),
       height: Cell::new(0.0),
+      scale: scale,
       color_cache: RefCell::new(HashMap::new()),
       src: String::from(src),
       color_alpha_cache,
@@ -55,21 +79,176 @@ impl Texture {
       last_size: Cell::default(),
       cache_hits: Cell::new(0),
       size_cache: RefCell::new(None),
+      atlas_region: None,
+    });
+  }
+
+  // Texture of a file under `static/`, already loaded, for native tests. Its
+  // image is a null handle, so only the software backend can draw it.
+  #[cfg(all(test, not(target_arch = "wasm32")))]
+  pub fn new_headless(src: &str) -> Rc<Texture> {
+    let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join(src.trim_start_matches('/'));
+    let file = std::fs::File::open(&path).expect("failed to open texture file");
+    let reader = png::Decoder::new(file)
+      .read_info()
+      .expect("png read_info failed");
+    return Rc::new(Texture {
+      loaded: Cell::new(true),
+      image: JsValue::NULL.unchecked_into(),
+      image_loaded: Shared::new(true),
+      load_generation: Shared::new(1),
+      image_failed: Shared::new(false),
+      placeholder: Shared::new(false),
+      load_attempts: Cell::new(1),
+      attempt_started_ms: Cell::new(0.0),
+      width: Cell::new(reader.info().width as f64),
+      height: Cell::new(reader.info().height as f64),
+      scale: 1.0,
+      color_cache: RefCell::new(HashMap::new()),
+      src: String::from(src),
+      color_alpha_cache: None,
+      color_alpha_cache_canvas: None,
+      last_size: Cell::default(),
+      cache_hits: Cell::new(0),
+      size_cache: RefCell::new(None),
+      atlas_region: None,
     });
   }
 
+  // Texture reading its pixels from part of an atlas page. It shares the page's
+  // image, which the TextureLoader loads, and behaves like a standalone
+  // texture of the region's size.
+  pub fn new_atlas_region(atlas: Rc<Texture>, position: F2, size: F2, src: &str) -> Rc<Texture> {
+    return Rc::new(Texture {
+      loaded: Cell::new(false),
+      image: atlas.image.clone(),
+      image_loaded: atlas.image_loaded.clone(),
+      load_generation: atlas.load_generation.clone(),
+      image_failed: atlas.image_failed.clone(),
+      placeholder: atlas.placeholder.clone(),
+      load_attempts: Cell::new(0),
+      attempt_started_ms: Cell::new(0.0),
+      width: Cell::new(0.0),
+      height: Cell::new(0.0),
+      scale: atlas.scale,
+      color_cache: RefCell::new(HashMap::new()),
+      src: String::from(src),
+      color_alpha_cache: None,
+      color_alpha_cache_canvas: None,
+      last_size: Cell::default(),
+      cache_hits: Cell::new(0),
+      size_cache: RefCell::new(None),
+      atlas_region: Some(TextureAtlasRegion {
+        atlas,
+        position,
+        size,
+      }),
+    });
+  }
+
+  pub fn get_atlas_region(&self) -> Option<&TextureAtlasRegion> {
+    return self.atlas_region.as_ref();
+  }
+
+  // Offset of this texture's pixels inside image().
+  pub fn get_atlas_offset(&self) -> Option<F2> {
+    return self.atlas_region.as_ref().map(|region| region.position);
+  }
+
   pub fn start_loading(&self) {
-    self.image.set_src(&self.src);
+    if self.atlas_region.is_some() {
+      return;
+    }
     {
       let imaged_loaded = self.image_loaded.clone();
+      let load_generation = self.load_generation.clone();
       let closure = Closure::wrap(Box::new(move || {
         imaged_loaded.replace(true);
+        load_generation.replace(load_generation.get() + 1);
       }) as Box<dyn FnMut()>);
       self
         .image
         .set_onload(Some(closure.as_ref().unchecked_ref()));
       closure.forget();
     }
+    {
+      let image_failed = self.image_failed.clone();
+      let closure = Closure::wrap(Box::new(move || {
+        image_failed.replace(true);
+      }) as Box<dyn FnMut()>);
+      self
+        .image
+        .set_onerror(Some(closure.as_ref().unchecked_ref()));
+      closure.forget();
+    }
+    self.start_attempt(&self.src);
+  }
+
+  fn start_attempt(&self, src: &str) {
+    self.image_failed.replace(false);
+    self.load_attempts.set(self.load_attempts.get() + 1);
+    self.attempt_started_ms.set(js_sys::Date::now());
+    self.image.set_src(src);
+  }
+
+  // Called every frame while loading. Retries attempts that failed or timed
+  // out, and gives up with the placeholder image once they run out.
+  pub fn update_loading(&self, now_ms: f64) {
+    if self.atlas_region.is_some()
+      || self.load_attempts.get() == 0
+      || self.image_loaded.get()
+      || self.placeholder.get()
+    {
+      return;
+    }
+    let elapsed_ms = now_ms - self.attempt_started_ms.get();
+    match get_texture_load_action(
+      self.image_failed.get(),
+      self.load_attempts.get(),
+      elapsed_ms,
+    ) {
+      TextureLoadAction::Wait => {}
+      TextureLoadAction::Retry => {
+        let src = get_cache_busting_src(&self.src, &format!("retry={}", self.load_attempts.get()));
+        self.start_attempt(&src);
+      }
+      TextureLoadAction::UsePlaceholder => {
+        console_log!(
+          "failed to load {} after {} attempts",
+          self.src,
+          self.load_attempts.get()
+        );
+        self.placeholder.replace(true);
+        self.image_failed.replace(false);
+        self.image.set_src(PLACEHOLDER_SRC);
+      }
+    }
+  }
+
+  // The image couldn't be loaded and the placeholder is drawn instead.
+  pub fn is_placeholder(&self) -> bool {
+    return self.placeholder.get();
+  }
+
+  // Loads the image again after it fell back to the placeholder. Atlas regions
+  // only forget their size, their page reloads the image.
+  pub fn reload(&self) {
+    self.loaded.set(false);
+    self.color_cache.borrow_mut().clear();
+    self.last_size.set(F2::default());
+    self.cache_hits.set(0);
+    if self.atlas_region.is_some() {
+      return;
+    }
+    self.image_loaded.replace(false);
+    self.placeholder.replace(false);
+    self.load_attempts.set(0);
+    let src = get_cache_busting_src(&self.src, &format!("reload={}", js_sys::Date::now() as u64));
+    self.start_attempt(&src);
+  }
+
+  pub fn get_load_generation(&self) -> u32 {
+    return self.load_generation.get();
   }
 
   pub fn image(&self) -> &web_sys::HtmlImageElement {
@@ -96,8 +275,17 @@ impl Texture {
       //   texture_copy.borrow().image.width(),
       //   texture_copy.borrow().image.height()
       // );
-      self.width.set(self.image.width() as f64);
-      self.height.set(self.image.height() as f64);
+      match &self.atlas_region {
+        Some(atlas_region) => {
+          atlas_region.atlas.finish_loading();
+          self.width.set(atlas_region.size.x as f64);
+          self.height.set(atlas_region.size.y as f64);
+        }
+        None => {
+          self.width.set(self.image.width() as f64);
+          self.height.set(self.image.height() as f64);
+        }
+      }
 
       self.generate_color_alpha_cache_canvas();
       self.loaded.set(true);
@@ -106,6 +294,14 @@ impl Texture {
     return self.loaded.get();
   }
 
+  // Size of the image at @1x, the same whichever variant was loaded.
+  pub fn get_size(&self) -> F2 {
+    return F2 {
+      x: self.width.get() as F1 / self.scale,
+      y: self.height.get() as F1 / self.scale,
+    };
+  }
+
   pub fn get_size_from_width(&self, width: F1) -> F2 {
     return F2 {
       x: width,
@@ -215,28 +411,24 @@ impl Texture {
       tmp_canvas_context
         .set_global_composite_operation("multiply")
         .expect("failed to set_global_composite_operation");
-      tmp_canvas_context
-        .draw_image_with_html_image_element_and_dw_and_dh(
-          &self.image,
-          0.0,
-          0.0,
-          self.width.get().into(),
-          self.height.get().into(),
-        )
-        .expect("draw_image_with_html_image_element_and_dw_and_dh failed");
+      self.draw_into(
+        &tmp_canvas_context,
+        &F2 {
+          x: self.width.get() as F1,
+          y: self.height.get() as F1,
+        },
+      );
 
       tmp_canvas_context
         .set_global_composite_operation("destination-in")
         .expect("failed to set_global_composite_operation");
-      tmp_canvas_context
-        .draw_image_with_html_image_element_and_dw_and_dh(
-          &self.image,
-          0.0,
-          0.0,
-          self.width.get().into(),
-          self.height.get().into(),
-        )
-        .expect("draw_image_with_html_image_element_and_dw_and_dh failed");
+      self.draw_into(
+        &tmp_canvas_context,
+        &F2 {
+          x: self.width.get() as F1,
+          y: self.height.get() as F1,
+        },
+      );
 
       color_cache.insert(*color, Rc::new(tmp_canvas));
     }
@@ -273,18 +465,46 @@ impl Texture {
         .dyn_into::<web_sys::CanvasRenderingContext2d>()
         .expect("failed to dyn_into::<web_sys::CanvasRenderingContext2d>()");
 
-      canvas_context
-        .draw_image_with_html_image_element_and_dw_and_dh(
-          &self.image,
-          0.0,
-          0.0,
-          size.x.into(),
-          size.y.into(),
-        )
-        .expect("draw_image_with_html_image_element_and_dw_and_dh failed");
+      self.draw_into(&canvas_context, &size);
 
       return Some(canvas);
     }
     return self.size_cache.borrow().clone();
   }
+
+  // Draws the whole texture at the top left corner of `canvas_context`.
+  fn draw_into(&self, canvas_context: &web_sys::CanvasRenderingContext2d, size: &F2) {
+    let (position, source_size) = match &self.atlas_region {
+      Some(atlas_region) => (atlas_region.position, atlas_region.size),
+      None => (
+        F2 { x: 0.0, y: 0.0 },
+        F2 {
+          x: self.image.width() as F1,
+          y: self.image.height() as F1,
+        },
+      ),
+    };
+    canvas_context
+      .draw_image_with_html_image_element_and_sw_and_sh_and_dx_and_dy_and_dw_and_dh(
+        &self.image,
+        position.x.into(),
+        position.y.into(),
+        source_size.x.into(),
+        source_size.y.into(),
+        0.0,
+        0.0,
+        size.x.into(),
+        size.y.into(),
+      )
+      .expect(
+        "draw_image_with_html_image_element_and_sw_and_sh_and_dx_and_dy_and_dw_and_dh failed",
+      );
+  }
+}
+
+// Adds `query` to `src`, so the browser doesn't reuse the cached response of a
+// failed attempt.
+fn get_cache_busting_src(src: &str, query: &str) -> String {
+  let separator = if src.contains('?') { '&' } else { '?' };
+  return format!("{}{}{}", src, separator, query);
 }
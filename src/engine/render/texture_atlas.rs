@@ -0,0 +1,49 @@
+use crate::engine::*;
+use serde::Deserialize;
+
+// Position of a packed image inside its atlas page, in pixels.
+#[derive(Deserialize)]
+pub struct TextureAtlasSprite {
+  pub x: F1,
+  pub y: F1,
+  pub width: F1,
+  pub height: F1,
+}
+
+#[derive(Deserialize)]
+pub struct TextureAtlasPage {
+  pub src: String,
+  pub width: F1,
+  pub height: F1,
+  // Keyed by the `src` the image would be loaded from on its own.
+  pub sprites: HashMap<String, TextureAtlasSprite>,
+}
+
+// Written by `cargo run --example pack_atlases`.
+#[derive(Deserialize, Default)]
+pub struct TextureAtlasManifest {
+  pub atlases: Vec<TextureAtlasPage>,
+}
+
+impl TextureAtlasManifest {
+  pub fn from_json(json: &str) -> TextureAtlasManifest {
+    return serde_json::from_str(json).expect("invalid texture atlas manifest");
+  }
+
+  // Returns the index of the page holding `src` and its sprite.
+  pub fn find_sprite(&self, src: &str) -> Option<(usize, &TextureAtlasSprite)> {
+    for (page_index, page) in self.atlases.iter().enumerate() {
+      if let Some(sprite) = page.sprites.get(src) {
+        return Some((page_index, sprite));
+      }
+    }
+    return None;
+  }
+}
+
+// Part of an atlas page a Texture reads its pixels from.
+pub struct TextureAtlasRegion {
+  pub atlas: Rc<Texture>,
+  pub position: F2,
+  pub size: F2,
+}
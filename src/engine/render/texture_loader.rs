@@ -1,12 +1,39 @@
 use crate::engine::*;
 
+// Where the images of the registered textures come from.
+enum TextureImageSource {
+  Document(Rc<web_sys::Document>),
+  // Files under `static/` read by native tests, without atlases.
+  #[cfg(all(test, not(target_arch = "wasm32")))]
+  Headless,
+}
+
 pub struct TextureLoader {
-  document: Rc<web_sys::Document>,
+  image_source: TextureImageSource,
+  atlas_manifest: Rc<TextureAtlasManifest>,
+  device_pixel_ratio: F1,
+  // Atlas pages used by the registered textures, by manifest index.
+  atlas_pages: HashMap<usize, Rc<Texture>>,
+  textures: Vec<Rc<Texture>>,
   loading: Vec<Rc<Texture>>,
   started_loading: bool,
   total_textures: Cell<usize>,
 }
 
+// Attempts to load an image before falling back to the placeholder.
+const TEXTURE_LOAD_MAX_ATTEMPTS: u32 = 3;
+// Attempts taking longer are abandoned and count as failed.
+const TEXTURE_LOAD_TIMEOUT_MS: f64 = 20000.0;
+// Grows with each attempt, counted from the start of the failed one.
+const TEXTURE_LOAD_RETRY_DELAY_MS: f64 = 1000.0;
+
+#[derive(Debug, PartialEq)]
+pub enum TextureLoadAction {
+  Wait,
+  Retry,
+  UsePlaceholder,
+}
+
 pub struct TextureLoaderProgress {
   pub loaded: usize,
   pub total_textures: usize,
@@ -21,6 +48,9 @@ pub struct TextureParams {
 #[derive(Default)]
 pub struct TextureParamsOptional {
   pub color_alpha_cache: Option<ColorAlphaCacheParams>,
+  // Scales other than 1 with a variant next to `src`, like 2 for
+  // "/static/star@2x.png".
+  pub scales: Vec<u32>,
 }
 
 pub struct ColorAlphaCacheParams {
@@ -29,9 +59,32 @@ pub struct ColorAlphaCacheParams {
 }
 
 impl TextureLoader {
-  pub fn new(document: Rc<web_sys::Document>) -> TextureLoader {
+  pub fn new(
+    document: Rc<web_sys::Document>,
+    atlas_manifest: Rc<TextureAtlasManifest>,
+    device_pixel_ratio: F1,
+  ) -> TextureLoader {
+    return TextureLoader {
+      image_source: TextureImageSource::Document(document),
+      atlas_manifest: atlas_manifest,
+      device_pixel_ratio: device_pixel_ratio,
+      atlas_pages: HashMap::new(),
+      textures: Vec::new(),
+      loading: Vec::new(),
+      started_loading: false,
+      total_textures: Cell::new(0),
+    };
+  }
+
+  // Registers textures that are already loaded, see Texture::new_headless.
+  #[cfg(all(test, not(target_arch = "wasm32")))]
+  pub fn new_headless() -> TextureLoader {
     return TextureLoader {
-      document: document,
+      image_source: TextureImageSource::Headless,
+      atlas_manifest: Rc::new(TextureAtlasManifest::default()),
+      device_pixel_ratio: 1.0,
+      atlas_pages: HashMap::new(),
+      textures: Vec::new(),
       loading: Vec::new(),
       started_loading: false,
       total_textures: Cell::new(0),
@@ -43,6 +96,9 @@ impl TextureLoader {
       panic!("start_loading was already called.");
     }
     self.started_loading = true;
+    for atlas_page in self.atlas_pages.values() {
+      atlas_page.start_loading();
+    }
     for texture in self.loading.iter() {
       texture.start_loading();
     }
@@ -52,11 +108,55 @@ impl TextureLoader {
     return self.started_loading;
   }
 
+  // Textures that failed to load count as loaded, drawn with a placeholder.
+  // See get_failed_srcs.
   pub fn all_loaded(&mut self) -> bool {
+    let now_ms = js_sys::Date::now();
+    for texture in self.atlas_pages.values().chain(self.loading.iter()) {
+      texture.update_loading(now_ms);
+    }
     self.loading.retain(|texture| !texture.finish_loading());
     return self.loading.is_empty();
   }
 
+  // Images drawn with the placeholder. Atlas pages are reported instead of
+  // their sprites.
+  pub fn get_failed_srcs(&self) -> Vec<String> {
+    let mut failed_srcs: Vec<String> = self
+      .atlas_pages
+      .values()
+      .chain(self.textures.iter())
+      .filter(|texture| texture.get_atlas_region().is_none() && texture.is_placeholder())
+      .map(|texture| texture.src.clone())
+      .collect();
+    failed_srcs.sort();
+    return failed_srcs;
+  }
+
+  // Loads the images drawn with the placeholder again.
+  pub fn retry_failed(&mut self) {
+    let failed: Vec<Rc<Texture>> = self
+      .atlas_pages
+      .values()
+      .chain(self.textures.iter())
+      .filter(|texture| texture.is_placeholder())
+      .cloned()
+      .collect();
+    for texture in failed.iter() {
+      texture.reload();
+      if !self.is_atlas_page(texture) {
+        self.loading.push(texture.clone());
+      }
+    }
+  }
+
+  fn is_atlas_page(&self, texture: &Rc<Texture>) -> bool {
+    return self
+      .atlas_pages
+      .values()
+      .any(|atlas_page| Rc::ptr_eq(atlas_page, texture));
+  }
+
   pub fn get_progress(&self) -> TextureLoaderProgress {
     return TextureLoaderProgress {
       loaded: self.total_textures.get() - self.loading.len(),
@@ -69,12 +169,162 @@ impl TextureLoader {
       panic!("Can't register more textures after start_loading was called.");
     }
     self.total_textures.set(self.total_textures.get() + 1);
-    let texture = Texture::new(
-      self.document.as_ref(),
-      texture_params.optional.color_alpha_cache,
-      &texture_params.src,
-    );
+    let document = match &self.image_source {
+      TextureImageSource::Document(document) => document.clone(),
+      #[cfg(all(test, not(target_arch = "wasm32")))]
+      TextureImageSource::Headless => {
+        let texture = Texture::new_headless(&texture_params.src);
+        self.textures.push(texture.clone());
+        return texture;
+      }
+    };
+    let scale = get_scale_variant(self.device_pixel_ratio, &texture_params.optional.scales);
+    // Color alpha caches are drawn from the whole image, so those textures
+    // keep their own file. Atlas pages only hold @1x images.
+    let atlas_sprite = match (&texture_params.optional.color_alpha_cache, scale) {
+      (None, 1) => self.atlas_manifest.find_sprite(&texture_params.src),
+      _ => None,
+    };
+    let texture = match atlas_sprite {
+      Some((page_index, sprite)) => {
+        let atlas_manifest = self.atlas_manifest.clone();
+        let atlas_page = self.atlas_pages.entry(page_index).or_insert_with(|| {
+          Texture::new(
+            document.as_ref(),
+            None,
+            &atlas_manifest.atlases[page_index].src,
+            1.0,
+          )
+        });
+        Texture::new_atlas_region(
+          atlas_page.clone(),
+          F2 {
+            x: sprite.x,
+            y: sprite.y,
+          },
+          F2 {
+            x: sprite.width,
+            y: sprite.height,
+          },
+          &texture_params.src,
+        )
+      }
+      None => Texture::new(
+        document.as_ref(),
+        texture_params.optional.color_alpha_cache,
+        &get_scale_variant_src(&texture_params.src, scale),
+        scale as F1,
+      ),
+    };
+    self.textures.push(texture.clone());
     self.loading.push(texture.clone());
     return texture;
   }
 }
+
+pub fn get_texture_load_action(failed: bool, attempts: u32, elapsed_ms: f64) -> TextureLoadAction {
+  if !failed && elapsed_ms < TEXTURE_LOAD_TIMEOUT_MS {
+    return TextureLoadAction::Wait;
+  }
+  if attempts >= TEXTURE_LOAD_MAX_ATTEMPTS {
+    return TextureLoadAction::UsePlaceholder;
+  }
+  if elapsed_ms < TEXTURE_LOAD_RETRY_DELAY_MS * attempts as f64 {
+    return TextureLoadAction::Wait;
+  }
+  return TextureLoadAction::Retry;
+}
+
+// Smallest of `scales` and 1 that covers `device_pixel_ratio`, or the largest
+// one if none does.
+pub fn get_scale_variant(device_pixel_ratio: F1, scales: &[u32]) -> u32 {
+  let mut best = 1;
+  for scale in scales.iter().copied() {
+    let covers = scale as F1 >= device_pixel_ratio;
+    let best_covers = best as F1 >= device_pixel_ratio;
+    if (covers && (!best_covers || scale < best)) || (!covers && !best_covers && scale > best) {
+      best = scale;
+    }
+  }
+  return best;
+}
+
+// "/static/star.png" at scale 2 is "/static/star@2x.png".
+pub fn get_scale_variant_src(src: &str, scale: u32) -> String {
+  if scale == 1 {
+    return String::from(src);
+  }
+  let file_start = src.rfind('/').map_or(0, |index| index + 1);
+  return match src[file_start..].rfind('.') {
+    Some(index) => format!(
+      "{}@{}x{}",
+      &src[..file_start + index],
+      scale,
+      &src[file_start + index..]
+    ),
+    None => format!("{}@{}x", src, scale),
+  };
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_get_scale_variant() {
+    assert_eq!(get_scale_variant(1.0, &[2, 3]), 1);
+    assert_eq!(get_scale_variant(1.5, &[2, 3]), 2);
+    assert_eq!(get_scale_variant(2.0, &[3, 2]), 2);
+    assert_eq!(get_scale_variant(2.75, &[2, 3]), 3);
+    assert_eq!(get_scale_variant(4.0, &[2, 3]), 3);
+    assert_eq!(get_scale_variant(3.0, &[]), 1);
+  }
+
+  #[test]
+  fn test_get_texture_load_action() {
+    assert_eq!(
+      get_texture_load_action(false, 1, 500.0),
+      TextureLoadAction::Wait
+    );
+    assert_eq!(
+      get_texture_load_action(true, 1, 500.0),
+      TextureLoadAction::Wait
+    );
+    assert_eq!(
+      get_texture_load_action(true, 1, 1000.0),
+      TextureLoadAction::Retry
+    );
+    assert_eq!(
+      get_texture_load_action(true, 2, 1000.0),
+      TextureLoadAction::Wait
+    );
+    assert_eq!(
+      get_texture_load_action(false, 2, 20000.0),
+      TextureLoadAction::Retry
+    );
+    assert_eq!(
+      get_texture_load_action(true, 3, 0.0),
+      TextureLoadAction::UsePlaceholder
+    );
+    assert_eq!(
+      get_texture_load_action(false, 3, 20000.0),
+      TextureLoadAction::UsePlaceholder
+    );
+  }
+
+  #[test]
+  fn test_get_scale_variant_src() {
+    assert_eq!(
+      get_scale_variant_src("/static/star.png", 1),
+      "/static/star.png"
+    );
+    assert_eq!(
+      get_scale_variant_src("/static/star.png", 2),
+      "/static/star@2x.png"
+    );
+    assert_eq!(
+      get_scale_variant_src("/static.v2/star", 3),
+      "/static.v2/star@3x"
+    );
+  }
+}
@@ -2,6 +2,8 @@ use crate::engine::*;
 
 #[derive(Default)]
 pub struct Viewport {
+  // Where the origin of the viewport is, it only matches the frame of the
+  // viewport when its camera shows the top left corner of the world.
   pub viewport_position_on_screen: F2,
   pub viewport_size_on_screen: F2,
   viewport_to_screen_ratio: F2,
@@ -11,11 +13,18 @@ pub struct Viewport {
   pub viewport_to_canvas_ratio: F2,
 
   pub viewport_yx_ratio: F1,
+  // Area of the viewport its camera can show, from (0, 0).
+  pub world_size: F2,
 
   pub screen_top_left_corner: F2,
   pub screen_bottom_right_corner: F2,
   pub screen_center: F2,
   pub screen_size: F2,
+
+  frame_position_on_screen: F2,
+  screen_size_in_pixels: F2,
+  device_pixel_ratio: F1,
+  camera: Option<Camera>,
 }
 
 impl Viewport {
@@ -26,21 +35,54 @@ impl Viewport {
     device_pixel_ratio: &F1,
   ) -> Viewport {
     let viewport_yx_ratio = viewport_size_on_screen.y / viewport_size_on_screen.x;
+    return Viewport::new_with_camera(
+      viewport_position_on_screen,
+      viewport_size_on_screen,
+      screen_size,
+      device_pixel_ratio,
+      &Camera::new(&F2 {
+        x: 1.0,
+        y: viewport_yx_ratio,
+      }),
+    );
+  }
+
+  // The frame is the area of the screen showing what `camera` sees, 1 unit of
+  // the world wide at zoom 1.
+  pub fn new_with_camera(
+    frame_position_on_screen: &F2,
+    frame_size_on_screen: &F2,
+    screen_size: &F2,
+    device_pixel_ratio: &F1,
+    camera: &Camera,
+  ) -> Viewport {
+    let viewport_yx_ratio = frame_size_on_screen.y / frame_size_on_screen.x;
     let viewport_to_screen_ratio = F2 {
-      x: viewport_size_on_screen.x,
-      y: viewport_size_on_screen.y / viewport_yx_ratio,
+      x: frame_size_on_screen.x * camera.zoom,
+      y: frame_size_on_screen.x * camera.zoom,
     };
+    let viewport_position_on_screen = frame_position_on_screen
+      - F2 {
+        x: camera.get_top_left().x * viewport_to_screen_ratio.x,
+        y: camera.get_top_left().y * viewport_to_screen_ratio.y,
+      };
 
     let mut viewport = Viewport {
-      viewport_position_on_screen: *viewport_position_on_screen,
-      viewport_size_on_screen: *viewport_size_on_screen,
+      viewport_position_on_screen: viewport_position_on_screen,
+      viewport_size_on_screen: *frame_size_on_screen,
       viewport_to_screen_ratio: viewport_to_screen_ratio,
 
       viewport_position_on_canvas: viewport_position_on_screen * device_pixel_ratio,
-      viewport_size_on_canvas: viewport_size_on_screen * device_pixel_ratio,
+      viewport_size_on_canvas: frame_size_on_screen * device_pixel_ratio,
       viewport_to_canvas_ratio: viewport_to_screen_ratio * device_pixel_ratio,
 
       viewport_yx_ratio: viewport_yx_ratio,
+      world_size: *camera.get_bounds_size(),
+
+      frame_position_on_screen: *frame_position_on_screen,
+      screen_size_in_pixels: *screen_size,
+      device_pixel_ratio: *device_pixel_ratio,
+      camera: Some(*camera),
       ..Default::default()
     };
 
@@ -52,6 +94,38 @@ impl Viewport {
     return viewport;
   }
 
+  pub fn get_camera(&self) -> Camera {
+    return self
+      .camera
+      .expect("Viewport::get_camera on a default viewport");
+  }
+
+  // Same frame, seen through `camera`.
+  pub fn with_camera(&self, camera: &Camera) -> Viewport {
+    return Viewport::new_with_camera(
+      &self.frame_position_on_screen,
+      &self.viewport_size_on_screen,
+      &self.screen_size_in_pixels,
+      &self.device_pixel_ratio,
+      camera,
+    );
+  }
+
+  // The whole world at `zoom`, from the top left corner of the canvas, to cache
+  // it.
+  pub fn with_world_at_origin(&self, zoom: F1) -> Viewport {
+    let mut camera = self.get_camera();
+    camera.zoom = zoom;
+    camera.center = camera.get_visible_size() * 0.5;
+    return Viewport::new_with_camera(
+      &F2::default(),
+      &self.viewport_size_on_screen,
+      &self.screen_size_in_pixels,
+      &self.device_pixel_ratio,
+      &camera,
+    );
+  }
+
   pub fn viewport_to_screen(&self, input: &F2) -> F2 {
     return F2 {
       x: input.x * self.viewport_to_screen_ratio.x + self.viewport_position_on_screen.x,
@@ -87,6 +161,10 @@ impl Viewport {
     };
   }
 
+  pub fn viewport_to_canvas_ratio_x(&self, input: &F1) -> F1 {
+    return input * self.viewport_to_canvas_ratio.x;
+  }
+
   pub fn viewport_to_canvas_ratio_y(&self, input: &F1) -> F1 {
     return input * self.viewport_to_canvas_ratio.y;
   }
@@ -99,13 +177,16 @@ impl Viewport {
   }
 
   pub fn is_inside_viewport(&self, input: &F2) -> bool {
-    return input.x >= 0.0 && input.x <= 1.0 && input.y >= 0.0 && input.y <= self.viewport_yx_ratio;
+    return input.x >= 0.0
+      && input.x <= self.world_size.x
+      && input.y >= 0.0
+      && input.y <= self.world_size.y;
   }
 
   pub fn move_inside_viewport(&self, input: &F2) -> F2 {
     return F2 {
-      x: F1Util::move_within_range(&input.x, &0.0, &1.0),
-      y: F1Util::move_within_range(&input.y, &0.0, &self.viewport_yx_ratio),
+      x: F1Util::move_within_range(&input.x, &0.0, &self.world_size.x),
+      y: F1Util::move_within_range(&input.y, &0.0, &self.world_size.y),
     };
   }
 }
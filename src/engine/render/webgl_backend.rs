@@ -0,0 +1,783 @@
+use crate::engine::render::draw_args::*;
+use crate::engine::*;
+use wasm_bindgen::JsCast;
+use web_sys::WebGlRenderingContext as GL;
+
+// x, y, u, v, r, g, b, opacity
+const FLOATS_PER_VERTEX: usize = 8;
+const MAX_QUADS_PER_BATCH: usize = 4096;
+
+const VERTEX_SHADER: &str = r#"
+attribute vec2 a_position;
+attribute vec2 a_tex_coord;
+attribute vec4 a_color;
+uniform vec2 u_canvas_size;
+varying vec2 v_tex_coord;
+varying vec4 v_color;
+void main() {
+  vec2 clip = a_position / u_canvas_size * 2.0 - 1.0;
+  gl_Position = vec4(clip.x, -clip.y, 0.0, 1.0);
+  v_tex_coord = a_tex_coord;
+  v_color = a_color;
+}
+"#;
+
+// Textures hold premultiplied alpha. The tint multiplies the colour channels,
+// which is what Texture::get_colored_image does with Canvas2D composition.
+const FRAGMENT_SHADER: &str = r#"
+precision mediump float;
+uniform sampler2D u_texture;
+varying vec2 v_tex_coord;
+varying vec4 v_color;
+void main() {
+  gl_FragColor = texture2D(u_texture, v_tex_coord) * vec4(v_color.rgb * v_color.a, v_color.a);
+}
+"#;
+
+#[derive(Clone, Copy, PartialEq)]
+enum BlendMode {
+  Normal,
+  // Canvas2D's "lighter" composite operation.
+  Additive,
+}
+
+impl BlendMode {
+  fn from_composite_operation(composite_operation: &Option<String>) -> BlendMode {
+    return match composite_operation.as_deref() {
+      Some("lighter") => BlendMode::Additive,
+      _ => BlendMode::Normal,
+    };
+  }
+}
+
+// Uploaded again when the texture loads another image.
+struct ImageGlTexture {
+  _texture: Rc<Texture>,
+  gl_texture: web_sys::WebGlTexture,
+  load_generation: Option<u32>,
+}
+
+// Uploaded again when CanvasRenderTarget::generation moves, and released once
+// a frame goes by without them.
+struct CanvasGlTexture {
+  _render_target: Rc<dyn RenderTarget>,
+  gl_texture: web_sys::WebGlTexture,
+  generation: u32,
+  used_frame: u32,
+}
+
+struct TextGlTexture {
+  text_cache: Rc<TextCache>,
+  gl_texture: web_sys::WebGlTexture,
+  generation: u32,
+  used_frame: u32,
+}
+
+struct Quad {
+  corners: [F2; 4],
+  tex_coords: [F2; 4],
+  // One colour per corner, opacity in the alpha channel.
+  colors: [[f32; 4]; 4],
+}
+
+// Draws the DrawArgs queue as textured quads, batching consecutive quads that
+// share a texture and blend mode into a single draw call.
+pub struct WebGlDrawBackend {
+  gl: GL,
+  canvas_size_location: web_sys::WebGlUniformLocation,
+  vertex_buffer: web_sys::WebGlBuffer,
+  white_texture: web_sys::WebGlTexture,
+
+  image_textures: RefCell<HashMap<usize, ImageGlTexture>>,
+  canvas_textures: RefCell<HashMap<usize, CanvasGlTexture>>,
+  text_textures: RefCell<HashMap<usize, TextGlTexture>>,
+  // Renders strings drawn without a TextCache of their own.
+  transient_text_caches: RefCell<HashMap<String, (Rc<TextCache>, u32)>>,
+
+  frame: Cell<u32>,
+  vertices: RefCell<Vec<f32>>,
+  batch_texture: RefCell<Option<web_sys::WebGlTexture>>,
+  batch_blend_mode: Cell<BlendMode>,
+}
+
+fn compile_shader(gl: &GL, shader_type: u32, source: &str) -> Option<web_sys::WebGlShader> {
+  let shader = gl.create_shader(shader_type)?;
+  gl.shader_source(&shader, source);
+  gl.compile_shader(&shader);
+  if !gl
+    .get_shader_parameter(&shader, GL::COMPILE_STATUS)
+    .as_bool()
+    .unwrap_or(false)
+  {
+    console_log!(
+      "WebGL shader compilation failed: {}",
+      gl.get_shader_info_log(&shader).unwrap_or_default()
+    );
+    return None;
+  }
+  return Some(shader);
+}
+
+fn link_program(gl: &GL) -> Option<web_sys::WebGlProgram> {
+  let vertex_shader = compile_shader(gl, GL::VERTEX_SHADER, VERTEX_SHADER)?;
+  let fragment_shader = compile_shader(gl, GL::FRAGMENT_SHADER, FRAGMENT_SHADER)?;
+  let program = gl.create_program()?;
+  gl.attach_shader(&program, &vertex_shader);
+  gl.attach_shader(&program, &fragment_shader);
+  gl.bind_attrib_location(&program, 0, "a_position");
+  gl.bind_attrib_location(&program, 1, "a_tex_coord");
+  gl.bind_attrib_location(&program, 2, "a_color");
+  gl.link_program(&program);
+  if !gl
+    .get_program_parameter(&program, GL::LINK_STATUS)
+    .as_bool()
+    .unwrap_or(false)
+  {
+    console_log!(
+      "WebGL program link failed: {}",
+      gl.get_program_info_log(&program).unwrap_or_default()
+    );
+    return None;
+  }
+  return Some(program);
+}
+
+fn create_gl_texture(gl: &GL) -> web_sys::WebGlTexture {
+  let gl_texture = gl.create_texture().expect("gl.create_texture failed");
+  gl.bind_texture(GL::TEXTURE_2D, Some(&gl_texture));
+  // Non power of two textures only support these parameters in WebGL 1.
+  gl.tex_parameteri(GL::TEXTURE_2D, GL::TEXTURE_MIN_FILTER, GL::LINEAR as i32);
+  gl.tex_parameteri(GL::TEXTURE_2D, GL::TEXTURE_MAG_FILTER, GL::LINEAR as i32);
+  gl.tex_parameteri(GL::TEXTURE_2D, GL::TEXTURE_WRAP_S, GL::CLAMP_TO_EDGE as i32);
+  gl.tex_parameteri(GL::TEXTURE_2D, GL::TEXTURE_WRAP_T, GL::CLAMP_TO_EDGE as i32);
+  return gl_texture;
+}
+
+fn upload_canvas(gl: &GL, gl_texture: &web_sys::WebGlTexture, canvas: &web_sys::HtmlCanvasElement) {
+  gl.bind_texture(GL::TEXTURE_2D, Some(gl_texture));
+  gl.tex_image_2d_with_u32_and_u32_and_canvas(
+    GL::TEXTURE_2D,
+    0,
+    GL::RGBA as i32,
+    GL::RGBA,
+    GL::UNSIGNED_BYTE,
+    canvas,
+  )
+  .expect("gl.tex_image_2d_with_u32_and_u32_and_canvas failed");
+}
+
+fn get_text_alignment_offset_x(alignment: TextAlignment, width: F1) -> F1 {
+  return match alignment {
+    TextAlignment::Left => 0.0,
+    TextAlignment::Center => -width * 0.5,
+    TextAlignment::Right => -width,
+  };
+}
+
+// Maps a texture coordinate of an atlas region to its atlas page.
+fn get_atlas_tex_coord(atlas_region: &TextureAtlasRegion, tex_coord: &F2) -> F2 {
+  return F2 {
+    x: (atlas_region.position.x + tex_coord.x * atlas_region.size.x)
+      / atlas_region.atlas.width.get() as F1,
+    y: (atlas_region.position.y + tex_coord.y * atlas_region.size.y)
+      / atlas_region.atlas.height.get() as F1,
+  };
+}
+
+// Corners in the order of Quad::corners: top left, top right, bottom right and
+// bottom left.
+fn get_rect_tex_coords(start: &F2, end: &F2) -> [F2; 4] {
+  return [
+    *start,
+    F2 {
+      x: end.x,
+      y: start.y,
+    },
+    *end,
+    F2 {
+      x: start.x,
+      y: end.y,
+    },
+  ];
+}
+
+fn get_transient_text_key(args: &DrawStringArgs) -> String {
+  return format!(
+    "{}|{}|{}|{:?}|{}|{:?}|{}|{:?}|{}|{:?}",
+    args.text,
+    args.font_size,
+    args.optional.alignment as u8,
+    args.optional.color,
+    args.optional.border,
+    args.optional.border_color,
+    args.optional.border_scale,
+    args.optional.max_width,
+    args.optional.line_spacing,
+    args.optional.spans
+  );
+}
+
+fn is_outside_canvas(top_left: &F2, size: &F2, canvas_size: &F2) -> bool {
+  return top_left.x > canvas_size.x
+    || top_left.y > canvas_size.y
+    || top_left.x + size.x < 0.0
+    || top_left.y + size.y < 0.0;
+}
+
+impl WebGlDrawBackend {
+  // Returns None when the browser can't provide a working WebGL context, in
+  // which case the caller should fall back to Canvas2dDrawBackend.
+  pub fn new(canvas: &web_sys::HtmlCanvasElement) -> Option<WebGlDrawBackend> {
+    let gl = canvas.get_context("webgl").ok()??.dyn_into::<GL>().ok()?;
+    let program = link_program(&gl)?;
+    gl.use_program(Some(&program));
+    let canvas_size_location = gl.get_uniform_location(&program, "u_canvas_size")?;
+
+    let vertex_buffer = gl.create_buffer()?;
+    gl.bind_buffer(GL::ARRAY_BUFFER, Some(&vertex_buffer));
+    let stride = (FLOATS_PER_VERTEX * 4) as i32;
+    gl.enable_vertex_attrib_array(0);
+    gl.vertex_attrib_pointer_with_i32(0, 2, GL::FLOAT, false, stride, 0);
+    gl.enable_vertex_attrib_array(1);
+    gl.vertex_attrib_pointer_with_i32(1, 2, GL::FLOAT, false, stride, 8);
+    gl.enable_vertex_attrib_array(2);
+    gl.vertex_attrib_pointer_with_i32(2, 4, GL::FLOAT, false, stride, 16);
+
+    let index_buffer = gl.create_buffer()?;
+    gl.bind_buffer(GL::ELEMENT_ARRAY_BUFFER, Some(&index_buffer));
+    let mut indices: Vec<u16> = Vec::with_capacity(MAX_QUADS_PER_BATCH * 6);
+    for quad in 0..MAX_QUADS_PER_BATCH {
+      let first = (quad * 4) as u16;
+      indices.extend_from_slice(&[first, first + 1, first + 2, first, first + 2, first + 3]);
+    }
+    gl.buffer_data_with_array_buffer_view(
+      GL::ELEMENT_ARRAY_BUFFER,
+      &js_sys::Uint16Array::from(indices.as_slice()),
+      GL::STATIC_DRAW,
+    );
+
+    gl.pixel_storei(GL::UNPACK_PREMULTIPLY_ALPHA_WEBGL, 1);
+    gl.enable(GL::BLEND);
+    gl.blend_func(GL::ONE, GL::ONE_MINUS_SRC_ALPHA);
+    gl.active_texture(GL::TEXTURE0);
+
+    let white_texture = create_gl_texture(&gl);
+    gl.tex_image_2d_with_i32_and_i32_and_i32_and_format_and_type_and_opt_u8_array(
+      GL::TEXTURE_2D,
+      0,
+      GL::RGBA as i32,
+      1,
+      1,
+      0,
+      GL::RGBA,
+      GL::UNSIGNED_BYTE,
+      Some(&[255, 255, 255, 255]),
+    )
+    .ok()?;
+
+    return Some(WebGlDrawBackend {
+      gl,
+      canvas_size_location,
+      vertex_buffer,
+      white_texture,
+      image_textures: RefCell::new(HashMap::new()),
+      canvas_textures: RefCell::new(HashMap::new()),
+      text_textures: RefCell::new(HashMap::new()),
+      transient_text_caches: RefCell::new(HashMap::new()),
+      frame: Cell::new(0),
+      vertices: RefCell::new(Vec::with_capacity(
+        MAX_QUADS_PER_BATCH * 4 * FLOATS_PER_VERTEX,
+      )),
+      batch_texture: RefCell::new(None),
+      batch_blend_mode: Cell::new(BlendMode::Normal),
+    });
+  }
+
+  // None until the texture's image has loaded.
+  fn get_image_texture(&self, texture: &Rc<Texture>) -> Option<web_sys::WebGlTexture> {
+    // Atlas regions share the texture of their atlas page.
+    if let Some(atlas_region) = texture.get_atlas_region() {
+      return self.get_image_texture(&atlas_region.atlas);
+    }
+    if !texture.image_loaded.get() {
+      return None;
+    }
+    let key = Rc::as_ptr(texture) as usize;
+    let mut image_textures = self.image_textures.borrow_mut();
+    let image_texture = image_textures.entry(key).or_insert_with(|| ImageGlTexture {
+      _texture: texture.clone(),
+      gl_texture: create_gl_texture(&self.gl),
+      load_generation: None,
+    });
+    let load_generation = texture.get_load_generation();
+    if image_texture.load_generation != Some(load_generation) {
+      image_texture.load_generation = Some(load_generation);
+      self
+        .gl
+        .bind_texture(GL::TEXTURE_2D, Some(&image_texture.gl_texture));
+      self
+        .gl
+        .tex_image_2d_with_u32_and_u32_and_image(
+          GL::TEXTURE_2D,
+          0,
+          GL::RGBA as i32,
+          GL::RGBA,
+          GL::UNSIGNED_BYTE,
+          texture.image(),
+        )
+        .expect("gl.tex_image_2d_with_u32_and_u32_and_image failed");
+    }
+    return Some(image_texture.gl_texture.clone());
+  }
+
+  // None for render targets that aren't canvases.
+  fn get_render_target_texture(
+    &self,
+    render_target: &Rc<dyn RenderTarget>,
+  ) -> Option<web_sys::WebGlTexture> {
+    let key = Rc::as_ptr(render_target) as *const () as usize;
+    let frame = self.frame.get();
+    let canvas_render_target = CanvasRenderTarget::from_render_target(render_target.as_ref())?;
+    let canvas = &canvas_render_target.canvas;
+    let generation = canvas_render_target.get_generation();
+    let mut canvas_textures = self.canvas_textures.borrow_mut();
+    if let Some(canvas_texture) = canvas_textures.get_mut(&key) {
+      canvas_texture.used_frame = frame;
+      if canvas_texture.generation != generation {
+        canvas_texture.generation = generation;
+        upload_canvas(&self.gl, &canvas_texture.gl_texture, canvas);
+      }
+      return Some(canvas_texture.gl_texture.clone());
+    }
+    let gl_texture = create_gl_texture(&self.gl);
+    upload_canvas(&self.gl, &gl_texture, canvas);
+    canvas_textures.insert(
+      key,
+      CanvasGlTexture {
+        _render_target: render_target.clone(),
+        gl_texture: gl_texture.clone(),
+        generation,
+        used_frame: frame,
+      },
+    );
+    return Some(gl_texture);
+  }
+
+  // Text caches only change when their text or style does, so they are
+  // uploaded again only when TextCache::generation moves.
+  fn get_text_texture(&self, text_cache: &Rc<TextCache>) -> web_sys::WebGlTexture {
+    let key = Rc::as_ptr(text_cache) as usize;
+    let frame = self.frame.get();
+    let generation = text_cache.generation.get();
+    let mut text_textures = self.text_textures.borrow_mut();
+    if let Some(text_texture) = text_textures.get_mut(&key) {
+      text_texture.used_frame = frame;
+      if text_texture.generation != generation {
+        text_texture.generation = generation;
+        upload_canvas(
+          &self.gl,
+          &text_texture.gl_texture,
+          &text_texture.text_cache.canvas_render_target.canvas,
+        );
+      }
+      return text_texture.gl_texture.clone();
+    }
+    let gl_texture = create_gl_texture(&self.gl);
+    upload_canvas(
+      &self.gl,
+      &gl_texture,
+      &text_cache.canvas_render_target.canvas,
+    );
+    text_textures.insert(
+      key,
+      TextGlTexture {
+        text_cache: text_cache.clone(),
+        gl_texture: gl_texture.clone(),
+        generation,
+        used_frame: frame,
+      },
+    );
+    return gl_texture;
+  }
+
+  fn push_solid_triangles(&self, triangles: &[[F2; 3]], color: &DrawColor, opacity: F1) {
+    let color = [
+      color.r as f32 / 255.0,
+      color.g as f32 / 255.0,
+      color.b as f32 / 255.0,
+      opacity,
+    ];
+    let white_texture = self.white_texture.clone();
+    // Triangles are pushed as quads with the last corner repeated.
+    for triangle in triangles.iter() {
+      self.push_quad(
+        &white_texture,
+        BlendMode::Normal,
+        Quad {
+          corners: [triangle[0], triangle[1], triangle[2], triangle[2]],
+          tex_coords: [F2::default(); 4],
+          colors: [color; 4],
+        },
+      );
+    }
+  }
+
+  fn push_quad(&self, gl_texture: &web_sys::WebGlTexture, blend_mode: BlendMode, quad: Quad) {
+    let batch_full = self.vertices.borrow().len() >= MAX_QUADS_PER_BATCH * 4 * FLOATS_PER_VERTEX;
+    if batch_full
+      || self.batch_blend_mode.get() != blend_mode
+      || self.batch_texture.borrow().as_ref() != Some(gl_texture)
+    {
+      self.flush();
+      self.batch_texture.replace(Some(gl_texture.clone()));
+      self.batch_blend_mode.set(blend_mode);
+    }
+    let mut vertices = self.vertices.borrow_mut();
+    for corner in 0..4 {
+      vertices.extend_from_slice(&[
+        quad.corners[corner].x,
+        quad.corners[corner].y,
+        quad.tex_coords[corner].x,
+        quad.tex_coords[corner].y,
+      ]);
+      vertices.extend_from_slice(&quad.colors[corner]);
+    }
+  }
+
+  fn flush(&self) {
+    let mut vertices = self.vertices.borrow_mut();
+    if vertices.is_empty() {
+      return;
+    }
+    let gl = &self.gl;
+    match self.batch_blend_mode.get() {
+      BlendMode::Normal => gl.blend_func(GL::ONE, GL::ONE_MINUS_SRC_ALPHA),
+      BlendMode::Additive => gl.blend_func(GL::ONE, GL::ONE),
+    }
+    gl.bind_texture(GL::TEXTURE_2D, self.batch_texture.borrow().as_ref());
+    gl.bind_buffer(GL::ARRAY_BUFFER, Some(&self.vertex_buffer));
+    gl.buffer_data_with_array_buffer_view(
+      GL::ARRAY_BUFFER,
+      &js_sys::Float32Array::from(vertices.as_slice()),
+      GL::STREAM_DRAW,
+    );
+    let quad_count = vertices.len() / (4 * FLOATS_PER_VERTEX);
+    gl.draw_elements_with_i32(
+      GL::TRIANGLES,
+      (quad_count * 6) as i32,
+      GL::UNSIGNED_SHORT,
+      0,
+    );
+    vertices.clear();
+  }
+}
+
+impl DrawBackend for WebGlDrawBackend {
+  fn clear_screen(&self, canvas_size: &F2) {
+    self.frame.set(self.frame.get() + 1);
+    self
+      .gl
+      .viewport(0, 0, canvas_size.x as i32, canvas_size.y as i32);
+    self.gl.uniform2f(
+      Some(&self.canvas_size_location),
+      canvas_size.x,
+      canvas_size.y,
+    );
+    self.gl.clear_color(0.0, 0.0, 0.0, 0.0);
+    self.gl.clear(GL::COLOR_BUFFER_BIT);
+  }
+
+  // Submits the last batch and releases textures of sources that weren't drawn
+  // this frame.
+  fn finish_frame(&self) {
+    self.flush();
+    let frame = self.frame.get();
+    let gl = &self.gl;
+    self
+      .canvas_textures
+      .borrow_mut()
+      .retain(|_, canvas_texture| {
+        let keep = canvas_texture.used_frame == frame;
+        if !keep {
+          gl.delete_texture(Some(&canvas_texture.gl_texture));
+        }
+        keep
+      });
+    self.text_textures.borrow_mut().retain(|_, text_texture| {
+      let keep = text_texture.used_frame == frame;
+      if !keep {
+        gl.delete_texture(Some(&text_texture.gl_texture));
+      }
+      keep
+    });
+    self
+      .transient_text_caches
+      .borrow_mut()
+      .retain(|_, (_, used_frame)| *used_frame == frame);
+  }
+
+  fn create_render_target(&self, size: &F2) -> Rc<dyn RenderTarget> {
+    return CanvasRenderTarget::new(size);
+  }
+
+  fn create_text_cache(&self) -> Option<Rc<TextCache>> {
+    return Some(TextCache::new());
+  }
+
+  fn execute_image_draw(&self, args: &DrawImageArgs, canvas_size: &F2) {
+    let mut center_position = args.position;
+    let mut top_left_rel_position = F2 {
+      x: -args.size.x * args.optional.anchor_point.x,
+      y: -args.size.y * args.optional.anchor_point.y,
+    };
+    let mut size = args.size;
+
+    // Do not draw images that are completely outside the screen.
+    if is_outside_canvas(
+      &(center_position + top_left_rel_position),
+      &size,
+      canvas_size,
+    ) {
+      return;
+    }
+    if args.optional.opacity == 0.0 {
+      return;
+    }
+
+    let mut tex_coord_start = args.optional.partial_region_offset;
+    let mut tex_coord_end = args.optional.partial_region_offset + args.optional.partial_region_size;
+    let (gl_texture, tint) = match &args.source {
+      DrawSource::RenderTarget(render_target) => {
+        let render_target_size = render_target.get_size();
+        if render_target_size.x < 1.0 || render_target_size.y < 1.0 {
+          return;
+        }
+        match self.get_render_target_texture(render_target) {
+          Some(gl_texture) => (gl_texture, DrawColor::default()),
+          None => return,
+        }
+      }
+      DrawSource::Texture(texture) => {
+        if let Some(atlas_region) = texture.get_atlas_region() {
+          tex_coord_start = get_atlas_tex_coord(atlas_region, &tex_coord_start);
+          tex_coord_end = get_atlas_tex_coord(atlas_region, &tex_coord_end);
+        }
+        match self.get_image_texture(texture) {
+          Some(gl_texture) => (gl_texture, args.optional.color),
+          None => return,
+        }
+      }
+    };
+
+    // Same rounding as Canvas2dDrawBackend, so both backends line up.
+    if !args.optional.subpixel_precision {
+      center_position.round();
+      top_left_rel_position.round();
+      size.round();
+    }
+    if size.x == 0.0 || size.y == 0.0 {
+      return;
+    }
+
+    let (sin, cos) = args.optional.rotation.sin_cos();
+    let relative_corners = [
+      top_left_rel_position,
+      top_left_rel_position + F2 { x: size.x, y: 0.0 },
+      top_left_rel_position + size,
+      top_left_rel_position + F2 { x: 0.0, y: size.y },
+    ];
+    let mut corners = [F2::default(); 4];
+    for (corner, relative_corner) in corners.iter_mut().zip(relative_corners.iter()) {
+      *corner = center_position
+        + F2 {
+          x: relative_corner.x * cos - relative_corner.y * sin,
+          y: relative_corner.x * sin + relative_corner.y * cos,
+        };
+    }
+
+    let color = [
+      tint.r as f32 / 255.0,
+      tint.g as f32 / 255.0,
+      tint.b as f32 / 255.0,
+      args.optional.opacity,
+    ];
+    self.push_quad(
+      &gl_texture,
+      BlendMode::from_composite_operation(&args.optional.composite_operation),
+      Quad {
+        corners,
+        tex_coords: get_rect_tex_coords(&tex_coord_start, &tex_coord_end),
+        colors: [color; 4],
+      },
+    );
+  }
+
+  fn execute_string_draw(&self, args: &DrawStringArgs, device_pixel_ratio: &F1, canvas_size: &F2) {
+    let text_cache = match args.optional.text_cache.as_ref() {
+      Some(text_cache) => text_cache.clone(),
+      None => {
+        let frame = self.frame.get();
+        let mut transient_text_caches = self.transient_text_caches.borrow_mut();
+        let (text_cache, used_frame) = transient_text_caches
+          .entry(get_transient_text_key(args))
+          .or_insert_with(|| (TextCache::new(), frame));
+        *used_frame = frame;
+        text_cache.clone()
+      }
+    };
+    text_cache.check_update_cache(args);
+
+    let size = text_cache.cached_canvas.canvas_size.get();
+    if size.x < 1.0 || size.y < 1.0 || args.optional.opacity == 0.0 {
+      return;
+    }
+    let top_left = args.position
+      + F2 {
+        x: get_text_alignment_offset_x(args.optional.alignment, size.x),
+        y: -size.y * 0.5 - 4.0 * device_pixel_ratio,
+      };
+    if is_outside_canvas(&top_left, &size, canvas_size) {
+      return;
+    }
+
+    let gl_texture = self.get_text_texture(&text_cache);
+    self.push_quad(
+      &gl_texture,
+      BlendMode::Normal,
+      Quad {
+        corners: [
+          top_left,
+          top_left + F2 { x: size.x, y: 0.0 },
+          top_left + size,
+          top_left + F2 { x: 0.0, y: size.y },
+        ],
+        tex_coords: get_rect_tex_coords(&F2 { x: 0.0, y: 0.0 }, &F2 { x: 1.0, y: 1.0 }),
+        colors: [[1.0, 1.0, 1.0, args.optional.opacity]; 4],
+      },
+    );
+  }
+
+  fn execute_gradient_box_draw(&self, args: &DrawGradientBoxArgs, canvas_size: &F2) {
+    let top_left = args.position
+      + F2 {
+        x: -args.size.x * args.anchor_point.x,
+        y: -args.size.y * args.anchor_point.y,
+      };
+    if is_outside_canvas(&top_left, &args.size, canvas_size) {
+      return;
+    }
+    let color_start = [
+      args.draw_color_start.r as f32 / 255.0,
+      args.draw_color_start.g as f32 / 255.0,
+      args.draw_color_start.b as f32 / 255.0,
+      args.alpha_start,
+    ];
+    let color_end = [
+      args.draw_color_end.r as f32 / 255.0,
+      args.draw_color_end.g as f32 / 255.0,
+      args.draw_color_end.b as f32 / 255.0,
+      args.alpha_end,
+    ];
+    let white_texture = self.white_texture.clone();
+    self.push_quad(
+      &white_texture,
+      BlendMode::Normal,
+      Quad {
+        corners: [
+          top_left,
+          top_left
+            + F2 {
+              x: args.size.x,
+              y: 0.0,
+            },
+          top_left + args.size,
+          top_left
+            + F2 {
+              x: 0.0,
+              y: args.size.y,
+            },
+        ],
+        tex_coords: get_rect_tex_coords(&F2 { x: 0.0, y: 0.0 }, &F2 { x: 1.0, y: 1.0 }),
+        colors: [color_start, color_end, color_end, color_start],
+      },
+    );
+  }
+
+  fn execute_stroke_draw(&self, args: &DrawStrokeArgs, canvas_size: &F2) {
+    if args.points.is_empty() || args.optional.opacity == 0.0 {
+      return;
+    }
+    let (min, max) = StrokeGeometry::get_bounds(&args.points);
+    if is_outside_canvas(&min, &(max - min), canvas_size) {
+      return;
+    }
+    let gl_texture = match &args.optional.brush {
+      Some(brush) => match self.get_image_texture(brush) {
+        Some(gl_texture) => gl_texture,
+        None => return,
+      },
+      None => self.white_texture.clone(),
+    };
+    let atlas_region = args
+      .optional
+      .brush
+      .as_ref()
+      .and_then(|brush| brush.get_atlas_region());
+    let color = [
+      args.optional.color.r as f32 / 255.0,
+      args.optional.color.g as f32 / 255.0,
+      args.optional.color.b as f32 / 255.0,
+      args.optional.opacity,
+    ];
+    // Triangles are pushed as quads with the last corner repeated.
+    for triangle in StrokeGeometry::get_triangles(&args.points).iter() {
+      let mut tex_coords = [F2::default(); 4];
+      for (tex_coord, vertex) in tex_coords.iter_mut().zip(triangle.iter()) {
+        *tex_coord = match atlas_region {
+          Some(atlas_region) => get_atlas_tex_coord(atlas_region, &vertex.tex_coord),
+          None => vertex.tex_coord,
+        };
+      }
+      tex_coords[3] = tex_coords[2];
+      self.push_quad(
+        &gl_texture,
+        BlendMode::Normal,
+        Quad {
+          corners: [
+            triangle[0].position,
+            triangle[1].position,
+            triangle[2].position,
+            triangle[2].position,
+          ],
+          tex_coords,
+          colors: [color; 4],
+        },
+      );
+    }
+  }
+
+  fn execute_shape_draw(&self, args: &DrawShapeArgs, canvas_size: &F2) {
+    if args.optional.opacity == 0.0 {
+      return;
+    }
+    let (min, max) = ShapeGeometry::get_bounds(args);
+    if is_outside_canvas(&min, &(max - min), canvas_size) {
+      return;
+    }
+    // Overlapping triangles, at the corners of strokes, are blended twice.
+    if let Some(fill_color) = args.optional.fill_color {
+      self.push_solid_triangles(
+        &ShapeGeometry::get_fill_triangles(&args.shape),
+        &fill_color,
+        args.optional.opacity,
+      );
+    }
+    if let Some(stroke_color) = args.optional.stroke_color {
+      self.push_solid_triangles(
+        &ShapeGeometry::get_stroke_triangles(&args.shape, &args.optional),
+        &stroke_color,
+        args.optional.opacity,
+      );
+    }
+  }
+}
@@ -2,6 +2,7 @@ pub mod ui_button;
 pub mod ui_container;
 pub mod ui_element;
 pub mod ui_manager;
+pub mod ui_nine_slice;
 pub mod ui_overlay;
 pub mod ui_pivot;
 pub mod ui_root;
@@ -6,6 +6,8 @@ pub struct UiElementParams {
   pub position: Shared<F2>,
   pub opacity: Shared<F1>,
   pub depth: Shared<F1>,
+  // The parent's layer when not set, ContextTrait::get_ui_layer at the root.
+  pub layer: Shared<Option<RenderLayer>>,
   pub visible: Shared<bool>,
 }
 
@@ -14,6 +16,7 @@ pub struct AbsoluteUiElementParams {
   pub position: F2,
   pub opacity: F1,
   pub depth: F1,
+  pub layer: RenderLayer,
   pub visible: bool,
 }
 
@@ -44,6 +47,7 @@ impl<C: ContextTrait + ?Sized> UiElement<C> {
         position: Shared::new(F2 { x: 0.0, y: 0.0 }),
         opacity: Shared::new(1.0),
         depth: Shared::new(0.0),
+        layer: Shared::new(None),
         visible: Shared::new(true),
       },
       parent: RefCell::new(None),
@@ -90,6 +94,9 @@ impl<C: ContextTrait + ?Sized> UiElement<C> {
   pub fn set_depth(&self, depth: F1) {
     *self.relative_params.depth.borrow_mut() = depth;
   }
+  pub fn set_layer(&self, layer: RenderLayer) {
+    *self.relative_params.layer.borrow_mut() = Some(layer);
+  }
   pub fn set_visible(&self, visible: bool) {
     *self.relative_params.visible.borrow_mut() = visible;
   }
@@ -130,6 +137,10 @@ impl<C: ContextTrait + ?Sized> UiElement<C> {
           let absolute_opacity = parent_absolute_params.opacity * *relative_params.opacity.borrow();
           let absolute_depth =
             parent_absolute_params.depth + *relative_params.depth.borrow() / 10.0;
+          let absolute_layer = relative_params
+            .layer
+            .borrow()
+            .unwrap_or(parent_absolute_params.layer);
           let absolute_visible =
             parent_absolute_params.visible && *relative_params.visible.borrow();
 
@@ -137,6 +148,7 @@ impl<C: ContextTrait + ?Sized> UiElement<C> {
             position: absolute_position,
             opacity: absolute_opacity,
             depth: absolute_depth,
+            layer: absolute_layer,
             visible: absolute_visible,
           }
         }
@@ -144,6 +156,10 @@ impl<C: ContextTrait + ?Sized> UiElement<C> {
           position: *relative_params.position.borrow(),
           opacity: *relative_params.opacity.borrow(),
           depth: *relative_params.depth.borrow(),
+          layer: relative_params
+            .layer
+            .borrow()
+            .unwrap_or(context.get_ui_layer()),
           visible: *relative_params.visible.borrow(),
         },
       },
@@ -151,6 +167,10 @@ impl<C: ContextTrait + ?Sized> UiElement<C> {
         position: *relative_params.position.borrow(),
         opacity: *relative_params.opacity.borrow(),
         depth: *relative_params.depth.borrow(),
+        layer: relative_params
+          .layer
+          .borrow()
+          .unwrap_or(context.get_ui_layer()),
         visible: *relative_params.visible.borrow(),
       },
     };
@@ -48,12 +48,12 @@ impl<C: ContextTrait + ?Sized> UiManager<C> {
     let sprite_fade = Rc::new(UiSprite::new(context.get_pixel_texture()));
     sprite_fade.set_opacity(0.0);
     sprite_fade.set_color(DrawColor { r: 0, g: 0, b: 0 });
-    sprite_fade.set_depth(context.get_front_board_depth());
+    sprite_fade.set_layer(context.get_front_board_layer());
 
     let sprite_overlay_background = Rc::new(UiSprite::new(context.get_pixel_texture()));
     sprite_overlay_background.set_opacity(0.5);
     sprite_overlay_background.set_color(DrawColor { r: 0, g: 0, b: 0 });
-    sprite_overlay_background.set_depth(context.get_front_board_depth());
+    sprite_overlay_background.set_layer(context.get_front_board_layer());
 
     let animation_switch_root =
       Effect::new_within_effect_manager(ChainedEffect::new(), &effect_manager);
@@ -143,6 +143,16 @@ impl<C: ContextTrait + ?Sized> UiManager<C> {
     return false;
   }
 
+  pub fn process_wheel(&self, context: &mut C, position: &F2, delta_y: F1) -> bool {
+    if self.block_input.get() || self.overlay.borrow().is_some() {
+      return false;
+    }
+    if let Some(current_page) = &*self.current_page.borrow() {
+      return current_page.process_wheel_game(context, position, delta_y);
+    }
+    return false;
+  }
+
   pub fn process_touch_ui(&self, context: &mut C, ui_touch: &mut UiTouch) -> bool {
     if self.block_input.get() {
       return false;
@@ -262,7 +272,6 @@ impl<C: ContextTrait + ?Sized> UiManager<C> {
     }
 
     if let Some(overlay) = self.overlay.borrow().as_ref() {
-      overlay.draw(context);
       self
         .sprite_overlay_background
         .set_position(context.get_ui_viewport_screen_center());
@@ -270,6 +279,7 @@ impl<C: ContextTrait + ?Sized> UiManager<C> {
         .sprite_overlay_background
         .set_size(context.get_ui_viewport_screen_size());
       self.sprite_overlay_background.draw(context);
+      overlay.draw(context);
     }
 
     self
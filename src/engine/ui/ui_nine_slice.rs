@@ -0,0 +1,306 @@
+use crate::engine::effect::*;
+use crate::engine::ui::*;
+use crate::engine::*;
+use std::ops::Deref;
+
+// Borders of the texture that are not stretched, in pixels of the @1x texture.
+// Corners keep their size, edges are only stretched along the border.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct NineSliceMargins {
+  pub left: F1,
+  pub top: F1,
+  pub right: F1,
+  pub bottom: F1,
+}
+
+impl NineSliceMargins {
+  pub fn new_uniform(margin: F1) -> NineSliceMargins {
+    return NineSliceMargins {
+      left: margin,
+      top: margin,
+      right: margin,
+      bottom: margin,
+    };
+  }
+
+  // Splits an element of `size` into up to 9 patches of the texture. When the
+  // element is smaller than its margins, they are shrunk proportionally and the
+  // empty patches are dropped.
+  pub fn get_patches(
+    &self,
+    texture_size: F2,
+    texture_pixel_size: F1,
+    size: F2,
+  ) -> Vec<NineSlicePatch> {
+    let columns = get_slices(
+      texture_size.x,
+      self.left,
+      self.right,
+      texture_pixel_size,
+      size.x,
+    );
+    let rows = get_slices(
+      texture_size.y,
+      self.top,
+      self.bottom,
+      texture_pixel_size,
+      size.y,
+    );
+
+    let mut patches = Vec::with_capacity(9);
+    for row in rows.iter() {
+      for column in columns.iter() {
+        patches.push(NineSlicePatch {
+          position: F2 {
+            x: column.start + 0.5 * column.size,
+            y: row.start + 0.5 * row.size,
+          },
+          size: F2 {
+            x: column.size,
+            y: row.size,
+          },
+          partial_region_offset: F2 {
+            x: column.region_offset,
+            y: row.region_offset,
+          },
+          partial_region_size: F2 {
+            x: column.region_size,
+            y: row.region_size,
+          },
+        });
+      }
+    }
+    return patches;
+  }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct NineSlicePatch {
+  // Relative to the center of the element.
+  pub position: F2,
+  pub size: F2,
+  pub partial_region_offset: F2,
+  pub partial_region_size: F2,
+}
+
+pub struct UiNineSlice<C: ContextTrait + ?Sized> {
+  pub element: UiElement<C>,
+
+  pub texture: RefCell<Rc<Texture>>,
+  pub margins: Cell<NineSliceMargins>,
+  // Size of a texture pixel once drawn, it scales the margins.
+  pub texture_pixel_size: Cell<F1>,
+  pub size: Shared<F2>,
+  pub color: Shared<DrawColor>,
+}
+
+impl<C: ContextTrait + ?Sized> UiNineSlice<C> {
+  pub fn new(
+    texture: Rc<Texture>,
+    margins: NineSliceMargins,
+    texture_pixel_size: F1,
+  ) -> UiNineSlice<C> {
+    return UiNineSlice {
+      element: UiElement::new(),
+
+      texture: RefCell::new(texture),
+      margins: Cell::new(margins),
+      texture_pixel_size: Cell::new(texture_pixel_size),
+      size: Shared::default(),
+      color: Shared::default(),
+    };
+  }
+
+  pub fn set_class(&self, class: &UiElementClass) {
+    if let Some(size) = class.size {
+      self.set_size(size);
+    }
+    if let Some(size_x) = class.size_x {
+      self.set_size_x(size_x);
+    }
+    if let Some(size_y) = class.size_y {
+      self.set_size_y(size_y);
+    }
+
+    self.element.set_class(class);
+  }
+
+  pub fn set_size(&self, size: F2) {
+    *self.size.borrow_mut() = size;
+  }
+
+  pub fn set_size_x(&self, size_x: F1) {
+    self.size.borrow_mut().x = size_x;
+  }
+
+  pub fn set_size_y(&self, size_y: F1) {
+    self.size.borrow_mut().y = size_y;
+  }
+
+  pub fn set_color(&self, color: DrawColor) {
+    *self.color.borrow_mut() = color;
+  }
+
+  pub fn set_margins(&self, margins: NineSliceMargins) {
+    self.margins.set(margins);
+  }
+
+  pub fn set_texture(&self, texture: Rc<Texture>) {
+    self.texture.replace(texture);
+  }
+}
+
+struct Slice {
+  start: F1,
+  size: F1,
+  region_offset: F1,
+  region_size: F1,
+}
+
+// The three slices of one axis, centered on 0.
+fn get_slices(
+  texture_size: F1,
+  margin_start: F1,
+  margin_end: F1,
+  texture_pixel_size: F1,
+  size: F1,
+) -> Vec<Slice> {
+  let mut drawn_start = margin_start * texture_pixel_size;
+  let mut drawn_end = margin_end * texture_pixel_size;
+  if drawn_start + drawn_end > size {
+    let shrink = size / (drawn_start + drawn_end);
+    drawn_start *= shrink;
+    drawn_end *= shrink;
+  }
+  let region_start = margin_start / texture_size;
+  let region_end = margin_end / texture_size;
+
+  let slices = vec![
+    Slice {
+      start: -0.5 * size,
+      size: drawn_start,
+      region_offset: 0.0,
+      region_size: region_start,
+    },
+    Slice {
+      start: -0.5 * size + drawn_start,
+      size: size - drawn_start - drawn_end,
+      region_offset: region_start,
+      region_size: 1.0 - region_start - region_end,
+    },
+    Slice {
+      start: 0.5 * size - drawn_end,
+      size: drawn_end,
+      region_offset: 1.0 - region_end,
+      region_size: region_end,
+    },
+  ];
+  return slices
+    .into_iter()
+    .filter(|slice| slice.size > 0.0 && slice.region_size > 0.0)
+    .collect();
+}
+
+impl<C: ContextTrait + ?Sized> EffectManagerTrait<C> for UiNineSlice<C> {
+  fn get_effect_manager(&self) -> Option<&EffectManager<C>> {
+    return None;
+  }
+}
+
+impl<C: ContextTrait + ?Sized> UiElementTrait<C> for UiNineSlice<C> {
+  fn get_ui_element(&self) -> &UiElement<C> {
+    return &self.element;
+  }
+
+  fn update(&self, _context: &mut C) {}
+
+  fn draw(&self, context: &mut C) {
+    let absolute_params = self.get_absolute_params(context);
+
+    if !absolute_params.visible {
+      return;
+    }
+
+    let texture = self.texture.borrow().clone();
+    let patches = self.margins.get().get_patches(
+      texture.get_size(),
+      self.texture_pixel_size.get(),
+      *self.size.borrow(),
+    );
+    for patch in patches {
+      context.draw_ui_viewport(DrawImageArgs {
+        source: DrawSource::Texture(texture.clone()),
+        position: absolute_params.position + patch.position,
+        size: patch.size,
+        layer: absolute_params.layer,
+        depth: absolute_params.depth,
+        optional: DrawImageOptionalArgs {
+          color: *self.color.borrow(),
+          opacity: absolute_params.opacity,
+          partial_region_offset: patch.partial_region_offset,
+          partial_region_size: patch.partial_region_size,
+          // Rounding each patch on its own would open gaps between them.
+          subpixel_precision: true,
+          ..Default::default()
+        },
+      });
+    }
+  }
+}
+
+impl<C: ContextTrait + ?Sized> Deref for UiNineSlice<C> {
+  type Target = UiElement<C>;
+
+  fn deref(&self) -> &Self::Target {
+    return &self.element;
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn get_patches(size: F2) -> Vec<NineSlicePatch> {
+    let margins = NineSliceMargins {
+      left: 10.0,
+      top: 10.0,
+      right: 20.0,
+      bottom: 10.0,
+    };
+    return margins.get_patches(F2 { x: 100.0, y: 50.0 }, 0.01, size);
+  }
+
+  #[test]
+  fn test_nine_slice_patches() {
+    let patches = get_patches(F2 { x: 2.0, y: 1.0 });
+    assert_eq!(patches.len(), 9);
+    // Corners keep their size.
+    assert!(patches[0].size.eq_near(&F2 { x: 0.1, y: 0.1 }));
+    assert!(patches[0].position.eq_near(&F2 { x: -0.95, y: -0.45 }));
+    assert!(patches[0]
+      .partial_region_size
+      .eq_near(&F2 { x: 0.1, y: 0.2 }));
+    assert!(patches[8].size.eq_near(&F2 { x: 0.2, y: 0.1 }));
+    assert!(patches[8].position.eq_near(&F2 { x: 0.9, y: 0.45 }));
+    assert!(patches[8]
+      .partial_region_offset
+      .eq_near(&F2 { x: 0.8, y: 0.8 }));
+    // The center is stretched.
+    assert!(patches[4].size.eq_near(&F2 { x: 1.7, y: 0.8 }));
+    assert!(patches[4]
+      .partial_region_offset
+      .eq_near(&F2 { x: 0.1, y: 0.2 }));
+    assert!(patches[4]
+      .partial_region_size
+      .eq_near(&F2 { x: 0.7, y: 0.6 }));
+  }
+
+  #[test]
+  fn test_nine_slice_patches_smaller_than_margins() {
+    let patches = get_patches(F2 { x: 0.15, y: 0.3 });
+    // No room left for the middle column.
+    assert_eq!(patches.len(), 6);
+    assert!(patches[0].size.eq_near(&F2 { x: 0.05, y: 0.1 }));
+    assert!(patches[1].size.eq_near(&F2 { x: 0.1, y: 0.1 }));
+  }
+}
@@ -15,4 +15,9 @@ pub trait UiRootTrait<C: ContextTrait + ?Sized>: UiElementTrait<C> {
   fn process_touch_game(&self, _context: &mut C, _ui_touch: &ScreenTouch) -> bool {
     return false;
   }
+
+  // Mouse wheel over the screen, `position` in screen coordinates.
+  fn process_wheel_game(&self, _context: &mut C, _position: &F2, _delta_y: F1) -> bool {
+    return false;
+  }
 }
@@ -93,6 +93,7 @@ impl<C: ContextTrait + ?Sized> UiElementTrait<C> for UiSprite<C> {
       source: DrawSource::Texture(self.texture.borrow().clone()),
       position: absolute_params.position,
       size: *self.size.borrow(),
+      layer: absolute_params.layer,
       depth: absolute_params.depth,
       optional: DrawImageOptionalArgs {
         color: *self.color.borrow(),
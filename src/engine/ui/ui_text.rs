@@ -10,6 +10,12 @@ pub struct UiText<C: ContextTrait + ?Sized> {
   alignment: Shared<TextAlignment>,
   color: Cell<DrawColor>,
   border: Cell<bool>,
+  max_width: Cell<Option<F1>>,
+  line_spacing: Cell<F1>,
+  spans: RefCell<Vec<TextSpan>>,
+  font_atlas: RefCell<Option<Rc<FontAtlas>>>,
+  // Created by the draw backend on the first draw.
+  use_text_cache: Cell<bool>,
   text_cache: RefCell<Option<Rc<TextCache>>>,
 }
 
@@ -22,16 +28,33 @@ impl<C: ContextTrait + ?Sized> UiText<C> {
       alignment: Shared::new(TextAlignment::Left),
       color: Cell::default(),
       border: Cell::new(false),
+      max_width: Cell::new(None),
+      line_spacing: Cell::new(1.0),
+      spans: RefCell::new(Vec::new()),
+      font_atlas: RefCell::new(None),
+      use_text_cache: Cell::new(false),
       text_cache: RefCell::new(None),
     });
   }
 
   pub fn set_text(&self, text: String) {
     *self.text.borrow_mut() = text;
+    self.spans.borrow_mut().clear();
+  }
+
+  pub fn set_styled_text(&self, parts: &[(&str, TextStyle)]) {
+    let (text, spans) = TextSpan::from_parts(parts);
+    *self.text.borrow_mut() = text;
+    self.spans.replace(spans);
   }
 
   pub fn use_text_cache(&self) {
-    self.text_cache.replace(Some(TextCache::new()));
+    self.use_text_cache.set(true);
+  }
+
+  // Cheaper than a text cache for text changing often, like counters.
+  pub fn use_font_atlas(&self, font_atlas: Rc<FontAtlas>) {
+    self.font_atlas.replace(Some(font_atlas));
   }
 
   pub fn set_font_size(&self, font_size: F1) {
@@ -49,6 +72,15 @@ impl<C: ContextTrait + ?Sized> UiText<C> {
   pub fn set_border(&self, border: bool) {
     self.border.set(border);
   }
+
+  // Wraps the text between words to fit the width.
+  pub fn set_max_width(&self, max_width: F1) {
+    self.max_width.set(Some(max_width));
+  }
+
+  pub fn set_line_spacing(&self, line_spacing: F1) {
+    self.line_spacing.set(line_spacing);
+  }
 }
 
 impl<C: ContextTrait + ?Sized> EffectManagerTrait<C> for UiText<C> {
@@ -70,11 +102,17 @@ impl<C: ContextTrait> UiElementTrait<C> for UiText<C> {
     if !absolute_params.visible {
       return;
     }
+    if self.use_text_cache.get() && self.text_cache.borrow().is_none() {
+      self
+        .text_cache
+        .replace(context.get_draw_manager().create_text_cache());
+    }
 
     context.draw_string_ui_viewport(DrawStringArgs {
       text: self.text.borrow().clone(),
       position: absolute_params.position,
       font_size: *self.font_size.borrow(),
+      layer: absolute_params.layer,
       depth: absolute_params.depth,
       optional: DrawStringOptionalArgs {
         alignment: *self.alignment.borrow(),
@@ -82,6 +120,10 @@ impl<C: ContextTrait> UiElementTrait<C> for UiText<C> {
         border: self.border.get(),
         opacity: absolute_params.opacity,
         text_cache: self.text_cache.borrow().clone(),
+        max_width: self.max_width.get(),
+        line_spacing: self.line_spacing.get(),
+        spans: self.spans.borrow().clone(),
+        font_atlas: self.font_atlas.borrow().clone(),
         ..Default::default()
       },
     });
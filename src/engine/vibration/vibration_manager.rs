@@ -1,10 +1,11 @@
 use crate::engine::*;
 
 pub struct VibrationManager {
-  local_storage: Rc<web_sys::Storage>,
+  local_storage: Rc<dyn KeyValueStorage>,
   local_storage_key: String,
   ios: bool,
-  navigator: web_sys::Navigator,
+  // None without a browser.
+  navigator: Option<web_sys::Navigator>,
   vibration_settings: VibrationManagerSettings,
 }
 
@@ -21,6 +22,10 @@ pub struct VibrationManagerSettings {
   vibration_level: Cell<VibrationLevel>,
 }
 
+impl VersionedData for VibrationManagerSettings {
+  const VERSION: u32 = 1;
+}
+
 impl Default for VibrationManagerSettings {
   fn default() -> VibrationManagerSettings {
     return VibrationManagerSettings {
@@ -29,38 +34,56 @@ impl Default for VibrationManagerSettings {
   }
 }
 
+fn load_vibration_settings(
+  local_storage: &dyn KeyValueStorage,
+  local_storage_key: &str,
+  ios: bool,
+) -> VibrationManagerSettings {
+  let vibration_settings: VibrationManagerSettings =
+    LocalStorageUtil::read(local_storage, local_storage_key).unwrap_or_default();
+  if ios {
+    vibration_settings.vibration_level.set(VibrationLevel::None);
+  }
+  return vibration_settings;
+}
+
 impl VibrationManager {
   pub fn new(
-    window: &web_sys::Window,
+    window: Option<&web_sys::Window>,
     ios: bool,
-    local_storage: Rc<web_sys::Storage>,
+    local_storage: Rc<dyn KeyValueStorage>,
     local_storage_key: String,
   ) -> VibrationManager {
-    let vibration_settings: VibrationManagerSettings =
-      LocalStorageUtil::read(&local_storage, &local_storage_key).unwrap_or_default();
-    if ios {
-      vibration_settings.vibration_level.set(VibrationLevel::None);
-    }
     return VibrationManager {
       local_storage: local_storage.clone(),
       local_storage_key: local_storage_key.clone(),
       ios: ios,
-      navigator: window.navigator(),
-      vibration_settings: vibration_settings,
+      navigator: window.map(|window| window.navigator()),
+      vibration_settings: load_vibration_settings(local_storage.as_ref(), &local_storage_key, ios),
     };
   }
 
+  pub fn set_local_storage_key(&mut self, local_storage_key: String) {
+    self.vibration_settings =
+      load_vibration_settings(self.local_storage.as_ref(), &local_storage_key, self.ios);
+    self.local_storage_key = local_storage_key;
+  }
+
   pub fn vibrate(&self) {
+    let navigator = match &self.navigator {
+      Some(navigator) => navigator,
+      None => return,
+    };
     match self.vibration_settings.vibration_level.get() {
       VibrationLevel::None => {}
       VibrationLevel::Low => {
-        self.navigator.vibrate_with_duration(10);
+        navigator.vibrate_with_duration(10);
       }
       VibrationLevel::Medium => {
-        self.navigator.vibrate_with_duration(20);
+        navigator.vibrate_with_duration(20);
       }
       VibrationLevel::High => {
-        self.navigator.vibrate_with_duration(30);
+        navigator.vibrate_with_duration(30);
       }
     }
   }
@@ -79,7 +102,7 @@ impl VibrationManager {
       },
     );
     LocalStorageUtil::write(
-      &self.local_storage,
+      self.local_storage.as_ref(),
       &self.local_storage_key,
       &self.vibration_settings,
     );
@@ -0,0 +1,166 @@
+use crate::engine::*;
+use crate::game::achievments_manager::AchievmentsManager;
+use crate::game::gameplay_event::*;
+use serde::Deserialize;
+
+#[derive(Deserialize, Clone)]
+pub struct AchievementData {
+  pub id: String,
+  pub title: String,
+  pub description: String,
+  pub condition: AchievementCondition,
+}
+
+#[derive(Deserialize, Clone)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AchievementCondition {
+  StageClearedWithPaintLeft { paint_left: F1 },
+  StageClearedWithoutUndo { stars: usize },
+  BookThreeStarsWithoutUndo,
+  Counter { counter: String, count: u32 },
+  TotalStars { stars: usize },
+}
+
+pub struct AchievementTracker {
+  catalogue: Vec<AchievementData>,
+  pub unlocked_events: Rc<EventManager<AchievementData>>,
+}
+
+impl AchievementTracker {
+  pub fn new() -> AchievementTracker {
+    let catalogue: Vec<AchievementData> =
+      serde_json::from_str(ACHIEVEMENTS_DATA).expect("failed to parse ACHIEVEMENTS_DATA as json");
+    return AchievementTracker {
+      catalogue: catalogue,
+      unlocked_events: EventManager::new(),
+    };
+  }
+
+  pub fn get_catalogue(&self) -> &Vec<AchievementData> {
+    return &self.catalogue;
+  }
+
+  pub fn process_gameplay_events(
+    &self,
+    gameplay_events: &EventManager<GameplayEvent>,
+    achievments_manager: &AchievmentsManager,
+  ) {
+    achievments_manager.begin_batch();
+    while let Some(event) = gameplay_events.consume_event() {
+      if let Some(counter) = event.counter() {
+        achievments_manager.increment_gameplay_counter(counter);
+      }
+      if let GameplayEvent::StageCleared(params) = &event {
+        if params.stars == 3 && params.undos == 0 {
+          achievments_manager
+            .mark_stage_three_stars_without_undo(params.book_number, params.stage_number);
+        }
+      }
+
+      for achievement in self.catalogue.iter() {
+        if achievments_manager.is_achievement_unlocked(&achievement.id) {
+          continue;
+        }
+        if is_condition_met(&achievement.condition, &event, achievments_manager) {
+          achievments_manager.unlock_achievement(&achievement.id);
+          self.unlocked_events.add_event(achievement.clone());
+        }
+      }
+    }
+    achievments_manager.end_batch();
+  }
+
+  // Returns (current, target) for conditions that accumulate over time, used to
+  // show progress on locked achievements.
+  pub fn get_progress(
+    &self,
+    achievement: &AchievementData,
+    achievments_manager: &AchievmentsManager,
+  ) -> Option<(u32, u32)> {
+    return match &achievement.condition {
+      AchievementCondition::Counter { counter, count } => Some((
+        achievments_manager
+          .get_gameplay_counter(counter)
+          .min(*count),
+        *count,
+      )),
+      AchievementCondition::TotalStars { stars } => Some((
+        achievments_manager.get_total_stars().min(*stars) as u32,
+        *stars as u32,
+      )),
+      _ => None,
+    };
+  }
+}
+
+fn is_condition_met(
+  condition: &AchievementCondition,
+  event: &GameplayEvent,
+  achievments_manager: &AchievmentsManager,
+) -> bool {
+  return match condition {
+    AchievementCondition::StageClearedWithPaintLeft { paint_left } => match event {
+      GameplayEvent::StageCleared(params) => 1.0 - params.paint_used >= *paint_left,
+      _ => false,
+    },
+    AchievementCondition::StageClearedWithoutUndo { stars } => match event {
+      GameplayEvent::StageCleared(params) => params.stars >= *stars && params.undos == 0,
+      _ => false,
+    },
+    AchievementCondition::BookThreeStarsWithoutUndo => match event {
+      GameplayEvent::StageCleared(params) => {
+        achievments_manager.is_book_three_stars_without_undo(params.book_number)
+      }
+      _ => false,
+    },
+    AchievementCondition::Counter { counter, count } => {
+      achievments_manager.get_gameplay_counter(counter) >= *count
+    }
+    AchievementCondition::TotalStars { stars } => achievments_manager.get_total_stars() >= *stars,
+  };
+}
+
+static ACHIEVEMENTS_DATA: &str = r###"[
+  {
+    "id": "first_doodle",
+    "title": "First Doodle",
+    "description": "Finish your first stage",
+    "condition": {"type": "counter", "counter": "stages_cleared", "count": 1}
+  },
+  {
+    "id": "paint_saver",
+    "title": "Paint Saver",
+    "description": "Finish a stage with 50% paint left",
+    "condition": {"type": "stage_cleared_with_paint_left", "paint_left": 0.5}
+  },
+  {
+    "id": "no_regrets",
+    "title": "No Regrets",
+    "description": "3-star a stage without undo",
+    "condition": {"type": "stage_cleared_without_undo", "stars": 3}
+  },
+  {
+    "id": "portal_hopper",
+    "title": "Portal Hopper",
+    "description": "Use a portal 100 times",
+    "condition": {"type": "counter", "counter": "portals_used", "count": 100}
+  },
+  {
+    "id": "flawless_book",
+    "title": "Flawless Book",
+    "description": "3-star a book without undo",
+    "condition": {"type": "book_three_stars_without_undo"}
+  },
+  {
+    "id": "star_collector",
+    "title": "Star Collector",
+    "description": "Collect 100 stars",
+    "condition": {"type": "total_stars", "stars": 100}
+  },
+  {
+    "id": "doodle_veteran",
+    "title": "Doodle Veteran",
+    "description": "Finish 100 stages",
+    "condition": {"type": "counter", "counter": "stages_cleared", "count": 100}
+  }
+]"###;
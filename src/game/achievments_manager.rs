@@ -1,44 +1,244 @@
 use crate::engine::*;
+use crate::game::progress_storage::*;
 use crate::game::stages_data::*;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
 
-static PANDA_DOODLE_ACHIEVMENTS: &str = "PandaDoodleAchievments";
 const BOOK_COUNT: usize = 5;
 const STARS_REQUIRED_TO_UNLOCK_BOOK: usize = 50;
 const MAX_STARS_PER_STAGE: usize = 3;
+const MAX_PAINT_USED_HISTORY: usize = 10;
+// Device of the counts saved before they were kept per device.
+const LEGACY_DEVICE_ID: &str = "legacy";
 
 pub struct AchievmentsManager {
-  pub local_storage: Rc<web_sys::Storage>,
+  storage: Box<dyn ProgressStorage>,
+  // Device the counts are added to.
+  device_id: String,
   pub achievments_data: RefCell<AchievmentsData>,
+  // Between begin_batch and end_batch, updates aren't stored one by one.
+  batching: Cell<bool>,
+  // The data was loaded for the current batch, which has updates to store.
+  batch_loaded: Cell<bool>,
 }
 
-#[derive(Serialize, Deserialize, Default)]
+#[derive(Serialize, Deserialize, Default, Clone, PartialEq, Debug)]
 pub struct AchievmentsData {
   #[serde(default)]
   stars_collected: HashMap<usize, usize>,
   #[serde(default)]
   scores: HashMap<usize, i32>,
+  #[serde(default)]
+  stage_stats: HashMap<usize, StageStats>,
+  // Achievement id -> milliseconds since the unix epoch of the unlock.
+  #[serde(default)]
+  achievement_unlocks: HashMap<String, f64>,
+  #[serde(default)]
+  gameplay_counters: HashMap<String, DeviceCount<u32>>,
+  // Indices of stages cleared with 3 stars and no undo in a single attempt.
+  #[serde(default)]
+  three_stars_without_undo: HashSet<usize>,
+}
+
+// Version 1 kept the counters as plain totals.
+impl VersionedData for AchievmentsData {
+  const VERSION: u32 = 2;
+
+  fn migrate(from_version: u32, mut data: serde_json::Value) -> Result<serde_json::Value, String> {
+    return match from_version {
+      0 => Ok(data),
+      1 => {
+        if let Some(stage_stats) = data["stage_stats"].as_object_mut() {
+          for stage_stats in stage_stats.values_mut() {
+            for counter in [
+              "attempts",
+              "restarts",
+              "undos",
+              "play_time_ms",
+              "hints_used",
+            ] {
+              if let Some(count) = stage_stats.get_mut(counter) {
+                *count = get_legacy_device_count(count.take());
+              }
+            }
+          }
+        }
+        if let Some(gameplay_counters) = data["gameplay_counters"].as_object_mut() {
+          for count in gameplay_counters.values_mut() {
+            *count = get_legacy_device_count(count.take());
+          }
+        }
+        Ok(data)
+      }
+      _ => Err(format!("no migration from version {}", from_version)),
+    };
+  }
 }
 
-fn load_achievments_data(local_storage: &web_sys::Storage) -> AchievmentsData {
-  return LocalStorageUtil::read(local_storage, PANDA_DOODLE_ACHIEVMENTS).unwrap_or_default();
+fn get_legacy_device_count(count: serde_json::Value) -> serde_json::Value {
+  let mut counts = serde_json::Map::new();
+  counts.insert(String::from(LEGACY_DEVICE_ID), count);
+  return serde_json::Value::Object(counts);
+}
+
+// Count added to by several devices. Each device keeps its own count, so the
+// counts of two devices add up when their progress is merged.
+#[derive(Serialize, Deserialize, Default, Clone, PartialEq, Debug)]
+#[serde(transparent)]
+pub struct DeviceCount<T> {
+  counts: BTreeMap<String, T>,
+}
+
+impl<T: Copy + Default + PartialOrd + std::ops::Add<Output = T>> DeviceCount<T> {
+  pub fn get(&self) -> T {
+    return self
+      .counts
+      .values()
+      .fold(T::default(), |total, count| total + *count);
+  }
+
+  pub fn add(&mut self, device_id: &str, amount: T) {
+    let count = self.counts.entry(device_id.to_string()).or_default();
+    *count = *count + amount;
+  }
+
+  // A device only ever increases its own count, so the higher one is newer.
+  fn merge(&mut self, other: &DeviceCount<T>) {
+    for (device_id, other_count) in other.counts.iter() {
+      let count = self.counts.entry(device_id.clone()).or_insert(*other_count);
+      if *other_count > *count {
+        *count = *other_count;
+      }
+    }
+  }
+}
+
+#[derive(Serialize, Deserialize, Default, Clone, PartialEq, Debug)]
+pub struct StageStats {
+  #[serde(default)]
+  pub attempts: DeviceCount<u32>,
+  #[serde(default)]
+  pub restarts: DeviceCount<u32>,
+  #[serde(default)]
+  pub undos: DeviceCount<u32>,
+  #[serde(default)]
+  pub play_time_ms: DeviceCount<f64>,
+  // The game doesn't show hints yet, the field keeps counts saved with it.
+  #[serde(default)]
+  pub hints_used: DeviceCount<u32>,
+  // Milliseconds since the unix epoch (Date.now()) of the first victory.
+  #[serde(default)]
+  pub first_clear_timestamp_ms: Option<f64>,
+  // Ratio of the stage paint used on every clear that beat the previous best,
+  // oldest first.
+  #[serde(default)]
+  pub best_paint_used_history: Vec<F1>,
+}
+
+impl StageStats {
+  pub fn best_paint_used(&self) -> Option<F1> {
+    return self.best_paint_used_history.last().cloned();
+  }
+
+  fn merge(&mut self, other: &StageStats) {
+    self.attempts.merge(&other.attempts);
+    self.restarts.merge(&other.restarts);
+    self.undos.merge(&other.undos);
+    self.play_time_ms.merge(&other.play_time_ms);
+    self.hints_used.merge(&other.hints_used);
+    self.first_clear_timestamp_ms = match (
+      self.first_clear_timestamp_ms,
+      other.first_clear_timestamp_ms,
+    ) {
+      (Some(a), Some(b)) => Some(a.min(b)),
+      (a, b) => a.or(b),
+    };
+    let other_is_better = match (self.best_paint_used(), other.best_paint_used()) {
+      (Some(best), Some(other_best)) => other_best < best,
+      (None, Some(_)) => true,
+      _ => false,
+    };
+    if other_is_better {
+      self.best_paint_used_history = other.best_paint_used_history.clone();
+    }
+  }
+}
+
+impl AchievmentsData {
+  // Best score and the stars collected with it, if the stage was ever cleared.
+  pub fn get_stage_result(&self, stage_index: usize) -> Option<(i32, usize)> {
+    let score = self.scores.get(&stage_index)?;
+    let stars = self.stars_collected.get(&stage_index).cloned().unwrap_or(0);
+    return Some((*score, stars));
+  }
+
+  // Combines progress made on two devices. Nothing achieved on either side is
+  // lost: stars and scores keep the best value per stage, counters add up the
+  // counts of every device and unlocks keep the earliest timestamp.
+  pub fn merge(&mut self, other: &AchievmentsData) {
+    for (stage_index, stars) in other.stars_collected.iter() {
+      let entry = self.stars_collected.entry(*stage_index).or_insert(0);
+      *entry = (*entry).max(*stars);
+    }
+    for (stage_index, score) in other.scores.iter() {
+      let entry = self.scores.entry(*stage_index).or_insert(*score);
+      *entry = (*entry).max(*score);
+    }
+    for (stage_index, stage_stats) in other.stage_stats.iter() {
+      match self.stage_stats.get_mut(stage_index) {
+        Some(entry) => entry.merge(stage_stats),
+        None => {
+          self.stage_stats.insert(*stage_index, stage_stats.clone());
+        }
+      }
+    }
+    for (id, timestamp_ms) in other.achievement_unlocks.iter() {
+      let entry = self
+        .achievement_unlocks
+        .entry(id.clone())
+        .or_insert(*timestamp_ms);
+      *entry = entry.min(*timestamp_ms);
+    }
+    for (counter, count) in other.gameplay_counters.iter() {
+      self
+        .gameplay_counters
+        .entry(counter.clone())
+        .or_default()
+        .merge(count);
+    }
+    self
+      .three_stars_without_undo
+      .extend(other.three_stars_without_undo.iter());
+  }
 }
 
 impl AchievmentsManager {
-  pub fn new(local_storage: Rc<web_sys::Storage>) -> AchievmentsManager {
+  pub fn new(storage: Box<dyn ProgressStorage>, device_id: String) -> AchievmentsManager {
     return AchievmentsManager {
-      local_storage: local_storage.clone(),
-      achievments_data: RefCell::new(load_achievments_data(local_storage.as_ref())),
+      achievments_data: RefCell::new(storage.load()),
+      storage: storage,
+      device_id: device_id,
+      batching: Cell::new(false),
+      batch_loaded: Cell::new(false),
     };
   }
 
+  // Picks up progress merged in by the storage backend.
+  pub fn update(&self) {
+    if let Some(achievments_data) = self.storage.update() {
+      self.achievments_data.replace(achievments_data);
+    }
+  }
+
+  // Clears stars, scores, stage stats and achievements. Settings are kept. A
+  // synced server copy is cleared too.
+  pub fn reset(&self) {
+    self.achievments_data.replace(AchievmentsData::default());
+    self.storage.reset(self.achievments_data.borrow().deref());
+  }
+
   fn store(&self) {
-    LocalStorageUtil::write(
-      self.local_storage.as_ref(),
-      PANDA_DOODLE_ACHIEVMENTS,
-      self.achievments_data.borrow().deref(),
-    );
+    self.storage.store(self.achievments_data.borrow().deref());
   }
 
   pub fn is_stage_available(&self, book_number: usize, stage_number: usize) -> bool {
@@ -145,26 +345,306 @@ impl AchievmentsManager {
     stars: usize,
   ) -> bool {
     let stage_index = self.get_stage_index(book_number, stage_number);
-    self
+    let mut best_score = false;
+    self.update_achievments_data(|achievments_data| {
+      best_score = match achievments_data.scores.get(&stage_index) {
+        Some(current_score) => score > *current_score,
+        None => true,
+      };
+      if best_score {
+        achievments_data.scores.insert(stage_index, score);
+        achievments_data.stars_collected.insert(stage_index, stars);
+      }
+    });
+    return best_score;
+  }
+
+  pub fn get_stage_stats(&self, book_number: usize, stage_number: usize) -> StageStats {
+    let stage_index = self.get_stage_index(book_number, stage_number);
+    return match self.achievments_data.borrow().stage_stats.get(&stage_index) {
+      Some(stage_stats) => stage_stats.clone(),
+      None => StageStats::default(),
+    };
+  }
+
+  pub fn record_stage_attempt(&self, book_number: usize, stage_number: usize) {
+    self.update_stage_stats(book_number, stage_number, |stage_stats| {
+      stage_stats.attempts.add(&self.device_id, 1);
+    });
+  }
+
+  pub fn record_stage_restart(&self, book_number: usize, stage_number: usize) {
+    self.update_stage_stats(book_number, stage_number, |stage_stats| {
+      stage_stats.restarts.add(&self.device_id, 1);
+    });
+  }
+
+  // Adds the play time and undos accumulated since the last call, in a single
+  // store.
+  pub fn record_stage_session(
+    &self,
+    book_number: usize,
+    stage_number: usize,
+    play_time_ms: f64,
+    undos: u32,
+  ) {
+    if play_time_ms <= 0.0 && undos == 0 {
+      return;
+    }
+    self.update_stage_stats(book_number, stage_number, |stage_stats| {
+      stage_stats
+        .play_time_ms
+        .add(&self.device_id, play_time_ms.max(0.0));
+      stage_stats.undos.add(&self.device_id, undos);
+    });
+  }
+
+  pub fn record_stage_clear(&self, book_number: usize, stage_number: usize, paint_used: F1) {
+    self.update_stage_stats(book_number, stage_number, |stage_stats| {
+      if stage_stats.first_clear_timestamp_ms.is_none() {
+        stage_stats.first_clear_timestamp_ms = Some(js_sys::Date::now());
+      }
+      let new_best = match stage_stats.best_paint_used() {
+        Some(best_paint_used) => paint_used < best_paint_used,
+        None => true,
+      };
+      if new_best {
+        stage_stats.best_paint_used_history.push(paint_used);
+        if stage_stats.best_paint_used_history.len() > MAX_PAINT_USED_HISTORY {
+          stage_stats.best_paint_used_history.remove(0);
+        }
+      }
+    });
+  }
+
+  fn update_stage_stats<F: FnOnce(&mut StageStats)>(
+    &self,
+    book_number: usize,
+    stage_number: usize,
+    update: F,
+  ) {
+    let stage_index = self.get_stage_index(book_number, stage_number);
+    self.update_achievments_data(|achievments_data| {
+      update(achievments_data.stage_stats.entry(stage_index).or_default());
+    });
+  }
+
+  pub fn is_achievement_unlocked(&self, id: &str) -> bool {
+    return self
+      .achievments_data
+      .borrow()
+      .achievement_unlocks
+      .contains_key(id);
+  }
+
+  pub fn get_achievement_unlock_timestamp(&self, id: &str) -> Option<f64> {
+    return self
+      .achievments_data
+      .borrow()
+      .achievement_unlocks
+      .get(id)
+      .cloned();
+  }
+
+  pub fn unlock_achievement(&self, id: &str) {
+    self.update_achievments_data(|achievments_data| {
+      achievments_data
+        .achievement_unlocks
+        .entry(id.to_string())
+        .or_insert_with(js_sys::Date::now);
+    });
+  }
+
+  pub fn get_gameplay_counter(&self, counter: &str) -> u32 {
+    return match self
       .achievments_data
-      .replace(load_achievments_data(&self.local_storage));
-    let best_score = match self.achievments_data.borrow().scores.get(&stage_index) {
-      Some(current_score) => score > *current_score,
-      None => true,
+      .borrow()
+      .gameplay_counters
+      .get(counter)
+    {
+      Some(count) => count.get(),
+      None => 0,
     };
-    if best_score {
-      self
-        .achievments_data
-        .borrow_mut()
-        .scores
-        .insert(stage_index, score);
-      self
-        .achievments_data
-        .borrow_mut()
-        .stars_collected
-        .insert(stage_index, stars);
+  }
+
+  pub fn increment_gameplay_counter(&self, counter: &str) {
+    self.update_achievments_data(|achievments_data| {
+      achievments_data
+        .gameplay_counters
+        .entry(counter.to_string())
+        .or_default()
+        .add(&self.device_id, 1);
+    });
+  }
+
+  pub fn mark_stage_three_stars_without_undo(&self, book_number: usize, stage_number: usize) {
+    let stage_index = self.get_stage_index(book_number, stage_number);
+    self.update_achievments_data(|achievments_data| {
+      achievments_data
+        .three_stars_without_undo
+        .insert(stage_index);
+    });
+  }
+
+  pub fn is_book_three_stars_without_undo(&self, book_number: usize) -> bool {
+    let achievments_data = self.achievments_data.borrow();
+    return (0..STAGES_PER_BOOK).all(|stage_number| {
+      achievments_data
+        .three_stars_without_undo
+        .contains(&self.get_stage_index(book_number, stage_number))
+    });
+  }
+
+  // Updates until end_batch are applied to the data loaded by the first one,
+  // and stored once by end_batch.
+  pub fn begin_batch(&self) {
+    self.batching.set(true);
+  }
+
+  pub fn end_batch(&self) {
+    self.batching.set(false);
+    if self.batch_loaded.replace(false) {
       self.store();
     }
-    return best_score;
+  }
+
+  fn update_achievments_data<F: FnOnce(&mut AchievmentsData)>(&self, update: F) {
+    if !self.batch_loaded.get() {
+      self.achievments_data.replace(self.storage.load());
+      self.batch_loaded.set(self.batching.get());
+    }
+    update(&mut self.achievments_data.borrow_mut());
+    if !self.batching.get() {
+      self.store();
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_merge_keeps_best_progress() {
+    let mut local = AchievmentsData::default();
+    local.stars_collected.insert(0, 3);
+    local.scores.insert(0, 100);
+    local.achievement_unlocks.insert(String::from("a"), 20.0);
+    local
+      .gameplay_counters
+      .entry(String::from("c"))
+      .or_default()
+      .add("a", 5);
+
+    let mut remote = AchievmentsData::default();
+    remote.stars_collected.insert(0, 2);
+    remote.stars_collected.insert(1, 1);
+    remote.scores.insert(0, 150);
+    remote.achievement_unlocks.insert(String::from("a"), 10.0);
+    remote
+      .gameplay_counters
+      .entry(String::from("c"))
+      .or_default()
+      .add("b", 3);
+    remote.three_stars_without_undo.insert(4);
+
+    local.merge(&remote);
+    assert_eq!(local.stars_collected[&0], 3);
+    assert_eq!(local.stars_collected[&1], 1);
+    assert_eq!(local.scores[&0], 150);
+    assert_eq!(local.achievement_unlocks["a"], 10.0);
+    assert_eq!(local.gameplay_counters["c"].get(), 8);
+    assert!(local.three_stars_without_undo.contains(&4));
+  }
+
+  #[test]
+  fn test_merge_stage_stats() {
+    let mut local = StageStats {
+      first_clear_timestamp_ms: Some(30.0),
+      best_paint_used_history: vec![0.9, 0.8],
+      ..Default::default()
+    };
+    local.attempts.add("a", 4);
+    local.attempts.add("b", 1);
+    let mut remote = StageStats {
+      first_clear_timestamp_ms: Some(10.0),
+      best_paint_used_history: vec![0.7],
+      ..Default::default()
+    };
+    // Device "b" played once more since the last sync.
+    remote.attempts.add("b", 2);
+    remote.attempts.add("c", 3);
+    remote.undos.add("c", 7);
+    local.merge(&remote);
+    assert_eq!(local.attempts.get(), 9);
+    assert_eq!(local.undos.get(), 7);
+    assert_eq!(local.first_clear_timestamp_ms, Some(10.0));
+    assert_eq!(local.best_paint_used(), Some(0.7));
+  }
+
+  // Keeps the data in memory and counts the loads and stores.
+  #[derive(Default)]
+  struct CountingStorage {
+    data: RefCell<AchievmentsData>,
+    loads: Cell<u32>,
+    stores: Cell<u32>,
+  }
+
+  impl ProgressStorage for Rc<CountingStorage> {
+    fn load(&self) -> AchievmentsData {
+      self.loads.set(self.loads.get() + 1);
+      return self.data.borrow().clone();
+    }
+
+    fn store(&self, achievments_data: &AchievmentsData) {
+      self.stores.set(self.stores.get() + 1);
+      self.data.replace(achievments_data.clone());
+    }
+
+    fn update(&self) -> Option<AchievmentsData> {
+      return None;
+    }
+  }
+
+  #[test]
+  fn test_batch_stores_once() {
+    let storage = Rc::new(CountingStorage::default());
+    let achievments_manager = AchievmentsManager::new(Box::new(storage.clone()), String::from("a"));
+
+    // An empty batch doesn't touch the storage.
+    achievments_manager.begin_batch();
+    achievments_manager.end_batch();
+    assert_eq!((storage.loads.get(), storage.stores.get()), (1, 0));
+
+    achievments_manager.begin_batch();
+    achievments_manager.increment_gameplay_counter("c");
+    achievments_manager.increment_gameplay_counter("c");
+    achievments_manager.mark_stage_three_stars_without_undo(0, 2);
+    achievments_manager.end_batch();
+    assert_eq!((storage.loads.get(), storage.stores.get()), (2, 1));
+    assert_eq!(storage.data.borrow().gameplay_counters["c"].get(), 2);
+    assert!(storage.data.borrow().three_stars_without_undo.contains(&2));
+  }
+
+  #[test]
+  fn test_decode_version_1_stage_stats() {
+    let stored = serde_json::json!({
+      "version": 1,
+      "data": {
+        "stage_stats": {
+          "3": { "attempts": 5, "undos": 2, "play_time_ms": 1500.0, "hints_used": 4 }
+        },
+        "gameplay_counters": { "c": 6 }
+      }
+    });
+    let mut data: AchievmentsData = LocalStorageUtil::decode(stored).unwrap();
+    assert_eq!(data.stage_stats[&3].attempts.get(), 5);
+    assert_eq!(data.stage_stats[&3].play_time_ms.get(), 1500.0);
+    assert_eq!(data.stage_stats[&3].hints_used.get(), 4);
+    assert_eq!(data.gameplay_counters["c"].get(), 6);
+
+    // Counts made after the migration add to the saved ones.
+    data.stage_stats.get_mut(&3).unwrap().attempts.add("a", 1);
+    assert_eq!(data.stage_stats[&3].attempts.get(), 6);
   }
 }
@@ -1,23 +1,25 @@
 use crate::context::Context;
+use crate::context::GAME_VIEWPORT_YX_RATIO;
 use crate::*;
 
 pub struct BackgroundCanvas {}
 
 impl BackgroundCanvas {
   pub fn draw(context: &mut Context, book: Book) {
+    // The canvas fits the game viewport frame, and grows to cover bigger stages.
+    let world_size = context.game_viewport.world_size;
+    let scale = world_size.x.max(world_size.y / GAME_VIEWPORT_YX_RATIO);
     context.draw_manager.draw_viewport(
       &context.game_viewport,
       DrawImageArgs {
         source: DrawSource::Texture(context.texture_manager.background_canvas.clone()),
-        position: F2 {
-          x: 0.5,
-          y: context.game_viewport.viewport_yx_ratio / 2.0,
-        },
+        position: world_size * 0.5,
         size: context
           .texture_manager
           .background_canvas
-          .get_size_from_width(1.0),
-        depth: context.draw_depths.background,
+          .get_size_from_width(scale),
+        layer: context.draw_layers.background,
+        depth: 0.0,
         optional: DrawImageOptionalArgs::default(),
       },
     );
@@ -42,20 +44,21 @@ impl BackgroundCanvas {
     let canvas_size = context
       .texture_manager
       .background_canvas
-      .get_size_from_width(1.0);
+      .get_size_from_width(scale);
 
     if let Some(texture_bot) = texture_bot {
-      let size = texture_bot.get_size_from_width(1.0);
+      let size = texture_bot.get_size_from_width(scale);
       context.draw_manager.draw_viewport(
         &context.game_viewport,
         DrawImageArgs {
           source: DrawSource::Texture(texture_bot),
           position: F2 {
-            x: 0.5,
-            y: (context.game_viewport.viewport_yx_ratio + canvas_size.y) * 0.5 + 1.0 / 320.0,
+            x: world_size.x * 0.5,
+            y: (world_size.y + canvas_size.y) * 0.5 + scale / 320.0,
           },
           size: size,
-          depth: context.draw_depths.background - 0.1,
+          layer: context.draw_layers.background_decoration,
+          depth: 0.0,
           optional: DrawImageOptionalArgs {
             anchor_point: F2 { x: 0.5, y: 1.0 },
             ..Default::default()
@@ -65,17 +68,18 @@ impl BackgroundCanvas {
     }
 
     if let Some(texture_top) = texture_top {
-      let size = texture_top.get_size_from_width(1.0);
+      let size = texture_top.get_size_from_width(scale);
       context.draw_manager.draw_viewport(
         &context.game_viewport,
         DrawImageArgs {
           source: DrawSource::Texture(texture_top),
           position: F2 {
-            x: 0.5,
-            y: (context.game_viewport.viewport_yx_ratio - canvas_size.y) * 0.5 - 1.0 / 320.0,
+            x: world_size.x * 0.5,
+            y: (world_size.y - canvas_size.y) * 0.5 - scale / 320.0,
           },
           size: size,
-          depth: context.draw_depths.background - 0.1,
+          layer: context.draw_layers.background_decoration,
+          depth: 0.0,
           optional: DrawImageOptionalArgs {
             anchor_point: F2 { x: 0.5, y: 0.0 },
             ..Default::default()
@@ -75,7 +75,8 @@ impl EntityTrait<Context> for Barrier {
         source: DrawSource::Texture(context.texture_manager.circle40.clone()),
         position: self.position,
         size: self.size,
-        depth: context.draw_depths.barrier,
+        layer: context.draw_layers.barrier,
+        depth: 0.0,
         optional: DrawImageOptionalArgs {
           opacity: context.stage_opacity.get(),
           color: DrawColor { r: 0, g: 0, b: 0 },
@@ -49,7 +49,7 @@ impl Brush {
 
         color: Cell::new(PaintColor::NoColor.get_draw_color()),
         texture: context.texture_manager.flare.clone(),
-        depth: context.draw_depths.source - 0.01,
+        layer: context.draw_layers.smoke,
 
         start_position_delta: F2 { x: 0.0, y: 0.0 },
         rand_region_size: F2 { x: 0.06, y: 0.06 },
@@ -91,7 +91,7 @@ impl Brush {
 
         color: Cell::new(PaintColor::NoColor.get_draw_color()),
         texture: context.texture_manager.flare.clone(),
-        depth: context.draw_depths.source - 0.01,
+        layer: context.draw_layers.smoke,
 
         start_position_delta: F2 { x: 0.0, y: 0.0 },
         rand_region_size: F2 { x: 0.1, y: 0.1 },
@@ -2,6 +2,7 @@ use crate::context::*;
 use crate::engine::*;
 use crate::game::background_canvas::BackgroundCanvas;
 use crate::game::brush::Brush;
+use crate::game::gameplay_event::GameplayEvent;
 use crate::game::paint_color;
 use crate::game::paint_path::*;
 use crate::game::paint_point;
@@ -10,12 +11,17 @@ use crate::game::paint_source::*;
 use crate::game::point_grid::PointGrid;
 use crate::game::source::Source;
 use crate::game::stage::Stage;
+use crate::game::stage_replay::*;
 use crate::game::stages_data::StageData;
 use crate::game::star_bar::*;
 use crate::game_ui::Book;
 use crate::game_ui::IngameUiEvent;
 use crate::*;
 
+// Moving sources advance in steps of this length, so a replay only needs the
+// number of steps between inputs to repeat them.
+pub const SIMULATION_STEP_MS: F1 = 1000.0 / 60.0;
+
 pub struct GameMode {
   events: Rc<EventManager<IngameUiEvent>>,
   effect_manager: EffectManager,
@@ -42,6 +48,11 @@ pub struct GameMode {
   pub paused: Cell<bool>,
   pub finished: Cell<bool>,
   pub state_history: StateHistory<GameModeState>,
+
+  // Frame time not yet turned into simulation steps.
+  unsimulated_ms: Cell<F1>,
+  frame_steps: Cell<u32>,
+  pub replay: RefCell<StageReplay>,
 }
 
 pub struct GameModeState {
@@ -101,6 +112,9 @@ impl GameMode {
       paused: Cell::new(false),
       finished: Cell::new(false),
       state_history: StateHistory::new(0),
+      unsimulated_ms: Cell::new(0.0),
+      frame_steps: Cell::new(0),
+      replay: RefCell::new(StageReplay::default()),
     });
 
     {
@@ -118,22 +132,37 @@ impl GameMode {
     return !self.paused.get() && !self.finished.get();
   }
 
-  pub fn start_puzzle(&self, context: &mut Context, book: Book, stage_number: usize) {
+  // `seed` drives the random values of the attempt, see StageReplay::seed.
+  pub fn start_puzzle(&self, context: &mut Context, book: Book, stage_number: usize, seed: u64) {
     self.paint_paths.clear();
     self.active_path.replace(Weak::new());
     self
       .brush
       .replace(Brush::new(context, self.particles.clone()));
+    // The point grid covers the whole stage, so the camera bounds come first.
+    let stage_size = context
+      .stages_data
+      .get_stage(book.number(), stage_number)
+      .size;
+    context.update_game_camera(|camera| {
+      camera.set_bounds_size(&stage_size);
+      camera.reset();
+    });
     self.point_grid_manager.clear();
     let new_point_grid = PointGrid::new(context);
     self.point_grid_manager.add(new_point_grid.clone());
     self.point_grid.replace(new_point_grid);
     self.paused.set(false);
     self.finished.set(false);
+    self.unsimulated_ms.set(0.0);
     context.stage_opacity.replace(1.0);
 
     self.book.set(book);
     self.stage_number.set(stage_number);
+    seed_random(seed);
+    self
+      .replay
+      .replace(StageReplay::new(book.number(), stage_number, seed));
 
     let stage_data = context.stages_data.get_stage(book.number(), stage_number);
 
@@ -155,6 +184,7 @@ impl GameMode {
     self.events.add_event(IngameUiEvent::Victory(VictoryParams {
       score: self.stage.borrow().get_current_score(),
       stars: self.stage.borrow().get_stars(),
+      paint_used: self.stage.borrow().get_paint_used(),
     }));
   }
 
@@ -306,6 +336,7 @@ impl GameMode {
       &self.checkpoint.get(),
       &paint_path.paint_color.get(),
       &paint_path.point_count.get(),
+      &paint_path.last_point.borrow(),
       // self.active_path.borrow().clone(),
     );
 
@@ -407,11 +438,13 @@ impl GameMode {
         if new_point.collide_with_circle(&portal.endpoint1) {
           paint_path.activated_portal.replace(Some(portal.clone()));
           paint_path.put_on_hold(&self.checkpoint.get());
+          context.gameplay_events.add_event(GameplayEvent::PortalUsed);
           self.create_paint_point(&portal.endpoint2.position, paint_path, context);
         }
         if new_point.collide_with_circle(&portal.endpoint2) {
           paint_path.activated_portal.replace(Some(portal.clone()));
           paint_path.put_on_hold(&self.checkpoint.get());
+          context.gameplay_events.add_event(GameplayEvent::PortalUsed);
           self.create_paint_point(&portal.endpoint1.position, paint_path, context);
         }
       }
@@ -485,10 +518,54 @@ impl GameMode {
     return None;
   }
 
+  // Called before each update of `game_root_entity_manager` with the time since
+  // the previous one. The time left over after the last whole step carries to
+  // the next frame.
+  pub fn start_frame(&self, dt_ms: F1) {
+    let unsimulated_ms = self.unsimulated_ms.get() + dt_ms;
+    let steps = (unsimulated_ms / SIMULATION_STEP_MS).floor();
+    self
+      .unsimulated_ms
+      .set(unsimulated_ms - steps * SIMULATION_STEP_MS);
+    self.start_frame_steps(steps as u32);
+  }
+
+  // Moving sources keep moving while paused, so paused steps are recorded too.
+  pub fn start_frame_steps(&self, steps: u32) {
+    self.frame_steps.set(steps);
+    if !self.finished.get() {
+      self
+        .replay
+        .borrow_mut()
+        .add_step(ReplayInput::Steps { count: steps });
+    }
+  }
+
+  // Simulation steps of the current frame.
+  pub fn get_frame_steps(&self) -> u32 {
+    return self.frame_steps.get();
+  }
+
+  // Goes back to the previous checkpoint. `game_root_entity_manager` is the
+  // manager holding this GameMode.
+  pub fn undo(&self, game_root_entity_manager: &EntityManager<GameMode>) -> bool {
+    if self.checkpoint.get() == 0 || !self.is_game_running() {
+      return false;
+    }
+    game_root_entity_manager.undo(&self.checkpoint.get());
+    self.checkpoint.set(self.checkpoint.get() - 1);
+    self.replay.borrow_mut().add_step(ReplayInput::Undo);
+    return true;
+  }
+
   pub fn process_touch(&self, context: &mut Context, game_touch: &mut GameTouch) {
     if !self.is_game_running() {
       return;
     }
+    self
+      .replay
+      .borrow_mut()
+      .add_step(ReplayInput::from_game_touch(game_touch));
     if let Some(active_path) = self.active_path.borrow().upgrade() {
       let active_path = active_path.as_ref();
       if active_path.path_on_hold.get() && game_touch.touch_type == TouchType::Pressed {
@@ -556,6 +633,15 @@ impl EntityTrait<Context> for GameMode {
 
   fn update(&self, context: &mut Context) {
     self.brush.borrow_mut().update(context);
+    let follow_target = match &*self.brush.borrow() {
+      brush if brush.active && self.is_game_running() => Some(brush.position),
+      _ => None,
+    };
+    let dt = *context.get_dt();
+    context.update_game_camera(|camera| {
+      camera.follow(follow_target);
+      camera.update(dt);
+    });
     self
       .star_bar
       .set_star_fills(self.stage.borrow().get_star_bar_fills());
@@ -0,0 +1,37 @@
+use crate::engine::*;
+
+#[derive(Clone, Copy, Debug)]
+pub enum GameplayEvent {
+  StageStarted(StageEventParams),
+  StageCleared(StageClearedParams),
+  PortalUsed,
+  UndoUsed,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct StageEventParams {
+  pub book_number: usize,
+  pub stage_number: usize,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct StageClearedParams {
+  pub book_number: usize,
+  pub stage_number: usize,
+  pub stars: usize,
+  pub paint_used: F1,
+  pub undos: u32,
+}
+
+impl GameplayEvent {
+  // Name of the persisted counter incremented by this event, as referenced by
+  // the "counter" conditions in the achievements catalogue.
+  pub fn counter(&self) -> Option<&'static str> {
+    return match self {
+      GameplayEvent::StageStarted(_) => Some("stages_started"),
+      GameplayEvent::StageCleared(_) => Some("stages_cleared"),
+      GameplayEvent::PortalUsed => Some("portals_used"),
+      GameplayEvent::UndoUsed => Some("undos_used"),
+    };
+  }
+}
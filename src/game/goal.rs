@@ -139,7 +139,8 @@ impl EntityTrait<Context> for Goal {
           source: DrawSource::Texture(self.texture_filled.clone()),
           position: self.position,
           size: size,
-          depth: context.draw_depths.goal,
+          layer: context.draw_layers.goal,
+          depth: 0.0,
           optional: DrawImageOptionalArgs {
             opacity: context.stage_opacity.get(),
             rotation: (time * 0.0015).sin() * 0.5,
@@ -155,7 +156,8 @@ impl EntityTrait<Context> for Goal {
           source: DrawSource::Texture(self.texture_empty.clone()),
           position: self.position,
           size: self.texture_empty.get_size_from_width(DRAW_WIDTH),
-          depth: context.draw_depths.goal,
+          layer: context.draw_layers.goal,
+          depth: 0.0,
           optional: DrawImageOptionalArgs {
             opacity: context.stage_opacity.get(),
             ..Default::default()
@@ -123,7 +123,8 @@ impl EntityTrait<Context> for GoalCollectEffect {
           source: DrawSource::Texture(self.texture_circle.clone()),
           position: position,
           size: size,
-          depth: context.draw_depths.goal - 0.01,
+          layer: context.draw_layers.goal_collect,
+          depth: 0.0,
           optional: DrawImageOptionalArgs {
             opacity: *self.opacity.borrow(),
             color: self.paint_color.get_draw_color(),
@@ -101,7 +101,8 @@ impl EntityTrait<Context> for GoalWrongReachAnimation {
         source: DrawSource::Texture(self.texture.clone()),
         position: self.position,
         size: *self.size.borrow(),
-        depth: context.draw_depths.goal - 0.02,
+        layer: context.draw_layers.goal_wrong_reach,
+        depth: 0.0,
         optional: DrawImageOptionalArgs {
           opacity: *self.opacity.borrow(),
           ..Default::default()
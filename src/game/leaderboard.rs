@@ -0,0 +1,292 @@
+use crate::context::*;
+use crate::engine::*;
+use crate::game::stage_replay::StageReplay;
+use serde::{Deserialize, Serialize};
+
+static PANDA_DOODLE_LEADERBOARD_CONFIG: &str = "PandaDoodleLeaderboardConfig";
+static PANDA_DOODLE_LOCAL_LEADERBOARDS: &str = "PandaDoodleLocalLeaderboards";
+pub const LEADERBOARD_SIZE: usize = 10;
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct LeaderboardEntry {
+  pub player_name: String,
+  pub score: i32,
+  pub stars: usize,
+}
+
+pub struct LeaderboardSubmission {
+  pub book_number: usize,
+  pub stage_number: usize,
+  pub player_name: String,
+  pub score: i32,
+  pub stars: usize,
+  pub replay: StageReplay,
+}
+
+// Entries sorted by descending score, or the reason they couldn't be loaded.
+pub type LeaderboardResult = Result<Vec<LeaderboardEntry>, String>;
+
+pub trait LeaderboardProvider {
+  // Shown on the leaderboard page, e.g. "Local".
+  fn get_name(&self) -> &str;
+  fn submit(&self, context: &Context, submission: LeaderboardSubmission);
+  // The result is added to `results` once available.
+  fn request_entries(
+    &self,
+    context: &Context,
+    book_number: usize,
+    stage_number: usize,
+    results: Rc<EventManager<LeaderboardResult>>,
+  );
+}
+
+fn sort_and_truncate(entries: &mut Vec<LeaderboardEntry>) {
+  entries.sort_by(|a, b| b.score.cmp(&a.score));
+  entries.truncate(LEADERBOARD_SIZE);
+}
+
+// Keeps the best score of each player name, so a profile submitting again only
+// replaces its own entry.
+fn add_entry(entries: &mut Vec<LeaderboardEntry>, entry: LeaderboardEntry) {
+  match entries
+    .iter_mut()
+    .find(|existing| existing.player_name == entry.player_name)
+  {
+    Some(existing) if existing.score >= entry.score => {}
+    Some(existing) => *existing = entry,
+    None => entries.push(entry),
+  }
+  sort_and_truncate(entries);
+}
+
+// Entries of every stage submitted on this device, by "{book}/{stage}". Shared
+// by all profiles.
+#[derive(Serialize, Deserialize, Default)]
+struct LocalLeaderboards {
+  #[serde(default)]
+  stages: HashMap<String, Vec<LeaderboardEntry>>,
+}
+
+impl VersionedData for LocalLeaderboards {
+  const VERSION: u32 = 1;
+}
+
+// Ranks the scores submitted by the profiles of this device, kept under a
+// storage key of its own.
+pub struct LocalLeaderboardProvider {
+  local_storage: Rc<dyn KeyValueStorage>,
+}
+
+impl LocalLeaderboardProvider {
+  pub fn new(local_storage: Rc<dyn KeyValueStorage>) -> LocalLeaderboardProvider {
+    return LocalLeaderboardProvider { local_storage };
+  }
+
+  fn load(&self) -> LocalLeaderboards {
+    return LocalStorageUtil::read(self.local_storage.as_ref(), PANDA_DOODLE_LOCAL_LEADERBOARDS)
+      .unwrap_or_default();
+  }
+}
+
+fn get_local_stage_key(book_number: usize, stage_number: usize) -> String {
+  return format!("{}/{}", book_number, stage_number);
+}
+
+impl LeaderboardProvider for LocalLeaderboardProvider {
+  fn get_name(&self) -> &str {
+    return "Local";
+  }
+
+  fn submit(&self, _context: &Context, submission: LeaderboardSubmission) {
+    let mut local_leaderboards = self.load();
+    add_entry(
+      local_leaderboards
+        .stages
+        .entry(get_local_stage_key(
+          submission.book_number,
+          submission.stage_number,
+        ))
+        .or_default(),
+      LeaderboardEntry {
+        player_name: submission.player_name,
+        score: submission.score,
+        stars: submission.stars,
+      },
+    );
+    LocalStorageUtil::write(
+      self.local_storage.as_ref(),
+      PANDA_DOODLE_LOCAL_LEADERBOARDS,
+      &local_leaderboards,
+    );
+  }
+
+  fn request_entries(
+    &self,
+    _context: &Context,
+    book_number: usize,
+    stage_number: usize,
+    results: Rc<EventManager<LeaderboardResult>>,
+  ) {
+    let entries = self
+      .load()
+      .stages
+      .remove(&get_local_stage_key(book_number, stage_number))
+      .unwrap_or_default();
+    results.add_event(Ok(entries));
+  }
+}
+
+// Leaderboard server read from the `leaderboard_url` query parameter and
+// remembered for later visits.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct LeaderboardConfig {
+  pub url: String,
+}
+
+impl VersionedData for LeaderboardConfig {
+  const VERSION: u32 = 1;
+}
+
+impl LeaderboardConfig {
+  pub fn load(
+    window: &web_sys::Window,
+    local_storage: &dyn KeyValueStorage,
+  ) -> Option<LeaderboardConfig> {
+    if let Some(url) = HttpUtil::get_query_param(window, "leaderboard_url") {
+      let leaderboard_config = LeaderboardConfig {
+        url: url.trim_end_matches('/').to_string(),
+      };
+      LocalStorageUtil::write(
+        local_storage,
+        PANDA_DOODLE_LEADERBOARD_CONFIG,
+        &leaderboard_config,
+      );
+      return Some(leaderboard_config);
+    }
+    return LocalStorageUtil::read(local_storage, PANDA_DOODLE_LEADERBOARD_CONFIG);
+  }
+}
+
+#[derive(Deserialize)]
+struct LeaderboardResponse {
+  entries: Vec<LeaderboardEntry>,
+}
+
+// Talks to a server speaking the protocol described in the README. Submissions
+// carry the replay of the attempt for servers able to play it back.
+pub struct HttpLeaderboardProvider {
+  window: Rc<web_sys::Window>,
+  url: String,
+  submit_results: Rc<EventManager<Result<HttpResponse, String>>>,
+}
+
+impl HttpLeaderboardProvider {
+  pub fn new(window: Rc<web_sys::Window>, config: &LeaderboardConfig) -> HttpLeaderboardProvider {
+    return HttpLeaderboardProvider {
+      window,
+      url: config.url.clone(),
+      submit_results: EventManager::new(),
+    };
+  }
+
+  fn get_stage_url(&self, book_number: usize, stage_number: usize) -> String {
+    return format!("{}/leaderboards/{}/{}", self.url, book_number, stage_number);
+  }
+
+  // Submissions are fire and forget; failures are only logged.
+  fn log_submit_results(&self) {
+    while let Some(result) = self.submit_results.consume_event() {
+      match result {
+        Ok(response) if response.status == 200 => {}
+        Ok(response) => console_log!(
+          "leaderboard submission rejected ({}): {}",
+          response.status,
+          response.body
+        ),
+        Err(error) => console_log!("leaderboard submission failed: {}", error),
+      }
+    }
+  }
+}
+
+impl LeaderboardProvider for HttpLeaderboardProvider {
+  fn get_name(&self) -> &str {
+    return "Online";
+  }
+
+  fn submit(&self, _context: &Context, submission: LeaderboardSubmission) {
+    self.log_submit_results();
+    let body = serde_json::json!({
+      "player_name": submission.player_name,
+      "score": submission.score,
+      "stars": submission.stars,
+      "replay": LocalStorageUtil::encode(&submission.replay),
+    });
+    HttpUtil::send_json(
+      &self.window,
+      "POST",
+      &self.get_stage_url(submission.book_number, submission.stage_number),
+      Some(body.to_string()),
+      self.submit_results.clone(),
+      Box::new(|result| result),
+    );
+  }
+
+  fn request_entries(
+    &self,
+    _context: &Context,
+    book_number: usize,
+    stage_number: usize,
+    results: Rc<EventManager<LeaderboardResult>>,
+  ) {
+    self.log_submit_results();
+    HttpUtil::send_json(
+      &self.window,
+      "GET",
+      &format!(
+        "{}?limit={}",
+        self.get_stage_url(book_number, stage_number),
+        LEADERBOARD_SIZE
+      ),
+      None,
+      results,
+      Box::new(|result| {
+        let response = result?;
+        if response.status != 200 {
+          return Err(format!("unexpected status {}", response.status));
+        }
+        let mut entries = serde_json::from_str::<LeaderboardResponse>(&response.body)
+          .map_err(|error| error.to_string())?
+          .entries;
+        sort_and_truncate(&mut entries);
+        return Ok(entries);
+      }),
+    );
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn create_entry(player_name: &str, score: i32) -> LeaderboardEntry {
+    return LeaderboardEntry {
+      player_name: String::from(player_name),
+      score,
+      stars: 3,
+    };
+  }
+
+  #[test]
+  fn test_add_entry_keeps_best_per_player() {
+    let mut entries = vec![];
+    add_entry(&mut entries, create_entry("Panda", 900));
+    add_entry(&mut entries, create_entry("Bamboo", 1200));
+    add_entry(&mut entries, create_entry("Panda", 1500));
+    add_entry(&mut entries, create_entry("Bamboo", 1000));
+    assert_eq!(
+      entries,
+      vec![create_entry("Panda", 1500), create_entry("Bamboo", 1200)]
+    );
+  }
+}
@@ -3,17 +3,17 @@ use crate::game::paint_path::*;
 use crate::*;
 use std::collections::HashSet;
 
-const MIRROR_DRAW_DX: F1 = 0.014;
+const MIRROR_WIDTH: F1 = 5.0 / 480.0;
+// Between the starts of two dashes.
+const MIRROR_DASH_PERIOD: F1 = 0.014;
 
 pub struct Mirror {
   entity_base: EntityBase,
   p1: F2,
   p2: F2,
   direction: F2,
-  length: F1,
   paths_created: RefCell<HashSet<HashablePointer<PaintPath>>>,
 
-  cached_canvas: CachedCanvasBackend,
   state_history: StateHistory<MirrorState>,
 }
 
@@ -22,18 +22,15 @@ pub struct MirrorState {
 }
 
 impl Mirror {
-  pub fn new(context: &Context, p1: F2, p2: F2) -> Rc<Mirror> {
+  pub fn new(_context: &Context, p1: F2, p2: F2) -> Rc<Mirror> {
     let mut direction = p2 - p1;
     direction.normalize();
-    let length = (p2 - p1).length();
     return Rc::new(Mirror {
       entity_base: EntityBase::new(),
       p1: p1,
       p2: p2,
       direction: direction,
-      length: length,
       paths_created: RefCell::new(HashSet::new()),
-      cached_canvas: CachedCanvasBackend::new(&context.get_canvas_size()),
       state_history: StateHistory::new(0),
     });
   }
@@ -118,53 +115,28 @@ impl EntityTrait<Context> for Mirror {
   fn update(&self, _context: &mut Context) {}
 
   fn draw(&self, context: &mut Context) {
-    self
-      .cached_canvas
-      .check_canvas_size_changed(&context.get_canvas_size());
-    if self.cached_canvas.check_clear_cache() {
-      let mut x = 0.0;
-      let size = context
-        .texture_manager
-        .pixel
-        .get_size_from_width(5.0 / 480.0);
-
-      while x < self.length {
-        let position = self.p1 + self.direction * x;
-        self.cached_canvas.draw_backend.execute_image_draw(
-          &mut context.draw_manager.convert_viewport_into_canvas_draw_args(
-            &context.game_viewport,
-            DrawImageArgs {
-              source: DrawSource::Texture(context.texture_manager.pixel.clone()),
-              position: position,
-              size: size,
-              depth: context.draw_depths.mirror,
-              optional: DrawImageOptionalArgs {
-                color: DrawColor {
-                  r: (133.0 / 2.0) as u8,
-                  g: (199.0 / 2.0) as u8,
-                  b: (191.0 / 2.0) as u8,
-                },
-                ..Default::default()
-              },
-            },
-          ),
-          &self.cached_canvas.canvas_size.get(),
-        );
-        x += MIRROR_DRAW_DX;
-      }
-    }
-
-    context.draw_manager.draw_screen(DrawImageArgs {
-      source: DrawSource::Canvas(self.cached_canvas.canvas.clone()),
-      position: F2 { x: 0.0, y: 0.0 },
-      size: context.screen_size,
-      depth: context.draw_depths.mirror,
-      optional: DrawImageOptionalArgs {
-        anchor_point: F2 { x: 0.0, y: 0.0 },
-        opacity: context.stage_opacity.get(),
-        ..Default::default()
+    context.draw_manager.draw_shape_viewport(
+      &context.game_viewport,
+      DrawShapeArgs {
+        shape: DrawShape::Line {
+          start: self.p1,
+          end: self.p2,
+        },
+        layer: context.draw_layers.mirror,
+        depth: 0.0,
+        optional: DrawShapeOptionalArgs {
+          stroke_color: Some(DrawColor {
+            r: (133.0 / 2.0) as u8,
+            g: (199.0 / 2.0) as u8,
+            b: (191.0 / 2.0) as u8,
+          }),
+          stroke_width: MIRROR_WIDTH,
+          dash: vec![MIRROR_WIDTH, MIRROR_DASH_PERIOD - MIRROR_WIDTH],
+          opacity: context.stage_opacity.get(),
+          ..Default::default()
+        },
       },
-    });
+    );
   }
 }
 
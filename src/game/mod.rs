@@ -1,20 +1,31 @@
+pub mod achievement_tracker;
 pub mod achievments_manager;
 pub mod background_canvas;
 pub mod barrier;
 pub mod brush;
 pub mod game_mode;
+pub mod gameplay_event;
 pub mod goal;
 pub mod goal_collect_effect;
 pub mod goal_wrong_reach_animation;
+pub mod leaderboard;
 pub mod mirror;
 pub mod moving_source;
 pub mod paint_color;
 pub mod paint_path;
 pub mod paint_point;
 pub mod paint_source;
+pub mod painting_export;
 pub mod point_grid;
 pub mod portal;
+pub mod profile_manager;
+pub mod progress_backup;
+pub mod progress_storage;
 pub mod source;
 pub mod stage;
+pub mod stage_replay;
+#[cfg(all(test, not(target_arch = "wasm32")))]
+pub mod stage_runner;
 pub mod stages_data;
 pub mod star_bar;
+pub mod time_lapse;
@@ -1,3 +1,4 @@
+use crate::game::game_mode::SIMULATION_STEP_MS;
 use crate::game::paint_color::PaintColor;
 use crate::game::paint_path::*;
 use crate::game::paint_point::*;
@@ -53,7 +54,11 @@ impl MovingSource {
       active_path: RefCell::new(None),
       paint_paths,
       current_paint_amount: Cell::new(50000.0),
-      cached_canvas: CachedCanvasBackend::new(&context.get_canvas_size()),
+      cached_canvas: CachedCanvasBackend::new(
+        context
+          .draw_manager
+          .create_render_target(&context.get_canvas_size()),
+      ),
       state_history: StateHistory::new(0),
     });
   }
@@ -127,19 +132,10 @@ impl EntityTrait<Context> for MovingSource {
   fn update(&self, context: &mut Context) {
     let game_mode = context.game_mode.borrow().clone().unwrap();
     self.register_current_state(game_mode.checkpoint.get());
-    let mut total_dt = *context.get_dt();
-    while total_dt > 0.0 {
-      let fps60_dt = 1000.0 / 60.0;
-      let curr_dt = {
-        if fps60_dt < total_dt {
-          fps60_dt
-        } else {
-          total_dt
-        }
-      };
-      total_dt -= curr_dt;
-
-      self.time_moved.set(self.time_moved.get() + curr_dt);
+    for _ in 0..game_mode.get_frame_steps() {
+      self
+        .time_moved
+        .set(self.time_moved.get() + SIMULATION_STEP_MS);
       let current_angle = 2.0 * std::f32::consts::PI * self.time_moved.get() * self.speed;
       self.paw.borrow_mut().paw_position = self.position
         + F2 {
@@ -157,7 +153,9 @@ impl EntityTrait<Context> for MovingSource {
         }
         self.active_path.replace(None);
         self.paint_color.set(PaintColor::NoColor);
-        break;
+        // Steps don't depend on the frame they run in, so a replay can run
+        // the steps of several frames at once.
+        continue;
       }
 
       if self.paint_color.get() == PaintColor::NoColor {
@@ -233,10 +231,11 @@ impl EntityTrait<Context> for MovingSource {
   fn draw(&self, context: &mut Context) {
     self
       .cached_canvas
-      .check_canvas_size_changed(&context.get_canvas_size());
+      .check_world_viewport_changed(&context.game_viewport);
     if self.cached_canvas.check_clear_cache() {
       let draw_point_count = self.radius * 240.0;
       let delta_angle = 2.0 * std::f32::consts::PI / draw_point_count;
+      let world_viewport = self.cached_canvas.get_world_viewport();
 
       for i in 0..(draw_point_count as i32) {
         let angle = delta_angle * (i as F1);
@@ -248,14 +247,15 @@ impl EntityTrait<Context> for MovingSource {
 
         let size = context.texture_manager.dot.get_size_from_width(6.0 / 480.0);
 
-        self.cached_canvas.draw_backend.execute_image_draw(
+        self.cached_canvas.get_draw_backend().execute_image_draw(
           &context.draw_manager.convert_viewport_into_canvas_draw_args(
-            &context.game_viewport,
+            &world_viewport,
             DrawImageArgs {
               source: DrawSource::Texture(context.texture_manager.dot.clone()),
               position: dot_position,
               size: size,
-              depth: context.draw_depths.mirror,
+              layer: context.draw_layers.mirror,
+              depth: 0.0,
               optional: DrawImageOptionalArgs {
                 color: DrawColor {
                   r: 78,
@@ -271,17 +271,21 @@ impl EntityTrait<Context> for MovingSource {
       }
     }
 
-    context.draw_manager.draw_screen(DrawImageArgs {
-      source: DrawSource::Canvas(self.cached_canvas.canvas.clone()),
-      position: F2 { x: 0.0, y: 0.0 },
-      size: context.screen_size,
-      depth: context.draw_depths.mirror,
-      optional: DrawImageOptionalArgs {
-        anchor_point: F2 { x: 0.0, y: 0.0 },
-        opacity: context.stage_opacity.get() * 100.0 / 255.0,
-        ..Default::default()
+    context.draw_manager.draw_viewport(
+      &context.game_viewport,
+      DrawImageArgs {
+        source: DrawSource::RenderTarget(self.cached_canvas.render_target.clone()),
+        position: F2 { x: 0.0, y: 0.0 },
+        size: self.cached_canvas.get_world_cache_size(),
+        layer: context.draw_layers.mirror,
+        depth: 0.0,
+        optional: DrawImageOptionalArgs {
+          anchor_point: F2 { x: 0.0, y: 0.0 },
+          opacity: context.stage_opacity.get() * 100.0 / 255.0,
+          ..Default::default()
+        },
       },
-    });
+    );
 
     context.draw_manager.draw_viewport(
       &context.game_viewport,
@@ -292,7 +296,8 @@ impl EntityTrait<Context> for MovingSource {
           .texture_manager
           .dot
           .get_size_from_width(40.0 / 480.0),
-        depth: context.draw_depths.source + 0.1,
+        layer: context.draw_layers.source_back,
+        depth: 0.0,
         optional: DrawImageOptionalArgs {
           opacity: context.stage_opacity.get(),
           color: self.paint_color.get().get_draw_color(),
@@ -311,7 +316,8 @@ impl EntityTrait<Context> for MovingSource {
           .texture_manager
           .dot
           .get_size_from_width(40.0 / 480.0),
-        depth: context.draw_depths.source,
+        layer: context.draw_layers.source,
+        depth: 0.0,
         optional: DrawImageOptionalArgs {
           opacity: context.stage_opacity.get() * 0.4,
           subpixel_precision: true,
@@ -167,7 +167,7 @@ impl SourcedPaintPath {
 
         color: Cell::new(source.paint_color.get_draw_color()),
         texture: context.texture_manager.flare.clone(),
-        depth: context.draw_depths.source - 0.01,
+        layer: context.draw_layers.smoke,
 
         start_position_delta: F2 { x: 0.0, y: -0.05 },
         rand_region_size: F2 { x: 0.1, y: 0.1 },
@@ -209,7 +209,7 @@ impl SourcedPaintPath {
 
         color: Cell::new(source.paint_color.get_draw_color()),
         texture: context.texture_manager.flare.clone(),
-        depth: context.draw_depths.source - 1.01,
+        layer: context.draw_layers.sparkle,
 
         start_position_delta: F2 { x: 0.0, y: 0.03 },
         rand_region_size: F2 { x: 0.06, y: 0.06 },
@@ -1,13 +1,22 @@
 use super::super::context::Context;
 use super::paint_color::PaintColor;
+use super::point_grid::PaintRenderer;
 use super::point_grid::PointGrid;
 use crate::engine::*;
+use std::rc::Weak;
 
 pub const POINT_RADIUS: F1 = 0.0100;
 pub const DISTANCE_BETWEEN_POINTS: F1 = 0.0095;
 pub const DISTANCE_SQUARED_BETWEEN_POINTS: F1 = 0.00009025;
 pub const DISTANCE_BETWEEN_POINTS_AMOUNT_OF_PAINT: F1 = 4.0;
 pub const DISTANCE_SQUARED_TO_REACTIVATE_PATH: F1 = 0.0020;
+// Points further from the previous point of their path, e.g. after going
+// through a portal, start a new stroke.
+const MAX_DISTANCE_SQUARED_TO_PREVIOUS_POINT: F1 = 4.0 * DISTANCE_SQUARED_BETWEEN_POINTS;
+// Width of a stroke relative to the mancha texture, whose blob doesn't reach
+// its borders.
+const STROKE_WIDTH_RATIO: F1 = 0.7;
+pub const STROKE_SUBDIVISIONS: usize = 3;
 
 pub struct PaintPoint {
   pub position: F2,
@@ -19,6 +28,7 @@ pub struct PaintPoint {
   paint_depth: F1,
   rotation: F1,
   size: F2,
+  previous_point: Weak<PaintPoint>,
 }
 
 impl PaintPoint {
@@ -29,9 +39,19 @@ impl PaintPoint {
     checkpoint: &u32,
     sin_scale_iterator: &i32,
     paint_depth: &F1,
+    previous_point: &Weak<PaintPoint>,
   ) -> PaintPoint {
     let width =
       0.09 * (0.4 + 0.13 * (30.0 * (*sin_scale_iterator as F1) * DISTANCE_BETWEEN_POINTS).sin());
+    let previous_point = match previous_point.upgrade() {
+      Some(previous_point)
+        if F2::distance2(&previous_point.position, position)
+          <= MAX_DISTANCE_SQUARED_TO_PREVIOUS_POINT =>
+      {
+        Rc::downgrade(&previous_point)
+      }
+      _ => Weak::new(),
+    };
     return PaintPoint {
       position: *position,
       grid_x: PointGrid::get_grid_x(position, context),
@@ -42,19 +62,34 @@ impl PaintPoint {
       paint_depth: *paint_depth,
       rotation: 0.1 * (*sin_scale_iterator as F1),
       size: context.texture_manager.mancha.get_size_from_width(width),
+      previous_point,
     };
   }
 
-  pub fn calculate_depth(&self, context: &Context, total_point_depth: &F1) -> F1 {
-    return context.draw_depths.path + ((total_point_depth - self.paint_depth) / total_point_depth);
+  // Creation order of the point in the stage.
+  pub fn get_paint_depth(&self) -> F1 {
+    return self.paint_depth;
+  }
+
+  // Point drawn before this one in the same stroke, if any.
+  pub fn get_previous_point(&self) -> Option<Rc<PaintPoint>> {
+    return self.previous_point.upgrade();
   }
 
-  pub fn draw(&self, context: &Context, total_point_depth: &F1) -> DrawImageArgs {
+  pub fn get_stroke_point(&self) -> StrokePoint {
+    return StrokePoint {
+      position: self.position,
+      width: self.size.x * STROKE_WIDTH_RATIO,
+    };
+  }
+
+  pub fn draw(&self, context: &Context) -> DrawImageArgs {
     return DrawImageArgs {
       source: DrawSource::Texture(context.texture_manager.mancha.clone()),
       position: self.position,
       size: self.size,
-      depth: self.calculate_depth(context, total_point_depth),
+      layer: context.draw_layers.path,
+      depth: 0.0,
       optional: DrawImageOptionalArgs {
         color: self.paint_color.get_draw_color(),
         rotation: self.rotation,
@@ -62,6 +97,71 @@ impl PaintPoint {
       },
     };
   }
+
+  // Part of the stroke from the previous point to this one.
+  pub fn draw_stroke(&self, context: &Context) -> DrawStrokeArgs {
+    let stroke_point = self.get_stroke_point();
+    let mut points = Vec::with_capacity(STROKE_SUBDIVISIONS + 1);
+    if let Some(previous_point) = self.get_previous_point() {
+      let before_previous_point = match previous_point.get_previous_point() {
+        Some(before_previous_point) => before_previous_point.get_stroke_point(),
+        None => previous_point.get_stroke_point(),
+      };
+      StrokeGeometry::push_catmull_rom_segment(
+        &mut points,
+        &before_previous_point,
+        &previous_point.get_stroke_point(),
+        &stroke_point,
+        &stroke_point,
+        STROKE_SUBDIVISIONS,
+      );
+    }
+    points.push(stroke_point);
+    return DrawStrokeArgs {
+      points,
+      layer: context.draw_layers.path,
+      depth: 0.0,
+      optional: DrawStrokeOptionalArgs {
+        color: self.paint_color.get_draw_color(),
+        ..Default::default()
+      },
+    };
+  }
+
+  pub fn draw_into_cached_canvas(&self, context: &Context, cached_canvas: &CachedCanvasBackend) {
+    let draw_backend = cached_canvas.get_draw_backend();
+    let canvas_size = cached_canvas.canvas_size.get();
+    let world_viewport = cached_canvas.get_world_viewport();
+    match context.paint_renderer {
+      PaintRenderer::Stamps => draw_backend.execute_image_draw(
+        &context
+          .draw_manager
+          .convert_viewport_into_canvas_draw_args(&world_viewport, self.draw(context)),
+        &canvas_size,
+      ),
+      PaintRenderer::Strokes => draw_backend.execute_stroke_draw(
+        &context
+          .draw_manager
+          .convert_viewport_into_canvas_stroke_args(&world_viewport, self.draw_stroke(context)),
+        &canvas_size,
+      ),
+    }
+  }
+
+  pub fn draw_viewport(&self, context: &mut Context, viewport: &Viewport) {
+    match context.paint_renderer {
+      PaintRenderer::Stamps => {
+        let draw_args = self.draw(context);
+        context.draw_manager.draw_viewport(viewport, draw_args);
+      }
+      PaintRenderer::Strokes => {
+        let draw_args = self.draw_stroke(context);
+        context
+          .draw_manager
+          .draw_stroke_viewport(viewport, draw_args);
+      }
+    }
+  }
 }
 
 impl CircleShape for PaintPoint {
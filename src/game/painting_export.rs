@@ -0,0 +1,260 @@
+use crate::context::Context;
+use crate::game::background_canvas::BackgroundCanvas;
+use crate::game::game_mode::GameMode;
+use crate::*;
+
+// In board widths.
+const FRAME_MARGIN: F1 = 0.04;
+const FRAME_FOOTER_HEIGHT: F1 = 0.16;
+const TIME_LAPSE_EXPORT_FRAMES: usize = 40;
+const TIME_LAPSE_EXPORT_FRAME_MS: u16 = 80;
+const TIME_LAPSE_EXPORT_LAST_FRAME_MS: u16 = 2000;
+
+#[derive(Clone, Copy)]
+pub struct PaintingExportParams {
+  // Width of the image in pixels. The height follows the game board.
+  pub width: F1,
+  // Surrounds the painting with a frame showing the book, stage and stars.
+  pub frame: bool,
+  pub stars: usize,
+  // Number of paint points drawn, in creation order. All of them if None.
+  pub painted_points: Option<usize>,
+}
+
+// Renders the painting of the current stage (the background canvas, the paint
+// and the sources and goals) into an image, independently from the screen
+// resolution.
+pub struct PaintingExport {}
+
+impl PaintingExport {
+  pub fn save(context: &mut Context, game_mode: &GameMode, params: &PaintingExportParams) {
+    let render_target = PaintingExport::render(context, game_mode, params);
+    FileTransferUtil::download_canvas_png(
+      context.window().expect("painting export without window"),
+      &PaintingExport::get_file_name(game_mode, ""),
+      &CanvasRenderTarget::from_render_target(render_target.as_ref())
+        .expect("painting export render target isn't a canvas")
+        .canvas,
+    );
+  }
+
+  // Saves an animated PNG redrawing the paint points in creation order, with
+  // `params.painted_points` ignored.
+  pub fn save_time_lapse(
+    context: &mut Context,
+    game_mode: &GameMode,
+    params: &PaintingExportParams,
+  ) {
+    let total_points = game_mode
+      .point_grid
+      .borrow()
+      .get_points_in_creation_order()
+      .len();
+    let mut frames = Vec::new();
+    let mut size = F2::default();
+    for frame in 0..=TIME_LAPSE_EXPORT_FRAMES {
+      let render_target = PaintingExport::render(
+        context,
+        game_mode,
+        &PaintingExportParams {
+          painted_points: Some(total_points * frame / TIME_LAPSE_EXPORT_FRAMES),
+          ..*params
+        },
+      );
+      size = render_target.get_size();
+      frames.push(ApngFrame {
+        rgba: CanvasRenderTarget::from_render_target(render_target.as_ref())
+          .expect("painting export render target isn't a canvas")
+          .get_rgba_pixels(),
+        delay_ms: if frame == TIME_LAPSE_EXPORT_FRAMES {
+          TIME_LAPSE_EXPORT_LAST_FRAME_MS
+        } else {
+          TIME_LAPSE_EXPORT_FRAME_MS
+        },
+      });
+    }
+    FileTransferUtil::download_bytes(
+      context.window().expect("time lapse export without window"),
+      &PaintingExport::get_file_name(game_mode, "-timelapse"),
+      "image/png",
+      &ApngEncoder::encode(size.x as u32, size.y as u32, &frames),
+    );
+  }
+
+  fn get_file_name(game_mode: &GameMode, suffix: &str) -> String {
+    return format!(
+      "pandadoodle-{}-{}{}.png",
+      game_mode.book.get().number() + 1,
+      game_mode.stage_number.get() + 1,
+      suffix
+    );
+  }
+
+  // Must be called outside of the draw phase, since it executes the queued
+  // draws.
+  pub fn render(
+    context: &mut Context,
+    game_mode: &GameMode,
+    params: &PaintingExportParams,
+  ) -> Rc<dyn RenderTarget> {
+    // The board shows the whole stage, even when the game camera is zoomed in.
+    let world_size = context.game_viewport.world_size;
+    let board_yx_ratio = world_size.y / world_size.x;
+    let (margin, footer_height) = if params.frame {
+      (FRAME_MARGIN, FRAME_FOOTER_HEIGHT)
+    } else {
+      (0.0, 0.0)
+    };
+    let board_width = params.width / (1.0 + 2.0 * margin);
+    let size = F2 {
+      x: params.width,
+      y: (board_width * (margin + board_yx_ratio + footer_height)).round(),
+    };
+    let board_position = F2 {
+      x: board_width * margin,
+      y: board_width * margin,
+    };
+    let board_size = F2 {
+      x: board_width,
+      y: board_width * board_yx_ratio,
+    };
+    let mut camera = Camera::new(&F2 {
+      x: 1.0,
+      y: board_yx_ratio,
+    });
+    camera.set_bounds_size(&world_size);
+    camera.reset();
+    let export_viewport = Rc::new(Viewport::new_with_camera(
+      &board_position,
+      &board_size,
+      &size,
+      &1.0,
+      &camera,
+    ));
+
+    // Stage entities draw with the game viewport and the stage opacity, which
+    // fades out after the victory.
+    let game_viewport = std::mem::replace(&mut context.game_viewport, export_viewport.clone());
+    let stage_opacity = context.stage_opacity.get();
+    context.stage_opacity.replace(1.0);
+
+    if params.frame {
+      // In board widths, whatever the size of the stage.
+      let frame_viewport = Viewport::new(&board_position, &board_size, &size, &1.0);
+      PaintingExport::draw_frame(context, &frame_viewport, game_mode, params, &size);
+    }
+    BackgroundCanvas::draw(context, game_mode.book.get());
+    let point_grid = game_mode.point_grid.borrow().clone();
+    let points = point_grid.get_points_in_creation_order();
+    let painted_points = params.painted_points.unwrap_or(points.len());
+    for paint_point in points.iter().take(painted_points) {
+      paint_point.draw_viewport(context, &export_viewport);
+    }
+    let stage = game_mode.stage.borrow().clone();
+    stage.barriers.draw(context);
+    stage.portals.draw(context);
+    stage.sources.draw(context);
+    stage.goals.draw(context);
+
+    context.game_viewport = game_viewport;
+    context.stage_opacity.replace(stage_opacity);
+
+    let render_target = context.draw_manager.create_render_target(&size);
+    context
+      .draw_manager
+      .execute_draws_on_render_target(&render_target, &1.0);
+    return render_target;
+  }
+
+  fn draw_frame(
+    context: &mut Context,
+    viewport: &Viewport,
+    game_mode: &GameMode,
+    params: &PaintingExportParams,
+    size: &F2,
+  ) {
+    let frame_color = DrawColor {
+      r: 122,
+      g: 84,
+      b: 48,
+    };
+    context
+      .draw_manager
+      .draw_gradient_box_canvas(DrawGradientBoxArgs {
+        position: F2 { x: 0.0, y: 0.0 },
+        size: *size,
+        draw_color_start: frame_color,
+        alpha_start: 1.0,
+        draw_color_end: frame_color,
+        alpha_end: 1.0,
+        layer: context.draw_layers.board,
+        depth: 0.0,
+        anchor_point: F2 { x: 0.0, y: 0.0 },
+      });
+
+    let footer_center_y = viewport.viewport_yx_ratio + FRAME_FOOTER_HEIGHT * 0.5;
+    context.draw_manager.draw_string_viewport(
+      viewport,
+      DrawStringArgs {
+        text: format!(
+          "Stage {}-{}",
+          game_mode.book.get().number() + 1,
+          game_mode.stage_number.get() + 1
+        ),
+        position: F2 {
+          x: 0.0,
+          y: footer_center_y - 0.02,
+        },
+        font_size: 0.07,
+        layer: context.draw_layers.hud,
+        depth: 0.0,
+        optional: DrawStringOptionalArgs {
+          border: true,
+          ..Default::default()
+        },
+      },
+    );
+    context.draw_manager.draw_string_viewport(
+      viewport,
+      DrawStringArgs {
+        text: String::from("Panda Doodle"),
+        position: F2 {
+          x: 0.0,
+          y: footer_center_y + 0.045,
+        },
+        font_size: 0.04,
+        layer: context.draw_layers.hud,
+        depth: 0.0,
+        optional: DrawStringOptionalArgs {
+          opacity: 0.7,
+          ..Default::default()
+        },
+      },
+    );
+
+    for i in 0..3 {
+      let texture = if i < params.stars {
+        context.texture_manager.star.clone()
+      } else {
+        context.texture_manager.star_empty.clone()
+      };
+      context.draw_manager.draw_viewport(
+        viewport,
+        DrawImageArgs {
+          source: DrawSource::Texture(texture),
+          position: F2 {
+            x: 1.0 - 0.05 - 0.1 * (2 - i) as F1,
+            y: footer_center_y,
+          },
+          size: F2 { x: 0.1, y: 0.1 },
+          layer: context.draw_layers.hud,
+          depth: 0.0,
+          optional: DrawImageOptionalArgs {
+            subpixel_precision: true,
+            ..Default::default()
+          },
+        },
+      );
+    }
+  }
+}
@@ -1,13 +1,38 @@
 use super::paint_color::PaintColor;
 use crate::context::Context;
+use crate::context::GAME_VIEWPORT_YX_RATIO;
 use crate::engine::*;
 use crate::game::paint_point::PaintPoint;
+use crate::game::paint_point::STROKE_SUBDIVISIONS;
 use crate::*;
+use std::collections::HashMap;
 use std::rc::Rc;
+use std::rc::Weak;
 
+// Cells over the width and height of the game viewport frame. Bigger stages get
+// more cells of the same size.
 const GRID_WIDTH: usize = 40;
 const GRID_HEIGHT: usize = 50;
 
+// How the paint is drawn, selected with the `paint_renderer` query parameter.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum PaintRenderer {
+  // The mancha texture stamped on every point.
+  Stamps,
+  // A smooth stroke along each path, sharper at high resolutions and drawn
+  // with far fewer draw calls when the cache is rebuilt.
+  Strokes,
+}
+
+impl PaintRenderer {
+  pub fn from_query_param(window: &web_sys::Window) -> PaintRenderer {
+    if HttpUtil::get_query_param(window, "paint_renderer").as_deref() == Some("strokes") {
+      return PaintRenderer::Strokes;
+    }
+    return PaintRenderer::Stamps;
+  }
+}
+
 pub struct PointGrid {
   entity_base: EntityBase,
   grid: RefCell<Vec<Vec<Vec<Rc<PaintPoint>>>>>,
@@ -22,15 +47,20 @@ impl PointGrid {
       entity_base: EntityBase::new(),
       grid: RefCell::new(Vec::new()),
       point_count: Cell::new(0),
-      cached_canvas: CachedCanvasBackend::new(&context.get_canvas_size()),
+      cached_canvas: CachedCanvasBackend::new(
+        context
+          .draw_manager
+          .create_render_target(&context.get_canvas_size()),
+      ),
       state_history: StateHistory::new(0),
     });
 
     {
       let mut grid = result.grid.borrow_mut();
-      for _ in 0..GRID_WIDTH {
+      let (grid_width, grid_height) = PointGrid::get_grid_size(context);
+      for _ in 0..grid_width {
         let mut v = Vec::new();
-        for _ in 0..GRID_HEIGHT {
+        for _ in 0..grid_height {
           v.push(Vec::new());
         }
         grid.push(v);
@@ -39,21 +69,24 @@ impl PointGrid {
     return result;
   }
 
-  pub fn get_grid_x(position: &F2, _: &Context) -> usize {
+  fn get_grid_size(context: &Context) -> (usize, usize) {
+    let world_size = context.game_viewport.world_size;
+    return (
+      (world_size.x * GRID_WIDTH as F1).round().max(1.0) as usize,
+      (world_size.y * GRID_HEIGHT as F1 / GAME_VIEWPORT_YX_RATIO)
+        .round()
+        .max(1.0) as usize,
+    );
+  }
+
+  pub fn get_grid_x(position: &F2, context: &Context) -> usize {
     let result = (position.x * (GRID_WIDTH as F1)).floor() as usize;
-    if result == GRID_WIDTH {
-      return GRID_WIDTH - 1;
-    }
-    return result;
+    return result.min(PointGrid::get_grid_size(context).0 - 1);
   }
 
   pub fn get_grid_y(position: &F2, context: &Context) -> usize {
-    let result =
-      (position.y * (GRID_HEIGHT as F1) / context.game_viewport.viewport_yx_ratio).floor() as usize;
-    if result == GRID_HEIGHT {
-      return GRID_HEIGHT - 1;
-    }
-    return result;
+    let result = (position.y * (GRID_HEIGHT as F1) / GAME_VIEWPORT_YX_RATIO).floor() as usize;
+    return result.min(PointGrid::get_grid_size(context).1 - 1);
   }
 
   pub fn create_point(
@@ -63,6 +96,7 @@ impl PointGrid {
     checkpoint: &u32,
     paint_color: &PaintColor,
     path_point_count: &i32,
+    previous_point: &Weak<PaintPoint>,
   ) -> Rc<PaintPoint> {
     self.point_count.set(self.point_count.get() + 1);
     let paint_point = Rc::new(PaintPoint::new(
@@ -72,15 +106,10 @@ impl PointGrid {
       checkpoint,
       path_point_count,
       &(self.point_count.get() as F1),
+      previous_point,
     ));
 
-    let draw_args = paint_point.draw(context, &(self.point_count.get() as F1));
-    self.cached_canvas.draw_backend.execute_image_draw(
-      &context
-        .draw_manager
-        .convert_viewport_into_canvas_draw_args(&context.game_viewport, draw_args),
-      &self.cached_canvas.canvas_size.get(),
-    );
+    paint_point.draw_into_cached_canvas(context, &self.cached_canvas);
 
     let grid_x = paint_point.grid_x;
     let grid_y = paint_point.grid_y;
@@ -93,6 +122,94 @@ impl PointGrid {
     // context.grid.get_paint_depth()
   }
 
+  pub fn get_points_in_creation_order(&self) -> Vec<Rc<PaintPoint>> {
+    let mut points: Vec<Rc<PaintPoint>> = Vec::new();
+    for grid_line in self.grid.borrow().iter() {
+      for grid_cell in grid_line.iter() {
+        points.extend(grid_cell.iter().cloned());
+      }
+    }
+    points.sort_by(|a, b| {
+      a.get_paint_depth()
+        .partial_cmp(&b.get_paint_depth())
+        .unwrap()
+    });
+    return points;
+  }
+
+  // Consecutive points of the paths, each drawn as a single stroke when the
+  // cache is rebuilt. A run ends where the color changes or the paint covers
+  // older paint of another color, so drawing the runs in order stacks the paint
+  // as it was painted.
+  fn get_stroke_runs(&self) -> Vec<Vec<Rc<PaintPoint>>> {
+    let mut runs: Vec<Vec<Rc<PaintPoint>>> = Vec::new();
+    // Run ending at each point, by pointer.
+    let mut run_ends: HashMap<usize, usize> = HashMap::new();
+    for paint_point in self.get_points_in_creation_order() {
+      let previous_point = paint_point.get_previous_point();
+      let continued_run = previous_point
+        .as_ref()
+        .and_then(|previous_point| run_ends.remove(&(Rc::as_ptr(previous_point) as usize)))
+        .filter(|run| {
+          runs[*run].last().unwrap().paint_color == paint_point.paint_color
+            && !self.covers_older_paint(&paint_point)
+        });
+      let run = match continued_run {
+        Some(run) => run,
+        None => {
+          runs.push(previous_point.into_iter().collect());
+          runs.len() - 1
+        }
+      };
+      run_ends.insert(Rc::as_ptr(&paint_point) as usize, run);
+      runs[run].push(paint_point);
+    }
+    return runs;
+  }
+
+  fn covers_older_paint(&self, paint_point: &Rc<PaintPoint>) -> bool {
+    return self.get_collisions(paint_point).iter().any(|other_point| {
+      other_point.paint_color != paint_point.paint_color
+        && other_point.get_paint_depth() < paint_point.get_paint_depth()
+    });
+  }
+
+  fn draw_all_points_into_cache(&self, context: &Context) {
+    let world_viewport = self.cached_canvas.get_world_viewport();
+    match context.paint_renderer {
+      PaintRenderer::Stamps => {
+        for paint_point in self.get_points_in_creation_order() {
+          paint_point.draw_into_cached_canvas(context, &self.cached_canvas);
+        }
+      }
+      PaintRenderer::Strokes => {
+        for run in self.get_stroke_runs() {
+          let control_points: Vec<StrokePoint> = run
+            .iter()
+            .map(|paint_point| paint_point.get_stroke_point())
+            .collect();
+          self.cached_canvas.get_draw_backend().execute_stroke_draw(
+            &context
+              .draw_manager
+              .convert_viewport_into_canvas_stroke_args(
+                &world_viewport,
+                DrawStrokeArgs {
+                  points: StrokeGeometry::smooth(&control_points, STROKE_SUBDIVISIONS),
+                  layer: context.draw_layers.path,
+                  depth: 0.0,
+                  optional: DrawStrokeOptionalArgs {
+                    color: run.last().unwrap().paint_color.get_draw_color(),
+                    ..Default::default()
+                  },
+                },
+              ),
+            &self.cached_canvas.canvas_size.get(),
+          );
+        }
+      }
+    }
+  }
+
   pub fn get_collisions(&self, being_checked: &Rc<PaintPoint>) -> Vec<Rc<PaintPoint>> {
     let mut result = Vec::new();
     let grid = self.grid.borrow();
@@ -100,10 +217,10 @@ impl PointGrid {
     let grid_y = being_checked.grid_y as i32;
     for other_grid_x in grid_x - 1..grid_x + 2 {
       for other_grid_y in grid_y - 1..grid_y + 2 {
-        if other_grid_x < 0 || other_grid_x >= GRID_WIDTH as i32 {
+        if other_grid_x < 0 || other_grid_x >= grid.len() as i32 {
           continue;
         }
-        if other_grid_y < 0 || other_grid_y >= GRID_HEIGHT as i32 {
+        if other_grid_y < 0 || other_grid_y >= grid[0].len() as i32 {
           continue;
         }
 
@@ -132,10 +249,10 @@ impl PointGrid {
     let grid_y = PointGrid::get_grid_y(being_checked.get_center(), context) as i32;
     for other_grid_x in grid_x - 1..grid_x + 2 {
       for other_grid_y in grid_y - 1..grid_y + 2 {
-        if other_grid_x < 0 || other_grid_x >= GRID_WIDTH as i32 {
+        if other_grid_x < 0 || other_grid_x >= grid.len() as i32 {
           continue;
         }
-        if other_grid_y < 0 || other_grid_y >= GRID_HEIGHT as i32 {
+        if other_grid_y < 0 || other_grid_y >= grid[0].len() as i32 {
           continue;
         }
 
@@ -200,37 +317,25 @@ impl EntityTrait<Context> for PointGrid {
   fn draw(&self, context: &mut Context) {
     self
       .cached_canvas
-      .check_canvas_size_changed(&context.get_canvas_size());
+      .check_world_viewport_changed(&context.game_viewport);
     if self.cached_canvas.check_clear_cache() {
-      let mut all_draw_args: Vec<DrawImageArgs> = Vec::new();
-      for grid_line in self.grid.borrow().iter() {
-        for grid_cell in grid_line.iter() {
-          for paint_point in grid_cell.iter() {
-            all_draw_args.push(paint_point.draw(context, &(self.point_count.get() as F1)))
-          }
-        }
-      }
-      all_draw_args.sort_by(|a, b| b.depth.partial_cmp(&a.depth).unwrap());
-      for draw_args in all_draw_args {
-        self.cached_canvas.draw_backend.execute_image_draw(
-          &context
-            .draw_manager
-            .convert_viewport_into_canvas_draw_args(&context.game_viewport, draw_args),
-          &self.cached_canvas.canvas_size.get(),
-        );
-      }
+      self.draw_all_points_into_cache(context);
     }
 
-    context.draw_manager.draw_screen(DrawImageArgs {
-      source: DrawSource::Canvas(self.cached_canvas.canvas.clone()),
-      position: F2 { x: 0.0, y: 0.0 },
-      size: context.screen_size,
-      depth: context.draw_depths.path,
-      optional: DrawImageOptionalArgs {
-        opacity: context.stage_opacity.get(),
-        anchor_point: F2 { x: 0.0, y: 0.0 },
-        ..Default::default()
+    context.draw_manager.draw_viewport(
+      &context.game_viewport,
+      DrawImageArgs {
+        source: DrawSource::RenderTarget(self.cached_canvas.render_target.clone()),
+        position: F2 { x: 0.0, y: 0.0 },
+        size: self.cached_canvas.get_world_cache_size(),
+        layer: context.draw_layers.path,
+        depth: 0.0,
+        optional: DrawImageOptionalArgs {
+          opacity: context.stage_opacity.get(),
+          anchor_point: F2 { x: 0.0, y: 0.0 },
+          ..Default::default()
+        },
       },
-    });
+    );
   }
 }
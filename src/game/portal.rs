@@ -94,7 +94,8 @@ impl EntityTrait<Context> for Portal {
         source: DrawSource::Texture(tex.clone()),
         position: self.endpoint1.position,
         size: size,
-        depth: context.draw_depths.mirror,
+        layer: context.draw_layers.mirror,
+        depth: 0.0,
         optional: DrawImageOptionalArgs {
           opacity: context.stage_opacity.get(),
           color: black,
@@ -108,7 +109,8 @@ impl EntityTrait<Context> for Portal {
         source: DrawSource::Texture(tex.clone()),
         position: self.endpoint2.position,
         size: size,
-        depth: context.draw_depths.mirror,
+        layer: context.draw_layers.mirror,
+        depth: 0.0,
         optional: DrawImageOptionalArgs {
           opacity: context.stage_opacity.get(),
           color: black,
@@ -129,7 +131,8 @@ impl EntityTrait<Context> for Portal {
         source: DrawSource::Texture(tex_glow.clone()),
         position: self.endpoint1.position,
         size: size,
-        depth: context.draw_depths.mirror - 0.1,
+        layer: context.draw_layers.portal_glow,
+        depth: 0.0,
         optional: DrawImageOptionalArgs {
           opacity: glow_opacity,
           color: glow_color,
@@ -144,7 +147,8 @@ impl EntityTrait<Context> for Portal {
         source: DrawSource::Texture(tex_glow.clone()),
         position: self.endpoint2.position,
         size: size,
-        depth: context.draw_depths.mirror - 0.1,
+        layer: context.draw_layers.portal_glow,
+        depth: 0.0,
         optional: DrawImageOptionalArgs {
           opacity: glow_opacity,
           color: glow_color,
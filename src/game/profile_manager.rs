@@ -0,0 +1,158 @@
+use crate::engine::*;
+use crate::texture_manager::TextureManager;
+use serde::{Deserialize, Serialize};
+
+static PANDA_DOODLE_PROFILES: &str = "PandaDoodleProfiles";
+pub const MAX_PROFILES: usize = 4;
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub enum ProfileAvatar {
+  Panda,
+  Cat,
+  Wolf,
+  Rabbit,
+}
+
+const AVATAR_ORDER: [ProfileAvatar; 4] = [
+  ProfileAvatar::Panda,
+  ProfileAvatar::Cat,
+  ProfileAvatar::Wolf,
+  ProfileAvatar::Rabbit,
+];
+
+impl ProfileAvatar {
+  pub fn next(&self) -> ProfileAvatar {
+    let index = AVATAR_ORDER
+      .iter()
+      .position(|avatar| avatar == self)
+      .expect("avatar not found in AVATAR_ORDER");
+    return AVATAR_ORDER[(index + 1) % AVATAR_ORDER.len()];
+  }
+
+  pub fn get_texture(&self, texture_manager: &TextureManager) -> Rc<Texture> {
+    return match self {
+      ProfileAvatar::Panda => texture_manager.chat_char_panda.clone(),
+      ProfileAvatar::Cat => texture_manager.chat_char_cat.clone(),
+      ProfileAvatar::Wolf => texture_manager.chat_char_wolf.clone(),
+      ProfileAvatar::Rabbit => texture_manager.chat_char_rabbit.clone(),
+    };
+  }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ProfileData {
+  pub id: u32,
+  pub name: String,
+  pub avatar: ProfileAvatar,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+struct ProfilesData {
+  #[serde(default)]
+  profiles: Vec<ProfileData>,
+  #[serde(default)]
+  active_profile_id: u32,
+  #[serde(default)]
+  next_profile_id: u32,
+}
+
+impl VersionedData for ProfilesData {
+  const VERSION: u32 = 1;
+}
+
+pub struct ProfileManager {
+  local_storage: Rc<dyn KeyValueStorage>,
+  profiles_data: RefCell<ProfilesData>,
+}
+
+impl ProfileManager {
+  pub fn new(local_storage: Rc<dyn KeyValueStorage>) -> ProfileManager {
+    let mut profiles_data: ProfilesData =
+      LocalStorageUtil::read(local_storage.as_ref(), PANDA_DOODLE_PROFILES).unwrap_or_default();
+    if profiles_data.profiles.is_empty() {
+      // The first profile owns the data saved before profiles existed.
+      profiles_data.profiles.push(ProfileData {
+        id: 0,
+        name: String::from("Player 1"),
+        avatar: ProfileAvatar::Panda,
+      });
+      profiles_data.active_profile_id = 0;
+      profiles_data.next_profile_id = 1;
+    }
+    let result = ProfileManager {
+      local_storage: local_storage,
+      profiles_data: RefCell::new(profiles_data),
+    };
+    result.store();
+    return result;
+  }
+
+  fn store(&self) {
+    LocalStorageUtil::write(
+      self.local_storage.as_ref(),
+      PANDA_DOODLE_PROFILES,
+      self.profiles_data.borrow().deref(),
+    );
+  }
+
+  pub fn get_profiles(&self) -> Vec<ProfileData> {
+    return self.profiles_data.borrow().profiles.clone();
+  }
+
+  pub fn get_active_profile(&self) -> ProfileData {
+    let profiles_data = self.profiles_data.borrow();
+    return profiles_data
+      .profiles
+      .iter()
+      .find(|profile| profile.id == profiles_data.active_profile_id)
+      .unwrap_or(&profiles_data.profiles[0])
+      .clone();
+  }
+
+  pub fn set_active_profile(&self, profile_id: u32) {
+    self.profiles_data.borrow_mut().active_profile_id = profile_id;
+    self.store();
+  }
+
+  pub fn can_add_profile(&self) -> bool {
+    return self.profiles_data.borrow().profiles.len() < MAX_PROFILES;
+  }
+
+  pub fn add_profile(&self, name: String) -> u32 {
+    let profile_id = {
+      let mut profiles_data = self.profiles_data.borrow_mut();
+      let profile_id = profiles_data.next_profile_id;
+      let avatar = AVATAR_ORDER[profiles_data.profiles.len() % AVATAR_ORDER.len()];
+      profiles_data.profiles.push(ProfileData {
+        id: profile_id,
+        name: name,
+        avatar: avatar,
+      });
+      profiles_data.next_profile_id += 1;
+      profile_id
+    };
+    self.store();
+    return profile_id;
+  }
+
+  pub fn set_profile_avatar(&self, profile_id: u32, avatar: ProfileAvatar) {
+    for profile in self.profiles_data.borrow_mut().profiles.iter_mut() {
+      if profile.id == profile_id {
+        profile.avatar = avatar;
+      }
+    }
+    self.store();
+  }
+
+  // Local storage key holding `base_key` data for the active profile.
+  pub fn get_storage_key(&self, base_key: &str) -> String {
+    return self.get_profile_storage_key(self.get_active_profile().id, base_key);
+  }
+
+  pub fn get_profile_storage_key(&self, profile_id: u32, base_key: &str) -> String {
+    if profile_id == 0 {
+      return base_key.to_string();
+    }
+    return format!("{}.profile{}", base_key, profile_id);
+  }
+}
@@ -0,0 +1,93 @@
+use crate::context::*;
+use crate::engine::*;
+use crate::game::achievments_manager::*;
+use crate::game::profile_manager::ProfileManager;
+use serde::{Deserialize, Serialize};
+
+static PROGRESS_BACKUP_FORMAT: &str = "pandadoodle-progress";
+const PROGRESS_BACKUP_VERSION: u32 = 1;
+
+// Portable snapshot of everything the game keeps in local storage for one
+// profile, used to move progress between browsers and devices. Entries are
+// keyed by the profile independent base keys.
+#[derive(Serialize, Deserialize)]
+pub struct ProgressBackup {
+  format: String,
+  version: u32,
+  exported_at_ms: f64,
+  entries: HashMap<String, serde_json::Value>,
+}
+
+fn persisted_keys() -> Vec<&'static str> {
+  return vec![
+    PANDA_DOODLE_ACHIEVMENTS,
+    PANDA_DOODLE_AUDIO_SETTINGS,
+    PANDA_DOODLE_VIBRATION_SETTINGS,
+    PANDA_DOODLE_SHOW_FPS,
+  ];
+}
+
+fn validate_entry<T: VersionedData>(
+  entries: &HashMap<String, serde_json::Value>,
+  key: &str,
+) -> Result<(), String> {
+  if let Some(value) = entries.get(key) {
+    if let Err(error) = LocalStorageUtil::decode::<T>(value.clone()) {
+      return Err(format!("invalid {} ({})", key, error));
+    }
+  }
+  return Ok(());
+}
+
+impl ProgressBackup {
+  pub fn export(local_storage: &dyn KeyValueStorage, profile_manager: &ProfileManager) -> String {
+    let mut entries = HashMap::new();
+    for key in persisted_keys() {
+      let value = LocalStorageUtil::read_raw(local_storage, &profile_manager.get_storage_key(key))
+        .and_then(|value_txt| serde_json::from_str(&value_txt).ok());
+      if let Some(value) = value {
+        entries.insert(key.to_string(), value);
+      }
+    }
+    let backup = ProgressBackup {
+      format: PROGRESS_BACKUP_FORMAT.to_string(),
+      version: PROGRESS_BACKUP_VERSION,
+      exported_at_ms: js_sys::Date::now(),
+      entries: entries,
+    };
+    return serde_json::to_string_pretty(&backup)
+      .expect("serde_json::to_string(ProgressBackup) failed.");
+  }
+
+  // Checks that `text` is a backup this version of the game understands and that
+  // every entry deserializes into the struct it will be loaded as.
+  pub fn parse(text: &str) -> Result<ProgressBackup, String> {
+    let backup: ProgressBackup = match serde_json::from_str(text) {
+      Ok(backup) => backup,
+      Err(error) => return Err(format!("not a valid progress file ({})", error)),
+    };
+    if backup.format != PROGRESS_BACKUP_FORMAT {
+      return Err(String::from("not a Panda Doodle progress file"));
+    }
+    if backup.version > PROGRESS_BACKUP_VERSION {
+      return Err(String::from("file was made by a newer version of the game"));
+    }
+    validate_entry::<AchievmentsData>(&backup.entries, PANDA_DOODLE_ACHIEVMENTS)?;
+    validate_entry::<AudioPlayerSettings>(&backup.entries, PANDA_DOODLE_AUDIO_SETTINGS)?;
+    validate_entry::<VibrationManagerSettings>(&backup.entries, PANDA_DOODLE_VIBRATION_SETTINGS)?;
+    validate_entry::<bool>(&backup.entries, PANDA_DOODLE_SHOW_FPS)?;
+    return Ok(backup);
+  }
+
+  // Overwrites the stored data with the backup. Keys missing from the backup are
+  // cleared so the result matches the exporting device.
+  pub fn apply(&self, local_storage: &dyn KeyValueStorage, profile_manager: &ProfileManager) {
+    for key in persisted_keys() {
+      let storage_key = profile_manager.get_storage_key(key);
+      match self.entries.get(key) {
+        Some(value) => LocalStorageUtil::write_raw(local_storage, &storage_key, &value.to_string()),
+        None => LocalStorageUtil::remove(local_storage, &storage_key),
+      }
+    }
+  }
+}
@@ -0,0 +1,417 @@
+use crate::engine::*;
+use crate::game::achievments_manager::AchievmentsData;
+use serde::{Deserialize, Serialize};
+
+static PANDA_DOODLE_SYNC_CONFIG: &str = "PandaDoodleSyncConfig";
+static PANDA_DOODLE_DEVICE_ID: &str = "PandaDoodleDeviceId";
+const MIN_RETRY_DELAY_MS: f64 = 1000.0;
+const MAX_RETRY_DELAY_MS: f64 = 60000.0;
+// Progress made on other devices is pulled again after this long.
+const PULL_INTERVAL_MS: f64 = 60000.0;
+
+// Random id of this browser, shared by its profiles, which keeps the counts
+// made here apart from the counts of other synced devices.
+pub fn load_device_id(local_storage: &dyn KeyValueStorage) -> String {
+  if let Some(device_id) = LocalStorageUtil::read(local_storage, PANDA_DOODLE_DEVICE_ID) {
+    return device_id;
+  }
+  let device_id = format!(
+    "{:08x}{:08x}",
+    (js_sys::Math::random() * u32::MAX as f64) as u32,
+    (js_sys::Math::random() * u32::MAX as f64) as u32
+  );
+  LocalStorageUtil::write(local_storage, PANDA_DOODLE_DEVICE_ID, &device_id);
+  return device_id;
+}
+
+// Where AchievmentsManager keeps its data. Reads and writes always go through
+// a local copy so they never block; backends that talk to a server do that work
+// from `update`.
+pub trait ProgressStorage {
+  fn load(&self) -> AchievmentsData;
+  fn store(&self, achievments_data: &AchievmentsData);
+  // Like store, but replaces any copy the backend keeps elsewhere instead of
+  // merging with it.
+  fn reset(&self, achievments_data: &AchievmentsData) {
+    self.store(achievments_data);
+  }
+  // Called every frame. Returns the new progress when the backend changed it,
+  // e.g. after merging data from another device.
+  fn update(&self) -> Option<AchievmentsData>;
+}
+
+pub struct LocalProgressStorage {
+  local_storage: Rc<dyn KeyValueStorage>,
+  local_storage_key: String,
+}
+
+impl LocalProgressStorage {
+  pub fn new(
+    local_storage: Rc<dyn KeyValueStorage>,
+    local_storage_key: String,
+  ) -> LocalProgressStorage {
+    return LocalProgressStorage {
+      local_storage,
+      local_storage_key,
+    };
+  }
+}
+
+impl ProgressStorage for LocalProgressStorage {
+  fn load(&self) -> AchievmentsData {
+    return LocalStorageUtil::read(self.local_storage.as_ref(), &self.local_storage_key)
+      .unwrap_or_default();
+  }
+
+  fn store(&self, achievments_data: &AchievmentsData) {
+    LocalStorageUtil::write(
+      self.local_storage.as_ref(),
+      &self.local_storage_key,
+      achievments_data,
+    );
+  }
+
+  fn update(&self) -> Option<AchievmentsData> {
+    return None;
+  }
+}
+
+// Remote server used by RemoteProgressStorage. Read from the `sync_url` and
+// `sync_id` query parameters and, once the player agrees to sync with it,
+// remembered for later visits.
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+pub struct SyncConfig {
+  pub url: String,
+  pub sync_id: String,
+}
+
+impl VersionedData for SyncConfig {
+  const VERSION: u32 = 1;
+}
+
+impl SyncConfig {
+  // A server given by the query parameters is only used after the player
+  // confirms it, since anyone can share a link sending progress elsewhere.
+  pub fn load(window: &web_sys::Window, local_storage: &dyn KeyValueStorage) -> Option<SyncConfig> {
+    let stored_sync_config = LocalStorageUtil::read(local_storage, PANDA_DOODLE_SYNC_CONFIG);
+    if let (Some(url), Some(sync_id)) = (
+      HttpUtil::get_query_param(window, "sync_url"),
+      HttpUtil::get_query_param(window, "sync_id"),
+    ) {
+      let sync_config = SyncConfig {
+        url: url.trim_end_matches('/').to_string(),
+        sync_id,
+      };
+      if stored_sync_config.as_ref() == Some(&sync_config) {
+        return stored_sync_config;
+      }
+      let confirmed = window
+        .confirm_with_message(&format!(
+          "Sync your progress with {}? It will be sent there every time you play.",
+          sync_config.get_host()
+        ))
+        .expect("window.confirm_with_message failed");
+      if confirmed {
+        LocalStorageUtil::write(local_storage, PANDA_DOODLE_SYNC_CONFIG, &sync_config);
+        return Some(sync_config);
+      }
+    }
+    return stored_sync_config;
+  }
+
+  // Stops syncing on later visits.
+  pub fn clear(local_storage: &dyn KeyValueStorage) {
+    LocalStorageUtil::remove(local_storage, PANDA_DOODLE_SYNC_CONFIG);
+  }
+
+  // Host of the server, shown to the player.
+  pub fn get_host(&self) -> String {
+    return match web_sys::Url::new(&self.url) {
+      Ok(url) => url.host(),
+      Err(_) => self.url.clone(),
+    };
+  }
+}
+
+// Sync bookkeeping persisted next to the progress, so changes made offline are
+// still pushed after the page is reloaded.
+#[derive(Serialize, Deserialize, Default)]
+struct SyncState {
+  #[serde(default)]
+  revision: Option<u64>,
+  #[serde(default)]
+  pending: bool,
+  // The local progress was reset and replaces the server copy on the next
+  // push, instead of being merged with it.
+  #[serde(default)]
+  reset_pending: bool,
+}
+
+impl VersionedData for SyncState {
+  const VERSION: u32 = 1;
+}
+
+#[derive(Deserialize)]
+struct RemoteProgress {
+  revision: u64,
+  data: serde_json::Value,
+}
+
+#[derive(Deserialize)]
+struct PushAccepted {
+  revision: u64,
+}
+
+enum SyncEvent {
+  Pulled(Result<HttpResponse, String>),
+  // The local generation that was sent.
+  Pushed(u64, Result<HttpResponse, String>),
+}
+
+// Keeps the progress in local storage and mirrors it to a server speaking the
+// protocol described in the README. Local writes are queued as `pending` and
+// pushed one request at a time; failed requests are retried with exponential
+// backoff. The server copy is pulled on start and every PULL_INTERVAL_MS. When
+// the server holds a newer revision, both sides are merged with
+// AchievmentsData::merge and the result is pushed again.
+pub struct RemoteProgressStorage {
+  window: Rc<web_sys::Window>,
+  local: LocalProgressStorage,
+  sync_state_key: String,
+  url: String,
+  sync_state: RefCell<SyncState>,
+  sync_events: Rc<EventManager<SyncEvent>>,
+  next_pull_ms: Cell<f64>,
+  request_in_flight: Cell<bool>,
+  local_generation: Cell<u64>,
+  // Local generation of the last reset.
+  reset_generation: Cell<u64>,
+  retry_delay_ms: Cell<f64>,
+  next_attempt_ms: Cell<f64>,
+}
+
+impl RemoteProgressStorage {
+  pub fn new(
+    window: Rc<web_sys::Window>,
+    local_storage: Rc<dyn KeyValueStorage>,
+    local_storage_key: String,
+    sync_config: &SyncConfig,
+  ) -> RemoteProgressStorage {
+    let sync_state_key = format!("{}.sync", local_storage_key);
+    let sync_state =
+      LocalStorageUtil::read(local_storage.as_ref(), &sync_state_key).unwrap_or_default();
+    let url = format!(
+      "{}/progress/{}/{}",
+      sync_config.url,
+      String::from(js_sys::encode_uri_component(&sync_config.sync_id)),
+      String::from(js_sys::encode_uri_component(&local_storage_key))
+    );
+    return RemoteProgressStorage {
+      window,
+      local: LocalProgressStorage::new(local_storage, local_storage_key),
+      sync_state_key,
+      url,
+      sync_state: RefCell::new(sync_state),
+      sync_events: EventManager::new(),
+      next_pull_ms: Cell::new(0.0),
+      request_in_flight: Cell::new(false),
+      local_generation: Cell::new(0),
+      reset_generation: Cell::new(0),
+      retry_delay_ms: Cell::new(MIN_RETRY_DELAY_MS),
+      next_attempt_ms: Cell::new(0.0),
+    };
+  }
+
+  fn store_sync_state(&self) {
+    LocalStorageUtil::write(
+      self.local.local_storage.as_ref(),
+      &self.sync_state_key,
+      self.sync_state.borrow().deref(),
+    );
+  }
+
+  fn on_request_succeeded(&self) {
+    self.retry_delay_ms.set(MIN_RETRY_DELAY_MS);
+    self.next_attempt_ms.set(0.0);
+  }
+
+  fn on_request_failed(&self, error: &str) {
+    console_log!("progress sync failed: {}", error);
+    let retry_delay_ms = self.retry_delay_ms.get();
+    self
+      .next_attempt_ms
+      .set(js_sys::Date::now() + retry_delay_ms);
+    self
+      .retry_delay_ms
+      .set((retry_delay_ms * 2.0).min(MAX_RETRY_DELAY_MS));
+  }
+
+  // Merges the server copy into the local one. Returns the merged progress,
+  // or None after a reset, which only takes the server revision so the next
+  // push replaces the server copy.
+  fn merge_remote(
+    &self,
+    remote_progress: RemoteProgress,
+  ) -> Result<Option<AchievmentsData>, String> {
+    if self.sync_state.borrow().reset_pending {
+      {
+        let mut sync_state = self.sync_state.borrow_mut();
+        sync_state.revision = Some(remote_progress.revision);
+        sync_state.pending = true;
+      }
+      self.store_sync_state();
+      return Ok(None);
+    }
+    let remote_data: AchievmentsData = LocalStorageUtil::decode(remote_progress.data)?;
+    let mut merged_data = self.local.load();
+    merged_data.merge(&remote_data);
+    self.local.store(&merged_data);
+    {
+      let mut sync_state = self.sync_state.borrow_mut();
+      sync_state.revision = Some(remote_progress.revision);
+      sync_state.pending = sync_state.pending || merged_data != remote_data;
+    }
+    self.store_sync_state();
+    return Ok(Some(merged_data));
+  }
+
+  fn process_pulled(&self, response: HttpResponse) -> Result<Option<AchievmentsData>, String> {
+    return match response.status {
+      200 => {
+        let remote_progress: RemoteProgress =
+          serde_json::from_str(&response.body).map_err(|error| error.to_string())?;
+        self.merge_remote(remote_progress)
+      }
+      404 => {
+        {
+          let mut sync_state = self.sync_state.borrow_mut();
+          sync_state.revision = None;
+          sync_state.pending = true;
+        }
+        self.store_sync_state();
+        Ok(None)
+      }
+      status => Err(format!("unexpected status {} on pull", status)),
+    };
+  }
+
+  fn process_pushed(
+    &self,
+    generation: u64,
+    response: HttpResponse,
+  ) -> Result<Option<AchievmentsData>, String> {
+    return match response.status {
+      200 => {
+        let push_accepted: PushAccepted =
+          serde_json::from_str(&response.body).map_err(|error| error.to_string())?;
+        {
+          let mut sync_state = self.sync_state.borrow_mut();
+          sync_state.revision = Some(push_accepted.revision);
+          // Changes stored while the request was in flight still need a push.
+          sync_state.pending = generation != self.local_generation.get();
+          if generation >= self.reset_generation.get() {
+            sync_state.reset_pending = false;
+          }
+        }
+        self.store_sync_state();
+        Ok(None)
+      }
+      409 => {
+        let remote_progress: RemoteProgress =
+          serde_json::from_str(&response.body).map_err(|error| error.to_string())?;
+        self.merge_remote(remote_progress)
+      }
+      status => Err(format!("unexpected status {} on push", status)),
+    };
+  }
+
+  fn send_pull(&self) {
+    HttpUtil::send_json(
+      &self.window,
+      "GET",
+      &self.url,
+      None,
+      self.sync_events.clone(),
+      Box::new(SyncEvent::Pulled),
+    );
+  }
+
+  fn send_push(&self) {
+    let generation = self.local_generation.get();
+    let body = serde_json::json!({
+      "base_revision": self.sync_state.borrow().revision,
+      "data": LocalStorageUtil::encode(&self.local.load()),
+    });
+    HttpUtil::send_json(
+      &self.window,
+      "PUT",
+      &self.url,
+      Some(body.to_string()),
+      self.sync_events.clone(),
+      Box::new(move |result| SyncEvent::Pushed(generation, result)),
+    );
+  }
+}
+
+impl ProgressStorage for RemoteProgressStorage {
+  fn load(&self) -> AchievmentsData {
+    return self.local.load();
+  }
+
+  fn store(&self, achievments_data: &AchievmentsData) {
+    self.local.store(achievments_data);
+    self.local_generation.set(self.local_generation.get() + 1);
+    self.sync_state.borrow_mut().pending = true;
+    self.store_sync_state();
+  }
+
+  fn reset(&self, achievments_data: &AchievmentsData) {
+    self.store(achievments_data);
+    self.reset_generation.set(self.local_generation.get());
+    self.sync_state.borrow_mut().reset_pending = true;
+    self.store_sync_state();
+  }
+
+  fn update(&self) -> Option<AchievmentsData> {
+    let mut result = None;
+    while let Some(sync_event) = self.sync_events.consume_event() {
+      self.request_in_flight.set(false);
+      let processed = match sync_event {
+        SyncEvent::Pulled(response) => response.and_then(|response| {
+          let processed = self.process_pulled(response);
+          if processed.is_ok() {
+            self
+              .next_pull_ms
+              .set(js_sys::Date::now() + PULL_INTERVAL_MS);
+          }
+          processed
+        }),
+        SyncEvent::Pushed(generation, response) => {
+          response.and_then(|response| self.process_pushed(generation, response))
+        }
+      };
+      match processed {
+        Ok(achievments_data) => {
+          self.on_request_succeeded();
+          if achievments_data.is_some() {
+            result = achievments_data;
+          }
+        }
+        Err(error) => self.on_request_failed(&error),
+      }
+    }
+
+    let now_ms = js_sys::Date::now();
+    if self.request_in_flight.get() || now_ms < self.next_attempt_ms.get() {
+      return result;
+    }
+    if now_ms >= self.next_pull_ms.get() {
+      self.request_in_flight.set(true);
+      self.send_pull();
+    } else if self.sync_state.borrow().pending {
+      self.request_in_flight.set(true);
+      self.send_push();
+    }
+    return result;
+  }
+}
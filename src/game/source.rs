@@ -20,7 +20,6 @@ pub struct Source {
   source_texture: Rc<Texture>,
   source_empty_texture: Rc<Texture>,
   state_history: StateHistory<SourceState>,
-  text_cache: Rc<TextCache>,
 }
 
 pub struct SourceState {
@@ -63,7 +62,6 @@ impl Source {
       },
 
       state_history: state_history,
-      text_cache: TextCache::new(),
     });
   }
 
@@ -121,7 +119,8 @@ impl EntityTrait<Context> for Source {
         source: DrawSource::Texture(self.source_empty_texture.clone()),
         position: self.position,
         size: size,
-        depth: context.draw_depths.source + 0.1,
+        layer: context.draw_layers.source_back,
+        depth: 0.0,
         optional: DrawImageOptionalArgs {
           opacity: context.stage_opacity.get(),
           ..Default::default()
@@ -142,7 +141,8 @@ impl EntityTrait<Context> for Source {
           y: self.position.y + ((total_height - size.y) / 2.0),
         },
         size: size,
-        depth: context.draw_depths.source,
+        layer: context.draw_layers.source,
+        depth: 0.0,
         optional: DrawImageOptionalArgs {
           opacity: context.stage_opacity.get(),
           partial_region_offset: F2 {
@@ -168,13 +168,15 @@ impl EntityTrait<Context> for Source {
         )),
         position: self.position + F2 { x: 0.0, y: 0.040 },
         font_size: 1.1 * SOURCE_DRAW_RADIUS,
-        depth: context.draw_depths.source - 0.1,
+        layer: context.draw_layers.source_label,
+        depth: 0.0,
         optional: DrawStringOptionalArgs {
           alignment: TextAlignment::Center,
           opacity: context.stage_opacity.get(),
           border: true,
           // border_scale: 4.0,
-          text_cache: Some(self.text_cache.clone()),
+          // The amount changes every frame while painting.
+          font_atlas: Some(context.texture_manager.font_oregano.clone()),
           ..Default::default()
         },
       },
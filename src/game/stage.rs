@@ -203,6 +203,14 @@ impl Stage {
     return 1;
   }
 
+  pub fn get_paint_used(&self) -> F1 {
+    let initial_paint_amount = self.get_total_initial_paint_amount();
+    if initial_paint_amount <= 0.0 {
+      return 0.0;
+    }
+    return 1.0 - self.get_total_current_paint_amount() / initial_paint_amount;
+  }
+
   fn get_max_score(&self) -> F1 {
     if self.record == -1.0 {
       return 20000.0;
@@ -0,0 +1,233 @@
+use crate::engine::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum ReplayTouchType {
+  Pressed,
+  Moved,
+  Released,
+}
+
+impl ReplayTouchType {
+  pub fn from_touch_type(touch_type: &TouchType) -> ReplayTouchType {
+    return match touch_type {
+      TouchType::Pressed => ReplayTouchType::Pressed,
+      TouchType::Moved => ReplayTouchType::Moved,
+      TouchType::Released => ReplayTouchType::Released,
+    };
+  }
+
+  pub fn to_touch_type(&self) -> TouchType {
+    return match self {
+      ReplayTouchType::Pressed => TouchType::Pressed,
+      ReplayTouchType::Moved => TouchType::Moved,
+      ReplayTouchType::Released => TouchType::Released,
+    };
+  }
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ReplayInput {
+  // Position in game viewport coordinates, as passed to GameMode::process_touch.
+  Touch {
+    x: F1,
+    y: F1,
+    touch_type: ReplayTouchType,
+  },
+  Undo,
+  // Simulation steps run since the previous input, paused or not, until the
+  // stage is finished. See GameMode::start_frame.
+  Steps {
+    count: u32,
+  },
+}
+
+impl ReplayInput {
+  pub fn from_game_touch(game_touch: &GameTouch) -> ReplayInput {
+    return ReplayInput::Touch {
+      x: game_touch.position.x,
+      y: game_touch.position.y,
+      touch_type: ReplayTouchType::from_touch_type(&game_touch.touch_type),
+    };
+  }
+
+  pub fn to_game_touch(&self) -> Option<GameTouch> {
+    return match self {
+      ReplayInput::Touch { x, y, touch_type } => Some(GameTouch {
+        id: 0,
+        position: F2 { x: *x, y: *y },
+        touch_type: touch_type.to_touch_type(),
+      }),
+      _ => None,
+    };
+  }
+}
+
+// Receives the steps of a replay in the order they were recorded.
+pub trait ReplayTarget {
+  fn advance(&mut self, steps: u32);
+  fn touch(&mut self, game_touch: &mut GameTouch);
+  fn undo(&mut self);
+}
+
+// Every input of one attempt at a stage, with the simulation steps between
+// them. The simulation only depends on these, so a target that drives GameMode
+// the way IngameUiRoot does ends the replay with the same score.
+#[derive(Serialize, Deserialize, Clone, Default, PartialEq, Debug)]
+pub struct StageReplay {
+  pub book_number: usize,
+  pub stage_number: usize,
+  // Seed of the random values of the attempt, like the particles of the brush.
+  pub seed: u64,
+  pub steps: Vec<ReplayInput>,
+}
+
+// Version 1 had no frame times, and version 2 recorded the time of each frame
+// instead of simulation steps. Neither can be played back.
+impl VersionedData for StageReplay {
+  const VERSION: u32 = 3;
+}
+
+impl StageReplay {
+  pub fn new(book_number: usize, stage_number: usize, seed: u64) -> StageReplay {
+    return StageReplay {
+      book_number,
+      stage_number,
+      seed,
+      steps: Vec::new(),
+    };
+  }
+
+  // Consecutive steps are merged, so a replay grows with the inputs rather
+  // than the time played. An update without steps is still kept after an
+  // input, as moving sources save their undo state in it.
+  pub fn add_step(&mut self, input: ReplayInput) {
+    if let ReplayInput::Steps { count } = input {
+      if let Some(ReplayInput::Steps { count: last_count }) = self.steps.last_mut() {
+        *last_count += count;
+        return;
+      }
+    }
+    self.steps.push(input);
+  }
+
+  pub fn play(&self, target: &mut dyn ReplayTarget) {
+    for step in self.steps.iter() {
+      match step {
+        ReplayInput::Steps { count } => target.advance(*count),
+        ReplayInput::Undo => target.undo(),
+        ReplayInput::Touch { .. } => target.touch(&mut step.to_game_touch().unwrap()),
+      }
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  // A paw that circles while time passes, like a MovingSource, and scores
+  // its distance from each press. Undo drops the latest press.
+  #[derive(Default)]
+  struct CirclingPaw {
+    time_ms: F1,
+    press_scores: Vec<F1>,
+    replay: StageReplay,
+  }
+
+  impl CirclingPaw {
+    fn get_score(&self) -> F1 {
+      return self.press_scores.iter().sum();
+    }
+  }
+
+  impl ReplayTarget for CirclingPaw {
+    fn advance(&mut self, steps: u32) {
+      self.replay.add_step(ReplayInput::Steps { count: steps });
+      for _ in 0..steps {
+        self.time_ms += 1000.0 / 60.0;
+      }
+    }
+
+    fn touch(&mut self, game_touch: &mut GameTouch) {
+      self
+        .replay
+        .add_step(ReplayInput::from_game_touch(game_touch));
+      if game_touch.touch_type == TouchType::Pressed {
+        let angle = self.time_ms * 0.003;
+        let paw = F2 {
+          x: angle.cos(),
+          y: angle.sin(),
+        };
+        self
+          .press_scores
+          .push(1000.0 - F2::distance2(&paw, &game_touch.position) * 100.0);
+      }
+    }
+
+    fn undo(&mut self) {
+      self.replay.add_step(ReplayInput::Undo);
+      self.press_scores.pop();
+    }
+  }
+
+  fn press(x: F1, y: F1) -> GameTouch {
+    return GameTouch {
+      id: 0,
+      position: F2 { x, y },
+      touch_type: TouchType::Pressed,
+    };
+  }
+
+  #[test]
+  fn test_play_reproduces_score() {
+    let mut attempt = CirclingPaw::default();
+    for (i, steps) in [1, 2, 0, 3, 1, 5].iter().enumerate() {
+      attempt.advance(*steps);
+      attempt.advance(1);
+      attempt.touch(&mut press(0.1 * i as F1, 0.5));
+      if i == 3 {
+        attempt.undo();
+      }
+    }
+
+    let mut replayed = CirclingPaw::default();
+    attempt.replay.play(&mut replayed);
+    assert_eq!(replayed.press_scores.len(), 5);
+    assert_eq!(replayed.get_score(), attempt.get_score());
+    assert_eq!(replayed.replay, attempt.replay);
+  }
+
+  #[test]
+  fn test_replay_json_format() {
+    let mut replay = StageReplay::new(1, 2, 7);
+    replay.add_step(ReplayInput::Steps { count: 1 });
+    replay.add_step(ReplayInput::Steps { count: 2 });
+    replay.add_step(ReplayInput::Touch {
+      x: 0.5,
+      y: 0.25,
+      touch_type: ReplayTouchType::Pressed,
+    });
+    replay.add_step(ReplayInput::Undo);
+    let value = serde_json::to_value(&replay).unwrap();
+    assert_eq!(
+      value,
+      serde_json::json!({
+        "book_number": 1,
+        "stage_number": 2,
+        "seed": 7,
+        "steps": [
+          { "type": "steps", "count": 3 },
+          { "type": "touch", "x": 0.5, "y": 0.25, "touch_type": "pressed" },
+          { "type": "undo" }
+        ]
+      })
+    );
+    assert_eq!(
+      serde_json::from_value::<StageReplay>(value).unwrap(),
+      replay
+    );
+  }
+}
@@ -0,0 +1,99 @@
+use crate::context::Context;
+use crate::game::game_mode::*;
+use crate::game::stage_replay::*;
+use crate::game_ui::IngameUiEvent;
+use crate::*;
+
+// Plays a stage without a page around it, driving GameMode the way
+// IngameUiRoot does.
+pub struct StageRunner {
+  pub context: Context,
+  game_root_entity_manager: EntityManager<GameMode>,
+  pub game_mode: Rc<GameMode>,
+}
+
+impl StageRunner {
+  pub fn new(book: Book, stage_number: usize, seed: u64) -> StageRunner {
+    let (mut context, _) = Context::new_headless(&F2 { x: 240.0, y: 360.0 });
+    let events: Rc<EventManager<IngameUiEvent>> = EventManager::new();
+    let game_root_entity_manager = EntityManager::new_root_manager();
+    let game_mode = GameMode::new(&mut context, events);
+    game_root_entity_manager.add(game_mode.clone());
+    game_mode.start_puzzle(&mut context, book, stage_number, seed);
+    return StageRunner {
+      context,
+      game_root_entity_manager,
+      game_mode,
+    };
+  }
+}
+
+impl ReplayTarget for StageRunner {
+  fn advance(&mut self, steps: u32) {
+    self.context.next_frame(steps as F1 * SIMULATION_STEP_MS);
+    self.game_mode.start_frame_steps(steps);
+    self.game_root_entity_manager.update(&mut self.context);
+  }
+
+  fn touch(&mut self, game_touch: &mut GameTouch) {
+    self.game_mode.process_touch(&mut self.context, game_touch);
+  }
+
+  fn undo(&mut self) {
+    self.game_mode.undo(&self.game_root_entity_manager);
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn drag(runner: &mut StageRunner, from: F2, to: F2, touch_count: usize) {
+    for i in 0..=touch_count {
+      runner.advance(1 + (i % 2) as u32);
+      runner.touch(&mut GameTouch {
+        id: 0,
+        position: from + (to - from) * (i as F1 / touch_count as F1),
+        touch_type: if i == 0 {
+          TouchType::Pressed
+        } else if i == touch_count {
+          TouchType::Released
+        } else {
+          TouchType::Moved
+        },
+      });
+    }
+  }
+
+  // Paints from the source to the top goal; the moving source picks up the
+  // paint and fills the other two goals on its way around.
+  #[test]
+  fn test_replay_reaches_same_score() {
+    let mut attempt = StageRunner::new(Book::Wolf, 0, 12345);
+    let source = F2 { x: 0.506, y: 0.46 };
+    let top_goal = F2 { x: 0.504, y: 0.185 };
+    drag(&mut attempt, source, F2 { x: 0.3, y: 0.4 }, 10);
+    attempt.undo();
+    drag(&mut attempt, source, top_goal, 40);
+    for _ in 0..2000 {
+      if attempt.game_mode.finished.get() {
+        break;
+      }
+      attempt.advance(3);
+    }
+    assert!(attempt.game_mode.finished.get());
+
+    let replay = attempt.game_mode.replay.borrow().clone();
+    let mut replayed = StageRunner::new(Book::Wolf, 0, replay.seed);
+    replay.play(&mut replayed);
+    assert!(replayed.game_mode.finished.get());
+    let attempt_stage = attempt.game_mode.stage.borrow();
+    let replayed_stage = replayed.game_mode.stage.borrow();
+    assert_eq!(
+      replayed_stage.get_current_score(),
+      attempt_stage.get_current_score()
+    );
+    assert_eq!(replayed_stage.get_stars(), attempt_stage.get_stars());
+    assert_eq!(*replayed.game_mode.replay.borrow(), replay);
+  }
+}
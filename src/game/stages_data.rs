@@ -1,3 +1,4 @@
+use crate::context::GAME_VIEWPORT_YX_RATIO;
 use crate::engine::*;
 use crate::game::paint_color::*;
 use serde::{Deserialize, Serialize};
@@ -24,6 +25,11 @@ pub struct StageDataRaw {
   stage_objects: String,
   user: String,
   version: i32,
+  // In game viewport widths, the size of the frame when missing.
+  #[serde(default)]
+  width: F1,
+  #[serde(default)]
+  height: F1,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -90,6 +96,8 @@ pub struct StageData {
   pub mirrors: Vec<MirrorData>,
   pub moving_sources: Vec<MovingSourceData>,
   pub portals: Vec<PortalData>,
+  // Area the game camera can show, from (0, 0).
+  pub size: F2,
 }
 
 fn get_paint_color_from_name(paint_color_name: &String) -> PaintColor {
@@ -207,6 +215,18 @@ impl AllStagesData {
         mirrors: mirrors,
         moving_sources: moving_sources,
         portals: portals,
+        size: F2 {
+          x: if stage_data_raw.width > 0.0 {
+            stage_data_raw.width
+          } else {
+            1.0
+          },
+          y: if stage_data_raw.height > 0.0 {
+            stage_data_raw.height
+          } else {
+            GAME_VIEWPORT_YX_RATIO
+          },
+        },
       };
 
       stages_data.insert(stage_id, stage);
@@ -1,6 +1,11 @@
+use crate::context::UiLayout;
+use crate::context::LANDSCAPE_HUD_WIDTH;
 use crate::engine::*;
 use crate::*;
 
+// Center of the star row in the portrait layout, in ui viewport coordinates.
+const ROW_CENTER: F2 = F2 { x: 0.17, y: 0.07 };
+
 pub struct StarBar {
   entity_base: EntityBase,
   star1: Rc<Star>,
@@ -13,9 +18,9 @@ impl StarBar {
   pub fn new(context: &Context) -> Rc<StarBar> {
     let entity_base = EntityBase::new();
     let stars_manager = EntityManager::new_within_parent_entity(&entity_base);
-    let star1 = Star::new(F2 { x: 0.27, y: 0.07 }, context);
-    let star2 = Star::new(F2 { x: 0.17, y: 0.07 }, context);
-    let star3 = Star::new(F2 { x: 0.07, y: 0.07 }, context);
+    let star1 = Star::new(ROW_CENTER + F2 { x: 0.1, y: 0.0 }, context);
+    let star2 = Star::new(ROW_CENTER, context);
+    let star3 = Star::new(ROW_CENTER - F2 { x: 0.1, y: 0.0 }, context);
     stars_manager.add(star1.clone());
     stars_manager.add(star2.clone());
     stars_manager.add(star3.clone());
@@ -64,7 +69,27 @@ impl EntityTrait<Context> for StarBar {
     return false;
   }
 
-  fn update(&self, _context: &mut Context) {}
+  fn update(&self, context: &mut Context) {
+    let offset = match context.ui_layout {
+      UiLayout::Portrait => context.get_hud_origin(),
+      UiLayout::Landscape { .. } => {
+        context.get_hud_origin()
+          + F2 {
+            x: LANDSCAPE_HUD_WIDTH * 0.5 - ROW_CENTER.x,
+            y: 0.0,
+          }
+      }
+    };
+    self
+      .star1
+      .position
+      .set(ROW_CENTER + offset + F2 { x: 0.1, y: 0.0 });
+    self.star2.position.set(ROW_CENTER + offset);
+    self
+      .star3
+      .position
+      .set(ROW_CENTER + offset - F2 { x: 0.1, y: 0.0 });
+  }
 
   fn draw(&self, _context: &mut Context) {}
 }
@@ -72,7 +97,7 @@ impl EntityTrait<Context> for StarBar {
 pub struct Star {
   effect_manager: EffectManager,
   entity_base: EntityBase,
-  position: F2,
+  position: Cell<F2>,
   size: F2,
   fill: Cell<F1>,
   bright_opacity: Shared<F1>,
@@ -109,7 +134,7 @@ impl Star {
     return Rc::new(Star {
       effect_manager,
       entity_base: EntityBase::new(),
-      position: position,
+      position: Cell::new(position),
       size: size,
       fill: Cell::new(1.0),
       bright_opacity: bright_opacity,
@@ -170,9 +195,10 @@ impl EntityTrait<Context> for Star {
   fn draw(&self, context: &mut Context) {
     context.draw_ui_viewport(DrawImageArgs {
       source: DrawSource::Texture(context.texture_manager.star_l.clone()),
-      position: self.position,
+      position: self.position.get(),
       size: self.size,
-      depth: context.draw_depths.ui,
+      layer: context.draw_layers.hud,
+      depth: 0.0,
       optional: DrawImageOptionalArgs {
         ..Default::default()
       },
@@ -181,14 +207,15 @@ impl EntityTrait<Context> for Star {
     context.draw_ui_viewport(DrawImageArgs {
       source: DrawSource::Texture(context.texture_manager.star.clone()),
       position: F2 {
-        x: self.position.x - self.size.x * 0.5,
-        y: self.position.y,
+        x: self.position.get().x - self.size.x * 0.5,
+        y: self.position.get().y,
       },
       size: F2 {
         x: self.size.x * self.fill.get(),
         y: self.size.y,
       },
-      depth: context.draw_depths.ui - 0.1,
+      layer: context.draw_layers.hud,
+      depth: 0.0,
       optional: DrawImageOptionalArgs {
         anchor_point: F2 { x: 0.0, y: 0.5 },
         partial_region_offset: F2 { x: 0.0, y: 0.0 },
@@ -203,9 +230,10 @@ impl EntityTrait<Context> for Star {
 
     context.draw_ui_viewport(DrawImageArgs {
       source: DrawSource::Texture(context.texture_manager.star_active_bright.clone()),
-      position: self.position,
+      position: self.position.get(),
       size: self.glow_size.get(),
-      depth: context.draw_depths.ui - 0.2,
+      layer: context.draw_layers.hud,
+      depth: 0.0,
       optional: DrawImageOptionalArgs {
         opacity: self.bright_opacity.get(),
         subpixel_precision: true,
@@ -0,0 +1,142 @@
+use crate::context::Context;
+use crate::game::game_mode::GameMode;
+use crate::game::paint_point::PaintPoint;
+use crate::*;
+
+const TIME_LAPSE_DURATION_MS: F1 = 3000.0;
+// Pause before each new stroke, in the time taken to draw a point.
+const STROKE_PAUSE_POINTS: F1 = 12.0;
+// The finished painting is shown for a while before starting over.
+const FINISHED_HOLD_MS: F1 = 1500.0;
+const TIME_LAPSE_OPACITY: F1 = 0.6;
+
+// Redraws the strokes of the stage in the order they were painted, at an
+// accelerated speed.
+pub struct TimeLapse {
+  points: RefCell<Vec<Rc<PaintPoint>>>,
+  // Time at which each point appears.
+  schedule: RefCell<Vec<F1>>,
+  time_ms: Cell<F1>,
+  drawn_points: Cell<usize>,
+  cached_canvas: CachedCanvasBackend,
+  active: Cell<bool>,
+}
+
+impl TimeLapse {
+  pub fn new(context: &Context) -> TimeLapse {
+    return TimeLapse {
+      points: RefCell::new(Vec::new()),
+      schedule: RefCell::new(Vec::new()),
+      time_ms: Cell::new(0.0),
+      drawn_points: Cell::new(0),
+      cached_canvas: CachedCanvasBackend::new(
+        context
+          .draw_manager
+          .create_render_target(&context.get_canvas_size()),
+      ),
+      active: Cell::new(false),
+    };
+  }
+
+  pub fn start(&self, game_mode: &GameMode) {
+    let point_grid = game_mode.point_grid.borrow();
+    let points = point_grid.get_points_in_creation_order();
+    let checkpoints: Vec<u32> = points.iter().map(|point| point.checkpoint).collect();
+    self.schedule.replace(get_time_lapse_schedule(&checkpoints));
+    self.points.replace(points);
+    self.restart();
+    self.active.set(true);
+  }
+
+  pub fn stop(&self) {
+    self.active.set(false);
+    self.points.replace(Vec::new());
+  }
+
+  fn restart(&self) {
+    self.time_ms.set(0.0);
+    self.drawn_points.set(0);
+    self.cached_canvas.clear_cache_required.set(true);
+  }
+
+  pub fn update(&self, context: &Context) {
+    if !self.active.get() {
+      return;
+    }
+    self.time_ms.set(self.time_ms.get() + context.get_dt());
+    if self.time_ms.get() > TIME_LAPSE_DURATION_MS + FINISHED_HOLD_MS {
+      self.restart();
+    }
+  }
+
+  pub fn draw(&self, context: &mut Context) {
+    if !self.active.get() {
+      return;
+    }
+    self
+      .cached_canvas
+      .check_world_viewport_changed(&context.game_viewport);
+    if self.cached_canvas.check_clear_cache() {
+      self.drawn_points.set(0);
+    }
+
+    // Only the points that appeared since the last frame are drawn.
+    let points = self.points.borrow();
+    let schedule = self.schedule.borrow();
+    let mut drawn_points = self.drawn_points.get();
+    while drawn_points < points.len() && schedule[drawn_points] <= self.time_ms.get() {
+      points[drawn_points].draw_into_cached_canvas(context, &self.cached_canvas);
+      drawn_points += 1;
+    }
+    self.drawn_points.set(drawn_points);
+
+    context.draw_manager.draw_viewport(
+      &context.game_viewport,
+      DrawImageArgs {
+        source: DrawSource::RenderTarget(self.cached_canvas.render_target.clone()),
+        position: F2 { x: 0.0, y: 0.0 },
+        size: self.cached_canvas.get_world_cache_size(),
+        layer: context.draw_layers.time_lapse,
+        depth: 0.0,
+        optional: DrawImageOptionalArgs {
+          opacity: TIME_LAPSE_OPACITY,
+          anchor_point: F2 { x: 0.0, y: 0.0 },
+          ..Default::default()
+        },
+      },
+    );
+  }
+}
+
+// Spreads the points over the time-lapse duration, pausing before every new
+// stroke (a change of checkpoint).
+pub fn get_time_lapse_schedule(checkpoints: &[u32]) -> Vec<F1> {
+  let mut steps = Vec::with_capacity(checkpoints.len());
+  let mut step = 0.0;
+  for (i, checkpoint) in checkpoints.iter().enumerate() {
+    if i > 0 && *checkpoint != checkpoints[i - 1] {
+      step += STROKE_PAUSE_POINTS;
+    }
+    steps.push(step);
+    step += 1.0;
+  }
+  let step_ms = TIME_LAPSE_DURATION_MS / step.max(1.0);
+  return steps.iter().map(|step| step * step_ms).collect();
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_time_lapse_schedule() {
+    let schedule = get_time_lapse_schedule(&[1, 1, 2, 2]);
+    // 4 points and one stroke pause.
+    let step_ms = TIME_LAPSE_DURATION_MS / (4.0 + STROKE_PAUSE_POINTS);
+    assert_eq!(schedule.len(), 4);
+    assert_eq!(schedule[0], 0.0);
+    assert_eq!(schedule[1], step_ms);
+    assert_eq!(schedule[2], (2.0 + STROKE_PAUSE_POINTS) * step_ms);
+    assert_eq!(schedule[3], (3.0 + STROKE_PAUSE_POINTS) * step_ms);
+  }
+}
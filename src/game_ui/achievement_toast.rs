@@ -0,0 +1,120 @@
+use crate::context::Context;
+use crate::game::achievement_tracker::AchievementData;
+use crate::*;
+use std::collections::VecDeque;
+
+#[derive(Clone, Copy, Debug)]
+enum AchievementToastEvent {
+  EffectToastEnd,
+}
+
+// Banner shown on top of whatever page is active when an achievement unlocks.
+// Unlocks happening close together are queued and shown one at a time.
+pub struct AchievementToast {
+  container: Rc<UiContainer>,
+  effect_manager: EffectManager,
+  effect_toast: Rc<Effect<ChainedEffect>>,
+  text_title: Rc<UiText>,
+  pending: RefCell<VecDeque<AchievementData>>,
+  showing: Cell<bool>,
+  events: Rc<EventManager<AchievementToastEvent>>,
+}
+
+impl AchievementToast {
+  pub fn new(context: &Context) -> AchievementToast {
+    let effect_manager = EffectManager::new();
+    let events = EventManager::new();
+
+    let container = UiContainer::new();
+    container.set_position(F2 {
+      x: 0.5,
+      y: 60.0 / 480.0,
+    });
+    container.set_layer(context.draw_layers.toast);
+    container.set_opacity(0.0);
+    container.set_visible(false);
+
+    let sprite_background = Rc::new(UiSprite::new(context.texture_manager.gui_btn_wood.clone()));
+    sprite_background.set_size(F2 {
+      x: 360.0 / 480.0,
+      y: 90.0 / 480.0,
+    });
+    sprite_background.set_depth(0.1);
+    container.add_child(sprite_background);
+
+    let text_header = UiText::new();
+    text_header.set_text(String::from("Achievement unlocked!"));
+    text_header.use_text_cache();
+    text_header.set_font_size(24.0 / 480.0);
+    text_header.set_alignment(TextAlignment::Center);
+    text_header.set_border(true);
+    text_header.set_position_y(-16.0 / 480.0);
+    container.add_child(text_header);
+
+    let text_title = UiText::new();
+    text_title.set_font_size(40.0 / 480.0);
+    text_title.set_alignment(TextAlignment::Center);
+    text_title.set_border(true);
+    text_title.set_position_y(22.0 / 480.0);
+    container.add_child(text_title.clone());
+
+    let effect_toast = Effect::new_within_effect_manager(ChainedEffect::new(), &effect_manager);
+    Effect::new_within_chained_effect(
+      VectorAffectorF1::new(container.get_opacity()).set_start_and_end(0.0, 1.0, 300.0),
+      &effect_toast,
+    );
+    Effect::new_within_chained_effect(WaitAffector::new(2500.0), &effect_toast);
+    Effect::new_within_chained_effect(
+      VectorAffectorF1::new(container.get_opacity()).set_start_and_end(1.0, 0.0, 300.0),
+      &effect_toast,
+    );
+    effect_toast.add_event_on_end(events.clone(), AchievementToastEvent::EffectToastEnd);
+
+    return AchievementToast {
+      container,
+      effect_manager,
+      effect_toast,
+      text_title,
+      pending: RefCell::new(VecDeque::new()),
+      showing: Cell::new(false),
+      events,
+    };
+  }
+
+  pub fn update(&self, context: &mut Context) {
+    while let Some(achievement) = context.achievement_tracker.unlocked_events.consume_event() {
+      self.pending.borrow_mut().push_back(achievement);
+    }
+
+    while let Some(event) = self.events.consume_event() {
+      match event {
+        AchievementToastEvent::EffectToastEnd => {
+          self.showing.set(false);
+          self.container.set_visible(false);
+        }
+      }
+    }
+
+    if !self.showing.get() {
+      let next = self.pending.borrow_mut().pop_front();
+      if let Some(achievement) = next {
+        self.text_title.set_text(achievement.title);
+        self.container.set_visible(true);
+        self.showing.set(true);
+        self.effect_toast.start();
+      }
+    }
+
+    self.update_effects(context);
+  }
+
+  pub fn draw(&self, context: &mut Context) {
+    self.container.draw(context);
+  }
+}
+
+impl EffectManagerTrait<Context> for AchievementToast {
+  fn get_effect_manager(&self) -> Option<&EffectManager> {
+    return Some(&self.effect_manager);
+  }
+}
@@ -0,0 +1,202 @@
+use crate::context::Context;
+use crate::game_ui::*;
+use crate::*;
+
+const FIRST_ROW_POSITION_Y: F1 = 0.3;
+const ROW_HEIGHT: F1 = 0.11;
+
+#[derive(Clone, Copy)]
+enum AchievementsEvent {
+  BackButtonPressed,
+}
+
+struct AchievementRow {
+  text_title: Rc<UiText>,
+  text_status: Rc<UiText>,
+}
+
+pub struct AchievementsUiRoot {
+  container: Rc<UiContainer>,
+  rows: Vec<AchievementRow>,
+  text_unlocked_count: Rc<UiText>,
+  events: Rc<EventManager<AchievementsEvent>>,
+}
+
+fn format_unlock_date(timestamp_ms: f64) -> String {
+  let date = js_sys::Date::new(&wasm_bindgen::JsValue::from_f64(timestamp_ms));
+  return String::from(date.to_locale_date_string("en-US", &wasm_bindgen::JsValue::UNDEFINED));
+}
+
+impl AchievementsUiRoot {
+  pub fn new(context: &Context) -> Rc<AchievementsUiRoot> {
+    let screen_bottom_right = &context.get_ui_viewport_safe_bottom_right();
+    let container = UiContainer::new();
+    let events = EventManager::new();
+
+    let text_title = UiText::new();
+    text_title.set_text(String::from("Achievements"));
+    text_title.use_text_cache();
+    text_title.set_font_size(60.0 / 480.0);
+    text_title.set_alignment(TextAlignment::Center);
+    text_title.set_border(true);
+    text_title.set_position(F2 { x: 0.5, y: 0.1 });
+    container.add_child(text_title);
+
+    let text_unlocked_count = UiText::new();
+    text_unlocked_count.set_font_size(30.0 / 480.0);
+    text_unlocked_count.set_alignment(TextAlignment::Center);
+    text_unlocked_count.set_border(true);
+    text_unlocked_count.set_position(F2 { x: 0.5, y: 0.18 });
+    container.add_child(text_unlocked_count.clone());
+
+    let description_color = DrawColor {
+      r: 102,
+      g: 43,
+      b: 0,
+    };
+    let mut rows = vec![];
+    for (index, achievement) in context
+      .achievement_tracker
+      .get_catalogue()
+      .iter()
+      .enumerate()
+    {
+      let row_position_y = FIRST_ROW_POSITION_Y + ROW_HEIGHT * index as F1;
+
+      let text_title = UiText::new();
+      text_title.set_text(achievement.title.clone());
+      text_title.set_font_size(36.0 / 480.0);
+      text_title.set_alignment(TextAlignment::Left);
+      text_title.set_position(F2 {
+        x: 30.0 / 480.0,
+        y: row_position_y,
+      });
+      container.add_child(text_title.clone());
+
+      let text_description = UiText::new();
+      text_description.set_text(achievement.description.clone());
+      text_description.use_text_cache();
+      text_description.set_font_size(20.0 / 480.0);
+      text_description.set_alignment(TextAlignment::Left);
+      text_description.set_border(false);
+      text_description.set_color(description_color);
+      text_description.set_position(F2 {
+        x: 30.0 / 480.0,
+        y: row_position_y + 26.0 / 480.0,
+      });
+      container.add_child(text_description);
+
+      let text_status = UiText::new();
+      text_status.set_font_size(24.0 / 480.0);
+      text_status.set_alignment(TextAlignment::Right);
+      text_status.set_position(F2 {
+        x: 450.0 / 480.0,
+        y: row_position_y,
+      });
+      container.add_child(text_status.clone());
+
+      rows.push(AchievementRow {
+        text_title,
+        text_status,
+      });
+    }
+
+    let back_button = UiButton::new(
+      context.texture_manager.gui_btn_back.clone(),
+      context.texture_manager.gui_btn_back_pressed.clone(),
+    );
+    back_button.set_size_from_x(60.0 / 480.0);
+    back_button.set_position(F2 {
+      x: 40.0 / 480.0,
+      y: screen_bottom_right.y - 60.0 / 480.0,
+    });
+    back_button.set_event_on_released(events.clone(), AchievementsEvent::BackButtonPressed);
+    back_button.set_sound_on_released(context.audio_manager.click.clone());
+    container.add_child(back_button);
+
+    return Rc::new(AchievementsUiRoot {
+      container,
+      rows,
+      text_unlocked_count,
+      events,
+    });
+  }
+
+  fn refresh_achievements(&self, context: &mut Context) {
+    let mut unlocked_count = 0;
+    let catalogue = context.achievement_tracker.get_catalogue();
+    for (achievement, row) in catalogue.iter().zip(self.rows.iter()) {
+      match context
+        .achievments_manager
+        .get_achievement_unlock_timestamp(&achievement.id)
+      {
+        Some(timestamp_ms) => {
+          unlocked_count += 1;
+          row.text_title.set_border(true);
+          row.text_status.set_border(true);
+          row.text_status.set_text(format_unlock_date(timestamp_ms));
+        }
+        None => {
+          row.text_title.set_border(false);
+          row.text_status.set_border(false);
+          let status = match context
+            .achievement_tracker
+            .get_progress(achievement, &context.achievments_manager)
+          {
+            Some((current, target)) => format!("{}/{}", current, target),
+            None => String::from("Locked"),
+          };
+          row.text_status.set_text(status);
+        }
+      }
+    }
+    self
+      .text_unlocked_count
+      .set_text(format!("{} / {} unlocked", unlocked_count, catalogue.len()));
+  }
+}
+
+impl EffectManagerTrait<Context> for AchievementsUiRoot {
+  fn get_effect_manager(&self) -> Option<&EffectManager> {
+    return None;
+  }
+}
+
+impl UiElementTrait<Context> for AchievementsUiRoot {
+  fn get_ui_element(&self) -> &UiElement {
+    return self.container.get_ui_element();
+  }
+
+  fn update(&self, context: &mut Context) {
+    while let Some(event) = self.events.consume_event() {
+      match event {
+        AchievementsEvent::BackButtonPressed => {
+          context
+            .artificial_input_events
+            .add_event(InputEvent::BackButton);
+        }
+      }
+    }
+    self.container.update(context);
+  }
+
+  fn draw(&self, context: &mut Context) {
+    BackgroundWood::draw(context);
+    BackgroundBorders::draw(context);
+    self.container.draw(context);
+  }
+
+  fn get_touched_element(
+    &self,
+    context: &mut Context,
+    ui_touch: &UiTouch,
+  ) -> Option<Rc<dyn UiElementTrait<Context>>> {
+    return self.container.get_touched_element(context, ui_touch);
+  }
+}
+
+impl UiRootTrait<Context> for AchievementsUiRoot {
+  fn on_navigate_to(&self, context: &mut Context) {
+    self.refresh_achievements(context);
+  }
+}
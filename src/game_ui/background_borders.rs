@@ -1,10 +1,15 @@
 use crate::context::Context;
+use crate::context::UiLayout;
 use crate::*;
 
 pub struct BackgroundBorders {}
 
 impl BackgroundBorders {
   pub fn draw(context: &mut Context) {
+    // The wood covers the whole screen in the landscape layout.
+    if context.ui_layout != UiLayout::Portrait {
+      return;
+    }
     let size_gradient_border = context.ui_viewport.viewport_size_on_screen.x * 0.5;
     let size_black_border = (context.screen_size.x - context.ui_viewport.viewport_size_on_screen.x)
       / 2.0
@@ -18,7 +23,8 @@ impl BackgroundBorders {
         x: size_black_border,
         y: context.screen_size.y,
       },
-      depth: -100.0,
+      layer: context.draw_layers.black_border,
+      depth: 0.0,
       optional: DrawImageOptionalArgs {
         color: DrawColor { r: 0, g: 0, b: 0 },
         opacity: 1.0,
@@ -37,7 +43,8 @@ impl BackgroundBorders {
         x: size_black_border,
         y: context.screen_size.y,
       },
-      depth: -100.0,
+      layer: context.draw_layers.black_border,
+      depth: 0.0,
       optional: DrawImageOptionalArgs {
         color: DrawColor { r: 0, g: 0, b: 0 },
         opacity: 1.0,
@@ -57,7 +64,8 @@ impl BackgroundBorders {
           x: size_gradient_border,
           y: context.screen_size.y,
         },
-        depth: -120.0,
+        layer: context.draw_layers.black_border,
+        depth: 0.0,
         draw_color_start: DrawColor::new(&0, &0, &0),
         alpha_start: 1.0,
         draw_color_end: DrawColor::new(&0, &0, &0),
@@ -76,7 +84,8 @@ impl BackgroundBorders {
           x: size_gradient_border,
           y: context.screen_size.y,
         },
-        depth: -120.0,
+        layer: context.draw_layers.black_border,
+        depth: 0.0,
         draw_color_start: DrawColor::new(&0, &0, &0),
         alpha_start: 0.0,
         draw_color_end: DrawColor::new(&0, &0, &0),
@@ -91,7 +100,8 @@ impl BackgroundBorders {
         x: context.ui_viewport.viewport_position_on_screen.x,
         y: context.screen_size.y,
       },
-      depth: context.draw_depths.background + 0.1,
+      layer: context.draw_layers.board,
+      depth: 0.0,
       optional: DrawImageOptionalArgs {
         color: DrawColor { r: 0, g: 0, b: 0 },
         opacity: 0.05,
@@ -111,7 +121,8 @@ impl BackgroundBorders {
         x: context.ui_viewport.viewport_position_on_screen.x,
         y: context.screen_size.y,
       },
-      depth: context.draw_depths.background + 0.1,
+      layer: context.draw_layers.board,
+      depth: 0.0,
       optional: DrawImageOptionalArgs {
         color: DrawColor { r: 0, g: 0, b: 0 },
         opacity: 0.05,
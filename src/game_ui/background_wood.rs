@@ -1,4 +1,5 @@
 use crate::context::Context;
+use crate::context::UiLayout;
 use crate::*;
 
 pub struct BackgroundWood {}
@@ -11,8 +12,9 @@ impl BackgroundWood {
       source: DrawSource::Texture(context.texture_manager.background_wood.clone()),
       position: context.screen_size * 0.5,
       size: F2 {
-        x: if context.ui_viewport.viewport_size_on_screen.x + size_extra_background
-          < context.screen_size.x
+        x: if context.ui_layout == UiLayout::Portrait
+          && context.ui_viewport.viewport_size_on_screen.x + size_extra_background
+            < context.screen_size.x
         {
           context.ui_viewport.viewport_size_on_screen.x + size_extra_background
         } else {
@@ -20,7 +22,8 @@ impl BackgroundWood {
         },
         y: context.screen_size.y,
       },
-      depth: context.draw_depths.background + 0.1,
+      layer: context.draw_layers.board,
+      depth: 0.0,
       optional: DrawImageOptionalArgs::default(),
     });
   }
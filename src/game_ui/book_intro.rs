@@ -12,16 +12,14 @@ pub struct BookIntro {
   effect_manager: EffectManager,
 
   chat_char: Rc<UiSprite>,
-  chat_bar: Rc<UiSprite>,
+  chat_bar: Rc<UiNineSlice>,
 
   effect_show: Rc<Effect<SetEffect>>,
   effect_hide: Rc<Effect<SetEffect>>,
 
-  text_line1: Rc<UiText>,
-  text_line2: Rc<UiText>,
-  text_line3: Rc<UiText>,
+  text: Rc<UiText>,
 
-  phrases: RefCell<Vec<Vec<String>>>,
+  phrases: RefCell<Vec<String>>,
   phrase_shown: Cell<usize>,
 
   events: Rc<EventManager<BookIntroEvent>>,
@@ -42,7 +40,7 @@ impl BookIntro {
     let ctn_bottom = UiContainer::new();
     ctn_bottom.set_position(F2 {
       x: 0.0,
-      y: context.ui_viewport.screen_bottom_right_corner.y - 140.0 / 480.0,
+      y: context.get_ui_viewport_safe_bottom_right().y - 140.0 / 480.0,
     });
     touchable.add_child(ctn_bottom.clone());
 
@@ -58,8 +56,11 @@ impl BookIntro {
     ctn_text.set_position(F2 { x: -2.0, y: 0.0 });
     ctn_bottom.add_child(ctn_text.clone());
 
-    let chat_bar = Rc::new(UiSprite::new(
+    // Only the center of the bar is stretched, keeping its shaded borders.
+    let chat_bar = Rc::new(UiNineSlice::new(
       context.texture_manager.chat_bar_panda.clone(),
+      NineSliceMargins::new_uniform(40.0),
+      1.0 / 480.0,
     ));
     chat_bar.set_size(F2 {
       x: 2.0, // 580.0 / 480.0,
@@ -90,38 +91,19 @@ impl BookIntro {
     btn_next.container.add_child(btn_next_text.clone());
     ctn_text.add_child(btn_next.clone());
 
-    let text_line1 = UiText::new();
-    text_line1.set_font_size(0.11);
-    text_line1.set_alignment(TextAlignment::Left);
-    text_line1.set_position(F2 {
-      x: -0.45,
-      y: -80.0 / 480.0 + 0.06,
-    });
-    text_line1.set_border(true);
-    text_line1.set_depth(-0.1);
-    ctn_text.add_child(text_line1.clone());
-
-    let text_line2 = UiText::new();
-    text_line2.set_font_size(0.11);
-    text_line2.set_alignment(TextAlignment::Left);
-    text_line2.set_position(F2 {
+    // Up to three lines, wrapped beside the character.
+    let text = UiText::new();
+    text.set_font_size(0.11);
+    text.set_alignment(TextAlignment::Left);
+    text.set_max_width(0.62);
+    text.set_line_spacing(0.84);
+    text.set_position(F2 {
       x: -0.45,
       y: -80.0 / 480.0 + 0.06 + 40.0 / 480.0,
     });
-    text_line2.set_border(true);
-    text_line2.set_depth(-0.1);
-    ctn_text.add_child(text_line2.clone());
-
-    let text_line3 = UiText::new();
-    text_line3.set_font_size(0.11);
-    text_line3.set_alignment(TextAlignment::Left);
-    text_line3.set_position(F2 {
-      x: -0.45,
-      y: -80.0 / 480.0 + 0.06 + 2.0 * 40.0 / 480.0,
-    });
-    text_line3.set_border(true);
-    text_line3.set_depth(-0.1);
-    ctn_text.add_child(text_line3.clone());
+    text.set_border(true);
+    text.set_depth(-0.1);
+    ctn_text.add_child(text.clone());
 
     let effect_show = Effect::new_within_effect_manager(SetEffect::new(), &effect_manager);
     Effect::new_within_set_effect(
@@ -163,9 +145,7 @@ impl BookIntro {
       chat_bar,
       effect_show,
       effect_hide,
-      text_line1,
-      text_line2,
-      text_line3,
+      text,
       phrases: RefCell::new(Vec::new()),
       phrase_shown: Cell::new(0),
       events,
@@ -178,36 +158,36 @@ impl BookIntro {
         context.texture_manager.chat_char_panda.clone(),
         context.texture_manager.chat_bar_panda.clone(),
         vec![
-          vec!["Hi, I'm Panda,", "and this is my", "doodle book."],
-          vec!["Touch the paw", "and drag to start", "drawing."],
-          vec!["Fill the red heart", "with red ink to", "solve the puzzle."],
+          "Hi, I'm Panda,\nand this is my doodle book.",
+          "Touch the paw and drag to start drawing.",
+          "Fill the red heart with red ink to solve the puzzle.",
         ],
       )),
       Book::Cat => Some((
         context.texture_manager.chat_char_cat.clone(),
         context.texture_manager.chat_bar_cat.clone(),
         vec![
-          vec!["Hi, I'm Cat,", "and this is my", "doodle book."],
-          vec!["These dotted lines", "are mirrors."],
-          vec!["Use the reflections", "to reach more", "doodles."],
+          "Hi, I'm Cat,\nand this is my doodle book.",
+          "These dotted lines are mirrors.",
+          "Use the reflections to reach more doodles.",
         ],
       )),
       Book::Wolf => Some((
         context.texture_manager.chat_char_wolf.clone(),
         context.texture_manager.chat_bar_wolf.clone(),
         vec![
-          vec!["Hi, I'm Wolf,", "and this is my", "doodle book."],
-          vec!["Moving paws can", "draw on their own."],
-          vec!["Use the natural flow", "to reach your goal."],
+          "Hi, I'm Wolf,\nand this is my doodle book.",
+          "Moving paws can draw on their own.",
+          "Use the natural flow to reach your goal.",
         ],
       )),
       Book::Rabbit => Some((
         context.texture_manager.chat_char_rabbit.clone(),
         context.texture_manager.chat_bar_rabbit.clone(),
         vec![
-          vec!["Hi, I'm Rabbit,", "and this is my", "doodle book."],
-          vec!["The glowing portals", "can teleport you."],
-          vec!["Use them to reach", "farther places."],
+          "Hi, I'm Rabbit,\nand this is my doodle book.",
+          "The glowing portals can teleport you.",
+          "Use them to reach farther places.",
         ],
       )),
       Book::Panda2 => None,
@@ -217,12 +197,9 @@ impl BookIntro {
       let (chat_char, chat_bar, phrases) = intro;
       self.chat_char.set_texture(chat_char);
       self.chat_bar.set_texture(chat_bar);
-      self.phrases.replace(
-        phrases
-          .iter()
-          .map(|v| v.iter().map(|s| String::from(*s)).collect())
-          .collect(),
-      );
+      self
+        .phrases
+        .replace(phrases.iter().map(|s| String::from(*s)).collect());
       self.phrase_shown.set(0);
       self.update_phrase_shown();
 
@@ -235,15 +212,7 @@ impl BookIntro {
   fn update_phrase_shown(&self) -> bool {
     let phrases = self.phrases.borrow();
     if let Some(curr_phrase) = phrases.get(self.phrase_shown.get()) {
-      self
-        .text_line1
-        .set_text(curr_phrase.get(0).map_or(String::from(""), |s| s.clone()));
-      self
-        .text_line2
-        .set_text(curr_phrase.get(1).map_or(String::from(""), |s| s.clone()));
-      self
-        .text_line3
-        .set_text(curr_phrase.get(2).map_or(String::from(""), |s| s.clone()));
+      self.text.set_text(curr_phrase.clone());
       return true;
     }
     return false;
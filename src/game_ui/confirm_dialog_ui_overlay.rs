@@ -0,0 +1,147 @@
+use crate::context::Context;
+use crate::*;
+
+pub struct ConfirmDialogUiOverlay {
+  container: Rc<UiContainer>,
+}
+
+impl ConfirmDialogUiOverlay {
+  pub fn new<E: 'static + Clone>(
+    context: &Context,
+    message_lines: Vec<&str>,
+    events: Rc<EventManager<E>>,
+    event_on_confirm: E,
+  ) -> Rc<ConfirmDialogUiOverlay> {
+    let screen_center = &context.ui_viewport.screen_center;
+
+    let container = UiContainer::new();
+    container.set_position_y(screen_center.y);
+    container.set_depth(-10.0);
+
+    let sprite_background = Rc::new(UiSprite::new(context.texture_manager.gui_btn_wood.clone()));
+    sprite_background.set_size(F2 {
+      x: 420.0 / 480.0,
+      y: 260.0 / 480.0,
+    });
+    sprite_background.set_position_x(0.5);
+    sprite_background.set_depth(0.1);
+    container.add_child(sprite_background);
+
+    let first_line_y = -70.0 / 480.0 - 20.0 / 480.0 * (message_lines.len() as F1 - 1.0);
+    for (index, line) in message_lines.iter().enumerate() {
+      let text_line = UiText::new();
+      text_line.set_text(line.to_string());
+      text_line.use_text_cache();
+      text_line.set_font_size(45.0 / 480.0);
+      text_line.set_alignment(TextAlignment::Center);
+      text_line.set_border(true);
+      text_line.set_position(F2 {
+        x: 0.5,
+        y: first_line_y + 40.0 / 480.0 * index as F1,
+      });
+      container.add_child(text_line);
+    }
+
+    let btn_class = UiElementClass {
+      size: Some(F2 {
+        x: 150.0 / 480.0,
+        y: 70.0 / 480.0,
+      }),
+      ..Default::default()
+    };
+
+    let btn_yes = UiButton::new(
+      context.texture_manager.gui_btn_wood.clone(),
+      context.texture_manager.gui_btn_wood_pressed.clone(),
+    );
+    btn_yes.set_class(&btn_class);
+    btn_yes.set_position(F2 {
+      x: 150.0 / 480.0,
+      y: 70.0 / 480.0,
+    });
+    btn_yes.set_sound_on_released(context.audio_manager.click.clone());
+    btn_yes.set_event_on_released(events, event_on_confirm);
+    btn_yes
+      .on_released_event
+      .add(Box::new(move |context, _ui_touch| {
+        context
+          .get_ui_manager_events()
+          .add_event(UiManagerEvent::HideUiOverlay);
+      }));
+    let btn_yes_text = UiText::new();
+    btn_yes_text.set_text(String::from("Yes"));
+    btn_yes_text.use_text_cache();
+    btn_yes_text.set_font_size(55.0 / 480.0);
+    btn_yes_text.set_alignment(TextAlignment::Center);
+    btn_yes_text.set_border(true);
+    btn_yes.container.add_child(btn_yes_text);
+    container.add_child(btn_yes);
+
+    let btn_no = UiButton::new(
+      context.texture_manager.gui_btn_wood.clone(),
+      context.texture_manager.gui_btn_wood_pressed.clone(),
+    );
+    btn_no.set_class(&btn_class);
+    btn_no.set_position(F2 {
+      x: 330.0 / 480.0,
+      y: 70.0 / 480.0,
+    });
+    btn_no.set_sound_on_released(context.audio_manager.click.clone());
+    btn_no
+      .on_released_event
+      .add(Box::new(move |context, _ui_touch| {
+        context
+          .get_ui_manager_events()
+          .add_event(UiManagerEvent::HideUiOverlay);
+      }));
+    let btn_no_text = UiText::new();
+    btn_no_text.set_text(String::from("No"));
+    btn_no_text.use_text_cache();
+    btn_no_text.set_font_size(55.0 / 480.0);
+    btn_no_text.set_alignment(TextAlignment::Center);
+    btn_no_text.set_border(true);
+    btn_no.container.add_child(btn_no_text);
+    container.add_child(btn_no);
+
+    return Rc::new(ConfirmDialogUiOverlay { container });
+  }
+}
+
+impl UiOverlayTrait<Context> for ConfirmDialogUiOverlay {}
+
+impl UiRootTrait<Context> for ConfirmDialogUiOverlay {
+  fn on_press_back(&self, context: &mut Context) -> InputState {
+    context
+      .get_ui_manager_events()
+      .add_event(UiManagerEvent::HideUiOverlay);
+    return InputState::Consumed;
+  }
+}
+
+impl EffectManagerTrait<Context> for ConfirmDialogUiOverlay {
+  fn get_effect_manager(&self) -> Option<&EffectManager> {
+    return None;
+  }
+}
+
+impl UiElementTrait<Context> for ConfirmDialogUiOverlay {
+  fn get_ui_element(&self) -> &UiElement {
+    return self.container.get_ui_element();
+  }
+
+  fn update(&self, context: &mut Context) {
+    self.container.update(context);
+  }
+
+  fn draw(&self, context: &mut Context) {
+    self.container.draw(context);
+  }
+
+  fn get_touched_element(
+    &self,
+    context: &mut Context,
+    ui_touch: &UiTouch,
+  ) -> Option<Rc<dyn UiElementTrait<Context>>> {
+    return self.container.get_touched_element(context, ui_touch);
+  }
+}
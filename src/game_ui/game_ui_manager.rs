@@ -5,6 +5,7 @@ use crate::*;
 
 pub struct GameUiManager {
   ui_manager: Rc<UiManager>,
+  achievement_toast: AchievementToast,
 }
 
 impl GameUiManager {
@@ -12,6 +13,7 @@ impl GameUiManager {
     context.ui_events.add_event(UiEvent::LoadLandingPage);
     return GameUiManager {
       ui_manager: UiManager::new(context),
+      achievement_toast: AchievementToast::new(context),
     };
   }
 
@@ -19,6 +21,10 @@ impl GameUiManager {
     self.ui_manager.process_touch(context, screen_touch);
   }
 
+  pub fn process_wheel(&mut self, context: &mut Context, position: &F2, delta_y: F1) {
+    self.ui_manager.process_wheel(context, position, delta_y);
+  }
+
   pub fn process_back_button(&self, context: &mut Context) -> InputState {
     return self.ui_manager.process_back_button(context);
   }
@@ -47,12 +53,34 @@ impl GameUiManager {
           in_game_ui.load_game(context, load_game_params);
           self.ui_manager.push_page_on_stack(in_game_ui, context);
         }
+        UiEvent::LoadStageStats(book) => {
+          self
+            .ui_manager
+            .push_page_on_stack(StageStatsUiRoot::new(context, book), context);
+        }
+        UiEvent::LoadAchievements => {
+          self
+            .ui_manager
+            .push_page_on_stack(AchievementsUiRoot::new(context), context);
+        }
+        UiEvent::LoadLeaderboard(book, stage_number) => {
+          self
+            .ui_manager
+            .push_page_on_stack(LeaderboardUiRoot::new(context, book, stage_number), context);
+        }
       }
     }
     self.ui_manager.update(context);
+
+    context.achievments_manager.update();
+    context
+      .achievement_tracker
+      .process_gameplay_events(&context.gameplay_events, &context.achievments_manager);
+    self.achievement_toast.update(context);
   }
 
   pub fn draw(&self, context: &mut Context) {
     self.ui_manager.draw(context);
+    self.achievement_toast.draw(context);
   }
 }
@@ -1,7 +1,6 @@
 use crate::context::*;
 use crate::engine::*;
 use std::rc::Rc;
-use wasm_bindgen::JsCast;
 
 const N_SLICES: u32 = 40;
 
@@ -10,42 +9,25 @@ pub struct GlowBar {
   pub time: F1,
   pub position: F2,
   pub size: F2,
-  cache_canvas: Rc<web_sys::HtmlCanvasElement>,
+  cache_render_target: Rc<dyn RenderTarget>,
   cache_canvas_size: F2,
-  cache_draw_backend: Canvas2dDrawBackend,
 }
 
 impl GlowBar {
   pub fn new(texture: Rc<Texture>, position: F2, size: F2, context: &Context) -> GlowBar {
-    let document = web_sys::window().unwrap().document().unwrap();
-    let cache_canvas = document
-      .create_element("canvas")
-      .expect("failed to create canvas")
-      .dyn_into::<web_sys::HtmlCanvasElement>()
-      .expect("failed to dyn_into");
     let mut canvas_size = context.get_canvas_size();
 
     canvas_size.x = 240.0;
     canvas_size.y = size.y * canvas_size.x / size.x;
-    cache_canvas.set_width(canvas_size.x as u32);
-    cache_canvas.set_height(canvas_size.y as u32);
-    let canvas_context = Rc::new(
-      cache_canvas
-        .get_context("2d")
-        .expect("failed to get_context(\"2d\")")
-        .unwrap()
-        .dyn_into::<web_sys::CanvasRenderingContext2d>()
-        .expect("failed to dyn_into::<web_sys::CanvasRenderingContext2d>()"),
-    );
+    let cache_render_target = context.draw_manager.create_render_target(&canvas_size);
 
     return GlowBar {
       texture: texture,
       time: 0.0,
       position: position,
       size: size,
-      cache_canvas: Rc::new(cache_canvas),
+      cache_render_target: cache_render_target,
       cache_canvas_size: canvas_size,
-      cache_draw_backend: Canvas2dDrawBackend::new(canvas_context),
     };
   }
 
@@ -66,9 +48,8 @@ impl GlowBar {
 
     let slice_pos_dest_x_initial = slice_pos_dest_x;
 
-    self
-      .cache_draw_backend
-      .clear_screen(&self.cache_canvas_size);
+    let cache_draw_backend = self.cache_render_target.get_draw_backend();
+    cache_draw_backend.clear_screen(&self.cache_canvas_size);
 
     for i in 0..(N_SLICES as u32) {
       let intensity: F1 = 0.4
@@ -83,7 +64,7 @@ impl GlowBar {
         slice_size_dest_x -= 1;
       }
 
-      self.cache_draw_backend.execute_image_draw(
+      cache_draw_backend.execute_image_draw(
         &DrawImageArgs {
           source: DrawSource::Texture(self.texture.clone()),
           position: F2 {
@@ -94,7 +75,8 @@ impl GlowBar {
             x: slice_size_dest_x as F1,
             y: slice_size_dest_y as F1,
           },
-          depth: context.draw_depths.ui - 100.0,
+          layer: context.draw_layers.glow_bar,
+          depth: 0.0,
           optional: DrawImageOptionalArgs {
             anchor_point: F2 { x: 0.0, y: 0.0 },
             partial_region_offset: F2 {
@@ -119,10 +101,11 @@ impl GlowBar {
     context.draw_manager.draw_viewport(
       &context.ui_viewport,
       DrawImageArgs {
-        source: DrawSource::Canvas(self.cache_canvas.clone()),
+        source: DrawSource::RenderTarget(self.cache_render_target.clone()),
         position: self.position,
         size: self.size,
-        depth: context.draw_depths.ui - 100.0,
+        layer: context.draw_layers.glow_bar,
+        depth: 0.0,
         optional: DrawImageOptionalArgs {
           opacity: 1.0,
           subpixel_precision: true,
@@ -1,16 +1,38 @@
 use crate::context::Context;
+use crate::context::UiEvent;
+use crate::context::UiLayout;
+use crate::context::LANDSCAPE_HUD_WIDTH;
+use crate::game::gameplay_event::*;
+use crate::game::leaderboard::*;
+use crate::game::painting_export::*;
 use crate::game::stages_data::*;
+use crate::game::time_lapse::*;
 use crate::*;
 
+const PAINTING_EXPORT_WIDTH: F1 = 1080.0;
+const TIME_LAPSE_EXPORT_WIDTH: F1 = 360.0;
+// Zoom factor per pixel scrolled with the mouse wheel.
+const WHEEL_ZOOM_SPEED: F1 = 0.002;
+
 pub struct IngameUiRoot {
   container: Rc<UiContainer>,
+  btn_undo: Rc<UiButton>,
+  btn_restart: Rc<UiButton>,
+  btn_menu: Rc<UiButton>,
+  ui_layout: Cell<Option<(UiLayout, F2)>>,
   effect_manager: EffectManager,
   events: Rc<EventManager<IngameUiEvent>>,
   game_root_entity_manager: EntityManager<GameMode>,
   game_mode: Rc<GameMode>,
 
-  victory_ui: VictoryUi,
+  victory_ui: VictoryUi<Context>,
+  time_lapse: TimeLapse,
   book_intro: Rc<BookIntro>,
+  play_time_ms: Cell<f64>,
+  undos_this_attempt: Cell<u32>,
+  // Undos not yet added to the stage stats, see flush_stage_stats.
+  unrecorded_undos: Cell<u32>,
+  pinch_gesture: RefCell<PinchGesture>,
 }
 
 #[derive(Clone, Copy)]
@@ -21,6 +43,9 @@ pub enum IngameUiEvent {
   ButtonVictoryRestart,
   ButtonVictoryNext,
   ButtonVictoryMenu,
+  ButtonVictoryLeaderboard,
+  ButtonVictorySaveImage,
+  ButtonVictorySaveTimeLapse,
   EffectVictoryRestart,
   EffectVictoryNext,
   Victory(VictoryParams),
@@ -34,255 +59,7 @@ pub enum IngameUiEvent {
 pub struct VictoryParams {
   pub score: F1,
   pub stars: usize,
-}
-
-pub struct VictoryUi {
-  container: Rc<UiContainer>,
-  image_star1: Rc<UiSprite>,
-  image_star2: Rc<UiSprite>,
-  image_star3: Rc<UiSprite>,
-  image_victory_title: Rc<UiSprite>,
-  text_score_count: Rc<UiText>,
-  text_best_score: Rc<UiText>,
-  effect_star_animation: Rc<Effect<SetEffect>>,
-  effect_show_best_score: Rc<Effect<VectorAffectorF1>>,
-  effect_hide_victory_restart: Rc<Effect<VectorAffectorF1>>,
-  effect_hide_victory_next: Rc<Effect<VectorAffectorF1>>,
-  effect_twinkle_next: Rc<Effect<ChainedEffect>>,
-  start_score_count: Shared<bool>,
-  score: Cell<F1>,
-  score_counted: Cell<F1>,
-  best_score: Cell<bool>,
-}
-
-impl VictoryUi {
-  fn new(
-    context: &Context,
-    events: Rc<EventManager<IngameUiEvent>>,
-    effect_manager: &EffectManager,
-  ) -> VictoryUi {
-    let container = UiContainer::new();
-    container.set_depth(-5.0);
-    container.set_visible(false);
-
-    let image_star1 = Rc::new(UiSprite::new(context.texture_manager.star_empty.clone()));
-    image_star1.set_position(F2 {
-      x: 0.5 - 0.3,
-      y: 300.0 / 480.0,
-    });
-    image_star1.set_subpixel_precision(true);
-    container.add_child(image_star1.clone());
-    let image_star2 = Rc::new(UiSprite::new(context.texture_manager.star_empty.clone()));
-    image_star2.set_position(F2 {
-      x: 0.5,
-      y: 300.0 / 480.0,
-    });
-    image_star2.set_subpixel_precision(true);
-    container.add_child(image_star2.clone());
-    let image_star3 = Rc::new(UiSprite::new(context.texture_manager.star_empty.clone()));
-    image_star3.set_position(F2 {
-      x: 0.5 + 0.3,
-      y: 300.0 / 480.0,
-    });
-    image_star3.set_subpixel_precision(true);
-    container.add_child(image_star3.clone());
-
-    let image_victory_title = Rc::new(UiSprite::new(context.texture_manager.gui_awesome.clone()));
-    image_victory_title.set_position(F2 {
-      x: 0.5,
-      y: 180.0 / 480.0,
-    });
-    image_victory_title.set_size_from_width(260.0 / 480.0);
-    container.add_child(image_victory_title.clone());
-
-    let text_score_count = UiText::new();
-    text_score_count.use_text_cache();
-    text_score_count.set_font_size(45.0 / 480.0);
-    text_score_count.set_border(false);
-    text_score_count.set_alignment(TextAlignment::Left);
-    text_score_count.set_color(DrawColor { r: 0, g: 0, b: 0 });
-    text_score_count.set_position(F2 {
-      x: 120.0 / 480.0,
-      y: 420.0 / 480.0,
-    });
-    container.add_child(text_score_count.clone());
-
-    let text_best_score = UiText::new();
-    text_best_score.use_text_cache();
-    text_best_score.set_font_size(45.0 / 480.0);
-    text_best_score.set_border(false);
-    text_best_score.set_alignment(TextAlignment::Center);
-    text_best_score.set_text(String::from("Best Score!!"));
-    text_best_score.set_color(DrawColor { r: 0, g: 0, b: 0 });
-    text_best_score.set_position(F2 {
-      x: 240.0 / 480.0,
-      y: 470.0 / 480.0,
-    });
-    text_best_score.set_opacity(0.0);
-    container.add_child(text_best_score.clone());
-
-    let container_buttons = UiContainer::new();
-    container_buttons.set_position(F2 {
-      x: 0.5,
-      y: 580.0 / 480.0,
-    });
-    container.add_child(container_buttons.clone());
-
-    let class_buttons_end = UiElementClass {
-      size: Some(F2 {
-        x: 80.0 / 480.0,
-        y: 80.0 / 480.0,
-      }),
-      ..Default::default()
-    };
-
-    let button_menu = UiButton::new(
-      context.texture_manager.gui_btn_wood_menu.clone(),
-      context.texture_manager.gui_btn_wood_menu_pressed.clone(),
-    );
-    button_menu.set_class(&class_buttons_end);
-    button_menu.set_position_x(-100.0 / 480.0);
-    button_menu.set_event_on_released(events.clone(), IngameUiEvent::ButtonVictoryMenu);
-    container_buttons.add_child(button_menu.clone());
-
-    let button_restart = UiButton::new(
-      context.texture_manager.gui_btn_wood_restart.clone(),
-      context.texture_manager.gui_btn_wood_restart_pressed.clone(),
-    );
-    button_restart.set_class(&class_buttons_end);
-    button_restart.set_position_x(0.0);
-    button_restart.set_event_on_released(events.clone(), IngameUiEvent::ButtonVictoryRestart);
-    container_buttons.add_child(button_restart.clone());
-
-    let button_next = UiButton::new(
-      context.texture_manager.gui_btn_wood_skip.clone(),
-      context.texture_manager.gui_btn_wood_skip_pressed.clone(),
-    );
-    button_next.set_class(&class_buttons_end);
-    button_next.set_position_x(100.0 / 480.0);
-    button_next.set_depth(-4.0);
-    button_next.set_event_on_released(events.clone(), IngameUiEvent::ButtonVictoryNext);
-    container_buttons.add_child(button_next.clone());
-
-    let image_next_bright = Rc::new(UiSprite::new(
-      context.texture_manager.gui_img_twinkle_next.clone(),
-    ));
-    image_next_bright.set_class(&class_buttons_end);
-    image_next_bright.set_position_x(100.0 / 480.0);
-    image_next_bright.set_depth(-5.0);
-    container_buttons.add_child(image_next_bright.clone());
-
-    let effect_twinkle_next =
-      Effect::new_within_effect_manager(ChainedEffect::new(), effect_manager);
-    Effect::new_within_chained_effect(
-      VectorAffectorF1::new(image_next_bright.get_opacity()).set_start_and_end(0.0, 0.0, 2000.0),
-      &effect_twinkle_next,
-    );
-    for _ in 0..10 {
-      Effect::new_within_chained_effect(
-        VectorAffectorF1::new(image_next_bright.get_opacity()).set_start_and_end(0.0, 1.0, 200.0),
-        &effect_twinkle_next,
-      );
-      Effect::new_within_chained_effect(
-        VectorAffectorF1::new(image_next_bright.get_opacity()).set_start_and_end(1.0, 1.0, 500.0),
-        &effect_twinkle_next,
-      );
-      Effect::new_within_chained_effect(
-        VectorAffectorF1::new(image_next_bright.get_opacity()).set_start_and_end(1.0, 0.0, 200.0),
-        &effect_twinkle_next,
-      );
-      Effect::new_within_chained_effect(
-        VectorAffectorF1::new(image_next_bright.get_opacity()).set_start_and_end(0.0, 0.0, 500.0),
-        &effect_twinkle_next,
-      );
-    }
-
-    let effect_star_animation = Effect::new_within_effect_manager(SetEffect::new(), effect_manager);
-    let star_size = F2 {
-      x: 120.0 / 480.0,
-      y: 120.0 / 480.0,
-    };
-
-    Effect::new_within_set_effect(
-      VectorAffectorF2::new(image_star1.size.clone())
-        .set_end(star_size, 400.0)
-        .set_progression(Box::new(ExpTransProgression::new(2.0, 6.0))),
-      300.0,
-      &effect_star_animation,
-    );
-
-    Effect::new_within_set_effect(
-      VectorAffectorF2::new(image_star2.size.clone())
-        .set_end(star_size, 400.0)
-        .set_progression(Box::new(ExpTransProgression::new(2.0, 6.0))),
-      500.0,
-      &effect_star_animation,
-    );
-    Effect::new_within_set_effect(
-      VectorAffectorF2::new(image_star3.size.clone())
-        .set_end(star_size, 400.0)
-        .set_progression(Box::new(ExpTransProgression::new(2.0, 6.0))),
-      700.0,
-      &effect_star_animation,
-    );
-    Effect::new_within_set_effect(
-      VectorAffectorF1::new(image_victory_title.get_opacity().clone()).set_end(1.0, 500.0),
-      0.0,
-      &effect_star_animation,
-    );
-    Effect::new_within_set_effect(
-      VectorAffectorF1::new(text_score_count.get_opacity().clone()).set_end(1.0, 400.0),
-      1000.0,
-      &effect_star_animation,
-    );
-
-    let start_score_count = Shared::new(false);
-    {
-      let start_score_count = start_score_count.clone();
-      effect_star_animation
-        .end_event
-        .add(Box::new(move |_context| {
-          start_score_count.replace(true);
-        }));
-    }
-
-    let effect_show_best_score = Effect::new_within_effect_manager(
-      VectorAffectorF1::new(text_best_score.get_opacity()).set_end(1.0, 1000.0),
-      effect_manager,
-    );
-
-    let effect_hide_victory_restart = Effect::new_within_effect_manager(
-      VectorAffectorF1::new(container.get_opacity()).set_end(0.0, 500.0),
-      effect_manager,
-    );
-    effect_hide_victory_restart
-      .add_event_on_end(events.clone(), IngameUiEvent::EffectVictoryRestart);
-
-    let effect_hide_victory_next = Effect::new_within_effect_manager(
-      VectorAffectorF1::new(container.get_opacity()).set_end(0.0, 500.0),
-      effect_manager,
-    );
-    effect_hide_victory_next.add_event_on_end(events.clone(), IngameUiEvent::EffectVictoryNext);
-
-    return VictoryUi {
-      container: container,
-      image_star1: image_star1,
-      image_star2: image_star2,
-      image_star3: image_star3,
-      image_victory_title: image_victory_title,
-      text_score_count: text_score_count,
-      text_best_score: text_best_score,
-      effect_star_animation: effect_star_animation,
-      effect_show_best_score: effect_show_best_score,
-      effect_hide_victory_restart: effect_hide_victory_restart,
-      effect_hide_victory_next: effect_hide_victory_next,
-      effect_twinkle_next,
-      start_score_count: start_score_count,
-      score: Cell::new(0.0),
-      score_counted: Cell::new(0.0),
-      best_score: Cell::new(false),
-    };
-  }
+  pub paint_used: F1,
 }
 
 impl IngameUiRoot {
@@ -303,9 +80,8 @@ impl IngameUiRoot {
     );
     btn_undo.set_class(&btn_top_class);
     btn_undo.set_size_x(70.0 / 480.0);
-    btn_undo.set_position_x(300.0 / 480.0);
     btn_undo.set_event_on_released(events.clone(), IngameUiEvent::ButtonUndo);
-    container.add_child(btn_undo);
+    container.add_child(btn_undo.clone());
 
     let btn_restart = UiButton::new(
       context.texture_manager.gui_btn_restart.clone(),
@@ -313,9 +89,8 @@ impl IngameUiRoot {
     );
     btn_restart.set_class(&btn_top_class);
     btn_restart.set_size_x(70.0 / 480.0);
-    btn_restart.set_position_x(375.0 / 480.0);
     btn_restart.set_event_on_released(events.clone(), IngameUiEvent::ButtonRestart);
-    container.add_child(btn_restart);
+    container.add_child(btn_restart.clone());
 
     let btn_menu = UiButton::new(
       context.texture_manager.gui_btn_menu.clone(),
@@ -323,128 +98,141 @@ impl IngameUiRoot {
     );
     btn_menu.set_class(&btn_top_class);
     btn_menu.set_size_x(70.0 / 480.0);
-    btn_menu.set_position_x(450.0 / 480.0);
     btn_menu.set_event_on_released(events.clone(), IngameUiEvent::ButtonMenu);
-    container.add_child(btn_menu);
+    container.add_child(btn_menu.clone());
 
     let game_root_entity_manager = EntityManager::new_root_manager();
     let game_mode = GameMode::new(context, events.clone());
     game_root_entity_manager.add(game_mode.clone());
 
-    let victory_ui = VictoryUi::new(context, events.clone(), &effect_manager);
+    let victory_ui = VictoryUi::new(
+      &context.texture_manager,
+      &context.audio_manager,
+      events.clone(),
+      &effect_manager,
+    );
     let result = Rc::new(IngameUiRoot {
       container,
+      btn_undo,
+      btn_restart,
+      btn_menu,
+      ui_layout: Cell::new(None),
       effect_manager,
       events: events.clone(),
       game_root_entity_manager,
       game_mode,
       victory_ui,
+      time_lapse: TimeLapse::new(context),
       book_intro: BookIntro::new(context),
+      play_time_ms: Cell::new(0.0),
+      undos_this_attempt: Cell::new(0),
+      unrecorded_undos: Cell::new(0),
+      pinch_gesture: RefCell::new(PinchGesture::default()),
     });
 
     result
       .container
       .add_child(result.victory_ui.container.clone());
     result.container.add_child(result.book_intro.clone());
+    result.refresh_layout(context);
     return result;
   }
 
+  // Places the buttons in a row above the board, or stacked in the HUD column
+  // beside it.
+  fn refresh_layout(&self, context: &Context) {
+    let hud_origin = context.get_hud_origin();
+    if self.ui_layout.get() == Some((context.ui_layout, hud_origin)) {
+      return;
+    }
+    self.ui_layout.set(Some((context.ui_layout, hud_origin)));
+    let buttons = [&self.btn_undo, &self.btn_restart, &self.btn_menu];
+    for (i, button) in buttons.iter().enumerate() {
+      button.set_position(
+        hud_origin
+          + match context.ui_layout {
+            UiLayout::Portrait => F2 {
+              x: (300.0 + 75.0 * i as F1) / 480.0,
+              y: 38.0 / 480.0,
+            },
+            UiLayout::Landscape { .. } => F2 {
+              x: LANDSCAPE_HUD_WIDTH * 0.5,
+              y: (130.0 + 80.0 * i as F1) / 480.0,
+            },
+          },
+      );
+    }
+  }
+
   pub fn load_game(&self, context: &mut Context, load_game_params: LoadGameParams) {
-    self.game_mode.start_puzzle(
+    self.start_puzzle(
       context,
       load_game_params.book,
       load_game_params.stage_number,
+      false,
     );
     if load_game_params.stage_number == 0 {
       self.book_intro.show(context, load_game_params.book);
     }
   }
 
+  // Restarts count as restarts of the current attempt, not as new attempts.
+  fn start_puzzle(&self, context: &mut Context, book: Book, stage_number: usize, restart: bool) {
+    context.achievments_manager.begin_batch();
+    self.flush_stage_stats(context);
+    self
+      .game_mode
+      .start_puzzle(context, book, stage_number, get_random_seed());
+    self.time_lapse.stop();
+    if restart {
+      context
+        .achievments_manager
+        .record_stage_restart(book.number(), stage_number);
+    } else {
+      context
+        .achievments_manager
+        .record_stage_attempt(book.number(), stage_number);
+    }
+    context.achievments_manager.end_batch();
+    self.undos_this_attempt.set(0);
+    context
+      .gameplay_events
+      .add_event(GameplayEvent::StageStarted(StageEventParams {
+        book_number: book.number(),
+        stage_number: stage_number,
+      }));
+  }
+
+  fn flush_stage_stats(&self, context: &mut Context) {
+    context.achievments_manager.record_stage_session(
+      self.game_mode.book.get().number(),
+      self.game_mode.stage_number.get(),
+      self.play_time_ms.replace(0.0),
+      self.unrecorded_undos.replace(0),
+    );
+  }
+
   fn restart(&self, context: &mut Context) {
     self.victory_ui.container.set_visible(false);
     self.victory_ui.effect_star_animation.stop();
     self.victory_ui.effect_show_best_score.stop();
-    self.game_mode.start_puzzle(
+    self.start_puzzle(
       context,
       self.game_mode.book.get(),
       self.game_mode.stage_number.get(),
+      true,
     );
   }
 
   fn start_victory_animation(&self, context: &mut Context, victory_params: VictoryParams) {
-    match victory_params.stars {
-      3 => {
-        self
-          .victory_ui
-          .image_star1
-          .set_texture(context.texture_manager.star.clone());
-        self
-          .victory_ui
-          .image_star2
-          .set_texture(context.texture_manager.star.clone());
-        self
-          .victory_ui
-          .image_star3
-          .set_texture(context.texture_manager.star.clone());
-        self
-          .victory_ui
-          .image_victory_title
-          .set_texture(context.texture_manager.gui_awesome.clone());
-      }
-      2 => {
-        self
-          .victory_ui
-          .image_star1
-          .set_texture(context.texture_manager.star.clone());
-        self
-          .victory_ui
-          .image_star2
-          .set_texture(context.texture_manager.star.clone());
-        self
-          .victory_ui
-          .image_star3
-          .set_texture(context.texture_manager.star_empty.clone());
-        self
-          .victory_ui
-          .image_victory_title
-          .set_texture(context.texture_manager.gui_very_good.clone());
-      }
-      _ => {
-        self
-          .victory_ui
-          .image_star1
-          .set_texture(context.texture_manager.star.clone());
-        self
-          .victory_ui
-          .image_star2
-          .set_texture(context.texture_manager.star_empty.clone());
-        self
-          .victory_ui
-          .image_star3
-          .set_texture(context.texture_manager.star_empty.clone());
-        self
-          .victory_ui
-          .image_victory_title
-          .set_texture(context.texture_manager.gui_nice.clone());
-      }
-    };
-    self.victory_ui.score.set(victory_params.score);
-    self.victory_ui.score_counted.set(0.0);
-    self.victory_ui.container.set_visible(true);
-    self.victory_ui.container.set_active(true);
-    self.victory_ui.container.set_opacity(1.0);
-    self.victory_ui.image_star1.set_size(F2 { x: 0.0, y: 0.0 });
-    self.victory_ui.image_star2.set_size(F2 { x: 0.0, y: 0.0 });
-    self.victory_ui.image_star3.set_size(F2 { x: 0.0, y: 0.0 });
-    self.victory_ui.image_victory_title.set_opacity(0.0);
-    self.victory_ui.text_score_count.set_opacity(0.0);
-    self
-      .victory_ui
-      .text_score_count
-      .set_text(format!("your score . . . "));
-    self.victory_ui.text_best_score.set_opacity(0.0);
-    self.victory_ui.effect_star_animation.start();
+    self.victory_ui.show(
+      &context.texture_manager,
+      victory_params.score,
+      victory_params.stars,
+    );
+    self.time_lapse.start(&self.game_mode);
 
+    context.achievments_manager.begin_batch();
     self
       .victory_ui
       .best_score
@@ -454,32 +242,48 @@ impl IngameUiRoot {
         victory_params.score as i32,
         victory_params.stars,
       ));
+    if self.victory_ui.best_score.get() {
+      context.leaderboard_provider.submit(
+        context,
+        LeaderboardSubmission {
+          book_number: self.game_mode.book.get().number(),
+          stage_number: self.game_mode.stage_number.get(),
+          player_name: context.profile_manager.get_active_profile().name,
+          score: victory_params.score as i32,
+          stars: victory_params.stars,
+          replay: self.game_mode.replay.borrow().clone(),
+        },
+      );
+    }
+    context.achievments_manager.record_stage_clear(
+      self.game_mode.book.get().number(),
+      self.game_mode.stage_number.get(),
+      victory_params.paint_used,
+    );
+    context
+      .gameplay_events
+      .add_event(GameplayEvent::StageCleared(StageClearedParams {
+        book_number: self.game_mode.book.get().number(),
+        stage_number: self.game_mode.stage_number.get(),
+        stars: victory_params.stars,
+        paint_used: victory_params.paint_used,
+        undos: self.undos_this_attempt.get(),
+      }));
+    self.flush_stage_stats(context);
+    context.achievments_manager.end_batch();
     self.victory_ui.effect_twinkle_next.start();
 
     context.audio_player.play_sound(&context.audio_manager.win);
   }
 
-  fn show_score(&self) {
-    self.victory_ui.text_score_count.set_text(format!(
-      "your score . . . {}",
-      self.victory_ui.score.get() as i32
-    ));
-    self.victory_ui.start_score_count.replace(false);
-    if self.victory_ui.best_score.get() {
-      self.victory_ui.effect_show_best_score.start();
-    }
-  }
-
   fn undo(&self, context: &mut Context) {
     context.audio_player.play_sound(&context.audio_manager.back);
-    if self.game_mode.checkpoint.get() > 0 && self.game_mode.is_game_running() {
+    if self.game_mode.undo(&self.game_root_entity_manager) {
       self
-        .game_root_entity_manager
-        .undo(&self.game_mode.checkpoint.get());
-      self
-        .game_mode
-        .checkpoint
-        .set(self.game_mode.checkpoint.get() - 1);
+        .undos_this_attempt
+        .set(self.undos_this_attempt.get() + 1);
+      self.unrecorded_undos.set(self.unrecorded_undos.get() + 1);
+      context.gameplay_events.add_event(GameplayEvent::UndoUsed);
     }
   }
 }
@@ -496,6 +300,7 @@ impl UiElementTrait<Context> for IngameUiRoot {
   }
 
   fn update(&self, context: &mut Context) {
+    self.refresh_layout(context);
     while let Some(event) = self.events.consume_event() {
       match event {
         IngameUiEvent::ButtonUndo => {
@@ -562,6 +367,36 @@ impl UiElementTrait<Context> for IngameUiRoot {
             .artificial_input_events
             .add_event(InputEvent::BackButton);
         }
+        IngameUiEvent::ButtonVictoryLeaderboard => {
+          context.ui_events.add_event(UiEvent::LoadLeaderboard(
+            self.game_mode.book.get(),
+            self.game_mode.stage_number.get(),
+          ));
+        }
+        IngameUiEvent::ButtonVictorySaveImage => {
+          PaintingExport::save(
+            context,
+            &self.game_mode,
+            &PaintingExportParams {
+              width: PAINTING_EXPORT_WIDTH,
+              frame: true,
+              stars: self.victory_ui.stars.get(),
+              painted_points: None,
+            },
+          );
+        }
+        IngameUiEvent::ButtonVictorySaveTimeLapse => {
+          PaintingExport::save_time_lapse(
+            context,
+            &self.game_mode,
+            &PaintingExportParams {
+              width: TIME_LAPSE_EXPORT_WIDTH,
+              frame: true,
+              stars: self.victory_ui.stars.get(),
+              painted_points: None,
+            },
+          );
+        }
         IngameUiEvent::EffectVictoryRestart => {
           self.restart(context);
         }
@@ -571,9 +406,7 @@ impl UiElementTrait<Context> for IngameUiRoot {
             .play_sound(&context.audio_manager.click);
           let next_stage_number = self.game_mode.stage_number.get() + 1;
           self.victory_ui.container.set_visible(false);
-          self
-            .game_mode
-            .start_puzzle(context, self.game_mode.book.get(), next_stage_number);
+          self.start_puzzle(context, self.game_mode.book.get(), next_stage_number, false);
         }
 
         IngameUiEvent::Victory(victory_params) => {
@@ -591,9 +424,7 @@ impl UiElementTrait<Context> for IngameUiRoot {
             .add_event(UiManagerEvent::HideUiOverlay);
           let next_stage_number = self.game_mode.stage_number.get() + 1;
           if next_stage_number < STAGES_PER_BOOK {
-            self
-              .game_mode
-              .start_puzzle(context, self.game_mode.book.get(), next_stage_number);
+            self.start_puzzle(context, self.game_mode.book.get(), next_stage_number, false);
           } else {
             context
               .get_ui_manager_events()
@@ -654,11 +485,19 @@ impl UiElementTrait<Context> for IngameUiRoot {
         context
           .audio_player
           .stop_sound(&context.audio_manager.count_point);
-        self.show_score();
+        self.victory_ui.show_score();
       }
     }
 
+    if self.game_mode.is_game_running() {
+      self
+        .play_time_ms
+        .set(self.play_time_ms.get() + *context.get_dt() as f64);
+    }
+
+    self.time_lapse.update(context);
     self.container.update(context);
+    self.game_mode.start_frame(*context.get_dt());
     self.game_root_entity_manager.update(context);
   }
 
@@ -666,6 +505,7 @@ impl UiElementTrait<Context> for IngameUiRoot {
     BackgroundWood::draw(context);
     BackgroundBorders::draw(context);
     self.container.draw(context);
+    self.time_lapse.draw(context);
     self.game_root_entity_manager.draw(context);
   }
 
@@ -688,6 +528,7 @@ impl UiRootTrait<Context> for IngameUiRoot {
   }
 
   fn on_navigate_from(&self, context: &mut Context) {
+    self.flush_stage_stats(context);
     context
       .audio_player
       .stop_sound(&context.audio_manager.count_point);
@@ -708,8 +549,80 @@ impl UiRootTrait<Context> for IngameUiRoot {
   }
 
   fn process_touch_game(&self, context: &mut Context, screen_touch: &ScreenTouch) -> bool {
-    let mut game_touch = GameTouch::from_screen_touch(screen_touch, context);
-    self.game_mode.process_touch(context, &mut game_touch);
+    let pinch_event = self.pinch_gesture.borrow_mut().process_touch(screen_touch);
+    match pinch_event {
+      Some(PinchEvent::Started) => {
+        // The first finger was painting until the second one came down.
+        let mut game_touch = GameTouch {
+          id: 0,
+          position: self.game_mode.brush.borrow().position,
+          touch_type: TouchType::Released,
+        };
+        self.game_mode.process_touch(context, &mut game_touch);
+      }
+      Some(PinchEvent::Moved {
+        anchor,
+        zoom_factor,
+        pan,
+      }) => {
+        if self.game_mode.is_game_running() {
+          let game_viewport = context.get_game_viewport().clone();
+          let anchor = game_viewport.screen_to_viewport(&(anchor - pan));
+          let pan = game_viewport.screen_to_viewport_ratio(&pan);
+          context.update_game_camera(|camera| {
+            camera.pan(&(pan * -1.0));
+            camera.zoom_at(&anchor, zoom_factor);
+          });
+        }
+      }
+      Some(PinchEvent::Ended) => {}
+      None => {
+        if self.pinch_gesture.borrow().is_pinching() || screen_touch.id != 0 {
+          return true;
+        }
+        let mut game_touch = GameTouch::from_screen_touch(screen_touch, context);
+        self.game_mode.process_touch(context, &mut game_touch);
+      }
+    }
+    return true;
+  }
+
+  fn process_wheel_game(&self, context: &mut Context, position: &F2, delta_y: F1) -> bool {
+    if !self.game_mode.is_game_running() {
+      return false;
+    }
+    let anchor = context.get_game_viewport().screen_to_viewport(position);
+    context.update_game_camera(|camera| {
+      camera.zoom_at(&anchor, (-delta_y * WHEEL_ZOOM_SPEED).exp());
+    });
     return true;
   }
 }
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_golden_ingame_hud() {
+    let (mut context, image) = Context::new_headless(&F2 { x: 240.0, y: 360.0 });
+    let ui_manager = UiManager::new(&context);
+    let ingame_ui_root = IngameUiRoot::new(&mut context);
+    ingame_ui_root.load_game(
+      &mut context,
+      LoadGameParams {
+        book: Book::Panda,
+        stage_number: 2,
+      },
+    );
+    ui_manager.push_page_on_stack(ingame_ui_root, &mut context);
+    for _ in 0..10 {
+      context.next_frame(100.0);
+      ui_manager.update(&mut context);
+    }
+    ui_manager.draw(&mut context);
+    context.draw_manager.execute_draws();
+
+    check_golden("ingame_hud", &image.borrow());
+  }
+}
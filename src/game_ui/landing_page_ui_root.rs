@@ -1,5 +1,6 @@
 use crate::context::Context;
 use crate::context::UiEvent;
+use crate::game::profile_manager::*;
 use crate::game_ui::*;
 use crate::*;
 
@@ -9,16 +10,26 @@ enum LandingPageState {
   WaitingLangingLoaders,
   Ready,
   WaitingLoaders,
+  LoadFailed,
   SwitchingToMainMenu,
 }
 
 #[derive(Clone, Copy)]
 enum LandingPageEvent {
   StartLoad,
+  PlayWithoutFailed,
   ToggleSound,
   Fullscreen,
+  SelectProfile(usize),
+  AddProfile,
 }
 
+const PROFILES_POSITION_Y: F1 = 0.64;
+const PROFILE_SLOT_SPACING: F1 = 0.19;
+const MAX_PROFILE_NAME_LENGTH: usize = 10;
+// Failed images listed by name on the loading screen, the rest are counted.
+const MAX_LISTED_FAILED_SRCS: usize = 3;
+
 pub struct LandingPageUiRoot {
   container: Rc<UiContainer>,
   state: Cell<LandingPageState>,
@@ -30,7 +41,16 @@ pub struct LandingPageUiRoot {
 struct UiElements {
   btn_start_load: Rc<UiButton>,
   btn_start_load_text: Rc<UiText>,
+  text_load_error: Rc<UiText>,
+  btn_play_without_failed: Rc<UiButton>,
   btn_sound_text: Rc<UiText>,
+  profile_slots: Vec<ProfileSlot>,
+  btn_add_profile: Rc<UiButton>,
+}
+
+struct ProfileSlot {
+  button: Rc<UiButton>,
+  text_name: Rc<UiText>,
 }
 
 impl LandingPageUiRoot {
@@ -53,7 +73,8 @@ impl LandingPageUiRoot {
 
   pub fn on_landing_loaders_done(&self, context: &mut Context) {
     let loading_div = context
-      .window
+      .window()
+      .expect("landing page without window")
       .document()
       .unwrap()
       .get_element_by_id("loading-div")
@@ -67,8 +88,8 @@ impl LandingPageUiRoot {
     self.container.set_visible(true);
 
     let sprite_icon = Rc::new(UiSprite::new(context.texture_manager.icon_original.clone()));
-    sprite_icon.set_size_from_width(0.7);
-    sprite_icon.set_position(F2 { x: 0.5, y: 0.4 });
+    sprite_icon.set_size_from_width(0.5);
+    sprite_icon.set_position(F2 { x: 0.5, y: 0.3 });
     sprite_icon.set_depth(1.0);
     self.container.add_child(sprite_icon);
 
@@ -90,6 +111,46 @@ impl LandingPageUiRoot {
     game_title_sub_text.set_border(false);
     self.container.add_child(game_title_sub_text.clone());
 
+    let mut profile_slots = vec![];
+    for slot_index in 0..MAX_PROFILES {
+      let button = UiButton::new(
+        context.texture_manager.chat_char_panda.clone(),
+        context.texture_manager.chat_char_panda.clone(),
+      );
+      button.set_event_on_released(
+        self.events.clone(),
+        LandingPageEvent::SelectProfile(slot_index),
+      );
+      button.set_sound_on_released(context.audio_manager.click.clone());
+      self.container.add_child(button.clone());
+
+      let text_name = UiText::new();
+      text_name.set_font_size(26.0 / 480.0);
+      text_name.set_alignment(TextAlignment::Center);
+      text_name.set_border(true);
+      self.container.add_child(text_name.clone());
+
+      profile_slots.push(ProfileSlot { button, text_name });
+    }
+
+    let btn_add_profile = UiButton::new(
+      context.texture_manager.gui_btn_wood.clone(),
+      context.texture_manager.gui_btn_wood_pressed.clone(),
+    );
+    btn_add_profile.set_size(F2 {
+      x: 60.0 / 480.0,
+      y: 60.0 / 480.0,
+    });
+    btn_add_profile.set_event_on_released(self.events.clone(), LandingPageEvent::AddProfile);
+    btn_add_profile.set_sound_on_released(context.audio_manager.click.clone());
+    let btn_add_profile_text = UiText::new();
+    btn_add_profile_text.set_text(String::from("+"));
+    btn_add_profile_text.set_font_size(60.0 / 480.0);
+    btn_add_profile_text.set_alignment(TextAlignment::Center);
+    btn_add_profile_text.set_border(true);
+    btn_add_profile.container.add_child(btn_add_profile_text);
+    self.container.add_child(btn_add_profile.clone());
+
     let btn_sound = UiButton::new(
       context.texture_manager.gui_btn_wood.clone(),
       context.texture_manager.gui_btn_wood_pressed.clone(),
@@ -166,11 +227,164 @@ impl LandingPageUiRoot {
       .add_child(btn_start_load_text.clone());
     self.container.add_child(btn_start_load.clone());
 
+    let text_load_error = UiText::new();
+    text_load_error.set_font_size(24.0 / 480.0);
+    text_load_error.set_max_width(0.9);
+    text_load_error.set_position(F2 { x: 0.5, y: 1.48 });
+    text_load_error.set_alignment(TextAlignment::Center);
+    text_load_error.set_color(DrawColor {
+      r: 180,
+      g: 30,
+      b: 30,
+    });
+    text_load_error.set_border(false);
+    text_load_error.set_visible(false);
+    self.container.add_child(text_load_error.clone());
+
+    let btn_play_without_failed = UiButton::new(
+      context.texture_manager.gui_btn_wood.clone(),
+      context.texture_manager.gui_btn_wood_pressed.clone(),
+    );
+    btn_play_without_failed.set_size_x(220.0 / 480.0);
+    btn_play_without_failed.set_size_y(60.0 / 480.0);
+    btn_play_without_failed.set_position_x(0.5);
+    btn_play_without_failed.set_position_y(1.62);
+    btn_play_without_failed
+      .set_event_on_released(self.events.clone(), LandingPageEvent::PlayWithoutFailed);
+    btn_play_without_failed.set_sound_on_released(context.audio_manager.click.clone());
+    let btn_play_without_failed_text = UiText::new();
+    btn_play_without_failed_text.set_text(String::from("Play anyway"));
+    btn_play_without_failed_text.set_font_size(45.0 / 480.0);
+    btn_play_without_failed_text.set_alignment(TextAlignment::Center);
+    btn_play_without_failed_text.set_border(true);
+    btn_play_without_failed
+      .container
+      .add_child(btn_play_without_failed_text);
+    btn_play_without_failed.set_visible(false);
+    btn_play_without_failed.set_active(false);
+    self.container.add_child(btn_play_without_failed.clone());
+
     self.ui_elements.replace(Some(UiElements {
       btn_start_load: btn_start_load,
       btn_start_load_text: btn_start_load_text,
+      text_load_error: text_load_error,
+      btn_play_without_failed: btn_play_without_failed,
       btn_sound_text: btn_sound_text,
+      profile_slots: profile_slots,
+      btn_add_profile: btn_add_profile,
     }));
+    self.refresh_profiles(context);
+  }
+
+  // The active profile is drawn opaque with its name bordered, the others are
+  // faded. Tapping the active profile cycles through the avatars.
+  fn refresh_profiles(&self, context: &Context) {
+    let ui_elements = self.ui_elements.borrow();
+    let ui_elements = ui_elements.as_ref().unwrap();
+    let profiles = context.profile_manager.get_profiles();
+    let active_profile_id = context.profile_manager.get_active_profile().id;
+
+    let slot_count = profiles.len()
+      + if context.profile_manager.can_add_profile() {
+        1
+      } else {
+        0
+      };
+    let first_slot_x = 0.5 - PROFILE_SLOT_SPACING * (slot_count as F1 - 1.0) / 2.0;
+
+    for (slot_index, slot) in ui_elements.profile_slots.iter().enumerate() {
+      let profile = match profiles.get(slot_index) {
+        Some(profile) => profile,
+        None => {
+          slot.button.set_visible(false);
+          slot.button.active.set(false);
+          slot.text_name.set_visible(false);
+          continue;
+        }
+      };
+      let position_x = first_slot_x + PROFILE_SLOT_SPACING * slot_index as F1;
+      let avatar_texture = profile.avatar.get_texture(&context.texture_manager);
+      slot.button.set_texture(avatar_texture.clone());
+      slot.button.set_texture_pressed(avatar_texture);
+      slot.button.set_size_from_x(0.14);
+      slot.button.set_position(F2 {
+        x: position_x,
+        y: PROFILES_POSITION_Y,
+      });
+      slot.button.set_visible(true);
+      slot.button.active.set(true);
+
+      let active = profile.id == active_profile_id;
+      slot.button.set_opacity(if active { 1.0 } else { 0.5 });
+      slot.text_name.set_text(profile.name.clone());
+      slot.text_name.set_border(active);
+      slot.text_name.set_position(F2 {
+        x: position_x,
+        y: PROFILES_POSITION_Y + 0.1,
+      });
+      slot.text_name.set_visible(true);
+    }
+
+    let can_add_profile = context.profile_manager.can_add_profile();
+    ui_elements.btn_add_profile.set_visible(can_add_profile);
+    ui_elements.btn_add_profile.active.set(can_add_profile);
+    ui_elements.btn_add_profile.set_position(F2 {
+      x: first_slot_x + PROFILE_SLOT_SPACING * profiles.len() as F1,
+      y: PROFILES_POSITION_Y,
+    });
+  }
+
+  // Lists the images that couldn't be loaded and turns the load button into a
+  // retry button, or hides the report when `failed_srcs` is empty.
+  fn show_load_errors(&self, failed_srcs: &[String]) {
+    let ui_elements = self.ui_elements.borrow();
+    let ui_elements = ui_elements.as_ref().unwrap();
+    let failed = !failed_srcs.is_empty();
+    if failed {
+      let mut names: Vec<&str> = failed_srcs
+        .iter()
+        .take(MAX_LISTED_FAILED_SRCS)
+        .map(|src| src.rsplit('/').next().unwrap_or(src))
+        .collect();
+      let more = format!("{} more", failed_srcs.len() - names.len());
+      if failed_srcs.len() > MAX_LISTED_FAILED_SRCS {
+        names.push(&more);
+      }
+      ui_elements.text_load_error.set_text(format!(
+        "Couldn't load {} image{}: {}. Check your connection and retry.",
+        failed_srcs.len(),
+        if failed_srcs.len() == 1 { "" } else { "s" },
+        names.join(", ")
+      ));
+      ui_elements
+        .btn_start_load_text
+        .set_text(String::from("Retry"));
+      ui_elements.btn_start_load_text.set_color(DrawColor {
+        r: 255,
+        g: 255,
+        b: 255,
+      });
+      ui_elements.btn_start_load.set_active(true);
+    }
+    ui_elements.text_load_error.set_visible(failed);
+    ui_elements.btn_play_without_failed.set_visible(failed);
+    ui_elements.btn_play_without_failed.set_active(failed);
+  }
+
+  fn refresh_sound(&self, context: &Context) {
+    let sound_on =
+      !context.audio_player.settings.song_muted || !context.audio_player.settings.sound_muted;
+    self.sound_on.replace(sound_on);
+    self
+      .ui_elements
+      .borrow()
+      .as_ref()
+      .unwrap()
+      .btn_sound_text
+      .set_text(String::from(format!(
+        "Sound: {}",
+        if sound_on { "on" } else { "off" }
+      )));
   }
 }
 
@@ -193,12 +407,16 @@ impl UiElementTrait<Context> for LandingPageUiRoot {
       }
       LandingPageState::WaitingLangingLoaders => {
         if context.texture_manager.landing_loader.all_loaded() {
+          // The landing page is drawn with placeholders for those.
+          let failed_srcs = context.texture_manager.landing_loader.get_failed_srcs();
+          if !failed_srcs.is_empty() {
+            console_log!("landing images failed to load: {:?}", failed_srcs);
+          }
           self.on_landing_loaders_done(context);
           self.state.set(LandingPageState::Ready);
         }
       }
-      LandingPageState::Ready => {
-      }
+      LandingPageState::Ready => {}
       LandingPageState::WaitingLoaders => {
         // // Wait for audio and textures
         // let audio_loaded =
@@ -224,8 +442,14 @@ impl UiElementTrait<Context> for LandingPageUiRoot {
         // Wait only for textures
         let textures_loaded = context.texture_manager.loader.all_loaded();
         if textures_loaded {
-          context.ui_events.add_event(UiEvent::LoadMainMenu);
-          self.state.set(LandingPageState::SwitchingToMainMenu);
+          let failed_srcs = context.texture_manager.loader.get_failed_srcs();
+          if failed_srcs.is_empty() {
+            context.ui_events.add_event(UiEvent::LoadMainMenu);
+            self.state.set(LandingPageState::SwitchingToMainMenu);
+          } else {
+            self.show_load_errors(&failed_srcs);
+            self.state.set(LandingPageState::LoadFailed);
+          }
         } else {
           let texture_loading_progress = context.texture_manager.loader.get_progress();
           let pct =
@@ -239,11 +463,28 @@ impl UiElementTrait<Context> for LandingPageUiRoot {
             .set_text(String::from(format!("Loading... {}%", pct)));
         }
       }
+      LandingPageState::LoadFailed => {}
       LandingPageState::SwitchingToMainMenu => {}
     }
 
     while let Some(event) = self.events.consume_event() {
       match event {
+        // After a failed load, the load button retries.
+        LandingPageEvent::StartLoad if context.texture_manager.loader.has_started_loading() => {
+          context.texture_manager.loader.retry_failed();
+          self.show_load_errors(&[]);
+          {
+            let ui_elements = self.ui_elements.borrow();
+            let ui_elements = ui_elements.as_ref().unwrap();
+            ui_elements.btn_start_load.set_active(false);
+            ui_elements.btn_start_load_text.set_color(DrawColor {
+              r: 150,
+              g: 150,
+              b: 150,
+            });
+          }
+          self.state.set(LandingPageState::WaitingLoaders);
+        }
         LandingPageEvent::StartLoad => {
           // panic!("crash on purpose");
           context.texture_manager.loader.start_loading();
@@ -285,6 +526,11 @@ impl UiElementTrait<Context> for LandingPageUiRoot {
             }
           }
         }
+        LandingPageEvent::PlayWithoutFailed => {
+          self.show_load_errors(&[]);
+          context.ui_events.add_event(UiEvent::LoadMainMenu);
+          self.state.set(LandingPageState::SwitchingToMainMenu);
+        }
         LandingPageEvent::ToggleSound => {
           self.sound_on.replace(!self.sound_on.get());
           self
@@ -298,6 +544,38 @@ impl UiElementTrait<Context> for LandingPageUiRoot {
               if self.sound_on.get() { "on" } else { "off" }
             )));
         }
+        LandingPageEvent::SelectProfile(slot_index) => {
+          if let Some(profile) = context.profile_manager.get_profiles().get(slot_index) {
+            if profile.id == context.profile_manager.get_active_profile().id {
+              context
+                .profile_manager
+                .set_profile_avatar(profile.id, profile.avatar.next());
+            } else {
+              context.switch_profile(profile.id);
+              self.refresh_sound(context);
+            }
+            self.refresh_profiles(context);
+          }
+        }
+        LandingPageEvent::AddProfile => {
+          let default_name = format!(
+            "Player {}",
+            context.profile_manager.get_profiles().len() + 1
+          );
+          let name = context
+            .window()
+            .expect("new profile without window")
+            .prompt_with_message_and_default("Profile name", &default_name)
+            .expect("window.prompt_with_message_and_default failed");
+          if let Some(name) = name {
+            let name: String = name.trim().chars().take(MAX_PROFILE_NAME_LENGTH).collect();
+            let name = if name.is_empty() { default_name } else { name };
+            let profile_id = context.profile_manager.add_profile(name);
+            context.switch_profile(profile_id);
+            self.refresh_sound(context);
+            self.refresh_profiles(context);
+          }
+        }
         LandingPageEvent::Fullscreen => {
           if context.running_as_pwa {
             context
@@ -0,0 +1,238 @@
+use crate::context::Context;
+use crate::game::leaderboard::*;
+use crate::game_ui::*;
+use crate::*;
+
+const COLUMN_POSITIONS_X: [F1; 4] = [0.1, 0.4, 0.72, 0.9];
+const FIRST_ROW_POSITION_Y: F1 = 0.34;
+const ROW_HEIGHT: F1 = 0.07;
+
+#[derive(Clone, Copy)]
+enum LeaderboardEvent {
+  BackButtonPressed,
+}
+
+pub struct LeaderboardUiRoot {
+  container: Rc<UiContainer>,
+  book: Book,
+  stage_number: usize,
+  row_texts: Vec<Vec<Rc<UiText>>>,
+  text_status: Rc<UiText>,
+  events: Rc<EventManager<LeaderboardEvent>>,
+  results: Rc<EventManager<LeaderboardResult>>,
+}
+
+fn create_cell_text(position: F2, color: DrawColor) -> Rc<UiText> {
+  let text = UiText::new();
+  text.set_font_size(28.0 / 480.0);
+  text.set_alignment(TextAlignment::Center);
+  text.set_border(false);
+  text.set_color(color);
+  text.set_position(position);
+  return text;
+}
+
+impl LeaderboardUiRoot {
+  pub fn new(context: &Context, book: Book, stage_number: usize) -> Rc<LeaderboardUiRoot> {
+    let screen_bottom_right = &context.get_ui_viewport_safe_bottom_right();
+    let container = UiContainer::new();
+    let events = EventManager::new();
+
+    let text_title = UiText::new();
+    text_title.set_text(format!("Stage {}-{}", book.number() + 1, stage_number + 1));
+    text_title.use_text_cache();
+    text_title.set_font_size(60.0 / 480.0);
+    text_title.set_alignment(TextAlignment::Center);
+    text_title.set_border(true);
+    text_title.set_position(F2 { x: 0.5, y: 0.1 });
+    container.add_child(text_title);
+
+    let text_provider = UiText::new();
+    text_provider.set_text(format!(
+      "{} Leaderboard",
+      context.leaderboard_provider.get_name()
+    ));
+    text_provider.use_text_cache();
+    text_provider.set_font_size(30.0 / 480.0);
+    text_provider.set_alignment(TextAlignment::Center);
+    text_provider.set_border(true);
+    text_provider.set_position(F2 { x: 0.5, y: 0.18 });
+    container.add_child(text_provider);
+
+    let header_color = DrawColor {
+      r: 255,
+      g: 255,
+      b: 255,
+    };
+    for (column, header) in ["Rank", "Player", "Score", "Stars"].iter().enumerate() {
+      let text_header = create_cell_text(
+        F2 {
+          x: COLUMN_POSITIONS_X[column],
+          y: FIRST_ROW_POSITION_Y - 1.2 * ROW_HEIGHT,
+        },
+        header_color,
+      );
+      text_header.set_text(header.to_string());
+      text_header.set_border(true);
+      text_header.use_text_cache();
+      container.add_child(text_header);
+    }
+
+    let mut row_texts = vec![];
+    for rank in 0..LEADERBOARD_SIZE {
+      let mut texts = vec![];
+      for column_position_x in COLUMN_POSITIONS_X.iter() {
+        let text = create_cell_text(
+          F2 {
+            x: *column_position_x,
+            y: FIRST_ROW_POSITION_Y + ROW_HEIGHT * rank as F1,
+          },
+          DrawColor {
+            r: 102,
+            g: 43,
+            b: 0,
+          },
+        );
+        container.add_child(text.clone());
+        texts.push(text);
+      }
+      row_texts.push(texts);
+    }
+
+    let text_status = UiText::new();
+    text_status.set_font_size(30.0 / 480.0);
+    text_status.set_alignment(TextAlignment::Center);
+    text_status.set_border(true);
+    text_status.set_position(F2 {
+      x: 0.5,
+      y: FIRST_ROW_POSITION_Y + ROW_HEIGHT * 2.0,
+    });
+    container.add_child(text_status.clone());
+
+    let back_button = UiButton::new(
+      context.texture_manager.gui_btn_back.clone(),
+      context.texture_manager.gui_btn_back_pressed.clone(),
+    );
+    back_button.set_size_from_x(60.0 / 480.0);
+    back_button.set_position(F2 {
+      x: 40.0 / 480.0,
+      y: screen_bottom_right.y - 60.0 / 480.0,
+    });
+    back_button.set_event_on_released(events.clone(), LeaderboardEvent::BackButtonPressed);
+    back_button.set_sound_on_released(context.audio_manager.click.clone());
+    container.add_child(back_button);
+
+    return Rc::new(LeaderboardUiRoot {
+      container,
+      book,
+      stage_number,
+      row_texts,
+      text_status,
+      events,
+      results: EventManager::new(),
+    });
+  }
+
+  fn show_entries(&self, context: &Context, entries: Vec<LeaderboardEntry>) {
+    let player_name = context.profile_manager.get_active_profile().name;
+    for (rank, texts) in self.row_texts.iter().enumerate() {
+      let entry = match entries.get(rank) {
+        Some(entry) => entry,
+        None => {
+          for text in texts.iter() {
+            text.set_text(String::new());
+          }
+          continue;
+        }
+      };
+      texts[0].set_text(format!("{}", rank + 1));
+      texts[1].set_text(entry.player_name.clone());
+      texts[2].set_text(format!("{}", entry.score));
+      texts[3].set_text(format!("{}", entry.stars));
+      let color = if entry.player_name == player_name {
+        DrawColor {
+          r: 255,
+          g: 255,
+          b: 255,
+        }
+      } else {
+        DrawColor {
+          r: 102,
+          g: 43,
+          b: 0,
+        }
+      };
+      for text in texts.iter() {
+        text.set_color(color);
+        text.set_border(entry.player_name == player_name);
+      }
+    }
+    if entries.is_empty() {
+      self.text_status.set_text(String::from("No scores yet"));
+    } else {
+      self.text_status.set_text(String::new());
+    }
+  }
+}
+
+impl EffectManagerTrait<Context> for LeaderboardUiRoot {
+  fn get_effect_manager(&self) -> Option<&EffectManager> {
+    return None;
+  }
+}
+
+impl UiElementTrait<Context> for LeaderboardUiRoot {
+  fn get_ui_element(&self) -> &UiElement {
+    return self.container.get_ui_element();
+  }
+
+  fn update(&self, context: &mut Context) {
+    while let Some(event) = self.events.consume_event() {
+      match event {
+        LeaderboardEvent::BackButtonPressed => {
+          context
+            .artificial_input_events
+            .add_event(InputEvent::BackButton);
+        }
+      }
+    }
+    while let Some(result) = self.results.consume_event() {
+      match result {
+        Ok(entries) => self.show_entries(context, entries),
+        Err(error) => {
+          console_log!("leaderboard request failed: {}", error);
+          self
+            .text_status
+            .set_text(String::from("Couldn't load the leaderboard"));
+        }
+      }
+    }
+    self.container.update(context);
+  }
+
+  fn draw(&self, context: &mut Context) {
+    BackgroundWood::draw(context);
+    BackgroundBorders::draw(context);
+    self.container.draw(context);
+  }
+
+  fn get_touched_element(
+    &self,
+    context: &mut Context,
+    ui_touch: &UiTouch,
+  ) -> Option<Rc<dyn UiElementTrait<Context>>> {
+    return self.container.get_touched_element(context, ui_touch);
+  }
+}
+
+impl UiRootTrait<Context> for LeaderboardUiRoot {
+  fn on_navigate_to(&self, context: &mut Context) {
+    self.text_status.set_text(String::from("Loading..."));
+    context.leaderboard_provider.request_entries(
+      context,
+      self.book.number(),
+      self.stage_number,
+      self.results.clone(),
+    );
+  }
+}
@@ -1,20 +1,33 @@
 use crate::context::Context;
 use crate::context::UiEvent;
+use crate::context::UiLayout;
+use crate::game::progress_backup::ProgressBackup;
+use crate::game::progress_storage::SyncConfig;
 use crate::game_ui::*;
 use crate::*;
 
 #[derive(Clone, Copy)]
 enum MainMenuEvent {
   Play,
+  Achievements,
   Options,
   OptionsBack,
   OptionsVibrate,
   OptionsReset,
+  OptionsResetConfirmed,
+  OptionsExport,
+  OptionsImport,
+  OptionsImportConfirmed,
+  OptionsStopSync,
+  OptionsStopSyncConfirmed,
 }
 
 pub struct MainMenuUiRoot {
   pub container: Rc<UiContainer>,
   effect_manager: EffectManager,
+  screen_size: Cell<F2>,
+  sprite_scroll: Rc<UiSprite>,
+  btn_back: Rc<UiButton>,
   glow_bar_top: Shared<GlowBar>,
   glow_bar_bottom: Shared<GlowBar>,
   ctn_menu: Rc<UiContainer>,
@@ -25,6 +38,8 @@ pub struct MainMenuUiRoot {
   effect_show_options: Rc<Effect<SetEffect>>,
   effect_hide_options: Rc<Effect<SetEffect>>,
   events: Rc<EventManager<MainMenuEvent>>,
+  imported_files: Rc<EventManager<String>>,
+  pending_import: RefCell<Option<ProgressBackup>>,
 }
 
 impl MainMenuUiRoot {
@@ -42,7 +57,7 @@ impl MainMenuUiRoot {
     sprite_scroll.set_position(*sprite_scroll_position);
     sprite_scroll.set_depth(1.0);
     sprite_scroll.set_subpixel_precision(true);
-    container.add_child(sprite_scroll);
+    container.add_child(sprite_scroll.clone());
 
     let ctn_menu = UiContainer::new();
     ctn_menu.set_position_y(screen_center.y);
@@ -55,7 +70,7 @@ impl MainMenuUiRoot {
     text_pandadoodle.set_font_size(110.0 / 480.0);
     text_pandadoodle.set_alignment(TextAlignment::Center);
     text_pandadoodle.set_position_x(0.5);
-    text_pandadoodle.set_position_y(-90.0 / 480.0);
+    text_pandadoodle.set_position_y(-120.0 / 480.0);
     text_pandadoodle.set_border(true);
     ctn_menu.add_child(text_pandadoodle);
 
@@ -66,7 +81,7 @@ impl MainMenuUiRoot {
     btn_play.set_size_x(240.0 / 480.0);
     btn_play.set_size_y(82.0 / 480.0);
     btn_play.set_position_x(0.5);
-    btn_play.set_position_y(50.0 / 480.0);
+    btn_play.set_position_y(10.0 / 480.0);
     btn_play.set_event_on_released(events.clone(), MainMenuEvent::Play);
     btn_play.set_sound_on_released(context.audio_manager.click.clone());
     let btn_play_text = UiText::new();
@@ -85,7 +100,7 @@ impl MainMenuUiRoot {
     btn_options.set_size_x(240.0 / 480.0);
     btn_options.set_size_y(82.0 / 480.0);
     btn_options.set_position_x(0.5);
-    btn_options.set_position_y(150.0 / 480.0);
+    btn_options.set_position_y(100.0 / 480.0);
     btn_options.set_event_on_released(events.clone(), MainMenuEvent::Options);
     btn_options.set_sound_on_released(context.audio_manager.click.clone());
     let btn_options_text = UiText::new();
@@ -97,6 +112,25 @@ impl MainMenuUiRoot {
     btn_options.container.add_child(btn_options_text);
     ctn_menu.add_child(btn_options.clone());
 
+    let btn_achievements = UiButton::new(
+      context.texture_manager.gui_btn_wood.clone(),
+      context.texture_manager.gui_btn_wood_pressed.clone(),
+    );
+    btn_achievements.set_size_x(300.0 / 480.0);
+    btn_achievements.set_size_y(82.0 / 480.0);
+    btn_achievements.set_position_x(0.5);
+    btn_achievements.set_position_y(190.0 / 480.0);
+    btn_achievements.set_event_on_released(events.clone(), MainMenuEvent::Achievements);
+    btn_achievements.set_sound_on_released(context.audio_manager.click.clone());
+    let btn_achievements_text = UiText::new();
+    btn_achievements_text.set_text(String::from("Achievements"));
+    btn_achievements_text.use_text_cache();
+    btn_achievements_text.set_font_size(60.0 / 480.0);
+    btn_achievements_text.set_alignment(TextAlignment::Center);
+    btn_achievements_text.set_border(true);
+    btn_achievements.container.add_child(btn_achievements_text);
+    ctn_menu.add_child(btn_achievements.clone());
+
     let ctn_options = UiContainer::new();
     ctn_options.set_position_y(screen_center.y - 350.0 / 480.0);
     ctn_options.set_depth(-5.0);
@@ -253,6 +287,78 @@ impl MainMenuUiRoot {
     btn_reset.container.add_child(btn_reset_text);
     ctn_options.add_child(btn_reset.clone());
 
+    let btn_export = UiButton::new(
+      context.texture_manager.gui_btn_wood.clone(),
+      context.texture_manager.gui_btn_wood_pressed.clone(),
+    );
+    btn_export.set_size(F2 {
+      x: 175.0 / 480.0,
+      y: 60.0 / 480.0,
+    });
+    btn_export.set_position(F2 {
+      x: 152.0 / 480.0,
+      y: 602.0 / 480.0,
+    });
+    btn_export.set_event_on_released(events.clone(), MainMenuEvent::OptionsExport);
+    btn_export.set_sound_on_released(context.audio_manager.click.clone());
+    let btn_export_text = UiText::new();
+    btn_export_text.set_text(String::from("Export"));
+    btn_export_text.use_text_cache();
+    btn_export_text.set_font_size(50.0 / 480.0);
+    btn_export_text.set_alignment(TextAlignment::Center);
+    btn_export_text.set_border(true);
+    btn_export.container.add_child(btn_export_text);
+    ctn_options.add_child(btn_export.clone());
+
+    let btn_import = UiButton::new(
+      context.texture_manager.gui_btn_wood.clone(),
+      context.texture_manager.gui_btn_wood_pressed.clone(),
+    );
+    btn_import.set_size(F2 {
+      x: 175.0 / 480.0,
+      y: 60.0 / 480.0,
+    });
+    btn_import.set_position(F2 {
+      x: 328.0 / 480.0,
+      y: 602.0 / 480.0,
+    });
+    btn_import.set_event_on_released(events.clone(), MainMenuEvent::OptionsImport);
+    btn_import.set_sound_on_released(context.audio_manager.click.clone());
+    let btn_import_text = UiText::new();
+    btn_import_text.set_text(String::from("Import"));
+    btn_import_text.use_text_cache();
+    btn_import_text.set_font_size(50.0 / 480.0);
+    btn_import_text.set_alignment(TextAlignment::Center);
+    btn_import_text.set_border(true);
+    btn_import.container.add_child(btn_import_text);
+    ctn_options.add_child(btn_import.clone());
+
+    // Shows where progress is synced to, tapping it stops syncing.
+    if let Some(sync_config) = context.sync_config.as_ref() {
+      let btn_sync = UiButton::new(
+        context.texture_manager.gui_btn_wood.clone(),
+        context.texture_manager.gui_btn_wood_pressed.clone(),
+      );
+      btn_sync.set_size(F2 {
+        x: 360.0 / 480.0,
+        y: 50.0 / 480.0,
+      });
+      btn_sync.set_position(F2 {
+        x: 240.0 / 480.0,
+        y: 674.0 / 480.0,
+      });
+      btn_sync.set_event_on_released(events.clone(), MainMenuEvent::OptionsStopSync);
+      btn_sync.set_sound_on_released(context.audio_manager.click.clone());
+      let btn_sync_text = UiText::new();
+      btn_sync_text.set_text(format!("Sync: {}", sync_config.get_host()));
+      btn_sync_text.set_font_size(32.0 / 480.0);
+      btn_sync_text.set_max_width(340.0 / 480.0);
+      btn_sync_text.set_alignment(TextAlignment::Center);
+      btn_sync_text.set_border(true);
+      btn_sync.container.add_child(btn_sync_text);
+      ctn_options.add_child(btn_sync);
+    }
+
     let btn_back = UiButton::new(
       context.texture_manager.gui_btn_back.clone(),
       context.texture_manager.gui_btn_back_pressed.clone(),
@@ -307,6 +413,21 @@ impl MainMenuUiRoot {
       0.0,
       &effect_show_options,
     );
+    Effect::new_within_set_effect(
+      VectorAffectorF2::new(btn_achievements.get_position()).set_start_and_end(
+        F2 {
+          x: 0.5,
+          y: btn_achievements.get_position().borrow().y,
+        },
+        F2 {
+          x: 1.25,
+          y: btn_achievements.get_position().borrow().y,
+        },
+        1000.0,
+      ),
+      0.0,
+      &effect_show_options,
+    );
 
     let effect_hide_options = Effect::new_within_effect_manager(SetEffect::new(), &effect_manager);
     Effect::new_within_set_effect(
@@ -353,10 +474,30 @@ impl MainMenuUiRoot {
       0.0,
       &effect_hide_options,
     );
+    Effect::new_within_set_effect(
+      VectorAffectorF2::new(btn_achievements.get_position())
+        .set_start_and_end(
+          F2 {
+            x: 1.25,
+            y: btn_achievements.get_position().borrow().y,
+          },
+          F2 {
+            x: 0.5,
+            y: btn_achievements.get_position().borrow().y,
+          },
+          1000.0,
+        )
+        .set_progression(Box::new(ExpTransProgression::new(2.0, 6.0))),
+      0.0,
+      &effect_hide_options,
+    );
 
-    return MainMenuUiRoot {
+    let result = MainMenuUiRoot {
       container: container,
       effect_manager,
+      screen_size: Cell::new(F2::default()),
+      sprite_scroll,
+      btn_back,
       glow_bar_top: Shared::new(GlowBar::new(
         context.texture_manager.gui_glow_top.clone(),
         F2 {
@@ -389,7 +530,38 @@ impl MainMenuUiRoot {
       effect_show_options: effect_show_options,
       effect_hide_options: effect_hide_options,
       events: events.clone(),
+      imported_files: EventManager::new(),
+      pending_import: RefCell::new(None),
     };
+    result.refresh_screen_size(context);
+    return result;
+  }
+
+  // Recenters the menu when the screen rotates, with the back button of the
+  // options in the corner of the screen in the landscape layout.
+  fn refresh_screen_size(&self, context: &Context) {
+    if self.screen_size.get() == context.ui_viewport.screen_size {
+      return;
+    }
+    self.screen_size.set(context.ui_viewport.screen_size);
+    let screen_center = &context.ui_viewport.screen_center;
+    let screen_bottom_right = &context.get_ui_viewport_safe_bottom_right();
+    let left = match context.ui_layout {
+      UiLayout::Portrait => context.get_ui_viewport_safe_top_left().x.max(0.0),
+      UiLayout::Landscape { .. } => context.get_ui_viewport_safe_top_left().x,
+    };
+
+    self.sprite_scroll.set_position(*screen_center);
+    self.ctn_menu.set_position_y(screen_center.y);
+    self
+      .ctn_options
+      .set_position_y(screen_center.y - 350.0 / 480.0);
+    self.btn_back.set_position(F2 {
+      x: left + 40.0 / 480.0,
+      y: screen_bottom_right.y - 60.0 / 480.0 - self.ctn_options.get_position().borrow().y,
+    });
+    self.glow_bar_top.borrow_mut().position.y = screen_center.y - 0.3820;
+    self.glow_bar_bottom.borrow_mut().position.y = screen_center.y + 0.4945;
   }
 }
 
@@ -405,11 +577,15 @@ impl UiElementTrait<Context> for MainMenuUiRoot {
   }
 
   fn update(&self, context: &mut Context) {
+    self.refresh_screen_size(context);
     while let Some(event) = self.events.consume_event() {
       match event {
         MainMenuEvent::Play => {
           context.ui_events.add_event(UiEvent::LoadMenuChooseStage);
         }
+        MainMenuEvent::Achievements => {
+          context.ui_events.add_event(UiEvent::LoadAchievements);
+        }
         MainMenuEvent::Options => {
           self.effect_show_options.start();
           self.ctn_options.set_active(true);
@@ -428,7 +604,96 @@ impl UiElementTrait<Context> for MainMenuUiRoot {
             context.vibration_manager.get_vibration_level()
           ));
         }
-        MainMenuEvent::OptionsReset => {}
+        MainMenuEvent::OptionsReset => {
+          context
+            .get_ui_manager_events()
+            .add_event(UiManagerEvent::ShowUiOverlay(ConfirmDialogUiOverlay::new(
+              context,
+              vec![
+                "Reset all progress?",
+                "Stars and achievements",
+                "will be lost.",
+              ],
+              self.events.clone(),
+              MainMenuEvent::OptionsResetConfirmed,
+            )));
+        }
+        MainMenuEvent::OptionsResetConfirmed => {
+          context.achievments_manager.reset();
+        }
+        MainMenuEvent::OptionsExport => {
+          FileTransferUtil::download_text(
+            context.window().expect("export without window"),
+            "pandadoodle-progress.json",
+            "application/json",
+            &ProgressBackup::export(context.local_storage(), &context.profile_manager),
+          );
+        }
+        MainMenuEvent::OptionsImport => {
+          FileTransferUtil::pick_text_file(
+            context.window().expect("import without window"),
+            ".json,application/json",
+            self.imported_files.clone(),
+          );
+        }
+        MainMenuEvent::OptionsStopSync => {
+          let host = context
+            .sync_config
+            .as_ref()
+            .map(|sync_config| sync_config.get_host())
+            .unwrap_or_default();
+          context
+            .get_ui_manager_events()
+            .add_event(UiManagerEvent::ShowUiOverlay(ConfirmDialogUiOverlay::new(
+              context,
+              vec!["Stop syncing", "progress with", &host],
+              self.events.clone(),
+              MainMenuEvent::OptionsStopSyncConfirmed,
+            )));
+        }
+        MainMenuEvent::OptionsStopSyncConfirmed => {
+          SyncConfig::clear(context.local_storage());
+          // Progress storages are created with the profiles, reloading
+          // recreates them without sync.
+          context
+            .window()
+            .expect("reload without window")
+            .location()
+            .reload()
+            .expect("location.reload failed");
+        }
+        MainMenuEvent::OptionsImportConfirmed => {
+          if let Some(backup) = self.pending_import.replace(None) {
+            backup.apply(context.local_storage(), &context.profile_manager);
+            // Every manager caches its data on construction, reloading is the
+            // simplest way to pick up the imported state everywhere.
+            context
+              .window()
+              .expect("reload without window")
+              .location()
+              .reload()
+              .expect("location.reload failed");
+          }
+        }
+      }
+    }
+
+    while let Some(imported_file) = self.imported_files.consume_event() {
+      match ProgressBackup::parse(&imported_file) {
+        Ok(backup) => {
+          self.pending_import.replace(Some(backup));
+          context
+            .get_ui_manager_events()
+            .add_event(UiManagerEvent::ShowUiOverlay(ConfirmDialogUiOverlay::new(
+              context,
+              vec!["Replace current", "progress with the", "imported one?"],
+              self.events.clone(),
+              MainMenuEvent::OptionsImportConfirmed,
+            )));
+        }
+        Err(error) => {
+          context.alert(&format!("Could not import progress: {}", error));
+        }
       }
     }
 
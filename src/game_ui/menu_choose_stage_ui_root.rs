@@ -1,4 +1,5 @@
 use crate::context::Context;
+use crate::context::UiLayout;
 use crate::engine::*;
 use crate::*;
 
@@ -11,6 +12,7 @@ pub enum MenuChooseStageEvent {
   EffectWaitToSlideEnd,
   EffectHideCreditsEnd,
   ShowNextBook,
+  ShowStageStats,
   StartStage(usize),
   SelectBook(usize),
 }
@@ -35,6 +37,7 @@ pub struct MenuChooseStageUiRoot {
   star_count_text: Rc<UiText>,
   medal_count_text: Rc<UiText>,
   back_button: Rc<UiButton>,
+  stats_button: Rc<UiButton>,
   events: Rc<EventManager<MenuChooseStageEvent>>,
 }
 
@@ -95,11 +98,11 @@ struct StageIcon {
   sprite_locked: Rc<UiSprite>,
   stars: Rc<UiSprite>,
   text: Rc<UiText>,
+  text_attempts: Rc<UiText>,
 }
 
 impl MenuChooseStageUiRoot {
   pub fn new(context: &Context) -> Rc<MenuChooseStageUiRoot> {
-    let screen_size = &context.ui_viewport.screen_size;
     let screen_center = &context.ui_viewport.screen_center;
     let screen_bottom_right = &context.ui_viewport.screen_bottom_right_corner;
     let screen_top_left = &context.ui_viewport.screen_top_left_corner;
@@ -540,6 +543,32 @@ impl MenuChooseStageUiRoot {
     back_button.set_sound_on_released(context.audio_manager.click.clone());
     container.add_child(back_button.clone());
 
+    let stats_button = UiButton::new(
+      context.texture_manager.gui_btn_wood.clone(),
+      context.texture_manager.gui_btn_wood_pressed.clone(),
+    );
+    stats_button.set_size(F2 {
+      x: 130.0 / 480.0,
+      y: 55.0 / 480.0,
+    });
+    stats_button.set_position(F2 {
+      x: 400.0 / 480.0,
+      y: screen_bottom_right.y - 60.0 / 480.0,
+    });
+    stats_button.set_depth(0.9);
+    stats_button.set_visible(false);
+    stats_button.active.set(false);
+    stats_button.set_event_on_released(events.clone(), MenuChooseStageEvent::ShowStageStats);
+    stats_button.set_sound_on_released(context.audio_manager.click.clone());
+    let stats_button_text = UiText::new();
+    stats_button_text.set_text(String::from("Stats"));
+    stats_button_text.use_text_cache();
+    stats_button_text.set_font_size(45.0 / 480.0);
+    stats_button_text.set_alignment(TextAlignment::Center);
+    stats_button_text.set_border(true);
+    stats_button.container.add_child(stats_button_text);
+    container.add_child(stats_button.clone());
+
     stages_container.set_position(*screen_center);
     stages_container.set_visible(false);
     stages_container.set_opacity(0.0);
@@ -600,6 +629,22 @@ impl MenuChooseStageUiRoot {
         text.set_text(format!("{}", i * STAGE_ROWS + j + 1));
         container.add_child(text.clone());
 
+        let text_attempts = UiText::new();
+        text_attempts.set_font_size(18.0 / 480.0);
+        text_attempts.set_alignment(TextAlignment::Center);
+        text_attempts.set_border(false);
+        text_attempts.set_color(DrawColor {
+          r: 102,
+          g: 43,
+          b: 0,
+        });
+        text_attempts.set_position(F2 {
+          x: 0.0,
+          y: 56.0 / 480.0,
+        });
+        text_attempts.set_depth(-0.9);
+        container.add_child(text_attempts.clone());
+
         let sprite_locked = Rc::new(UiSprite::new(
           context.texture_manager.gui_stage_icon_lock.clone(),
         ));
@@ -614,6 +659,7 @@ impl MenuChooseStageUiRoot {
           sprite_locked: sprite_locked,
           stars: stars,
           text: text,
+          text_attempts: text_attempts,
         });
       }
     }
@@ -621,7 +667,7 @@ impl MenuChooseStageUiRoot {
     let result = Rc::new(MenuChooseStageUiRoot {
       container: container.clone(),
       effect_manager,
-      screen_size: Cell::new(*screen_size),
+      screen_size: Cell::new(F2::default()),
       pivot_books: pivot_books.clone(),
       books_ui: books_ui,
       stages_container: stages_container.clone(),
@@ -638,9 +684,11 @@ impl MenuChooseStageUiRoot {
       star_count_text: star_count_text,
       medal_count_text: medal_count_text,
       back_button: back_button.clone(),
+      stats_button: stats_button,
       events: events.clone(),
     });
 
+    result.refresh_screen_size(context);
     return result;
   }
 
@@ -651,6 +699,8 @@ impl MenuChooseStageUiRoot {
     self.stages_container.set_visible(true);
     self.stages_container.set_active(true);
     self.effect_show_stages.start();
+    self.stats_button.set_visible(true);
+    self.stats_button.active.set(true);
     self.showing_book.set(book);
     self.refresh_star_counts(context);
     self.refresh_stage_stars(context);
@@ -660,6 +710,8 @@ impl MenuChooseStageUiRoot {
     self.pivot_books.set_active(true);
     self.choosing_stage.set(false);
     self.stages_container.set_active(false);
+    self.stats_button.set_visible(false);
+    self.stats_button.active.set(false);
     self.effect_hide_stages.start();
     for book_ui in self.books_ui.iter() {
       book_ui.effect_step_return.start();
@@ -748,6 +800,19 @@ impl MenuChooseStageUiRoot {
         stage_icon.text.set_visible(true);
         stage_icon.sprite_locked.set_visible(false);
 
+        let stage_stats = context
+          .achievments_manager
+          .get_stage_stats(self.showing_book.get().number(), stage_number);
+        stage_icon
+          .text_attempts
+          .set_visible(stage_stats.attempts.get() > 0);
+        stage_icon
+          .text_attempts
+          .set_text(match stage_stats.attempts.get() {
+            1 => String::from("1 try"),
+            attempts => format!("{} tries", attempts),
+          });
+
         match context
           .achievments_manager
           .get_stage_stars(self.showing_book.get().number(), stage_number)
@@ -778,19 +843,38 @@ impl MenuChooseStageUiRoot {
         stage_icon.button.active.set(false);
         stage_icon.stars.set_visible(false);
         stage_icon.text.set_visible(false);
+        stage_icon.text_attempts.set_visible(false);
         stage_icon.sprite_locked.set_visible(true);
       }
     }
   }
 
-  fn refresh_screen_size(&self, context: &mut Context) {
+  // Also reflows the menu when the screen rotates, moving the bottom buttons to
+  // the corners of the screen in the landscape layout. They stay clear of
+  // notches and home indicators.
+  fn refresh_screen_size(&self, context: &Context) {
     if self.screen_size.get() != context.ui_viewport.screen_size {
       self.screen_size.set(context.ui_viewport.screen_size);
-      let screen_bottom_right = &context.ui_viewport.screen_bottom_right_corner;
+      let screen_center = &context.ui_viewport.screen_center;
+      let screen_bottom_right = &context.get_ui_viewport_safe_bottom_right();
       let screen_top_left = &context.ui_viewport.screen_top_left_corner;
+      let safe_top_left = context.get_ui_viewport_safe_top_left();
+      let (left, right) = match context.ui_layout {
+        UiLayout::Portrait => (safe_top_left.x.max(0.0), screen_bottom_right.x.min(1.0)),
+        UiLayout::Landscape { .. } => (safe_top_left.x, screen_bottom_right.x),
+      };
 
+      self.pivot_books.set_position_y(screen_center.y);
+      self.stages_container.set_position(*screen_center);
+      self
+        .star_count_container
+        .set_position_y(screen_center.y * 0.1);
       self.back_button.set_position(F2 {
-        x: 40.0 / 480.0,
+        x: left + 40.0 / 480.0,
+        y: screen_bottom_right.y - 60.0 / 480.0,
+      });
+      self.stats_button.set_position(F2 {
+        x: right - 80.0 / 480.0,
         y: screen_bottom_right.y - 60.0 / 480.0,
       });
 
@@ -884,6 +968,11 @@ impl UiElementTrait<Context> for MenuChooseStageUiRoot {
           }
           self_step_up.start();
         }
+        MenuChooseStageEvent::ShowStageStats => {
+          context
+            .ui_events
+            .add_event(UiEvent::LoadStageStats(self.showing_book.get()));
+        }
         MenuChooseStageEvent::StartStage(stage_number) => {
           self.start_stage(context, stage_number);
         }
@@ -953,3 +1042,26 @@ impl UiRootTrait<Context> for MenuChooseStageUiRoot {
     context.audio_player.play_song(&context.audio_manager.song2);
   }
 }
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_golden_stage_select() {
+    let (mut context, image) = Context::new_headless(&F2 { x: 240.0, y: 360.0 });
+    let ui_manager = UiManager::new(&context);
+    ui_manager.push_page_on_stack(MenuChooseStageUiRoot::new(&context), &mut context);
+    context
+      .menu_choose_stages_events
+      .add_event(MenuChooseStageEvent::SelectBook(0));
+    for _ in 0..30 {
+      context.next_frame(100.0);
+      ui_manager.update(&mut context);
+    }
+    ui_manager.draw(&mut context);
+    context.draw_manager.execute_draws();
+
+    check_golden("stage_select", &image.borrow());
+  }
+}
@@ -1,17 +1,26 @@
+mod achievement_toast;
+mod achievements_ui_root;
 mod background_borders;
 mod background_wood;
 mod book_intro;
+mod confirm_dialog_ui_overlay;
 mod game_ui_manager;
 mod glow_bar;
 mod ingame_menu_ui_overlay;
 mod ingame_ui_root;
 mod landing_page_ui_root;
+mod leaderboard_ui_root;
 mod main_menu_ui_root;
 mod menu_choose_stage_ui_root;
+mod stage_stats_ui_root;
+mod victory_ui;
 
+pub use achievement_toast::AchievementToast;
+pub use achievements_ui_root::AchievementsUiRoot;
 pub use background_borders::BackgroundBorders;
 pub use background_wood::BackgroundWood;
 pub use book_intro::BookIntro;
+pub use confirm_dialog_ui_overlay::ConfirmDialogUiOverlay;
 pub use game_ui_manager::GameUiManager;
 pub use glow_bar::GlowBar;
 pub use ingame_menu_ui_overlay::IngameMenuUiOverlay;
@@ -19,7 +28,10 @@ pub use ingame_ui_root::IngameUiEvent;
 pub use ingame_ui_root::IngameUiRoot;
 pub use ingame_ui_root::VictoryParams;
 pub use landing_page_ui_root::LandingPageUiRoot;
+pub use leaderboard_ui_root::LeaderboardUiRoot;
 pub use main_menu_ui_root::MainMenuUiRoot;
 pub use menu_choose_stage_ui_root::Book;
 pub use menu_choose_stage_ui_root::MenuChooseStageEvent;
 pub use menu_choose_stage_ui_root::MenuChooseStageUiRoot;
+pub use stage_stats_ui_root::StageStatsUiRoot;
+pub use victory_ui::VictoryUi;
@@ -0,0 +1,225 @@
+use crate::context::Context;
+use crate::game::stages_data::*;
+use crate::game_ui::*;
+use crate::*;
+
+const COLUMN_POSITIONS_X: [F1; 7] = [0.07, 0.2, 0.34, 0.48, 0.62, 0.76, 0.91];
+const MONTH_NAMES: [&str; 12] = [
+  "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+const FIRST_ROW_POSITION_Y: F1 = 0.26;
+const ROW_HEIGHT: F1 = 0.042;
+
+#[derive(Clone, Copy)]
+enum StageStatsEvent {
+  BackButtonPressed,
+}
+
+pub struct StageStatsUiRoot {
+  container: Rc<UiContainer>,
+  book: Book,
+  row_texts: Vec<Vec<Rc<UiText>>>,
+  text_totals: Rc<UiText>,
+  events: Rc<EventManager<StageStatsEvent>>,
+}
+
+fn format_play_time(play_time_ms: f64) -> String {
+  let total_seconds = (play_time_ms / 1000.0) as u64;
+  return format!("{}:{:02}", total_seconds / 60, total_seconds % 60);
+}
+
+fn format_paint_used(paint_used: Option<F1>) -> String {
+  return match paint_used {
+    Some(paint_used) => format!("{}%", (paint_used * 100.0).round() as i32),
+    None => String::from("-"),
+  };
+}
+
+// Local date of the first clear, like "Oct 19".
+fn format_first_clear(first_clear_timestamp_ms: Option<f64>) -> String {
+  return match first_clear_timestamp_ms {
+    Some(timestamp_ms) => {
+      let date = js_sys::Date::new(&JsValue::from_f64(timestamp_ms));
+      format!(
+        "{} {}",
+        MONTH_NAMES[date.get_month() as usize],
+        date.get_date()
+      )
+    }
+    None => String::from("-"),
+  };
+}
+
+fn create_cell_text(position: F2, color: DrawColor) -> Rc<UiText> {
+  let text = UiText::new();
+  text.set_font_size(18.0 / 480.0);
+  text.set_alignment(TextAlignment::Center);
+  text.set_border(false);
+  text.set_color(color);
+  text.set_position(position);
+  return text;
+}
+
+impl StageStatsUiRoot {
+  pub fn new(context: &Context, book: Book) -> Rc<StageStatsUiRoot> {
+    let screen_bottom_right = &context.get_ui_viewport_safe_bottom_right();
+    let container = UiContainer::new();
+    let events = EventManager::new();
+
+    let text_title = UiText::new();
+    text_title.set_text(format!("Book {} Stats", book.number() + 1));
+    text_title.use_text_cache();
+    text_title.set_font_size(60.0 / 480.0);
+    text_title.set_alignment(TextAlignment::Center);
+    text_title.set_border(true);
+    text_title.set_position(F2 { x: 0.5, y: 0.1 });
+    container.add_child(text_title);
+
+    let header_color = DrawColor {
+      r: 255,
+      g: 255,
+      b: 255,
+    };
+    for (column, header) in [
+      "Stage", "Tries", "Restarts", "Undos", "Time", "Paint", "Cleared",
+    ]
+    .iter()
+    .enumerate()
+    {
+      let text_header = create_cell_text(
+        F2 {
+          x: COLUMN_POSITIONS_X[column],
+          y: FIRST_ROW_POSITION_Y - 1.5 * ROW_HEIGHT,
+        },
+        header_color,
+      );
+      text_header.set_text(header.to_string());
+      text_header.set_border(true);
+      text_header.use_text_cache();
+      container.add_child(text_header);
+    }
+
+    let row_color = DrawColor {
+      r: 102,
+      g: 43,
+      b: 0,
+    };
+    let mut row_texts = vec![];
+    for stage_number in 0..STAGES_PER_BOOK {
+      let mut texts = vec![];
+      for column_position_x in COLUMN_POSITIONS_X.iter() {
+        let text = create_cell_text(
+          F2 {
+            x: *column_position_x,
+            y: FIRST_ROW_POSITION_Y + ROW_HEIGHT * stage_number as F1,
+          },
+          row_color,
+        );
+        container.add_child(text.clone());
+        texts.push(text);
+      }
+      row_texts.push(texts);
+    }
+
+    let text_totals = UiText::new();
+    text_totals.set_font_size(30.0 / 480.0);
+    text_totals.set_alignment(TextAlignment::Center);
+    text_totals.set_border(true);
+    text_totals.set_position(F2 {
+      x: 0.5,
+      y: FIRST_ROW_POSITION_Y + ROW_HEIGHT * (STAGES_PER_BOOK as F1 + 0.5),
+    });
+    container.add_child(text_totals.clone());
+
+    let back_button = UiButton::new(
+      context.texture_manager.gui_btn_back.clone(),
+      context.texture_manager.gui_btn_back_pressed.clone(),
+    );
+    back_button.set_size_from_x(60.0 / 480.0);
+    back_button.set_position(F2 {
+      x: 40.0 / 480.0,
+      y: screen_bottom_right.y - 60.0 / 480.0,
+    });
+    back_button.set_event_on_released(events.clone(), StageStatsEvent::BackButtonPressed);
+    back_button.set_sound_on_released(context.audio_manager.click.clone());
+    container.add_child(back_button);
+
+    return Rc::new(StageStatsUiRoot {
+      container,
+      book,
+      row_texts,
+      text_totals,
+      events,
+    });
+  }
+
+  fn refresh_stats(&self, context: &mut Context) {
+    let mut total_attempts = 0;
+    let mut total_play_time_ms = 0.0;
+    for (stage_number, texts) in self.row_texts.iter().enumerate() {
+      let stage_stats = context
+        .achievments_manager
+        .get_stage_stats(self.book.number(), stage_number);
+      total_attempts += stage_stats.attempts.get();
+      total_play_time_ms += stage_stats.play_time_ms.get();
+
+      texts[0].set_text(format!("{}", stage_number + 1));
+      texts[1].set_text(format!("{}", stage_stats.attempts.get()));
+      texts[2].set_text(format!("{}", stage_stats.restarts.get()));
+      texts[3].set_text(format!("{}", stage_stats.undos.get()));
+      texts[4].set_text(format_play_time(stage_stats.play_time_ms.get()));
+      texts[5].set_text(format_paint_used(stage_stats.best_paint_used()));
+      texts[6].set_text(format_first_clear(stage_stats.first_clear_timestamp_ms));
+    }
+    self.text_totals.set_text(format!(
+      "Total: {} tries, {}",
+      total_attempts,
+      format_play_time(total_play_time_ms)
+    ));
+  }
+}
+
+impl EffectManagerTrait<Context> for StageStatsUiRoot {
+  fn get_effect_manager(&self) -> Option<&EffectManager> {
+    return None;
+  }
+}
+
+impl UiElementTrait<Context> for StageStatsUiRoot {
+  fn get_ui_element(&self) -> &UiElement {
+    return self.container.get_ui_element();
+  }
+
+  fn update(&self, context: &mut Context) {
+    while let Some(event) = self.events.consume_event() {
+      match event {
+        StageStatsEvent::BackButtonPressed => {
+          context
+            .artificial_input_events
+            .add_event(InputEvent::BackButton);
+        }
+      }
+    }
+    self.container.update(context);
+  }
+
+  fn draw(&self, context: &mut Context) {
+    BackgroundWood::draw(context);
+    BackgroundBorders::draw(context);
+    self.container.draw(context);
+  }
+
+  fn get_touched_element(
+    &self,
+    context: &mut Context,
+    ui_touch: &UiTouch,
+  ) -> Option<Rc<dyn UiElementTrait<Context>>> {
+    return self.container.get_touched_element(context, ui_touch);
+  }
+}
+
+impl UiRootTrait<Context> for StageStatsUiRoot {
+  fn on_navigate_to(&self, context: &mut Context) {
+    self.refresh_stats(context);
+  }
+}
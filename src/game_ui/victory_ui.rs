@@ -0,0 +1,433 @@
+use crate::audio_manager::AudioManager;
+use crate::texture_manager::TextureManager;
+use crate::*;
+
+// Stars, title, score and buttons shown over a stage once it's cleared.
+pub struct VictoryUi<C: ContextTrait> {
+  pub container: Rc<UiContainerGeneric<C>>,
+  pub image_star1: Rc<UiSpriteGeneric<C>>,
+  pub image_star2: Rc<UiSpriteGeneric<C>>,
+  pub image_star3: Rc<UiSpriteGeneric<C>>,
+  pub image_victory_title: Rc<UiSpriteGeneric<C>>,
+  pub text_score_count: Rc<UiTextGeneric<C>>,
+  pub text_best_score: Rc<UiTextGeneric<C>>,
+  pub effect_star_animation: Rc<EffectGeneric<C, SetEffectGeneric<C>>>,
+  pub effect_show_best_score: Rc<EffectGeneric<C, VectorAffectorF1>>,
+  pub effect_hide_victory_restart: Rc<EffectGeneric<C, VectorAffectorF1>>,
+  pub effect_hide_victory_next: Rc<EffectGeneric<C, VectorAffectorF1>>,
+  pub effect_twinkle_next: Rc<EffectGeneric<C, ChainedEffectGeneric<C>>>,
+  pub start_score_count: Shared<bool>,
+  pub score: Cell<F1>,
+  pub score_counted: Cell<F1>,
+  pub best_score: Cell<bool>,
+  pub stars: Cell<usize>,
+}
+
+impl<C: ContextTrait> VictoryUi<C> {
+  pub fn new(
+    texture_manager: &TextureManager,
+    audio_manager: &AudioManager,
+    events: Rc<EventManager<IngameUiEvent>>,
+    effect_manager: &EffectManagerGeneric<C>,
+  ) -> VictoryUi<C> {
+    let container = UiContainerGeneric::new();
+    container.set_depth(-5.0);
+    container.set_visible(false);
+
+    let image_star1 = Rc::new(UiSpriteGeneric::new(texture_manager.star_empty.clone()));
+    image_star1.set_position(F2 {
+      x: 0.5 - 0.3,
+      y: 300.0 / 480.0,
+    });
+    image_star1.set_subpixel_precision(true);
+    container.add_child(image_star1.clone());
+    let image_star2 = Rc::new(UiSpriteGeneric::new(texture_manager.star_empty.clone()));
+    image_star2.set_position(F2 {
+      x: 0.5,
+      y: 300.0 / 480.0,
+    });
+    image_star2.set_subpixel_precision(true);
+    container.add_child(image_star2.clone());
+    let image_star3 = Rc::new(UiSpriteGeneric::new(texture_manager.star_empty.clone()));
+    image_star3.set_position(F2 {
+      x: 0.5 + 0.3,
+      y: 300.0 / 480.0,
+    });
+    image_star3.set_subpixel_precision(true);
+    container.add_child(image_star3.clone());
+
+    let image_victory_title = Rc::new(UiSpriteGeneric::new(texture_manager.gui_awesome.clone()));
+    image_victory_title.set_position(F2 {
+      x: 0.5,
+      y: 180.0 / 480.0,
+    });
+    image_victory_title.set_size_from_width(260.0 / 480.0);
+    container.add_child(image_victory_title.clone());
+
+    let text_score_count = UiTextGeneric::new();
+    text_score_count.use_font_atlas(texture_manager.font_oregano.clone());
+    text_score_count.set_font_size(45.0 / 480.0);
+    text_score_count.set_border(false);
+    text_score_count.set_alignment(TextAlignment::Left);
+    text_score_count.set_color(DrawColor { r: 0, g: 0, b: 0 });
+    text_score_count.set_position(F2 {
+      x: 120.0 / 480.0,
+      y: 420.0 / 480.0,
+    });
+    container.add_child(text_score_count.clone());
+
+    let text_best_score = UiTextGeneric::new();
+    text_best_score.use_text_cache();
+    text_best_score.set_font_size(45.0 / 480.0);
+    text_best_score.set_border(false);
+    text_best_score.set_alignment(TextAlignment::Center);
+    text_best_score.set_text(String::from("Best Score!!"));
+    text_best_score.set_color(DrawColor { r: 0, g: 0, b: 0 });
+    text_best_score.set_position(F2 {
+      x: 240.0 / 480.0,
+      y: 470.0 / 480.0,
+    });
+    text_best_score.set_opacity(0.0);
+    container.add_child(text_best_score.clone());
+
+    let container_buttons = UiContainerGeneric::new();
+    container_buttons.set_position(F2 {
+      x: 0.5,
+      y: 580.0 / 480.0,
+    });
+    container.add_child(container_buttons.clone());
+
+    let class_buttons_end = UiElementClass {
+      size: Some(F2 {
+        x: 80.0 / 480.0,
+        y: 80.0 / 480.0,
+      }),
+      ..Default::default()
+    };
+
+    let button_menu = UiButtonGeneric::new(
+      texture_manager.gui_btn_wood_menu.clone(),
+      texture_manager.gui_btn_wood_menu_pressed.clone(),
+    );
+    button_menu.set_class(&class_buttons_end);
+    button_menu.set_position_x(-100.0 / 480.0);
+    button_menu.set_event_on_released(events.clone(), IngameUiEvent::ButtonVictoryMenu);
+    container_buttons.add_child(button_menu.clone());
+
+    let button_restart = UiButtonGeneric::new(
+      texture_manager.gui_btn_wood_restart.clone(),
+      texture_manager.gui_btn_wood_restart_pressed.clone(),
+    );
+    button_restart.set_class(&class_buttons_end);
+    button_restart.set_position_x(0.0);
+    button_restart.set_event_on_released(events.clone(), IngameUiEvent::ButtonVictoryRestart);
+    container_buttons.add_child(button_restart.clone());
+
+    let button_next = UiButtonGeneric::new(
+      texture_manager.gui_btn_wood_skip.clone(),
+      texture_manager.gui_btn_wood_skip_pressed.clone(),
+    );
+    button_next.set_class(&class_buttons_end);
+    button_next.set_position_x(100.0 / 480.0);
+    button_next.set_depth(-4.0);
+    button_next.set_event_on_released(events.clone(), IngameUiEvent::ButtonVictoryNext);
+    container_buttons.add_child(button_next.clone());
+
+    let class_buttons_wide = UiElementClass {
+      size: Some(F2 {
+        x: 150.0 / 480.0,
+        y: 60.0 / 480.0,
+      }),
+      position_y: Some(95.0 / 480.0),
+      ..Default::default()
+    };
+
+    let button_leaderboard = UiButtonGeneric::new(
+      texture_manager.gui_btn_wood.clone(),
+      texture_manager.gui_btn_wood_pressed.clone(),
+    );
+    button_leaderboard.set_class(&class_buttons_wide);
+    button_leaderboard.set_position_x(-155.0 / 480.0);
+    button_leaderboard.set_sound_on_released(audio_manager.click.clone());
+    button_leaderboard
+      .set_event_on_released(events.clone(), IngameUiEvent::ButtonVictoryLeaderboard);
+    let button_leaderboard_text = UiTextGeneric::new();
+    button_leaderboard_text.set_text(String::from("Leaderboard"));
+    button_leaderboard_text.use_text_cache();
+    button_leaderboard_text.set_font_size(28.0 / 480.0);
+    button_leaderboard_text.set_alignment(TextAlignment::Center);
+    button_leaderboard_text.set_border(true);
+    button_leaderboard
+      .container
+      .add_child(button_leaderboard_text);
+    container_buttons.add_child(button_leaderboard);
+
+    let button_save_image = UiButtonGeneric::new(
+      texture_manager.gui_btn_wood.clone(),
+      texture_manager.gui_btn_wood_pressed.clone(),
+    );
+    button_save_image.set_class(&class_buttons_wide);
+    button_save_image.set_position_x(0.0);
+    button_save_image.set_sound_on_released(audio_manager.click.clone());
+    button_save_image.set_event_on_released(events.clone(), IngameUiEvent::ButtonVictorySaveImage);
+    let button_save_image_text = UiTextGeneric::new();
+    button_save_image_text.set_text(String::from("Save Image"));
+    button_save_image_text.use_text_cache();
+    button_save_image_text.set_font_size(28.0 / 480.0);
+    button_save_image_text.set_alignment(TextAlignment::Center);
+    button_save_image_text.set_border(true);
+    button_save_image
+      .container
+      .add_child(button_save_image_text);
+    container_buttons.add_child(button_save_image);
+
+    let button_save_time_lapse = UiButtonGeneric::new(
+      texture_manager.gui_btn_wood.clone(),
+      texture_manager.gui_btn_wood_pressed.clone(),
+    );
+    button_save_time_lapse.set_class(&class_buttons_wide);
+    button_save_time_lapse.set_position_x(155.0 / 480.0);
+    button_save_time_lapse.set_sound_on_released(audio_manager.click.clone());
+    button_save_time_lapse
+      .set_event_on_released(events.clone(), IngameUiEvent::ButtonVictorySaveTimeLapse);
+    let button_save_time_lapse_text = UiTextGeneric::new();
+    button_save_time_lapse_text.set_text(String::from("Time-lapse"));
+    button_save_time_lapse_text.use_text_cache();
+    button_save_time_lapse_text.set_font_size(28.0 / 480.0);
+    button_save_time_lapse_text.set_alignment(TextAlignment::Center);
+    button_save_time_lapse_text.set_border(true);
+    button_save_time_lapse
+      .container
+      .add_child(button_save_time_lapse_text);
+    container_buttons.add_child(button_save_time_lapse);
+
+    let image_next_bright = Rc::new(UiSpriteGeneric::new(
+      texture_manager.gui_img_twinkle_next.clone(),
+    ));
+    image_next_bright.set_class(&class_buttons_end);
+    image_next_bright.set_position_x(100.0 / 480.0);
+    image_next_bright.set_depth(-5.0);
+    container_buttons.add_child(image_next_bright.clone());
+
+    let effect_twinkle_next =
+      EffectGeneric::new_within_effect_manager(ChainedEffectGeneric::new(), effect_manager);
+    EffectGeneric::new_within_chained_effect(
+      VectorAffectorF1::new(image_next_bright.get_opacity()).set_start_and_end(0.0, 0.0, 2000.0),
+      &effect_twinkle_next,
+    );
+    for _ in 0..10 {
+      EffectGeneric::new_within_chained_effect(
+        VectorAffectorF1::new(image_next_bright.get_opacity()).set_start_and_end(0.0, 1.0, 200.0),
+        &effect_twinkle_next,
+      );
+      EffectGeneric::new_within_chained_effect(
+        VectorAffectorF1::new(image_next_bright.get_opacity()).set_start_and_end(1.0, 1.0, 500.0),
+        &effect_twinkle_next,
+      );
+      EffectGeneric::new_within_chained_effect(
+        VectorAffectorF1::new(image_next_bright.get_opacity()).set_start_and_end(1.0, 0.0, 200.0),
+        &effect_twinkle_next,
+      );
+      EffectGeneric::new_within_chained_effect(
+        VectorAffectorF1::new(image_next_bright.get_opacity()).set_start_and_end(0.0, 0.0, 500.0),
+        &effect_twinkle_next,
+      );
+    }
+
+    let effect_star_animation =
+      EffectGeneric::new_within_effect_manager(SetEffectGeneric::new(), effect_manager);
+    let star_size = F2 {
+      x: 120.0 / 480.0,
+      y: 120.0 / 480.0,
+    };
+
+    EffectGeneric::new_within_set_effect(
+      VectorAffectorF2::new(image_star1.size.clone())
+        .set_end(star_size, 400.0)
+        .set_progression(Box::new(ExpTransProgression::new(2.0, 6.0))),
+      300.0,
+      &effect_star_animation,
+    );
+
+    EffectGeneric::new_within_set_effect(
+      VectorAffectorF2::new(image_star2.size.clone())
+        .set_end(star_size, 400.0)
+        .set_progression(Box::new(ExpTransProgression::new(2.0, 6.0))),
+      500.0,
+      &effect_star_animation,
+    );
+    EffectGeneric::new_within_set_effect(
+      VectorAffectorF2::new(image_star3.size.clone())
+        .set_end(star_size, 400.0)
+        .set_progression(Box::new(ExpTransProgression::new(2.0, 6.0))),
+      700.0,
+      &effect_star_animation,
+    );
+    EffectGeneric::new_within_set_effect(
+      VectorAffectorF1::new(image_victory_title.get_opacity().clone()).set_end(1.0, 500.0),
+      0.0,
+      &effect_star_animation,
+    );
+    EffectGeneric::new_within_set_effect(
+      VectorAffectorF1::new(text_score_count.get_opacity().clone()).set_end(1.0, 400.0),
+      1000.0,
+      &effect_star_animation,
+    );
+
+    let start_score_count = Shared::new(false);
+    {
+      let start_score_count = start_score_count.clone();
+      effect_star_animation
+        .end_event
+        .add(Box::new(move |_context| {
+          start_score_count.replace(true);
+        }));
+    }
+
+    let effect_show_best_score = EffectGeneric::new_within_effect_manager(
+      VectorAffectorF1::new(text_best_score.get_opacity()).set_end(1.0, 1000.0),
+      effect_manager,
+    );
+
+    let effect_hide_victory_restart = EffectGeneric::new_within_effect_manager(
+      VectorAffectorF1::new(container.get_opacity()).set_end(0.0, 500.0),
+      effect_manager,
+    );
+    effect_hide_victory_restart
+      .add_event_on_end(events.clone(), IngameUiEvent::EffectVictoryRestart);
+
+    let effect_hide_victory_next = EffectGeneric::new_within_effect_manager(
+      VectorAffectorF1::new(container.get_opacity()).set_end(0.0, 500.0),
+      effect_manager,
+    );
+    effect_hide_victory_next.add_event_on_end(events.clone(), IngameUiEvent::EffectVictoryNext);
+
+    return VictoryUi {
+      container: container,
+      image_star1: image_star1,
+      image_star2: image_star2,
+      image_star3: image_star3,
+      image_victory_title: image_victory_title,
+      text_score_count: text_score_count,
+      text_best_score: text_best_score,
+      effect_star_animation: effect_star_animation,
+      effect_show_best_score: effect_show_best_score,
+      effect_hide_victory_restart: effect_hide_victory_restart,
+      effect_hide_victory_next: effect_hide_victory_next,
+      effect_twinkle_next,
+      start_score_count: start_score_count,
+      score: Cell::new(0.0),
+      score_counted: Cell::new(0.0),
+      best_score: Cell::new(false),
+      stars: Cell::new(0),
+    };
+  }
+
+  // Starts the animation showing `score` and `stars`, see show_score.
+  pub fn show(&self, texture_manager: &TextureManager, score: F1, stars: usize) {
+    match stars {
+      3 => {
+        self.image_star1.set_texture(texture_manager.star.clone());
+        self.image_star2.set_texture(texture_manager.star.clone());
+        self.image_star3.set_texture(texture_manager.star.clone());
+        self
+          .image_victory_title
+          .set_texture(texture_manager.gui_awesome.clone());
+      }
+      2 => {
+        self.image_star1.set_texture(texture_manager.star.clone());
+        self.image_star2.set_texture(texture_manager.star.clone());
+        self
+          .image_star3
+          .set_texture(texture_manager.star_empty.clone());
+        self
+          .image_victory_title
+          .set_texture(texture_manager.gui_very_good.clone());
+      }
+      _ => {
+        self.image_star1.set_texture(texture_manager.star.clone());
+        self
+          .image_star2
+          .set_texture(texture_manager.star_empty.clone());
+        self
+          .image_star3
+          .set_texture(texture_manager.star_empty.clone());
+        self
+          .image_victory_title
+          .set_texture(texture_manager.gui_nice.clone());
+      }
+    };
+    self.score.set(score);
+    self.stars.set(stars);
+    self.score_counted.set(0.0);
+    self.container.set_visible(true);
+    self.container.set_active(true);
+    self.container.set_opacity(1.0);
+    self.image_star1.set_size(F2 { x: 0.0, y: 0.0 });
+    self.image_star2.set_size(F2 { x: 0.0, y: 0.0 });
+    self.image_star3.set_size(F2 { x: 0.0, y: 0.0 });
+    self.image_victory_title.set_opacity(0.0);
+    self.text_score_count.set_opacity(0.0);
+    self.text_score_count.set_text(format!("your score . . . "));
+    self.text_best_score.set_opacity(0.0);
+    self.effect_star_animation.start();
+  }
+
+  // Shows the whole score once it's counted, or the count is skipped.
+  pub fn show_score(&self) {
+    self
+      .text_score_count
+      .set_text(format!("your score . . . {}", self.score.get() as i32));
+    self.start_score_count.replace(false);
+    if self.best_score.get() {
+      self.effect_show_best_score.start();
+    }
+  }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+  use super::*;
+
+  struct TestEffects {
+    effect_manager: EffectManagerGeneric<HeadlessContext>,
+  }
+
+  impl EffectManagerTrait<HeadlessContext> for TestEffects {
+    fn get_effect_manager(&self) -> Option<&EffectManagerGeneric<HeadlessContext>> {
+      return Some(&self.effect_manager);
+    }
+  }
+
+  fn play_effects(context: &mut HeadlessContext, effects: &TestEffects, duration_ms: F1) {
+    for _ in 0..(duration_ms / 100.0) as usize {
+      context.next_frame(100.0);
+      effects.update_effects(context);
+    }
+  }
+
+  #[test]
+  fn test_golden_victory_screen() {
+    let mut context = HeadlessContext::new(&F2 { x: 240.0, y: 360.0 });
+    let texture_manager = TextureManager::new_headless();
+    let effects = TestEffects {
+      effect_manager: EffectManagerGeneric::new(),
+    };
+    let victory_ui = VictoryUi::new(
+      &texture_manager,
+      &AudioManager::new(),
+      EventManager::new(),
+      &effects.effect_manager,
+    );
+    victory_ui.best_score.set(true);
+
+    victory_ui.show(&texture_manager, 1460.0, 2);
+    play_effects(&mut context, &effects, 2000.0);
+    victory_ui.show_score();
+    play_effects(&mut context, &effects, 2000.0);
+    victory_ui.container.update(&mut context);
+    victory_ui.container.draw(&mut context);
+
+    context.check_golden("victory_screen");
+  }
+}
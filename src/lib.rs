@@ -5,7 +5,7 @@
 mod logging;
 mod audio_manager;
 mod context;
-mod draw_depths;
+mod draw_layers;
 mod engine;
 mod game;
 mod game_ui;
@@ -33,6 +33,7 @@ pub type UiContainer = UiContainerGeneric<Context>;
 pub type UiElement = UiElementGeneric<Context>;
 pub type UiManager = UiManagerGeneric<Context>;
 pub type UiManagerEvent = UiManagerEventGeneric<Context>;
+pub type UiNineSlice = UiNineSliceGeneric<Context>;
 pub type UiPivot = UiPivotGeneric<Context>;
 pub type UiSlider = UiSliderGeneric<Context>;
 pub type UiSprite = UiSpriteGeneric<Context>;
@@ -116,31 +117,50 @@ fn register_input_listeners(window: &web_sys::Window, app_shared: Shared<App>) {
   InputManager::register(
     app_shared.clone(),
     window,
-    &context.canvas,
-    context.history.clone(),
+    context.canvas.as_ref().expect("register_input_listeners without canvas"),
+    context.history.clone().expect("register_input_listeners without history"),
   );
 }
 
 impl AppTrait for App {
   fn process_input_event(&mut self, input_event: InputEvent) {
     if let Some(screen_touch) = ScreenTouch::from_event(&input_event) {
-      if screen_touch.id != 0 {
+      // A second finger is only used for pinching the game camera.
+      if screen_touch.id > 1 {
         return;
       }
       self
         .ui_manager
         .process_touch(&mut self.context, &screen_touch);
     }
+    if let InputEvent::Wheel(wheel_event) = input_event {
+      self.ui_manager.process_wheel(
+        &mut self.context,
+        &F2 {
+          x: wheel_event.x as F1,
+          y: wheel_event.y as F1,
+        },
+        wheel_event.delta_y as F1,
+      );
+    }
     if let InputEvent::BackButton = input_event {
       let process_back_button_result = self.ui_manager.process_back_button(&mut self.context);
       if process_back_button_result == InputState::Consumed {
         self
           .context
           .history
+          .as_ref()
+          .expect("back button without history")
           .push_state(&JsValue::from_str("gameRunning"), "Game Running")
           .expect("history.push_state failed");
       } else {
-        self.context.history.back().expect("history.back failed");
+        self
+          .context
+          .history
+          .as_ref()
+          .expect("back button without history")
+          .back()
+          .expect("history.back failed");
       }
     }
   }
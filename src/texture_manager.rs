@@ -27,6 +27,7 @@ pub struct TextureManager {
   pub cross: Rc<Texture>,
   pub dot: Rc<Texture>,
   pub flare: Rc<Texture>,
+  pub font_oregano: Rc<FontAtlas>,
   pub gui_awesome: Rc<Texture>,
   pub gui_book_cat: Rc<Texture>,
   pub gui_book_panda: Rc<Texture>,
@@ -112,10 +113,23 @@ pub struct TextureManager {
 }
 
 impl TextureManager {
-  pub fn new(document: Rc<web_sys::Document>) -> TextureManager {
-    let mut landing_loader = TextureLoader::new(document.clone());
-    let mut loader = TextureLoader::new(document);
+  pub fn new(document: Rc<web_sys::Document>, device_pixel_ratio: F1) -> TextureManager {
+    let atlas_manifest = Rc::new(TextureAtlasManifest::from_json(include_str!(
+      "../static/atlases/manifest.json"
+    )));
+    return TextureManager::register(
+      TextureLoader::new(document.clone(), atlas_manifest.clone(), device_pixel_ratio),
+      TextureLoader::new(document, atlas_manifest, device_pixel_ratio),
+    );
+  }
+
+  // Textures read from `static/` without a browser, for native tests.
+  #[cfg(all(test, not(target_arch = "wasm32")))]
+  pub fn new_headless() -> TextureManager {
+    return TextureManager::register(TextureLoader::new_headless(), TextureLoader::new_headless());
+  }
 
+  fn register(mut landing_loader: TextureLoader, mut loader: TextureLoader) -> TextureManager {
     return TextureManager {
       background_wood: landing_loader.register(TextureParams {
         src: String::from("/static/gui_madeira800.png"),
@@ -165,19 +179,19 @@ impl TextureManager {
         src: String::from("/static/gui_chatBarWolf.png"),
         ..Default::default()
       }),
-      chat_char_cat: loader.register(TextureParams {
+      chat_char_cat: landing_loader.register(TextureParams {
         src: String::from("/static/chars_cat.png"),
         ..Default::default()
       }),
-      chat_char_panda: loader.register(TextureParams {
+      chat_char_panda: landing_loader.register(TextureParams {
         src: String::from("/static/chars_panda.png"),
         ..Default::default()
       }),
-      chat_char_rabbit: loader.register(TextureParams {
+      chat_char_rabbit: landing_loader.register(TextureParams {
         src: String::from("/static/chars_rabbit.png"),
         ..Default::default()
       }),
-      chat_char_wolf: loader.register(TextureParams {
+      chat_char_wolf: landing_loader.register(TextureParams {
         src: String::from("/static/chars_wolf.png"),
         ..Default::default()
       }),
@@ -209,6 +223,13 @@ impl TextureManager {
         src: String::from("/static/dot.png"),
         ..Default::default()
       }),
+      font_oregano: FontAtlas::new(
+        loader.register(TextureParams {
+          src: String::from("/static/fonts/Oregano-Regular.png"),
+          ..Default::default()
+        }),
+        FontAtlasManifest::from_json(include_str!("../static/fonts/Oregano-Regular.json")),
+      ),
       // flare: Texture::new(document, gl.clone(), true, "/static/flare.png"),
       flare: loader.register(TextureParams {
         src: String::from("/static/flare_small.png"),
@@ -229,6 +250,7 @@ impl TextureManager {
             .collect(),
             opacity_levels: 10,
           }),
+          scales: vec![2, 3],
         },
       }),
       gui_awesome: loader.register(TextureParams {
@@ -469,7 +491,10 @@ impl TextureManager {
       }),
       mini_star: loader.register(TextureParams {
         src: String::from("/static/miniStar.png"),
-        ..Default::default()
+        optional: TextureParamsOptional {
+          scales: vec![2],
+          ..Default::default()
+        },
       }),
       pixel: landing_loader.register(TextureParams {
         src: String::from("/static/pixel.png"),